@@ -0,0 +1,123 @@
+use crate::learn::*;
+use crate::syntax::*;
+use crate::trace::*;
+
+/// Learns formulas actively, asking for the labels of the traces that matter rather than requiring them upfront:
+/// as long as several candidate formulas are consistent with the sample (see [`learn_top_k`]),
+/// a trace on which they disagree is labelled by a membership query, i.e., a user-supplied callback,
+/// and added to the sample.
+///
+/// The candidates differ on some position of some trace of the sample, so the distinguishing trace is
+/// the shortest suffix of a trace of the sample on which they disagree.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ActiveLearner {
+    /// The number of candidate formulas compared before each query.
+    pub candidates: usize,
+    /// The maximum size of the formulas.
+    pub max_size: usize,
+    /// The maximum number of queries.
+    pub max_queries: usize,
+}
+
+impl Default for ActiveLearner {
+    fn default() -> Self {
+        ActiveLearner {
+            candidates: 3,
+            max_size: 8,
+            max_queries: 20,
+        }
+    }
+}
+
+impl ActiveLearner {
+    pub fn candidates(mut self, candidates: usize) -> Self {
+        self.candidates = candidates;
+        self
+    }
+
+    pub fn max_size(mut self, max_size: usize) -> Self {
+        self.max_size = max_size;
+        self
+    }
+
+    pub fn max_queries(mut self, max_queries: usize) -> Self {
+        self.max_queries = max_queries;
+        self
+    }
+
+    /// Learns a formula, adding the traces labelled by the oracle (`true` for positive) to the sample,
+    /// until the candidates agree on every trace of the sample, or the maximum number of queries is reached.
+    /// Returns the smallest formula consistent with the final sample,
+    /// or `None` if there is none up to the maximum size.
+    pub fn learn<const N: usize, F: FnMut(&Trace<N>) -> bool>(
+        &self,
+        sample: &mut Sample<N>,
+        mut oracle: F,
+    ) -> Option<SyntaxTree> {
+        for _ in 0..self.max_queries {
+            let candidates = learn_top_k(sample, self.candidates.max(2), self.max_size);
+            let Some(query) = distinguishing_trace(sample, &candidates) else {
+                return candidates.into_iter().next();
+            };
+            if oracle(&query) {
+                sample.positive_traces.push(query);
+            } else {
+                sample.negative_traces.push(query);
+            }
+        }
+        learn_top_k(sample, 1, self.max_size).into_iter().next()
+    }
+}
+
+/// The shortest suffix of a trace of the sample on which the formulas disagree, if any.
+fn distinguishing_trace<const N: usize>(
+    sample: &Sample<N>,
+    formulas: &[SyntaxTree],
+) -> Option<Trace<N>> {
+    sample
+        .positive_traces
+        .iter()
+        .chain(&sample.negative_traces)
+        .flat_map(|trace| (0..trace.len()).map(move |time| (trace, time)))
+        .filter(|(trace, time)| {
+            let mut values = formulas
+                .iter()
+                .map(|formula| formula.eval_at_time(trace, *time));
+            values
+                .next()
+                .is_some_and(|first| values.any(|value| value != first))
+        })
+        .min_by_key(|(trace, time)| trace.len() - time)
+        .map(|(trace, time)| trace[time..].to_vec())
+}
+
+#[cfg(test)]
+mod active_learning {
+    use super::*;
+
+    #[test]
+    fn learn() {
+        // The traces to learn are those where x0 holds until x1 does.
+        let target = SyntaxTree::Atom(0).until(SyntaxTree::Atom(1));
+        let mut sample = Sample::<2> {
+            positive_traces: vec![vec![[true, false], [true, false], [false, true]]],
+            negative_traces: vec![vec![[true, false], [false, false], [false, true]]],
+            ..Sample::default()
+        };
+        let mut queries = 0;
+        let formula = ActiveLearner::default()
+            .max_size(4)
+            .learn(&mut sample, |trace| {
+                queries += 1;
+                target.eval(trace)
+            })
+            .expect("solvable");
+        assert!(queries > 0);
+        assert_eq!(
+            sample.positive_traces.len() + sample.negative_traces.len(),
+            2 + queries
+        );
+        assert!(sample.is_consistent(&target));
+        assert_eq!(formula, target);
+    }
+}
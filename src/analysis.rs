@@ -0,0 +1,105 @@
+use crate::syntax::*;
+use crate::trace::*;
+use rand::Rng;
+
+/// Estimates the probability that a formula is satisfied by a random trace of the given length,
+/// where every propositional variable is true or false with equal probability at every time instant.
+/// The estimate is computed by evaluating the formula over `samples` randomly generated traces.
+pub fn satisfaction_probability<const N: usize, R: Rng + ?Sized>(
+    formula: &SyntaxTree,
+    length: usize,
+    samples: usize,
+    rng: &mut R,
+) -> f64 {
+    assert!(length > 0, "traces must not be empty");
+
+    if samples == 0 {
+        return 0.0;
+    }
+
+    let satisfied = (0..samples)
+        .filter(|_| formula.eval(&random_trace::<N, R>(length, rng)))
+        .count();
+
+    satisfied as f64 / samples as f64
+}
+
+/// A formula together with its triviality score,
+/// i.e., the estimated probability that it is satisfied by a random trace.
+/// Formulae with a score close to 1 are near-tautologies:
+/// they hold on almost every trace and so carry little information about the sample.
+#[derive(Debug, Clone)]
+pub struct Triviality {
+    pub formula: SyntaxTree,
+    pub score: f64,
+}
+
+impl Triviality {
+    /// Computes the triviality score of the formula over random traces of the given length.
+    pub fn new<const N: usize, R: Rng + ?Sized>(
+        formula: SyntaxTree,
+        length: usize,
+        samples: usize,
+        rng: &mut R,
+    ) -> Self {
+        let score = satisfaction_probability::<N, R>(&formula, length, samples, rng);
+        Triviality { formula, score }
+    }
+
+    /// Whether the formula is a near-tautology with respect to the given threshold.
+    pub fn is_trivial(&self, threshold: f64) -> bool {
+        self.score >= threshold
+    }
+}
+
+/// Scores every formula of a population and discards those whose triviality score reaches the threshold.
+pub fn filter_trivial<const N: usize, R: Rng + ?Sized>(
+    formulas: &[SyntaxTree],
+    length: usize,
+    samples: usize,
+    threshold: f64,
+    rng: &mut R,
+) -> Vec<Triviality> {
+    formulas
+        .iter()
+        .map(|formula| Triviality::new::<N, R>(formula.clone(), length, samples, rng))
+        .filter(|triviality| !triviality.is_trivial(threshold))
+        .collect()
+}
+
+#[cfg(test)]
+mod triviality {
+    use super::*;
+    use rand::{rngs::StdRng, SeedableRng};
+    use std::sync::Arc;
+
+    const ATOM_0: SyntaxTree = SyntaxTree::Atom(0);
+
+    #[test]
+    fn tautology() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let formula = SyntaxTree::Or(
+            Arc::new(ATOM_0),
+            Arc::new(SyntaxTree::Not(Arc::new(ATOM_0))),
+        );
+
+        let probability = satisfaction_probability::<2, _>(&formula, 5, 200, &mut rng);
+        assert_eq!(probability, 1.0);
+    }
+
+    #[test]
+    fn filter() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let tautology = SyntaxTree::Or(
+            Arc::new(ATOM_0),
+            Arc::new(SyntaxTree::Not(Arc::new(ATOM_0))),
+        );
+        let globally = SyntaxTree::Globally(Arc::new(ATOM_0));
+
+        let filtered =
+            filter_trivial::<1, _>(&[tautology, globally.clone()], 5, 200, 0.95, &mut rng);
+        assert_eq!(filtered.len(), 1);
+        assert_eq!(filtered[0].formula, globally);
+        assert!(filtered[0].score < 0.2);
+    }
+}
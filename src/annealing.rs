@@ -0,0 +1,280 @@
+use crate::cost::*;
+use crate::genetic::*;
+use crate::learn::*;
+use crate::learner::*;
+use crate::source::*;
+use crate::syntax::*;
+use crate::trace::*;
+use rand::Rng;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Instant;
+
+/// How the temperature of simulated annealing decreases from its initial value over the steps.
+///
+/// Schedules are parsed from their names: `linear`, `logarithmic`, or `exponential`,
+/// optionally followed by the factor, e.g., `exponential:0.95`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Cooling {
+    /// Decreases the temperature linearly, down to zero at the last step.
+    Linear,
+    /// Multiplies the temperature by the given factor, between 0 and 1, at every step.
+    Exponential(f64),
+    /// Divides the temperature by the logarithm of the number of steps so far,
+    /// which cools slowly enough to escape most local optima.
+    Logarithmic,
+}
+
+impl Default for Cooling {
+    fn default() -> Self {
+        Cooling::Exponential(0.995)
+    }
+}
+
+impl Cooling {
+    /// The temperature at the given step, out of the given number of steps.
+    pub fn temperature(&self, initial: f64, step: usize, steps: usize) -> f64 {
+        match self {
+            Cooling::Linear => initial * (1.0 - step as f64 / steps.max(1) as f64),
+            Cooling::Exponential(factor) => initial * factor.powi(step as i32),
+            Cooling::Logarithmic => initial * 2f64.ln() / (step as f64 + 2.0).ln(),
+        }
+    }
+}
+
+impl FromStr for Cooling {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (name, factor) = match s.trim().split_once(':') {
+            Some((name, factor)) => (name, Some(factor)),
+            None => (s.trim(), None),
+        };
+        match (name.to_lowercase().as_str(), factor) {
+            ("linear", None) => Ok(Cooling::Linear),
+            ("logarithmic" | "log", None) => Ok(Cooling::Logarithmic),
+            ("exponential" | "exp", None) => Ok(Cooling::default()),
+            ("exponential" | "exp", Some(factor)) => match factor.trim().parse::<f64>() {
+                Ok(factor) if 0.0 < factor && factor < 1.0 => Ok(Cooling::Exponential(factor)),
+                _ => Err(format!("invalid cooling factor: {factor}")),
+            },
+            _ => Err(format!("unknown cooling schedule: {s}")),
+        }
+    }
+}
+
+/// Learns formulas by simulated annealing: starting from a random formula of a given size,
+/// each step moves to a random neighbour (see [`neighbour`]) if it is fitter (see [`Fitness`]),
+/// or otherwise with a probability decreasing with the loss of fitness and with the temperature.
+/// Unlike the [`GeneticLearner`], it improves a single formula rather than a population.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AnnealingLearner {
+    /// The size of the initial formula.
+    pub size: usize,
+    /// The number of steps.
+    pub steps: usize,
+    /// The initial temperature, in units of fitness score.
+    pub temperature: f64,
+    /// How the temperature decreases over the steps.
+    pub cooling: Cooling,
+    /// The maximum size of the formulas, if any: larger neighbours are never visited.
+    pub max_size: Option<usize>,
+    /// The costs penalizing formulas in their fitness, instead of their size.
+    pub costs: OperatorCosts,
+}
+
+impl Default for AnnealingLearner {
+    fn default() -> Self {
+        AnnealingLearner::new(1000)
+    }
+}
+
+impl AnnealingLearner {
+    /// A learner running for the given number of steps, starting from a formula of size 3.
+    pub fn new(steps: usize) -> Self {
+        AnnealingLearner {
+            size: 3,
+            steps,
+            temperature: 2.0,
+            cooling: Cooling::default(),
+            max_size: None,
+            costs: OperatorCosts::default(),
+        }
+    }
+
+    pub fn size(mut self, size: usize) -> Self {
+        self.size = size;
+        self
+    }
+
+    pub fn temperature(mut self, temperature: f64) -> Self {
+        self.temperature = temperature;
+        self
+    }
+
+    pub fn cooling(mut self, cooling: Cooling) -> Self {
+        self.cooling = cooling;
+        self
+    }
+
+    pub fn max_size(mut self, max_size: usize) -> Self {
+        self.max_size = Some(max_size);
+        self
+    }
+
+    pub fn costs(mut self, costs: OperatorCosts) -> Self {
+        self.costs = costs;
+        self
+    }
+
+    /// Anneals for the configured number of steps, or until the deadline,
+    /// returning the fittest formula visited with its fitness,
+    /// or `None` if there is no initial formula, e.g., because the sample allows no variables.
+    pub fn anneal<const N: usize, S: SampleSource<N>, R: Rng + ?Sized>(
+        &self,
+        sample: &S,
+        deadline: Option<Instant>,
+        rng: &mut R,
+    ) -> Option<(SyntaxTree, Fitness)> {
+        let vars = sample.vars();
+        let size = self.max_size.map_or(self.size, |max| self.size.min(max));
+        let initial = gen_formulae::<N>(size, &vars);
+        if initial.is_empty() {
+            return None;
+        }
+        let mut current = initial[rng.gen_range(0..initial.len())].clone();
+        let mut fitness = Fitness::weighted(&current, sample, &self.costs);
+        let mut best = (current.clone(), fitness);
+        for step in 0..self.steps {
+            if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+                break;
+            }
+            let candidate = neighbour(&current, &vars, rng);
+            if self.max_size.is_some_and(|max| candidate.size() > max) {
+                continue;
+            }
+            let candidate_fitness = Fitness::weighted(&candidate, sample, &self.costs);
+            let loss = fitness.score() - candidate_fitness.score();
+            let temperature = self.cooling.temperature(self.temperature, step, self.steps);
+            if loss <= 0
+                || (temperature > 0.0 && rng.gen::<f64>() < (-loss as f64 / temperature).exp())
+            {
+                current = candidate;
+                fitness = candidate_fitness;
+                if fitness.score() > best.1.score() {
+                    best = (current.clone(), fitness);
+                }
+            }
+        }
+        Some(best)
+    }
+}
+
+impl<const N: usize> Learner<N> for AnnealingLearner {
+    /// Anneals for the configured number of steps, or until the time is up,
+    /// never visiting formulas larger than the maximum size, and returns the fittest formula visited.
+    fn learn(&self, sample: &Sample<N>, budget: Budget) -> LearnResult {
+        if !sample.is_solvable() {
+            return LearnResult::Unsolvable;
+        }
+
+        let mut learner = self.clone();
+        if let Some(max) = budget.max_size {
+            learner.max_size = Some(self.max_size.map_or(max, |own| own.min(max)));
+        }
+        match learner.anneal(sample, budget.deadline(), &mut rand::thread_rng()) {
+            Some((formula, _)) if sample.is_consistent(&formula) => {
+                LearnResult::Consistent(formula)
+            }
+            Some((formula, _)) => LearnResult::Approximate(formula),
+            None => LearnResult::Exhausted,
+        }
+    }
+}
+
+/// A random neighbour of a formula, for local search: a random node of the formula is either
+/// replaced by another operator (or variable) of the same arity, with binary operators mutated by [`mutate`],
+/// replaced by one of its branches, wrapped in a random unary operator,
+/// or combined with a random variable (among the given ones) under a random binary operator.
+pub fn neighbour<R: Rng + ?Sized>(formula: &SyntaxTree, vars: &[Idx], rng: &mut R) -> SyntaxTree {
+    let node = rng.gen_range(0..formula.size());
+    formula.replace_subformula(node, &mut |node| modify(node, vars, rng))
+}
+
+/// Modifies a node of a formula at random, as described by [`neighbour`].
+fn modify<R: Rng + ?Sized>(node: &SyntaxTree, vars: &[Idx], rng: &mut R) -> SyntaxTree {
+    let atom = SyntaxTree::Atom(vars[rng.gen_range(0..vars.len())]);
+    match (rng.gen_range(0..4), node) {
+        (0 | 1, SyntaxTree::Atom(_)) => atom,
+        (0, SyntaxTree::Not(branch))
+        | (0, SyntaxTree::Next(branch))
+        | (0, SyntaxTree::Globally(branch))
+        | (0, SyntaxTree::Finally(branch)) => unary_operator(branch.clone(), rng),
+        (0, _) => mutate(node, rng),
+        (1, SyntaxTree::Not(branch))
+        | (1, SyntaxTree::Next(branch))
+        | (1, SyntaxTree::Globally(branch))
+        | (1, SyntaxTree::Finally(branch)) => branch.as_ref().clone(),
+        (1, SyntaxTree::And(left, right))
+        | (1, SyntaxTree::Or(left, right))
+        | (1, SyntaxTree::Implies(left, right))
+        | (1, SyntaxTree::Until(left, right)) => {
+            if rng.gen() {
+                left.as_ref().clone()
+            } else {
+                right.as_ref().clone()
+            }
+        }
+        (2, _) => unary_operator(Arc::new(node.clone()), rng),
+        _ => {
+            let (node, atom) = (Arc::new(node.clone()), Arc::new(atom));
+            let (left, right) = if rng.gen() {
+                (node, atom)
+            } else {
+                (atom, node)
+            };
+            match rng.gen_range(0..4) {
+                0 => SyntaxTree::And(left, right),
+                1 => SyntaxTree::Or(left, right),
+                2 => SyntaxTree::Implies(left, right),
+                _ => SyntaxTree::Until(left, right),
+            }
+        }
+    }
+}
+
+fn unary_operator<R: Rng + ?Sized>(branch: Arc<SyntaxTree>, rng: &mut R) -> SyntaxTree {
+    match rng.gen_range(0..4) {
+        0 => SyntaxTree::Not(branch),
+        1 => SyntaxTree::Next(branch),
+        2 => SyntaxTree::Globally(branch),
+        _ => SyntaxTree::Finally(branch),
+    }
+}
+
+#[cfg(test)]
+mod simulated_annealing {
+    use super::*;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    #[test]
+    fn anneal() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let formula = SyntaxTree::Atom(0).until(SyntaxTree::Atom(1).globally());
+        for _ in 0..100 {
+            let neighbour = neighbour(&formula, &[0, 1], &mut rng);
+            // Neighbours grow by at most one operator and one variable.
+            assert!(neighbour.size() <= formula.size() + 2);
+        }
+
+        assert_eq!(Cooling::Linear.temperature(2.0, 5, 10), 1.0);
+        assert_eq!("exponential:0.5".parse(), Ok(Cooling::Exponential(0.5)));
+        assert!("exponential:2".parse::<Cooling>().is_err());
+
+        let sample = Sample::always_x0();
+        let learner = AnnealingLearner::new(500).max_size(4);
+        let (formula, fitness) = learner.anneal(&sample, None, &mut rng).expect("formula");
+        assert!(formula.size() <= 4);
+        assert!(fitness.score() >= Fitness::new(&SyntaxTree::Atom(0).globally(), &sample).score());
+    }
+}
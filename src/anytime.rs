@@ -0,0 +1,156 @@
+use crate::learn::*;
+use crate::source::*;
+use crate::syntax::*;
+use std::ops::ControlFlow;
+
+/// How often (in checked formulas) [`learn_anytime`] reports its progress while searching.
+const PROGRESS_INTERVAL: usize = 4096;
+
+/// A formula found by [`learn_anytime`], with how many traces of the sample it classifies correctly.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Candidate {
+    pub formula: SyntaxTree,
+    /// The positive traces satisfying the formula plus the negative traces not satisfying it.
+    pub correct: usize,
+    pub total: usize,
+}
+
+impl Candidate {
+    pub fn accuracy(&self) -> f64 {
+        if self.total == 0 {
+            1.0
+        } else {
+            self.correct as f64 / self.total as f64
+        }
+    }
+
+    pub fn is_consistent(&self) -> bool {
+        self.correct == self.total
+    }
+}
+
+/// The progress of [`learn_anytime`], reported to its callback.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Progress<'a> {
+    /// A formula classifying more traces correctly than any before.
+    Improved(&'a Candidate),
+    /// The search is checking formulas of the given size, and has checked the given number of formulas overall.
+    Searching { size: usize, checked: usize },
+}
+
+/// Counts the traces misclassified by the formula, giving up once they reach the bound.
+fn misclassified<const N: usize, S: SampleSource<N>>(
+    sample: &S,
+    formula: &SyntaxTree,
+    bound: usize,
+) -> usize {
+    let mut errors = 0;
+    let errors_positive = sample.iter_positive().filter(|trace| !formula.eval(trace));
+    let errors_negative = sample.iter_negative().filter(|trace| formula.eval(trace));
+    for _ in errors_positive.chain(errors_negative) {
+        errors += 1;
+        if errors >= bound {
+            break;
+        }
+    }
+    errors
+}
+
+/// Searches formulas by increasing size, like [`solve`], over a sample which can be any [`SampleSource`],
+/// reporting to the callback the best formula found so far (by accuracy, then size) whenever it improves,
+/// and regularly how far the search has gone.
+/// The search stops when the callback returns [`ControlFlow::Break`], or when a consistent formula is found,
+/// and returns the best formula found, if any.
+///
+/// If no formula is consistent with the sample, the search only stops when the callback says so.
+pub fn learn_anytime<const N: usize, S, F>(sample: &S, mut callback: F) -> Option<Candidate>
+where
+    S: SampleSource<N>,
+    F: FnMut(Progress) -> ControlFlow<()>,
+{
+    let vars = sample.vars();
+    let total = sample.positive_count() + sample.negative_count();
+    let mut best: Option<Candidate> = None;
+    let mut checked = 0;
+
+    for size in 1.. {
+        if callback(Progress::Searching { size, checked }).is_break() {
+            break;
+        }
+        for formula in gen_formulae_iter::<N>(size, &vars) {
+            checked += 1;
+            if checked % PROGRESS_INTERVAL == 0
+                && callback(Progress::Searching { size, checked }).is_break()
+            {
+                return best;
+            }
+
+            let bound = best
+                .as_ref()
+                .map_or(total + 1, |best| best.total - best.correct);
+            let errors = misclassified(sample, &formula, bound);
+            if errors < bound {
+                let candidate = Candidate {
+                    formula,
+                    correct: total - errors,
+                    total,
+                };
+                let stop = callback(Progress::Improved(&candidate)).is_break()
+                    || candidate.is_consistent();
+                best = Some(candidate);
+                if stop {
+                    return best;
+                }
+            }
+        }
+    }
+
+    best
+}
+
+#[cfg(test)]
+mod anytime_learning {
+    use super::*;
+    use crate::trace::*;
+
+    #[test]
+    fn improving() {
+        // No formula is consistent with the sample, as the last traces contradict each other.
+        let sample = Sample::<2> {
+            positive_traces: vec![
+                vec![[true, false], [false, false]],
+                vec![[false, false], [true, true]],
+                vec![[false, true]],
+            ],
+            negative_traces: vec![vec![[false, false]], vec![[false, true]]],
+            ..Sample::default()
+        };
+
+        let mut improvements = Vec::new();
+        let best = learn_anytime(&sample, |progress| {
+            match progress {
+                Progress::Improved(candidate) => improvements.push(candidate.clone()),
+                Progress::Searching { size, .. } if size > 3 => return ControlFlow::Break(()),
+                Progress::Searching { .. } => {}
+            }
+            ControlFlow::Continue(())
+        })
+        .expect("some formula");
+
+        assert!(improvements
+            .windows(2)
+            .all(|pair| pair[0].correct < pair[1].correct));
+        assert_eq!(improvements.last(), Some(&best));
+        assert_eq!(best.correct, 4);
+        assert!(!best.is_consistent());
+
+        // Stops as soon as a consistent formula is found.
+        let solvable = Sample {
+            negative_traces: vec![vec![[false, false]]],
+            ..sample
+        };
+        let best = learn_anytime(&solvable, |_| ControlFlow::Continue(())).expect("solvable");
+        assert!(best.is_consistent());
+        assert_eq!(best.accuracy(), 1.0);
+    }
+}
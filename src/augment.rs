@@ -0,0 +1,156 @@
+use crate::trace::*;
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+/// A random alteration of a trace, used to produce traces close to existing ones.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Perturbation {
+    /// Flips the value of the given number of variables at random times (possibly flipping the same value twice).
+    Flip(usize),
+    /// Cuts the trace at a random time, keeping a non-empty prefix.
+    Truncate,
+    /// Shuffles the states of the trace from a random time onward.
+    ShuffleSuffix,
+}
+
+impl Perturbation {
+    /// Returns a perturbed copy of the trace.
+    pub fn apply<const N: usize, R: Rng + ?Sized>(
+        &self,
+        trace: &[[bool; N]],
+        rng: &mut R,
+    ) -> Trace<N> {
+        let mut trace = trace.to_vec();
+        if trace.is_empty() {
+            return trace;
+        }
+
+        match self {
+            Perturbation::Flip(flips) => {
+                if N > 0 {
+                    for _ in 0..*flips {
+                        let time = rng.gen_range(0..trace.len());
+                        let var = rng.gen_range(0..N);
+                        trace[time][var] = !trace[time][var];
+                    }
+                }
+            }
+            Perturbation::Truncate => {
+                let len = rng.gen_range(1..=trace.len());
+                trace.truncate(len);
+            }
+            Perturbation::ShuffleSuffix => {
+                let start = rng.gen_range(0..trace.len());
+                trace[start..].shuffle(rng);
+            }
+        }
+        trace
+    }
+}
+
+impl<const N: usize> Sample<N> {
+    /// Adds up to `count` near-miss negative traces, obtained by perturbing random positive traces
+    /// with a random perturbation among the given ones.
+    /// Since a perturbed trace is not necessarily negative, it is only added if `is_negative` holds on it
+    /// (e.g., if it violates the ground-truth formula, or if a domain expert rejects it).
+    /// Traces already in the sample are skipped, and at most `100 * count` perturbations are tried.
+    /// Returns the number of negative traces added.
+    pub fn augment_negatives<R: Rng + ?Sized>(
+        &mut self,
+        perturbations: &[Perturbation],
+        count: usize,
+        mut is_negative: impl FnMut(&Trace<N>) -> bool,
+        rng: &mut R,
+    ) -> usize {
+        if self.positive_traces.is_empty() || perturbations.is_empty() {
+            return 0;
+        }
+
+        let mut added = 0;
+        for _ in 0..100 * count {
+            if added == count {
+                break;
+            }
+
+            let seed = self
+                .positive_traces
+                .choose(rng)
+                .expect("positive traces are not empty");
+            let perturbation = perturbations
+                .choose(rng)
+                .expect("perturbations are not empty");
+            let trace = perturbation.apply(seed, rng);
+            if !self.negative_traces.contains(&trace)
+                && is_negative(&trace)
+                && self.add_negative_trace(trace).is_ok()
+            {
+                added += 1;
+            }
+        }
+        added
+    }
+}
+
+#[cfg(test)]
+mod augmentation {
+    use super::*;
+    use crate::syntax::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use std::sync::Arc;
+
+    #[test]
+    fn perturbations() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let trace = vec![[true, false], [false, false], [true, true], [false, true]];
+
+        let flipped = Perturbation::Flip(1).apply(&trace, &mut rng);
+        let diff = flipped
+            .iter()
+            .flatten()
+            .zip(trace.iter().flatten())
+            .filter(|(a, b)| a != b)
+            .count();
+        assert_eq!(diff, 1);
+
+        let truncated = Perturbation::Truncate.apply(&trace, &mut rng);
+        assert!(!truncated.is_empty() && trace.starts_with(&truncated));
+
+        let mut shuffled = Perturbation::ShuffleSuffix.apply(&trace, &mut rng);
+        shuffled.sort();
+        let mut sorted = trace.clone();
+        sorted.sort();
+        assert_eq!(shuffled, sorted);
+    }
+
+    #[test]
+    fn near_misses() {
+        let mut rng = StdRng::seed_from_u64(0);
+        // G(x0 -> F x1)
+        let formula = SyntaxTree::Globally(Arc::new(SyntaxTree::Implies(
+            Arc::new(SyntaxTree::Atom(0)),
+            Arc::new(SyntaxTree::Finally(Arc::new(SyntaxTree::Atom(1)))),
+        )));
+        let mut sample = Sample::<2> {
+            positive_traces: vec![
+                vec![[true, false], [false, false], [false, true], [false, false]],
+                vec![[false, false], [true, true], [true, false], [false, true]],
+            ],
+            ..Sample::default()
+        };
+
+        let added = sample.augment_negatives(
+            &[
+                Perturbation::Flip(1),
+                Perturbation::Truncate,
+                Perturbation::ShuffleSuffix,
+            ],
+            3,
+            |trace| !formula.eval(trace),
+            &mut rng,
+        );
+        assert_eq!(added, 3);
+        assert_eq!(sample.negative_traces(), 3);
+        assert!(sample.is_consistent(&formula));
+    }
+}
@@ -0,0 +1,106 @@
+use crate::trace::*;
+use rand::seq::SliceRandom;
+use rand::Rng;
+use std::str::FromStr;
+
+/// How to even out the number of positive and negative traces of a sample.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BalanceStrategy {
+    /// Keep a random subset of the majority class, as large as the minority class.
+    Undersample,
+    /// Add random copies (drawn with replacement) of the minority class, until it is as large as the majority class.
+    Oversample,
+}
+
+impl FromStr for BalanceStrategy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "undersample" | "under" => Ok(BalanceStrategy::Undersample),
+            "oversample" | "over" => Ok(BalanceStrategy::Oversample),
+            _ => Err(format!("unknown balance strategy: {s}")),
+        }
+    }
+}
+
+impl<const N: usize> Sample<N> {
+    /// Returns a copy of the sample with as many positive as negative traces,
+    /// so that measures counting satisfied traces (such as fitness functions) are not dominated by the majority class.
+    /// Oversampling repeats traces (with their metadata), which then weigh more.
+    /// Lassos are left untouched, and a sample with no traces of some class cannot be balanced, so it is returned as is.
+    pub fn balance<R: Rng + ?Sized>(&self, strategy: BalanceStrategy, rng: &mut R) -> Sample<N> {
+        let mut balanced = self.clone();
+        let positives = self.positive_traces.len();
+        let negatives = self.negative_traces.len();
+        if positives == 0 || negatives == 0 {
+            return balanced;
+        }
+
+        let (minority, majority) = if positives < negatives {
+            (Label::Positive, Label::Negative)
+        } else {
+            (Label::Negative, Label::Positive)
+        };
+        let (minority_len, majority_len) = (positives.min(negatives), positives.max(negatives));
+        match strategy {
+            BalanceStrategy::Undersample => {
+                let mut indices = (0..majority_len).collect::<Vec<_>>();
+                indices.shuffle(rng);
+                indices.truncate(minority_len);
+                balanced.select_traces(majority, &indices);
+            }
+            BalanceStrategy::Oversample => {
+                let indices = (0..minority_len)
+                    .chain((minority_len..majority_len).map(|_| rng.gen_range(0..minority_len)))
+                    .collect::<Vec<_>>();
+                balanced.select_traces(minority, &indices);
+            }
+        }
+        balanced
+    }
+}
+
+#[cfg(test)]
+mod balancing {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    fn sample() -> Sample<1> {
+        Sample {
+            positive_traces: vec![vec![[true]], vec![[true], [true]]],
+            negative_traces: (1..=10).map(|len| vec![[false]; len]).collect(),
+            ..Sample::default()
+        }
+    }
+
+    #[test]
+    fn undersample() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let sample = sample();
+
+        let balanced = sample.balance(BalanceStrategy::Undersample, &mut rng);
+        assert_eq!(balanced.positive_traces, sample.positive_traces);
+        assert_eq!(balanced.negative_traces(), 2);
+        assert!(balanced
+            .negative_traces
+            .iter()
+            .all(|trace| sample.negative_traces.contains(trace)));
+    }
+
+    #[test]
+    fn oversample() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let sample = sample();
+
+        let balanced = sample.balance(BalanceStrategy::Oversample, &mut rng);
+        assert_eq!(balanced.negative_traces, sample.negative_traces);
+        assert_eq!(balanced.positive_traces(), 10);
+        assert_eq!(balanced.positive_traces[..2], sample.positive_traces[..]);
+        assert!(balanced
+            .positive_traces
+            .iter()
+            .all(|trace| sample.positive_traces.contains(trace)));
+    }
+}
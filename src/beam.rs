@@ -0,0 +1,145 @@
+use crate::learn::*;
+use crate::learner::*;
+use crate::syntax::*;
+use crate::trace::*;
+use std::sync::Arc;
+use std::time::Instant;
+
+/// Learns formulas by beam search: formulas of each size are built from the kept formulas of smaller sizes,
+/// as in the exhaustive search of [`solve`], but only the most accurate ones on the sample are kept, up to the beam width.
+/// It is a middle ground between exhaustive enumeration, which a wide enough beam amounts to,
+/// and the randomness of the genetic algorithm.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BeamLearner {
+    /// The number of formulas kept for each size.
+    pub width: usize,
+    /// The maximum size of the formulas, which the budget can lower.
+    pub max_size: usize,
+}
+
+impl Default for BeamLearner {
+    fn default() -> Self {
+        BeamLearner {
+            width: 100,
+            max_size: 10,
+        }
+    }
+}
+
+impl BeamLearner {
+    pub fn width(mut self, width: usize) -> Self {
+        self.width = width;
+        self
+    }
+
+    pub fn max_size(mut self, max_size: usize) -> Self {
+        self.max_size = max_size;
+        self
+    }
+}
+
+impl<const N: usize> Learner<N> for BeamLearner {
+    /// Searches by increasing size until a consistent formula is found,
+    /// and otherwise returns the most accurate (and then smallest) formula found.
+    fn learn(&self, sample: &Sample<N>, budget: Budget) -> LearnResult {
+        if !sample.is_solvable() {
+            return LearnResult::Unsolvable;
+        }
+
+        let deadline = budget.deadline();
+        let max_size = budget
+            .max_size
+            .map_or(self.max_size, |max| max.min(self.max_size));
+        let vars = sample.vars();
+        let mut beams: Vec<Vec<Arc<SyntaxTree>>> = vec![Vec::new()];
+        let mut best: Option<(usize, SyntaxTree)> = None;
+        for size in 1..=max_size {
+            if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+                break;
+            }
+
+            let mut candidates = Vec::new();
+            if size == 1 {
+                candidates.extend(vars.iter().map(|var| SyntaxTree::Atom(*var)));
+            } else {
+                for child in &beams[size - 1] {
+                    push_unary(&mut candidates, child.clone());
+                }
+                for left_size in 1..(size - 1) {
+                    for left in &beams[left_size] {
+                        for right in &beams[size - 1 - left_size] {
+                            push_binary(&mut candidates, left.clone(), right.clone());
+                        }
+                    }
+                }
+            }
+
+            let mut scored = candidates
+                .into_iter()
+                .map(|formula| (sample.confusion(&formula).misclassified(), formula))
+                .collect::<Vec<_>>();
+            // The sort is stable, so the beam is deterministic.
+            scored.sort_by_key(|(errors, _)| *errors);
+            scored.truncate(self.width);
+            if let Some((errors, formula)) = scored.first() {
+                if *errors == 0 {
+                    return LearnResult::Consistent(formula.clone());
+                }
+                if best
+                    .as_ref()
+                    .is_none_or(|(best_errors, _)| errors < best_errors)
+                {
+                    best = Some((*errors, formula.clone()));
+                }
+            }
+            beams.push(
+                scored
+                    .into_iter()
+                    .map(|(_, formula)| Arc::new(formula))
+                    .collect(),
+            );
+        }
+
+        match best {
+            Some((_, formula)) => LearnResult::Approximate(formula),
+            None => LearnResult::Exhausted,
+        }
+    }
+}
+
+#[cfg(test)]
+mod beam_search {
+    use super::*;
+
+    #[test]
+    fn learn() {
+        // Positive traces are those where x0 holds at first and x1 holds eventually.
+        let sample = Sample::<2> {
+            positive_traces: vec![
+                vec![[true, false], [false, true]],
+                vec![[true, true]],
+                vec![[true, false], [false, false], [true, true]],
+            ],
+            negative_traces: vec![
+                vec![[false, true], [true, false]],
+                vec![[true, false], [true, false]],
+                vec![[false, false]],
+            ],
+            ..Sample::default()
+        };
+        let result = BeamLearner::default().learn(&sample, Budget::default());
+        let formula = result.formula().expect("some formula");
+        assert!(result.is_consistent());
+        assert_eq!(
+            formula.size(),
+            solve(&sample, false, false).expect("solvable").size()
+        );
+
+        // A narrow and shallow beam only finds an approximation.
+        let narrow = BeamLearner::default().width(2).max_size(2);
+        assert!(matches!(
+            narrow.learn(&sample, Budget::default()),
+            LearnResult::Approximate(_)
+        ));
+    }
+}
@@ -0,0 +1,283 @@
+use crate::lengths::*;
+use crate::metadata::*;
+use crate::migrate::*;
+use crate::trace::*;
+use std::io::{self, Read, Write};
+
+/// The first bytes of a sample in binary format, used to recognize it.
+pub const BINARY_MAGIC: &[u8; 4] = b"LTLB";
+
+/// Whether the contents of a file are a sample in binary format.
+pub fn is_binary_sample(contents: &[u8]) -> bool {
+    contents.starts_with(BINARY_MAGIC)
+}
+
+/// Reads the number of variables from the header of a sample in binary format.
+pub(crate) fn binary_sample_vars(contents: &[u8]) -> io::Result<usize> {
+    let mut reader = contents;
+    read_header(&mut reader).map(|(_, vars)| vars)
+}
+
+fn invalid_data(msg: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+fn read_u32<R: Read>(reader: &mut R) -> io::Result<u32> {
+    let mut bytes = [0; 4];
+    reader.read_exact(&mut bytes)?;
+    Ok(u32::from_le_bytes(bytes))
+}
+
+pub(crate) fn read_u64<R: Read>(reader: &mut R) -> io::Result<usize> {
+    let mut bytes = [0; 8];
+    reader.read_exact(&mut bytes)?;
+    usize::try_from(u64::from_le_bytes(bytes))
+        .map_err(|_| invalid_data("length out of range".to_string()))
+}
+
+fn write_u64<W: Write>(writer: &mut W, n: usize) -> io::Result<()> {
+    writer.write_all(&(n as u64).to_le_bytes())
+}
+
+/// Reads the magic bytes, the format version and the number of variables.
+pub(crate) fn read_header<R: Read>(reader: &mut R) -> io::Result<(u32, usize)> {
+    let mut magic = [0; 4];
+    reader.read_exact(&mut magic)?;
+    if magic != *BINARY_MAGIC {
+        return Err(invalid_data("not a binary sample".to_string()));
+    }
+    let version = read_u32(reader)?;
+    if version > SAMPLE_FORMAT_VERSION {
+        return Err(invalid_data(format!(
+            "sample format version {version} is newer than the supported version {SAMPLE_FORMAT_VERSION}"
+        )));
+    }
+    let vars = read_u32(reader)? as usize;
+    Ok((version, vars))
+}
+
+/// The number of bytes taken by a state of `N` variables, packed as bits.
+pub(crate) const fn state_bytes(n: usize) -> usize {
+    n.div_ceil(8)
+}
+
+fn write_trace<const N: usize, W: Write>(writer: &mut W, trace: &[[bool; N]]) -> io::Result<()> {
+    write_u64(writer, trace.len())?;
+    let mut bytes = vec![0u8; state_bytes(N) * trace.len()];
+    for (state, chunk) in trace.iter().zip(bytes.chunks_mut(state_bytes(N).max(1))) {
+        for (var, val) in state.iter().enumerate() {
+            chunk[var / 8] |= (*val as u8) << (var % 8);
+        }
+    }
+    writer.write_all(&bytes)
+}
+
+pub(crate) fn read_trace<const N: usize, R: Read>(reader: &mut R) -> io::Result<Trace<N>> {
+    let len = read_u64(reader)?;
+    let mut bytes = vec![0u8; state_bytes(N)];
+    let mut trace = Vec::with_capacity(len.min(1 << 20));
+    for _ in 0..len {
+        reader.read_exact(&mut bytes)?;
+        let mut state = [false; N];
+        for (var, val) in state.iter_mut().enumerate() {
+            *val = bytes[var / 8] & (1 << (var % 8)) != 0;
+        }
+        trace.push(state);
+    }
+    Ok(trace)
+}
+
+fn write_traces<const N: usize, W: Write>(writer: &mut W, traces: &[Trace<N>]) -> io::Result<()> {
+    write_u64(writer, traces.len())?;
+    traces
+        .iter()
+        .try_for_each(|trace| write_trace(writer, trace))
+}
+
+fn read_traces<const N: usize, R: Read>(reader: &mut R) -> io::Result<Vec<Trace<N>>> {
+    let count = read_u64(reader)?;
+    (0..count).map(|_| read_trace(reader)).collect()
+}
+
+fn write_lassos<const N: usize, W: Write>(writer: &mut W, lassos: &[Lasso<N>]) -> io::Result<()> {
+    write_u64(writer, lassos.len())?;
+    lassos.iter().try_for_each(|lasso| {
+        write_trace(writer, &lasso.prefix)?;
+        write_trace(writer, &lasso.cycle)
+    })
+}
+
+fn read_lassos<const N: usize, R: Read>(reader: &mut R) -> io::Result<Vec<Lasso<N>>> {
+    let count = read_u64(reader)?;
+    (0..count)
+        .map(|_| {
+            let prefix = read_trace(reader)?;
+            let cycle = read_trace(reader)?;
+            if cycle.is_empty() {
+                return Err(invalid_data("lasso with empty cycle".to_string()));
+            }
+            Ok(Lasso::new(prefix, cycle))
+        })
+        .collect()
+}
+
+fn write_string<W: Write>(writer: &mut W, string: &str) -> io::Result<()> {
+    write_u64(writer, string.len())?;
+    writer.write_all(string.as_bytes())
+}
+
+pub(crate) fn read_string<R: Read>(reader: &mut R) -> io::Result<String> {
+    let mut bytes = vec![0; read_u64(reader)?];
+    reader.read_exact(&mut bytes)?;
+    String::from_utf8(bytes).map_err(|err| invalid_data(err.to_string()))
+}
+
+fn write_metadata<W: Write>(writer: &mut W, metadata: &MetadataMap) -> io::Result<()> {
+    write_u64(writer, metadata.len())?;
+    for (index, data) in metadata {
+        write_u64(writer, *index)?;
+        write_u64(writer, data.len())?;
+        for (key, value) in data {
+            write_string(writer, key)?;
+            write_string(writer, value)?;
+        }
+    }
+    Ok(())
+}
+
+fn read_metadata<R: Read>(reader: &mut R) -> io::Result<MetadataMap> {
+    let count = read_u64(reader)?;
+    (0..count)
+        .map(|_| {
+            let index = read_u64(reader)?;
+            let entries = read_u64(reader)?;
+            let data = (0..entries)
+                .map(|_| Ok((read_string(reader)?, read_string(reader)?)))
+                .collect::<io::Result<Metadata>>()?;
+            Ok((index, data))
+        })
+        .collect()
+}
+
+fn length_policy_byte(policy: LengthPolicy) -> u8 {
+    match policy {
+        LengthPolicy::Natural => 0,
+        LengthPolicy::Pad => 1,
+        LengthPolicy::Truncate => 2,
+        LengthPolicy::Strict => 3,
+    }
+}
+
+fn length_policy_from_byte(byte: u8) -> io::Result<LengthPolicy> {
+    match byte {
+        0 => Ok(LengthPolicy::Natural),
+        1 => Ok(LengthPolicy::Pad),
+        2 => Ok(LengthPolicy::Truncate),
+        3 => Ok(LengthPolicy::Strict),
+        _ => Err(invalid_data(format!("unknown length policy {byte}"))),
+    }
+}
+
+impl<const N: usize> Sample<N> {
+    /// Writes the sample in a compact binary format, much faster to load than RON or JSON for large samples.
+    /// States are packed as bits, and the format starts with [`BINARY_MAGIC`],
+    /// so that it is recognized when loading (see [`Sample::load`] and [`visit_sample`](crate::visit_sample)).
+    pub fn write_binary<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        writer.write_all(BINARY_MAGIC)?;
+        writer.write_all(&SAMPLE_FORMAT_VERSION.to_le_bytes())?;
+        writer.write_all(&(N as u32).to_le_bytes())?;
+        for name in &self.var_names {
+            write_string(&mut writer, name)?;
+        }
+        write_traces(&mut writer, &self.positive_traces)?;
+        write_traces(&mut writer, &self.negative_traces)?;
+        write_lassos(&mut writer, &self.positive_lassos)?;
+        write_lassos(&mut writer, &self.negative_lassos)?;
+        write_metadata(&mut writer, &self.positive_metadata)?;
+        write_metadata(&mut writer, &self.negative_metadata)?;
+        writer.write_all(&[length_policy_byte(self.length_policy)])?;
+        writer.flush()
+    }
+
+    /// Reads a sample written by [`Sample::write_binary`].
+    pub fn read_binary<R: Read>(mut reader: R) -> io::Result<Self> {
+        let (version, vars) = read_header(&mut reader)?;
+        if vars != N {
+            return Err(invalid_data(format!(
+                "sample has {vars} variables, but {N} were expected"
+            )));
+        }
+
+        let mut sample = Sample {
+            version,
+            ..Sample::default()
+        };
+        for name in sample.var_names.iter_mut() {
+            *name = read_string(&mut reader)?;
+        }
+        sample.positive_traces = read_traces(&mut reader)?;
+        sample.negative_traces = read_traces(&mut reader)?;
+        sample.positive_lassos = read_lassos(&mut reader)?;
+        sample.negative_lassos = read_lassos(&mut reader)?;
+        // Metadata was introduced with version 3.
+        if version >= 3 {
+            sample.positive_metadata = read_metadata(&mut reader)?;
+            sample.negative_metadata = read_metadata(&mut reader)?;
+        }
+        // The length policy was introduced with version 4.
+        if version >= 4 {
+            let mut byte = [0];
+            reader.read_exact(&mut byte)?;
+            sample.length_policy = length_policy_from_byte(byte[0])?;
+        }
+        Ok(sample)
+    }
+}
+
+#[cfg(test)]
+mod binary_format {
+    use super::*;
+    use crate::load::*;
+
+    struct CountVars;
+
+    impl SampleVisitor for CountVars {
+        type Output = usize;
+
+        fn visit<const N: usize>(self, _sample: Sample<N>) -> Self::Output {
+            N
+        }
+    }
+
+    #[test]
+    fn roundtrip() {
+        let sample = Sample::<10> {
+            positive_traces: vec![vec![[true; 10], [false; 10]]],
+            negative_traces: vec![vec![[
+                true, false, false, true, false, false, false, false, false, true,
+            ]]],
+            positive_lassos: vec![Lasso::new(vec![], vec![[false; 10]])],
+            ..Sample::default()
+        };
+
+        let mut contents = Vec::new();
+        sample.write_binary(&mut contents).expect("write sample");
+        assert!(is_binary_sample(&contents));
+        // Header, names, and lengths of 8 bytes followed by states of 2 bytes.
+        let names = 10 * (8 + 2);
+        let traces = (8 + 8 + 2 * 2) + (8 + 8 + 2);
+        let lassos = (8 + 8 + (8 + 2)) + 8;
+        let metadata = 8 + 8;
+        assert_eq!(contents.len(), 12 + names + traces + lassos + metadata + 1);
+
+        let loaded = Sample::<10>::read_binary(contents.as_slice()).expect("read sample");
+        assert_eq!(loaded.var_names, sample.var_names);
+        assert_eq!(loaded.positive_traces, sample.positive_traces);
+        assert_eq!(loaded.negative_traces, sample.negative_traces);
+        assert_eq!(loaded.positive_lassos, sample.positive_lassos);
+        assert!(Sample::<9>::read_binary(contents.as_slice()).is_err());
+
+        let vars = visit_sample_contents(&contents, SampleFormat::Ron, CountVars).expect("visit");
+        assert_eq!(vars, 10);
+    }
+}
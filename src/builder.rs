@@ -0,0 +1,300 @@
+use crate::trace::*;
+use std::error::Error;
+use std::fmt;
+
+/// An error found while validating a sample built with a [`SampleBuilder`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SampleError {
+    /// The sample is built with a different number of variables than the builder was created with.
+    VarsMismatch { expected: usize, found: usize },
+    /// The number of variable names differs from the number of variables.
+    VarNamesMismatch { expected: usize, found: usize },
+    /// A state of a trace has the wrong number of variables.
+    StateWidth {
+        label: Label,
+        trace: usize,
+        time: usize,
+        expected: usize,
+        found: usize,
+    },
+    /// A state of a lasso has the wrong number of variables.
+    LassoStateWidth {
+        label: Label,
+        lasso: usize,
+        position: usize,
+        expected: usize,
+        found: usize,
+    },
+    /// A trace has no states, so no formula can be evaluated on it.
+    EmptyTrace { label: Label, trace: usize },
+    /// A lasso has no states in its cycle, so it does not represent an infinite trace.
+    EmptyCycle { label: Label, lasso: usize },
+    /// A state of a numeric trace has no value for the signal of a predicate.
+    MissingSignal {
+        label: Label,
+        trace: usize,
+        time: usize,
+        signal: usize,
+    },
+    /// The same trace is both positive and negative.
+    Contradiction { positive: usize, negative: usize },
+    /// The same lasso is both positive and negative.
+    LassoContradiction { positive: usize, negative: usize },
+}
+
+impl fmt::Display for SampleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SampleError::VarsMismatch { expected, found } => write!(
+                f,
+                "sample built over {found} variables, but builder has {expected} variables"
+            ),
+            SampleError::VarNamesMismatch { expected, found } => {
+                write!(f, "{found} variable names given for {expected} variables")
+            }
+            SampleError::StateWidth {
+                label,
+                trace,
+                time,
+                expected,
+                found,
+            } => write!(
+                f,
+                "{label:?} trace {trace} has {found} variables at time {time}, but {expected} were expected"
+            ),
+            SampleError::LassoStateWidth {
+                label,
+                lasso,
+                position,
+                expected,
+                found,
+            } => write!(
+                f,
+                "{label:?} lasso {lasso} has {found} variables at position {position}, but {expected} were expected"
+            ),
+            SampleError::EmptyTrace { label, trace } => write!(f, "{label:?} trace {trace} is empty"),
+            SampleError::EmptyCycle { label, lasso } => {
+                write!(f, "{label:?} lasso {lasso} has an empty cycle")
+            }
+            SampleError::MissingSignal {
+                label,
+                trace,
+                time,
+                signal,
+            } => write!(
+                f,
+                "{label:?} trace {trace} has no value for signal {signal} at time {time}"
+            ),
+            SampleError::Contradiction { positive, negative } => write!(
+                f,
+                "Positive trace {positive} is the same as Negative trace {negative}"
+            ),
+            SampleError::LassoContradiction { positive, negative } => write!(
+                f,
+                "Positive lasso {positive} is the same as Negative lasso {negative}"
+            ),
+        }
+    }
+}
+
+impl Error for SampleError {}
+
+/// Builds a [`Sample`] from traces given at runtime, validating them.
+///
+/// ```
+/// use learn_ltl::SampleBuilder;
+///
+/// let sample = SampleBuilder::new(2)
+///     .positive(vec![[true, false], [false, true]])
+///     .negative(vec![vec![false, false]])
+///     .build::<2>()
+///     .expect("valid sample");
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct SampleBuilder {
+    vars: usize,
+    var_names: Option<Vec<String>>,
+    positive_traces: Vec<Vec<Vec<bool>>>,
+    negative_traces: Vec<Vec<Vec<bool>>>,
+}
+
+impl SampleBuilder {
+    /// Creates a builder for a sample over the given number of variables.
+    pub fn new(vars: usize) -> Self {
+        SampleBuilder {
+            vars,
+            ..Default::default()
+        }
+    }
+
+    /// Sets custom names for the variables of the sample.
+    pub fn var_names<S: Into<String>>(mut self, names: impl IntoIterator<Item = S>) -> Self {
+        self.var_names = Some(names.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Adds a positive trace, given as a sequence of states.
+    pub fn positive<S: AsRef<[bool]>>(mut self, trace: impl IntoIterator<Item = S>) -> Self {
+        self.positive_traces.push(collect_trace(trace));
+        self
+    }
+
+    /// Adds a negative trace, given as a sequence of states.
+    pub fn negative<S: AsRef<[bool]>>(mut self, trace: impl IntoIterator<Item = S>) -> Self {
+        self.negative_traces.push(collect_trace(trace));
+        self
+    }
+
+    /// Validates the traces and builds the sample, where `N` has to match the number of variables of the builder.
+    /// Traces repeated within the same set are only added once.
+    pub fn build<const N: usize>(self) -> Result<Sample<N>, SampleError> {
+        if N != self.vars {
+            return Err(SampleError::VarsMismatch {
+                expected: self.vars,
+                found: N,
+            });
+        }
+
+        let mut sample = Sample::<N>::default();
+        if let Some(names) = self.var_names {
+            sample.var_names =
+                names
+                    .try_into()
+                    .map_err(|names: Vec<String>| SampleError::VarNamesMismatch {
+                        expected: N,
+                        found: names.len(),
+                    })?;
+        }
+
+        let positive_traces = convert_traces::<N>(Label::Positive, self.positive_traces)?;
+        let negative_traces = convert_traces::<N>(Label::Negative, self.negative_traces)?;
+
+        for (positive, trace) in positive_traces.iter().enumerate() {
+            if let Some(negative) = negative_traces.iter().position(|neg| neg == trace) {
+                return Err(SampleError::Contradiction { positive, negative });
+            }
+        }
+
+        for trace in positive_traces {
+            if !sample.positive_traces.contains(&trace) {
+                sample.positive_traces.push(trace);
+            }
+        }
+        for trace in negative_traces {
+            if !sample.negative_traces.contains(&trace) {
+                sample.negative_traces.push(trace);
+            }
+        }
+
+        Ok(sample)
+    }
+}
+
+fn collect_trace<S: AsRef<[bool]>>(trace: impl IntoIterator<Item = S>) -> Vec<Vec<bool>> {
+    trace
+        .into_iter()
+        .map(|state| state.as_ref().to_vec())
+        .collect()
+}
+
+fn convert_traces<const N: usize>(
+    label: Label,
+    traces: Vec<Vec<Vec<bool>>>,
+) -> Result<Vec<Trace<N>>, SampleError> {
+    traces
+        .into_iter()
+        .enumerate()
+        .map(|(trace_idx, trace)| {
+            if trace.is_empty() {
+                return Err(SampleError::EmptyTrace {
+                    label,
+                    trace: trace_idx,
+                });
+            }
+            trace
+                .into_iter()
+                .enumerate()
+                .map(|(time, state)| {
+                    let found = state.len();
+                    state.try_into().map_err(|_| SampleError::StateWidth {
+                        label,
+                        trace: trace_idx,
+                        time,
+                        expected: N,
+                        found,
+                    })
+                })
+                .collect()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod validation {
+    use super::*;
+
+    #[test]
+    fn build() {
+        let sample = SampleBuilder::new(2)
+            .var_names(["request", "grant"])
+            .positive(vec![[true, false], [false, true]])
+            .positive(vec![[true, false], [false, true]])
+            .negative(vec![vec![false, false]])
+            .build::<2>()
+            .expect("valid sample");
+
+        assert_eq!(
+            sample.var_names,
+            ["request".to_string(), "grant".to_string()]
+        );
+        assert_eq!(sample.positive_traces(), 1);
+        assert_eq!(sample.negative_traces(), 1);
+    }
+
+    #[test]
+    fn errors() {
+        let builder = SampleBuilder::new(2).positive(vec![[true, false]]);
+        assert_eq!(
+            builder.clone().build::<3>().unwrap_err(),
+            SampleError::VarsMismatch {
+                expected: 2,
+                found: 3
+            }
+        );
+        assert_eq!(
+            builder
+                .clone()
+                .negative(vec![[true, false]])
+                .build::<2>()
+                .unwrap_err(),
+            SampleError::Contradiction {
+                positive: 0,
+                negative: 0
+            }
+        );
+        assert_eq!(
+            builder
+                .clone()
+                .negative(vec![vec![true, false], vec![true]])
+                .build::<2>()
+                .unwrap_err(),
+            SampleError::StateWidth {
+                label: Label::Negative,
+                trace: 0,
+                time: 1,
+                expected: 2,
+                found: 1
+            }
+        );
+        assert_eq!(
+            builder
+                .negative(Vec::<[bool; 2]>::new())
+                .build::<2>()
+                .unwrap_err(),
+            SampleError::EmptyTrace {
+                label: Label::Negative,
+                trace: 0
+            }
+        );
+    }
+}
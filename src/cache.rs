@@ -0,0 +1,193 @@
+use crate::index::trace_hash;
+use crate::learn::*;
+use crate::source::*;
+use crate::syntax::*;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::time::Duration;
+
+/// The progress of the brute-force search on a sample.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CacheEntry {
+    /// A hash of the sample (see [`sample_hash`]).
+    pub sample: u64,
+    /// The largest size whose formulas have all been checked, with no solution.
+    pub searched: usize,
+    /// The solution found, if any.
+    pub solution: Option<SyntaxTree>,
+}
+
+/// A persistent record of the progress of brute-force searches, indexed by a hash of their samples,
+/// so that a search that was interrupted (e.g., killed, or out of time) resumes at the size it reached,
+/// rather than starting from scratch, and a solved sample is not searched again.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SearchCache {
+    pub entries: Vec<CacheEntry>,
+}
+
+impl SearchCache {
+    /// Opens the cache stored in the given file, or an empty one if the file does not exist yet.
+    pub fn open(path: &Path) -> io::Result<Self> {
+        if path.exists() {
+            Self::load(path)
+        } else {
+            Ok(SearchCache::default())
+        }
+    }
+
+    /// Loads a cache from file.
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let contents = fs::read(path)?;
+        ron::de::from_bytes(&contents)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+    }
+
+    /// Saves the cache to file.
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let contents = ron::ser::to_string(self)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+        fs::write(path, contents)
+    }
+
+    /// The entry of the sample with the given hash, if any.
+    pub fn get(&self, sample: u64) -> Option<&CacheEntry> {
+        self.entries.iter().find(|entry| entry.sample == sample)
+    }
+
+    /// The entry of the sample with the given hash, created if missing.
+    fn entry(&mut self, sample: u64) -> &mut CacheEntry {
+        match self.entries.iter().position(|entry| entry.sample == sample) {
+            Some(index) => &mut self.entries[index],
+            None => {
+                self.entries.push(CacheEntry {
+                    sample,
+                    searched: 0,
+                    solution: None,
+                });
+                self.entries.last_mut().expect("entry just added")
+            }
+        }
+    }
+}
+
+/// A hash of the content of the sample, which can be any [`SampleSource`],
+/// and of the variables formulas are allowed to use, stable across runs.
+pub fn sample_hash<const N: usize, S: SampleSource<N>>(sample: &S) -> u64 {
+    const PRIME: u64 = 0x100000001b3;
+
+    let combine = |hash: u64, value: u64| (hash ^ value).wrapping_mul(PRIME);
+    let positive = sample
+        .iter_positive()
+        .fold(N as u64, |hash, trace| combine(hash, trace_hash(&trace)));
+    let negative = sample
+        .iter_negative()
+        .fold(combine(positive, u64::MAX), |hash, trace| {
+            combine(hash, trace_hash(&trace))
+        });
+    sample
+        .vars()
+        .into_iter()
+        .fold(combine(negative, u64::MAX), |hash, var| {
+            combine(hash, var as u64)
+        })
+}
+
+/// Find a formula consistent with the given sample, which can be any [`SampleSource`], within the budget,
+/// like [`solve_with_budget`], recording the progress of the search in the cache stored in the given file.
+/// The search starts after the largest size already searched for the same sample,
+/// and a solution already found is returned straight away (with no formula checked).
+/// The cache is saved after every size searched, so that the progress survives an interrupted run.
+pub fn solve_cached<const N: usize, S: SampleSource<N> + Sync>(
+    sample: &S,
+    multithread: bool,
+    log: bool,
+    budget: SearchBudget,
+    path: &Path,
+) -> io::Result<(Option<SyntaxTree>, SearchStats)> {
+    let hash = sample_hash(sample);
+    let mut cache = SearchCache::open(path)?;
+    let entry = cache.entry(hash);
+    if let Some(solution) = &entry.solution {
+        let stats = SearchStats {
+            end: SearchEnd::Solved,
+            formulas: 0,
+            size: solution.size(),
+            duration: Duration::ZERO,
+            sizes: Vec::new(),
+        };
+        return Ok((Some(solution.clone()), stats));
+    }
+    let first_size = entry.searched + 1;
+    if log && first_size > 1 {
+        println!("Resuming search after size {}", entry.searched);
+    }
+
+    let mut saved = Ok(());
+    let mut on_exhausted = |size: usize| {
+        cache.entry(hash).searched = size;
+        if saved.is_ok() {
+            saved = cache.save(path);
+        }
+    };
+    let options = SearchOptions {
+        first_size,
+        on_exhausted: Some(&mut on_exhausted),
+        ..SearchOptions::default()
+    };
+    let (solution, stats) = search(sample, multithread, log, budget, options);
+    saved?;
+    if solution.is_some() {
+        cache.entry(hash).solution = solution.clone();
+        cache.save(path)?;
+    }
+    Ok((solution, stats))
+}
+
+#[cfg(test)]
+mod search_cache {
+    use super::*;
+    use crate::trace::*;
+
+    #[test]
+    fn resume() {
+        let path = std::env::temp_dir().join(format!("learn_ltl_cache_{}.ron", std::process::id()));
+        // Positive traces are those where x0 holds next and x1 holds eventually.
+        let sample = Sample::<2> {
+            positive_traces: vec![
+                vec![[false, false], [true, true]],
+                vec![[false, true], [true, false]],
+            ],
+            negative_traces: vec![
+                vec![[true, true], [false, true]],
+                vec![[false, false], [true, false]],
+            ],
+            ..Sample::default()
+        };
+        let (expected, _) = solve_with_budget(&sample, false, false, SearchBudget::default());
+        let expected = expected.expect("solvable");
+
+        // An interrupted search records the sizes it searched.
+        let budget = SearchBudget::default().max_size(expected.size() - 1);
+        let (formula, _) = solve_cached(&sample, false, false, budget, &path).expect("cache");
+        assert_eq!(formula, None);
+        let cache = SearchCache::load(&path).expect("load cache");
+        let entry = cache.get(sample_hash(&sample)).expect("cached sample");
+        assert_eq!(entry.searched, expected.size() - 1);
+
+        // The resumed search only checks the remaining sizes.
+        let (formula, stats) =
+            solve_cached(&sample, false, false, SearchBudget::default(), &path).expect("cache");
+        assert_eq!(formula.as_ref(), Some(&expected));
+        let (_, full) = solve_with_budget(&sample, false, false, SearchBudget::default());
+        assert!(stats.formulas < full.formulas);
+
+        // A solved sample is not searched again.
+        let (formula, stats) =
+            solve_cached(&sample, false, false, SearchBudget::default(), &path).expect("cache");
+        assert_eq!(formula, Some(expected));
+        assert_eq!(stats.formulas, 0);
+        fs::remove_file(&path).expect("remove file");
+    }
+}
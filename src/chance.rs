@@ -0,0 +1,151 @@
+use crate::genetic::*;
+use crate::source::*;
+use crate::syntax::*;
+use std::fmt;
+use std::str::FromStr;
+
+/// A relaxed notion of consistency, for samples where no formula is consistent with every trace:
+/// a formula satisfying at least a given fraction of the positive traces and at most a given fraction of the negative ones.
+/// The default requires all positive traces and no negative one, i.e., plain consistency.
+///
+/// Parsed from the two fractions separated by a comma, e.g., `0.9,0.1`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ChanceConstraint {
+    /// The minimum fraction of positive traces satisfying the formula.
+    pub min_positives: f64,
+    /// The maximum fraction of negative traces satisfying the formula.
+    pub max_negatives: f64,
+}
+
+impl Default for ChanceConstraint {
+    fn default() -> Self {
+        ChanceConstraint {
+            min_positives: 1.0,
+            max_negatives: 0.0,
+        }
+    }
+}
+
+impl ChanceConstraint {
+    /// A constraint with the given fractions, panicking if they are not in `[0, 1]`.
+    pub fn new(min_positives: f64, max_negatives: f64) -> Self {
+        assert!(
+            (0.0..=1.0).contains(&min_positives) && (0.0..=1.0).contains(&max_negatives),
+            "fractions must be in [0, 1]"
+        );
+        ChanceConstraint {
+            min_positives,
+            max_negatives,
+        }
+    }
+
+    /// Whether the constraint is plain consistency.
+    pub fn is_strict(&self) -> bool {
+        self.min_positives >= 1.0 && self.max_negatives <= 0.0
+    }
+
+    /// Whether a formula satisfying the given numbers of positive and negative traces,
+    /// out of the given totals, meets the constraint. With no traces of a kind, its fraction is met.
+    pub fn admits_counts(
+        &self,
+        satisfied_positives: usize,
+        positives: usize,
+        satisfied_negatives: usize,
+        negatives: usize,
+    ) -> bool {
+        // The tolerance keeps fractions like 0.9 of 10 traces from requiring 10 traces by rounding.
+        const TOLERANCE: f64 = 1e-9;
+
+        satisfied_positives as f64 >= self.min_positives * positives as f64 - TOLERANCE
+            && satisfied_negatives as f64 <= self.max_negatives * negatives as f64 + TOLERANCE
+    }
+
+    /// Whether a formula with the given fitness over the sample meets the constraint.
+    pub fn admits_fitness<const N: usize, S: SampleSource<N>>(
+        &self,
+        fitness: &Fitness,
+        sample: &S,
+    ) -> bool {
+        self.admits_counts(
+            fitness.positives,
+            sample.positive_count(),
+            fitness.negatives,
+            sample.negative_count(),
+        )
+    }
+
+    /// Whether the formula meets the constraint on the sample, which can be any [`SampleSource`].
+    pub fn admits<const N: usize, S: SampleSource<N>>(
+        &self,
+        formula: &SyntaxTree,
+        sample: &S,
+    ) -> bool {
+        if self.is_strict() {
+            return sample.is_consistent(formula);
+        }
+        self.admits_fitness(&Fitness::new(formula, sample), sample)
+    }
+}
+
+impl fmt::Display for ChanceConstraint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "at least {:.1}% of positive traces and at most {:.1}% of negative traces",
+            100.0 * self.min_positives,
+            100.0 * self.max_negatives
+        )
+    }
+}
+
+impl FromStr for ChanceConstraint {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (min_positives, max_negatives) = s
+            .split_once(',')
+            .ok_or_else(|| format!("expected two fractions separated by a comma: {s}"))?;
+        let fraction = |fraction: &str| match fraction.trim().parse::<f64>() {
+            Ok(fraction) if (0.0..=1.0).contains(&fraction) => Ok(fraction),
+            _ => Err(format!("invalid fraction: {fraction}")),
+        };
+        Ok(ChanceConstraint {
+            min_positives: fraction(min_positives)?,
+            max_negatives: fraction(max_negatives)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod chance_constraints {
+    use super::*;
+    use crate::learn::*;
+    use crate::trace::*;
+
+    #[test]
+    fn relaxed_consistency() {
+        // Positive traces are those where x0 holds at first, but for a mislabelled one.
+        let sample = Sample::<2> {
+            positive_traces: vec![
+                vec![[true, false]],
+                vec![[true, true]],
+                vec![[true, false], [false, false]],
+                vec![[false, true]],
+            ],
+            negative_traces: vec![vec![[false, false]], vec![[false, true], [true, true]]],
+            ..Sample::default()
+        };
+        let x0 = SyntaxTree::Atom(0);
+        assert!(!ChanceConstraint::default().admits(&x0, &sample));
+        let constraint: ChanceConstraint = "0.75, 0".parse().expect("valid constraint");
+        assert_eq!(constraint, ChanceConstraint::new(0.75, 0.0));
+        assert!(constraint.admits(&x0, &sample));
+        assert!("0.75".parse::<ChanceConstraint>().is_err());
+        assert!("1.5,0".parse::<ChanceConstraint>().is_err());
+
+        let (formula, stats) =
+            solve_chance_constrained(&sample, &constraint, false, false, SearchBudget::default());
+        assert_eq!(formula, Some(x0));
+        assert_eq!(stats.end, SearchEnd::Solved);
+    }
+}
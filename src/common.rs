@@ -0,0 +1,181 @@
+use crate::learn::*;
+use crate::merge::*;
+use crate::source::*;
+use crate::syntax::*;
+use crate::trace::*;
+use std::borrow::Cow;
+
+/// Several samples over the same variables, e.g., logs from different deployments,
+/// seen as a single [`SampleSource`] without merging them, so that each can still be evaluated on its own.
+#[derive(Debug, Clone, Copy)]
+pub struct SampleSet<'a, const N: usize> {
+    samples: &'a [Sample<N>],
+}
+
+impl<'a, const N: usize> SampleSet<'a, N> {
+    /// The set of the given samples, which must name their variables the same.
+    pub fn new(samples: &'a [Sample<N>]) -> Result<Self, MergeError<N>> {
+        if samples
+            .windows(2)
+            .any(|pair| pair[0].var_names != pair[1].var_names)
+        {
+            return Err(MergeError::VarNamesMismatch);
+        }
+        Ok(SampleSet { samples })
+    }
+
+    pub fn samples(&self) -> &'a [Sample<N>] {
+        self.samples
+    }
+
+    /// The accuracy of the formula on each sample (see [`Sample::accuracy`]), in order.
+    pub fn accuracies(&self, formula: &SyntaxTree) -> Vec<f64> {
+        self.samples
+            .iter()
+            .map(|sample| sample.accuracy(formula))
+            .collect()
+    }
+}
+
+impl<const N: usize> SampleSource<N> for SampleSet<'_, N> {
+    type Traces<'a>
+        = Box<dyn Iterator<Item = Cow<'a, Trace<N>>> + 'a>
+    where
+        Self: 'a;
+
+    fn iter_positive(&self) -> Self::Traces<'_> {
+        Box::new(
+            self.samples
+                .iter()
+                .flat_map(|sample| sample.positive_traces.iter().map(Cow::Borrowed)),
+        )
+    }
+
+    fn iter_negative(&self) -> Self::Traces<'_> {
+        Box::new(
+            self.samples
+                .iter()
+                .flat_map(|sample| sample.negative_traces.iter().map(Cow::Borrowed)),
+        )
+    }
+
+    fn positive_count(&self) -> usize {
+        self.samples
+            .iter()
+            .map(|sample| sample.positive_traces.len())
+            .sum()
+    }
+
+    fn negative_count(&self) -> usize {
+        self.samples
+            .iter()
+            .map(|sample| sample.negative_traces.len())
+            .sum()
+    }
+
+    fn positive_lassos(&self) -> Cow<'_, [Lasso<N>]> {
+        Cow::Owned(
+            self.samples
+                .iter()
+                .flat_map(|sample| sample.positive_lassos.iter().cloned())
+                .collect(),
+        )
+    }
+
+    fn negative_lassos(&self) -> Cow<'_, [Lasso<N>]> {
+        Cow::Owned(
+            self.samples
+                .iter()
+                .flat_map(|sample| sample.negative_lassos.iter().cloned())
+                .collect(),
+        )
+    }
+
+    fn vars(&self) -> Vec<Idx> {
+        self.samples
+            .first()
+            .map_or_else(|| (0..N as Idx).collect(), Sample::vars)
+    }
+
+    // Traces can be positive in a sample and negative in another one,
+    // but lassos are only compared within each sample.
+    fn is_solvable(&self) -> bool {
+        self.samples.iter().all(Sample::is_solvable) && {
+            let vars = SampleSource::vars(self);
+            self.iter_positive().all(|pos_trace| {
+                self.iter_negative().all(|neg_trace| {
+                    pos_trace.len() != neg_trace.len()
+                        || pos_trace.iter().zip(neg_trace.iter()).any(|(pos, neg)| {
+                            vars.iter().any(|n| pos[*n as usize] != neg[*n as usize])
+                        })
+                })
+            })
+        }
+    }
+
+    fn is_consistent(&self, formula: &SyntaxTree) -> bool {
+        self.samples
+            .iter()
+            .all(|sample| sample.is_consistent(formula))
+    }
+}
+
+/// Find a formula consistent with all the samples at once, e.g., logs from different deployments, within the budget,
+/// with the brute-force search of [`solve_with_budget`], without merging the samples.
+/// The samples must name their variables the same.
+pub fn learn_common<const N: usize>(
+    samples: &[Sample<N>],
+    multithread: bool,
+    log: bool,
+    budget: SearchBudget,
+) -> Result<(Option<SyntaxTree>, SearchStats), MergeError<N>> {
+    let set = SampleSet::new(samples)?;
+    Ok(solve_with_budget(&set, multithread, log, budget))
+}
+
+#[cfg(test)]
+mod shared_formula {
+    use super::*;
+
+    #[test]
+    fn learn_common() {
+        // In both deployments, x0 holds at first in positive traces,
+        // but each of them can also be told apart by x1 alone.
+        let first = Sample::<2> {
+            positive_traces: vec![vec![[true, true]], vec![[true, true], [false, false]]],
+            negative_traces: vec![vec![[false, false]]],
+            ..Sample::default()
+        };
+        let second = Sample::<2> {
+            positive_traces: vec![vec![[true, false]]],
+            negative_traces: vec![vec![[false, true]], vec![[false, true], [true, true]]],
+            ..Sample::default()
+        };
+        let x0 = SyntaxTree::Atom(0);
+        let x1 = SyntaxTree::Atom(1);
+        assert!(first.is_consistent(&x1) && !second.is_consistent(&x1));
+
+        let samples = [first, second];
+        let (formula, stats) = super::learn_common(&samples, false, false, SearchBudget::default())
+            .expect("same variables");
+        assert_eq!(formula, Some(x0.clone()));
+        assert_eq!(stats.end, SearchEnd::Solved);
+        let set = SampleSet::new(&samples).expect("same variables");
+        assert_eq!(set.accuracies(&x1), vec![1.0, 0.0]);
+        assert_eq!(set.positive_count(), 3);
+
+        // A trace positive in a sample and negative in the other cannot be told apart.
+        let mut conflicting = samples.clone();
+        conflicting[1].negative_traces.push(vec![[true, true]]);
+        let (formula, stats) =
+            super::learn_common(&conflicting, false, false, SearchBudget::default())
+                .expect("same variables");
+        assert_eq!((formula, stats.end), (None, SearchEnd::Unsolvable));
+
+        conflicting[1].var_names = ["a".to_string(), "b".to_string()];
+        assert_eq!(
+            SampleSet::new(&conflicting).err(),
+            Some(MergeError::VarNamesMismatch)
+        );
+    }
+}
@@ -0,0 +1,188 @@
+use crate::genetic::*;
+use crate::syntax::*;
+use serde::{Deserialize, Serialize};
+use serde_with::*;
+
+/// The parameters of a [`GeneticLearner`], e.g., read from a configuration file (see [`GeneticConfig::load`]),
+/// so that experiments can be versioned.
+/// Parameters left out keep their value in the learner the configuration is applied to.
+///
+/// In RON:
+///
+/// ```
+/// use learn_ltl::*;
+///
+/// let config: GeneticConfig = ron::from_str(
+///     r#"(
+///         iterations: Some(50),
+///         survivors: Some(200),
+///         selection: Some("tournament:5"),
+///         mutation_rate: Some("adaptive:0.3"),
+///         patience: Some(10),
+///     )"#,
+/// )
+/// .expect("valid configuration");
+/// let learner = config.apply(GeneticLearner::new(2));
+/// assert_eq!(learner.selection, ParentSelection::Tournament(5));
+/// assert_eq!(learner.size, 2);
+/// ```
+#[serde_as]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct GeneticConfig {
+    /// The size of the formulas of the initial population.
+    pub size: Option<usize>,
+    /// The number of generations.
+    pub iterations: Option<usize>,
+    /// The maximum number of formulas of the initial population.
+    pub population: Option<usize>,
+    /// The number of offspring bred each generation.
+    pub offspring: Option<usize>,
+    /// The number of the fittest formulas surviving each generation.
+    pub survivors: Option<usize>,
+    /// The number of the fittest formulas carried over into the next generation.
+    pub elitism: Option<usize>,
+    /// How offspring are bred, e.g., `"subtree"`.
+    #[serde_as(as = "Option<DisplayFromStr>")]
+    pub crossover: Option<CrossoverMethod>,
+    /// How mutants are bred, e.g., `"point"`.
+    #[serde_as(as = "Option<DisplayFromStr>")]
+    pub mutation: Option<MutationMethod>,
+    /// How often formulas are mutated, e.g., `"fixed:0.2"`.
+    #[serde_as(as = "Option<DisplayFromStr>")]
+    pub mutation_rate: Option<MutationRate>,
+    /// How the parents of offspring are picked, e.g., `"tournament:3"`.
+    #[serde_as(as = "Option<DisplayFromStr>")]
+    pub selection: Option<ParentSelection>,
+    /// The niche radius of fitness sharing.
+    pub sharing: Option<usize>,
+    /// Whether formulas are ranked by non-dominated sorting over accuracy and size.
+    pub pareto: Option<bool>,
+    /// The maximum size of the offspring and mutants.
+    pub max_size: Option<usize>,
+    /// Whether runs stop as soon as the fittest formula is consistent with the sample.
+    pub stop_when_consistent: Option<bool>,
+    /// The number of generations without improvement after which runs stop.
+    pub patience: Option<usize>,
+    /// The seed of the random number generator.
+    pub random_seed: Option<u64>,
+    /// Formulas added to the initial population.
+    pub seeds: Option<Vec<SyntaxTree>>,
+}
+
+impl GeneticConfig {
+    /// The configuration with the parameters of this one, and those of the other one for the parameters left out,
+    /// e.g., to override a configuration file with command-line options.
+    pub fn or(self, other: GeneticConfig) -> GeneticConfig {
+        GeneticConfig {
+            size: self.size.or(other.size),
+            iterations: self.iterations.or(other.iterations),
+            population: self.population.or(other.population),
+            offspring: self.offspring.or(other.offspring),
+            survivors: self.survivors.or(other.survivors),
+            elitism: self.elitism.or(other.elitism),
+            crossover: self.crossover.or(other.crossover),
+            mutation: self.mutation.or(other.mutation),
+            mutation_rate: self.mutation_rate.or(other.mutation_rate),
+            selection: self.selection.or(other.selection),
+            sharing: self.sharing.or(other.sharing),
+            pareto: self.pareto.or(other.pareto),
+            max_size: self.max_size.or(other.max_size),
+            stop_when_consistent: self.stop_when_consistent.or(other.stop_when_consistent),
+            patience: self.patience.or(other.patience),
+            random_seed: self.random_seed.or(other.random_seed),
+            seeds: self.seeds.or(other.seeds),
+        }
+    }
+
+    /// The learner with the parameters of the configuration, its other parameters unchanged.
+    pub fn apply(&self, mut learner: GeneticLearner) -> GeneticLearner {
+        if let Some(size) = self.size {
+            learner.size = size;
+        }
+        if let Some(iterations) = self.iterations {
+            learner = learner.iterations(iterations);
+        }
+        if let Some(population) = self.population {
+            learner = learner.population(population);
+        }
+        if let Some(offspring) = self.offspring {
+            learner = learner.offspring(offspring);
+        }
+        if let Some(survivors) = self.survivors {
+            learner = learner.survivors(survivors);
+        }
+        if let Some(elitism) = self.elitism {
+            learner = learner.elitism(elitism);
+        }
+        if let Some(crossover) = self.crossover {
+            learner = learner.crossover(crossover);
+        }
+        if let Some(mutation) = self.mutation {
+            learner = learner.mutation(mutation);
+        }
+        if let Some(mutation_rate) = self.mutation_rate {
+            learner = learner.mutation_rate(mutation_rate);
+        }
+        if let Some(selection) = self.selection {
+            learner = learner.selection(selection);
+        }
+        if let Some(radius) = self.sharing {
+            learner = learner.sharing(radius);
+        }
+        if let Some(pareto) = self.pareto {
+            learner = learner.pareto(pareto);
+        }
+        if let Some(max_size) = self.max_size {
+            learner = learner.max_size(max_size);
+        }
+        if let Some(stop) = self.stop_when_consistent {
+            learner = learner.stop_when_consistent(stop);
+        }
+        if let Some(patience) = self.patience {
+            learner = learner.patience(patience);
+        }
+        if let Some(seed) = self.random_seed {
+            learner = learner.random_seed(seed);
+        }
+        if let Some(seeds) = &self.seeds {
+            learner = learner.seeds(seeds.clone());
+        }
+        learner
+    }
+}
+
+#[cfg(test)]
+mod genetic_config {
+    use super::*;
+
+    #[test]
+    fn apply() {
+        let file = GeneticConfig {
+            iterations: Some(50),
+            selection: Some(ParentSelection::Uniform),
+            seeds: Some(vec![SyntaxTree::Atom(0).globally()]),
+            ..GeneticConfig::default()
+        };
+        let options = GeneticConfig {
+            iterations: Some(20),
+            patience: Some(5),
+            ..GeneticConfig::default()
+        };
+        let config = options.or(file);
+        assert_eq!(config.iterations, Some(20));
+        assert_eq!(config.selection, Some(ParentSelection::Uniform));
+
+        let learner = config.apply(GeneticLearner::new(2));
+        assert_eq!(learner.size, 2);
+        assert_eq!(learner.iterations, 20);
+        assert_eq!(learner.patience, Some(5));
+        assert_eq!(learner.seeds, [SyntaxTree::Atom(0).globally()]);
+        assert_eq!(GeneticConfig::default().apply(learner.clone()), learner);
+
+        let ron = ron::to_string(&config).expect("serializable");
+        assert_eq!(ron::from_str::<GeneticConfig>(&ron), Ok(config));
+        assert!(ron::from_str::<GeneticConfig>(r#"(selection: Some("best"))"#).is_err());
+        assert!(ron::from_str::<GeneticConfig>("(generations: Some(5))").is_err());
+    }
+}
@@ -0,0 +1,120 @@
+use crate::builder::*;
+use crate::syntax::*;
+use crate::trace::*;
+use std::collections::HashMap;
+
+/// The values of the given variables along the states.
+fn project<'a, const N: usize>(
+    states: impl Iterator<Item = &'a [bool; N]>,
+    vars: &[Idx],
+) -> Vec<Vec<bool>> {
+    states
+        .map(|state| vars.iter().map(|var| state[*var as usize]).collect())
+        .collect()
+}
+
+impl<const N: usize> Sample<N> {
+    /// The pairs of a positive and a negative trace (or lasso) that are identical over the allowed variables,
+    /// so that no formula can tell them apart.
+    /// Each negative trace is reported at most once, with the first positive trace identical to it.
+    pub(crate) fn contradictions(&self) -> impl Iterator<Item = SampleError> + '_ {
+        let vars = self.vars();
+
+        let mut positives = HashMap::new();
+        for (index, trace) in self.positive_traces.iter().enumerate().rev() {
+            positives.insert(project(trace.iter(), &vars), index);
+        }
+        let traces =
+            self.negative_traces
+                .iter()
+                .enumerate()
+                .filter_map(move |(negative, trace)| {
+                    positives
+                        .get(&project(trace.iter(), &vars))
+                        .map(|&positive| SampleError::Contradiction { positive, negative })
+                });
+
+        // Lassos are only compared position by position,
+        // so two lassos representing the same infinite word with different shapes are not detected.
+        let vars = self.vars();
+        let lasso_key = move |lasso: &Lasso<N>| {
+            let states = lasso.prefix.iter().chain(lasso.cycle.iter());
+            (lasso.prefix.len(), project(states, &vars))
+        };
+        let mut positive_lassos = HashMap::new();
+        for (index, lasso) in self.positive_lassos.iter().enumerate().rev() {
+            positive_lassos.insert(lasso_key(lasso), index);
+        }
+        let lassos =
+            self.negative_lassos
+                .iter()
+                .enumerate()
+                .filter_map(move |(negative, lasso)| {
+                    positive_lassos
+                        .get(&lasso_key(lasso))
+                        .map(|&positive| SampleError::LassoContradiction { positive, negative })
+                });
+
+        traces.chain(lassos)
+    }
+
+    /// Checks that no positive trace (or lasso) is identical to a negative one over the allowed variables,
+    /// as no formula would then be consistent with the sample, and reports those that are.
+    /// Variables discarded by their name (see [`Sample::vars`]) are ignored,
+    /// so traces differing only in them are identical.
+    pub fn check_consistency(&self) -> Result<(), Vec<SampleError>> {
+        let contradictions = self.contradictions().collect::<Vec<_>>();
+        if contradictions.is_empty() {
+            Ok(())
+        } else {
+            Err(contradictions)
+        }
+    }
+}
+
+#[cfg(test)]
+mod consistency_check {
+    use super::*;
+
+    #[test]
+    pub(crate) fn contradictions() {
+        let mut sample = Sample::<2> {
+            var_names: ["a".to_string(), "b".to_string()],
+            positive_traces: vec![vec![[true, false]], vec![[true, true]]],
+            negative_traces: vec![vec![[false, false]], vec![[true, true]]],
+            positive_lassos: vec![Lasso::new(vec![], vec![[true, true]])],
+            negative_lassos: vec![Lasso::new(vec![], vec![[true, true]])],
+            ..Sample::default()
+        };
+        assert_eq!(
+            sample.check_consistency(),
+            Err(vec![
+                SampleError::Contradiction {
+                    positive: 1,
+                    negative: 1
+                },
+                SampleError::LassoContradiction {
+                    positive: 0,
+                    negative: 0
+                }
+            ])
+        );
+        assert!(!sample.is_solvable());
+
+        // Once `b` is discarded, the first traces cannot be told apart either.
+        sample.negative_traces.pop();
+        sample.negative_traces[0] = vec![[true, true]];
+        sample.negative_lassos.clear();
+        sample.var_names[1] = "~b".to_string();
+        assert_eq!(
+            sample.check_consistency(),
+            Err(vec![SampleError::Contradiction {
+                positive: 0,
+                negative: 0
+            }])
+        );
+        sample.negative_traces[0] = vec![[false, true]];
+        assert_eq!(sample.check_consistency(), Ok(()));
+        assert!(sample.is_solvable());
+    }
+}
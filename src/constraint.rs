@@ -0,0 +1,99 @@
+use crate::source::*;
+use crate::syntax::*;
+
+/// Known domain invariants that learned formulas have to respect:
+/// formulas they must entail (e.g., `G safe`) and formulas they must be entailed by.
+///
+/// Entailment between LTL formulas is not decided, but checked on the sample:
+/// at every position of every trace, positive or negative, where the learned formula holds,
+/// the formulas it must entail hold too, and where the formulas it must be entailed by hold, it holds too.
+/// Checking every position, rather than only the start of the traces, matters:
+/// a formula consistent with the sample holds at the start of exactly the positive traces,
+/// whatever it entails elsewhere.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Constraints {
+    /// The formulas that learned formulas must entail.
+    pub entails: Vec<SyntaxTree>,
+    /// The formulas that learned formulas must be entailed by.
+    pub entailed_by: Vec<SyntaxTree>,
+}
+
+impl Constraints {
+    pub fn entails(mut self, formula: SyntaxTree) -> Self {
+        self.entails.push(formula);
+        self
+    }
+
+    pub fn entailed_by(mut self, formula: SyntaxTree) -> Self {
+        self.entailed_by.push(formula);
+        self
+    }
+
+    /// Whether there are no constraints.
+    pub fn is_empty(&self) -> bool {
+        self.entails.is_empty() && self.entailed_by.is_empty()
+    }
+
+    /// Whether the formula respects the constraints on the traces of the sample,
+    /// which can be any [`SampleSource`].
+    pub fn admits<const N: usize, S: SampleSource<N>>(
+        &self,
+        formula: &SyntaxTree,
+        sample: &S,
+    ) -> bool {
+        if self.is_empty() {
+            return true;
+        }
+        sample
+            .iter_positive()
+            .chain(sample.iter_negative())
+            .all(|trace| {
+                (0..trace.len()).all(|time| {
+                    let holds = |formula: &SyntaxTree| formula.eval_at_time(&trace, time);
+                    if holds(formula) {
+                        self.entails.iter().all(holds)
+                    } else {
+                        !self.entailed_by.iter().any(holds)
+                    }
+                })
+            })
+    }
+}
+
+#[cfg(test)]
+mod domain_constraints {
+    use super::*;
+    use crate::learn::*;
+    use crate::trace::*;
+
+    #[test]
+    fn constrained_search() {
+        // Positive traces are those where x0 holds at first; x1 holds wherever x0 does.
+        let sample = Sample::<2> {
+            positive_traces: vec![
+                vec![[true, true], [false, false]],
+                vec![[true, true], [false, true]],
+            ],
+            negative_traces: vec![vec![[false, true], [true, true]], vec![[false, false]]],
+            ..Sample::default()
+        };
+        assert_eq!(solve(&sample, false, false), Some(SyntaxTree::Atom(0)));
+
+        // The solution has to hold only where x1 holds, and wherever x0 and x1 both hold, which x0 does.
+        let x1 = SyntaxTree::Atom(1);
+        let constraints = Constraints::default()
+            .entails(x1.clone())
+            .entailed_by(SyntaxTree::Atom(0).and(x1.clone()));
+        assert!(constraints.admits(&SyntaxTree::Atom(0), &sample));
+        assert!(!constraints.admits(&!SyntaxTree::Atom(0), &sample));
+
+        // Requiring the solution to entail F ¬x0 rules out x0, which holds at the end of the first negative trace.
+        let constraints = Constraints::default().entails((!SyntaxTree::Atom(0)).finally());
+        let (formula, _) =
+            solve_constrained(&sample, &constraints, false, false, SearchBudget::default());
+        let formula = formula.expect("solvable");
+        assert_ne!(formula, SyntaxTree::Atom(0));
+        assert!(sample.is_consistent(&formula));
+        assert!(constraints.admits(&formula, &sample));
+    }
+}
@@ -0,0 +1,161 @@
+use crate::learn::*;
+use crate::syntax::*;
+use crate::trace::*;
+use std::collections::HashSet;
+use std::fmt;
+
+/// A formula characterizing a difference between two sets of traces,
+/// with the fraction of the traces of each set satisfying it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Contrast {
+    pub formula: SyntaxTree,
+    /// The fraction of the traces of the first set satisfying the formula.
+    pub first: f64,
+    /// The fraction of the traces of the second set satisfying the formula.
+    pub second: f64,
+}
+
+impl Contrast {
+    /// How much more often the formula holds on the first set than on the second one, from `-1.0` to `1.0`.
+    pub fn gap(&self) -> f64 {
+        self.first - self.second
+    }
+}
+
+impl fmt::Display for Contrast {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} holds on {:.1}% of the first traces and {:.1}% of the second ones",
+            self.formula,
+            100.0 * self.first,
+            100.0 * self.second
+        )
+    }
+}
+
+/// Learns formulas telling apart two sets of unlabeled traces, e.g., the logs of two versions of a software,
+/// by searching all the formulas up to a given size for the largest satisfaction gaps between the sets
+/// (in either direction).
+/// Formulas satisfied by the same traces as a smaller (or earlier) one, or by the others, are skipped,
+/// so that the contrasts found describe different differences.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ContrastiveLearner {
+    /// The maximum size of the formulas.
+    pub max_size: usize,
+    /// The number of contrasts returned.
+    pub count: usize,
+}
+
+impl Default for ContrastiveLearner {
+    fn default() -> Self {
+        ContrastiveLearner {
+            max_size: 4,
+            count: 5,
+        }
+    }
+}
+
+impl ContrastiveLearner {
+    pub fn max_size(mut self, max_size: usize) -> Self {
+        self.max_size = max_size;
+        self
+    }
+
+    pub fn count(mut self, count: usize) -> Self {
+        self.count = count;
+        self
+    }
+
+    /// The formulas with the largest satisfaction gaps (in absolute value) between the two sets of traces,
+    /// largest first, smaller formulas first among equal gaps.
+    /// Returns nothing if either set is empty.
+    pub fn contrasts<const N: usize>(
+        &self,
+        first: &[Trace<N>],
+        second: &[Trace<N>],
+    ) -> Vec<Contrast> {
+        if first.is_empty() || second.is_empty() {
+            return Vec::new();
+        }
+        let vars = (0..N as Idx).collect::<Vec<_>>();
+        let mut behaviours = HashSet::new();
+        let mut contrasts: Vec<Contrast> = Vec::new();
+        for size in 1..=self.max_size {
+            for formula in gen_formulae_iter::<N>(size, &vars) {
+                let behaviour = first
+                    .iter()
+                    .chain(second)
+                    .map(|trace| formula.eval(trace))
+                    .collect::<Vec<_>>();
+                let fraction = |values: &[bool]| {
+                    values.iter().filter(|value| **value).count() as f64 / values.len() as f64
+                };
+                let contrast = Contrast {
+                    first: fraction(&behaviour[..first.len()]),
+                    second: fraction(&behaviour[first.len()..]),
+                    formula,
+                };
+                // The negation of a formula shows the same difference.
+                let negation = behaviour.iter().map(|value| !value).collect::<Vec<_>>();
+                if behaviours.contains(&negation) || !behaviours.insert(behaviour) {
+                    continue;
+                }
+                // The contrasts are kept sorted, so a new one goes after those with at least its gap.
+                let gap = contrast.gap().abs();
+                let pos = contrasts.partition_point(|other| other.gap().abs() >= gap);
+                if pos < self.count {
+                    contrasts.insert(pos, contrast);
+                    contrasts.truncate(self.count);
+                }
+            }
+        }
+        contrasts
+    }
+
+    /// The formula with the largest satisfaction gap between the two sets of traces, if any (see [`ContrastiveLearner::contrasts`]).
+    pub fn contrast<const N: usize>(
+        &self,
+        first: &[Trace<N>],
+        second: &[Trace<N>],
+    ) -> Option<Contrast> {
+        self.count(1).contrasts(first, second).into_iter().next()
+    }
+}
+
+#[cfg(test)]
+mod contrastive_learning {
+    use super::*;
+
+    #[test]
+    fn contrasts() {
+        // In the new version, x1 always follows x0, while it never does in the old one.
+        let old = vec![
+            vec![[true, false], [false, false]],
+            vec![[false, false], [true, false], [false, false]],
+            vec![[true, false]],
+        ];
+        let new = vec![
+            vec![[true, false], [false, true]],
+            vec![[false, false], [true, false], [false, true]],
+            vec![[false, false]],
+        ];
+        let learner = ContrastiveLearner::default().max_size(5);
+        let contrast = learner.contrast(&old, &new).expect("some contrast");
+        assert_eq!(contrast.gap().abs(), 1.0);
+        let satisfied = contrast.gap() > 0.0;
+        assert!(old
+            .iter()
+            .all(|trace| contrast.formula.eval(trace) == satisfied));
+        assert!(new
+            .iter()
+            .all(|trace| contrast.formula.eval(trace) != satisfied));
+
+        let contrasts = learner.contrasts(&old, &new);
+        assert_eq!(contrasts.len(), 5);
+        assert!(contrasts
+            .windows(2)
+            .all(|pair| pair[0].gap().abs() >= pair[1].gap().abs()));
+        assert!(learner.contrasts(&old, &[]).is_empty());
+    }
+}
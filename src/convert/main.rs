@@ -0,0 +1,63 @@
+use learn_ltl::*;
+
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::Path;
+
+use clap::Parser;
+
+/// Convert a sample to a different file format, e.g., to compare with other LTL learners.
+/// Supported file types: ron, json, trace, binary (bin).
+#[derive(Parser, Debug)]
+#[clap(name = "convert")]
+struct Convert {
+    /// The sample to convert
+    sample: String,
+
+    /// The converted sample, in the format given by its extension
+    output: String,
+
+    /// The dialect of trace files to write (flie or scarlet)
+    #[arg(long, default_value = "flie")]
+    dialect: TraceFileDialect,
+}
+
+/// Writes a sample with any number of variables.
+struct Write<'a> {
+    output: &'a Path,
+    dialect: TraceFileDialect,
+}
+
+impl SampleVisitor for Write<'_> {
+    type Output = std::io::Result<()>;
+
+    fn visit<const N: usize>(self, sample: Sample<N>) -> Self::Output {
+        let writer = BufWriter::new(File::create(self.output)?);
+        if matches!(self.output.extension(), Some(ext) if ext == "bin") {
+            return sample.write_binary(writer);
+        }
+        match SampleFormat::from_path(self.output) {
+            Some(SampleFormat::Ron) => {
+                ron::ser::to_writer(writer, &sample).map_err(std::io::Error::other)
+            }
+            Some(SampleFormat::Json) => {
+                serde_json::to_writer(writer, &sample).map_err(std::io::Error::other)
+            }
+            Some(SampleFormat::Trace) => sample.write_trace_file(writer, self.dialect),
+            None => Err(std::io::Error::new(
+                std::io::ErrorKind::Unsupported,
+                "file format unknown or not supported",
+            )),
+        }
+    }
+}
+
+fn main() -> std::io::Result<()> {
+    let convert = Convert::parse();
+
+    let write = Write {
+        output: Path::new(&convert.output),
+        dialect: convert.dialect,
+    };
+    visit_sample(Path::new(&convert.sample), write)?
+}
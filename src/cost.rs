@@ -0,0 +1,290 @@
+use crate::learn::*;
+use crate::source::*;
+use crate::syntax::*;
+use itertools::Itertools;
+use std::str::FromStr;
+use std::sync::Arc;
+
+/// The cost of each operator (and of variables) in the size of a formula,
+/// to prefer formulas with the most readable operators.
+/// By default, every operator costs 1, so that the cost of a formula is its size.
+///
+/// Costs are parsed from a comma-separated list of operator names with their cost, e.g., `until=3,not=0`,
+/// where operators not listed cost 1.
+/// Only negation can be free, as repeating any other operator would give infinitely many formulas of the same cost.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct OperatorCosts {
+    pub atom: usize,
+    pub not: usize,
+    pub next: usize,
+    pub globally: usize,
+    pub finally: usize,
+    pub and: usize,
+    pub or: usize,
+    pub implies: usize,
+    pub until: usize,
+}
+
+impl Default for OperatorCosts {
+    fn default() -> Self {
+        OperatorCosts {
+            atom: 1,
+            not: 1,
+            next: 1,
+            globally: 1,
+            finally: 1,
+            and: 1,
+            or: 1,
+            implies: 1,
+            until: 1,
+        }
+    }
+}
+
+impl OperatorCosts {
+    /// The sum of the costs of the operators and variables of the formula.
+    pub fn cost(&self, formula: &SyntaxTree) -> usize {
+        match formula {
+            SyntaxTree::Atom(_) => self.atom,
+            SyntaxTree::Not(branch) => self.not + self.cost(branch),
+            SyntaxTree::Next(branch) => self.next + self.cost(branch),
+            SyntaxTree::Globally(branch) => self.globally + self.cost(branch),
+            SyntaxTree::Finally(branch) => self.finally + self.cost(branch),
+            SyntaxTree::And(left_branch, right_branch) => {
+                self.and + self.cost(left_branch) + self.cost(right_branch)
+            }
+            SyntaxTree::Or(left_branch, right_branch) => {
+                self.or + self.cost(left_branch) + self.cost(right_branch)
+            }
+            SyntaxTree::Implies(left_branch, right_branch) => {
+                self.implies + self.cost(left_branch) + self.cost(right_branch)
+            }
+            SyntaxTree::Until(left_branch, right_branch) => {
+                self.until + self.cost(left_branch) + self.cost(right_branch)
+            }
+        }
+    }
+
+    fn check(&self) -> Result<(), String> {
+        let costs = [
+            self.atom,
+            self.next,
+            self.globally,
+            self.finally,
+            self.and,
+            self.or,
+            self.implies,
+            self.until,
+        ];
+        if costs.contains(&0) {
+            Err("only negation can be free".to_string())
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl FromStr for OperatorCosts {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut costs = OperatorCosts::default();
+        for entry in s
+            .split(',')
+            .map(str::trim)
+            .filter(|entry| !entry.is_empty())
+        {
+            let (name, cost) = entry
+                .split_once('=')
+                .ok_or_else(|| format!("expected `operator=cost`: {entry}"))?;
+            let cost = cost
+                .trim()
+                .parse()
+                .map_err(|_| format!("invalid cost: {entry}"))?;
+            let operator = match name.trim().to_lowercase().as_str() {
+                "atom" | "var" => &mut costs.atom,
+                "not" => &mut costs.not,
+                "next" | "x" => &mut costs.next,
+                "globally" | "g" => &mut costs.globally,
+                "finally" | "f" => &mut costs.finally,
+                "and" => &mut costs.and,
+                "or" => &mut costs.or,
+                "implies" => &mut costs.implies,
+                "until" | "u" => &mut costs.until,
+                _ => return Err(format!("unknown operator: {name}")),
+            };
+            *operator = cost;
+        }
+        costs.check()?;
+        Ok(costs)
+    }
+}
+
+/// Generates the formulas of each cost up to the given one, with the same filtering as [`gen_formulae`],
+/// so that formulas of cost `n` are at index `n`.
+/// Panics if an operator other than negation is free.
+pub fn gen_formulae_by_cost<const N: usize>(
+    costs: &OperatorCosts,
+    max_cost: usize,
+    vars: &[Idx],
+) -> Vec<Vec<Arc<SyntaxTree>>> {
+    costs.check().expect("invalid costs");
+
+    let mut formulas = Vec::with_capacity(max_cost + 1);
+    for _ in 0..=max_cost {
+        push_next_cost(&mut formulas, costs, vars);
+    }
+    formulas
+}
+
+/// The cost of an operator, its filtering criterion (see [`gen_formulae`]), and its constructor.
+type UnaryOperator = (
+    usize,
+    fn(&SyntaxTree) -> bool,
+    fn(Arc<SyntaxTree>) -> SyntaxTree,
+);
+type BinaryOperator = (
+    usize,
+    fn(&SyntaxTree, &SyntaxTree) -> bool,
+    fn(Arc<SyntaxTree>, Arc<SyntaxTree>) -> SyntaxTree,
+);
+
+/// Pushes the formulas of the next cost, given those of every lower cost.
+fn push_next_cost(formulas: &mut Vec<Vec<Arc<SyntaxTree>>>, costs: &OperatorCosts, vars: &[Idx]) {
+    let cost = formulas.len();
+    let mut level = Vec::new();
+    if cost == costs.atom {
+        level.extend(vars.iter().map(|var| Arc::new(SyntaxTree::Atom(*var))));
+    }
+    let unary: [UnaryOperator; 3] = [
+        (costs.next, check_next, SyntaxTree::Next),
+        (costs.globally, check_globally, SyntaxTree::Globally),
+        (costs.finally, check_finally, SyntaxTree::Finally),
+    ];
+    for (op_cost, check, op) in unary {
+        if let Some(children) = cost.checked_sub(op_cost).map(|cost| &formulas[cost]) {
+            level.extend(
+                children
+                    .iter()
+                    .filter(|child| check(child))
+                    .map(|child| Arc::new(op(child.clone()))),
+            );
+        }
+    }
+    let binary: [BinaryOperator; 4] = [
+        (costs.and, check_and, SyntaxTree::And),
+        (costs.or, check_or, SyntaxTree::Or),
+        (costs.implies, check_implies, SyntaxTree::Implies),
+        (costs.until, check_until, SyntaxTree::Until),
+    ];
+    for (op_cost, check, op) in binary {
+        let Some(children_cost) = cost.checked_sub(op_cost) else {
+            continue;
+        };
+        for left_cost in 0..=children_cost {
+            let children = formulas[left_cost]
+                .iter()
+                .cartesian_product(&formulas[children_cost - left_cost]);
+            level.extend(
+                children
+                    .filter(|(left, right)| check(left, right))
+                    .map(|(left, right)| Arc::new(op(left.clone(), right.clone()))),
+            );
+        }
+    }
+    // Negations of formulas of the same cost, if it is free, are never negations themselves.
+    let negated: &[Arc<SyntaxTree>] = match cost.checked_sub(costs.not) {
+        Some(child_cost) if child_cost < cost => &formulas[child_cost],
+        Some(_) => &level,
+        None => &[],
+    };
+    let negations = negated
+        .iter()
+        .filter(|child| check_not(child))
+        .map(|child| Arc::new(SyntaxTree::Not(child.clone())))
+        .collect_vec();
+    level.extend(negations);
+    formulas.push(level);
+}
+
+/// Find a formula of least cost consistent with the given sample, which can be any [`SampleSource`],
+/// searching formulas by increasing cost up to the maximum one, if any, like [`solve`] does by size.
+/// Panics if an operator other than negation is free.
+pub fn solve_weighted<const N: usize, S: SampleSource<N>>(
+    sample: &S,
+    costs: &OperatorCosts,
+    max_cost: Option<usize>,
+) -> Option<SyntaxTree> {
+    if !sample.is_solvable() {
+        return None;
+    }
+
+    costs.check().expect("invalid costs");
+    let vars = sample.vars();
+    let mut formulas = Vec::new();
+    while max_cost.is_none_or(|max_cost| formulas.len() <= max_cost) {
+        push_next_cost(&mut formulas, costs, &vars);
+        let level = formulas.last().expect("formulas of some cost");
+        if let Some(formula) = level.iter().find(|formula| sample.is_consistent(formula)) {
+            return Some(formula.as_ref().clone());
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod operator_costs {
+    use super::*;
+    use crate::trace::*;
+
+    #[test]
+    fn parse() {
+        let costs = "until=3, not=0".parse::<OperatorCosts>().expect("costs");
+        assert_eq!(costs.until, 3);
+        assert_eq!(costs.not, 0);
+        assert_eq!(costs.and, 1);
+        assert_eq!(
+            costs.cost(&!SyntaxTree::Atom(0).until(SyntaxTree::Atom(1))),
+            5
+        );
+
+        assert!("next=0".parse::<OperatorCosts>().is_err());
+        assert!("since=1".parse::<OperatorCosts>().is_err());
+    }
+
+    #[test]
+    fn weighted_search() {
+        // Default costs give the size.
+        let costs = OperatorCosts::default();
+        let formulas = gen_formulae_by_cost::<2>(&costs, 4, &[0, 1]);
+        for (size, level) in formulas.iter().enumerate().skip(1) {
+            assert_eq!(
+                level
+                    .iter()
+                    .map(|formula| formula.as_ref())
+                    .sorted()
+                    .collect_vec(),
+                gen_formulae::<2>(size, &[0, 1])
+                    .iter()
+                    .sorted()
+                    .collect_vec()
+            );
+        }
+
+        // Positive traces are those where x0 holds at first, but not globally.
+        let sample = Sample::<2> {
+            positive_traces: vec![
+                vec![[true, false], [false, false]],
+                vec![[true, true], [false, true]],
+            ],
+            negative_traces: vec![vec![[true, false]], vec![[false, true], [true, true]]],
+            ..Sample::default()
+        };
+        let costs = "not=0,globally=5".parse().expect("costs");
+        let formula = solve_weighted(&sample, &costs, None).expect("solvable");
+        assert!(sample.is_consistent(&formula));
+        let brute_force = solve(&sample, false, false).expect("solvable");
+        assert!(costs.cost(&formula) <= costs.cost(&brute_force));
+    }
+}
@@ -0,0 +1,290 @@
+use crate::learn::*;
+use crate::learner::*;
+use crate::syntax::*;
+use crate::trace::*;
+use std::sync::Arc;
+
+/// A decision tree classifying traces, whose nodes test (small) LTL formulas.
+#[derive(Debug, Clone, PartialEq)]
+pub enum DecisionTree {
+    /// Traces reaching the leaf are positive (`true`) or negative (`false`).
+    Leaf(bool),
+    /// Traces satisfying the test go down the first branch, the others down the second.
+    Node {
+        test: SyntaxTree,
+        then: Box<DecisionTree>,
+        otherwise: Box<DecisionTree>,
+    },
+}
+
+impl DecisionTree {
+    /// Classifies a trace as positive (`true`) or negative (`false`).
+    pub fn eval<S: AtomValuation>(&self, trace: &[S]) -> bool {
+        match self {
+            DecisionTree::Leaf(label) => *label,
+            DecisionTree::Node {
+                test,
+                then,
+                otherwise,
+            } => {
+                if test.eval(trace) {
+                    then.eval(trace)
+                } else {
+                    otherwise.eval(trace)
+                }
+            }
+        }
+    }
+
+    /// The number of tests.
+    pub fn nodes(&self) -> usize {
+        match self {
+            DecisionTree::Leaf(_) => 0,
+            DecisionTree::Node {
+                then, otherwise, ..
+            } => 1 + then.nodes() + otherwise.nodes(),
+        }
+    }
+
+    /// The maximum number of tests on a path from the root to a leaf.
+    pub fn depth(&self) -> usize {
+        match self {
+            DecisionTree::Leaf(_) => 0,
+            DecisionTree::Node {
+                then, otherwise, ..
+            } => 1 + then.depth().max(otherwise.depth()),
+        }
+    }
+
+    /// Encodes the tree as an equivalent formula, i.e., `(test ∧ then) ∨ (¬test ∧ otherwise)` at each node,
+    /// simplified where a branch is a leaf.
+    /// Returns `None` if the tree is a single leaf, as formulas have no constants.
+    pub fn to_formula(&self) -> Option<SyntaxTree> {
+        let DecisionTree::Node {
+            test,
+            then,
+            otherwise,
+        } = self
+        else {
+            return None;
+        };
+        let test = Arc::new(test.clone());
+        let not_test = || Arc::new(SyntaxTree::Not(test.clone()));
+        let formula = match (then.as_ref(), otherwise.as_ref()) {
+            (DecisionTree::Leaf(true), DecisionTree::Leaf(false)) => test.as_ref().clone(),
+            (DecisionTree::Leaf(false), DecisionTree::Leaf(true)) => SyntaxTree::Not(test),
+            (DecisionTree::Leaf(true), DecisionTree::Leaf(true)) => return None,
+            (DecisionTree::Leaf(false), DecisionTree::Leaf(false)) => return None,
+            (DecisionTree::Leaf(true), otherwise) => {
+                SyntaxTree::Or(test, Arc::new(otherwise.to_formula()?))
+            }
+            (DecisionTree::Leaf(false), otherwise) => {
+                SyntaxTree::And(not_test(), Arc::new(otherwise.to_formula()?))
+            }
+            (then, DecisionTree::Leaf(true)) => {
+                SyntaxTree::Implies(test, Arc::new(then.to_formula()?))
+            }
+            (then, DecisionTree::Leaf(false)) => {
+                SyntaxTree::And(test, Arc::new(then.to_formula()?))
+            }
+            (then, otherwise) => SyntaxTree::Or(
+                Arc::new(SyntaxTree::And(test.clone(), Arc::new(then.to_formula()?))),
+                Arc::new(SyntaxTree::And(
+                    not_test(),
+                    Arc::new(otherwise.to_formula()?),
+                )),
+            ),
+        };
+        Some(formula)
+    }
+}
+
+/// The entropy of a set of traces with the given number of positive and negative ones.
+fn entropy(positives: usize, negatives: usize) -> f64 {
+    let total = (positives + negatives) as f64;
+    [positives, negatives]
+        .into_iter()
+        .filter(|&count| count > 0)
+        .map(|count| {
+            let p = count as f64 / total;
+            -p * p.log2()
+        })
+        .sum()
+}
+
+/// Learns a decision tree whose tests are the formulas up to a given size (see [`DecisionTree`]),
+/// which classifies samples that no small formula is consistent with.
+///
+/// At each node, if a test is consistent with the traces reaching the node, it becomes the last test of the branch;
+/// otherwise, the test with the highest information gain splits the traces, and the branches are learned recursively.
+/// Lassos are ignored.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DecisionTreeLearner {
+    /// The maximum size of the tests.
+    pub test_size: usize,
+    /// The maximum depth of the tree, past which traces are classified by majority.
+    pub max_depth: usize,
+}
+
+impl Default for DecisionTreeLearner {
+    fn default() -> Self {
+        DecisionTreeLearner {
+            test_size: 3,
+            max_depth: 8,
+        }
+    }
+}
+
+impl DecisionTreeLearner {
+    pub fn test_size(mut self, test_size: usize) -> Self {
+        self.test_size = test_size;
+        self
+    }
+
+    pub fn max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    /// Learns a decision tree classifying the traces of the sample,
+    /// exactly unless the depth limit is reached or two traces of different classes cannot be told apart.
+    pub fn learn_tree<const N: usize>(&self, sample: &Sample<N>) -> DecisionTree {
+        let vars = sample.vars();
+        let tests = (1..=self.test_size)
+            .flat_map(|size| gen_formulae::<N>(size, &vars))
+            .collect::<Vec<_>>();
+        let positives = sample.positive_traces.iter().collect::<Vec<_>>();
+        let negatives = sample.negative_traces.iter().collect::<Vec<_>>();
+        self.split(&tests, &positives, &negatives, 0)
+    }
+
+    fn split<const N: usize>(
+        &self,
+        tests: &[SyntaxTree],
+        positives: &[&Trace<N>],
+        negatives: &[&Trace<N>],
+        depth: usize,
+    ) -> DecisionTree {
+        if negatives.is_empty() || positives.is_empty() || depth >= self.max_depth {
+            return DecisionTree::Leaf(positives.len() >= negatives.len());
+        }
+
+        let last_test = |test: &SyntaxTree, label: bool| DecisionTree::Node {
+            test: test.clone(),
+            then: Box::new(DecisionTree::Leaf(label)),
+            otherwise: Box::new(DecisionTree::Leaf(!label)),
+        };
+        let mut best: Option<(f64, &SyntaxTree)> = None;
+        for test in tests {
+            let satisfied_positives = positives.iter().filter(|trace| test.eval(trace)).count();
+            let satisfied_negatives = negatives.iter().filter(|trace| test.eval(trace)).count();
+            if satisfied_positives == positives.len() && satisfied_negatives == 0 {
+                return last_test(test, true);
+            }
+            if satisfied_positives == 0 && satisfied_negatives == negatives.len() {
+                return last_test(test, false);
+            }
+
+            let satisfied = satisfied_positives + satisfied_negatives;
+            let unsatisfied = positives.len() + negatives.len() - satisfied;
+            if satisfied == 0 || unsatisfied == 0 {
+                continue;
+            }
+            // Minimizing the weighted entropy of the branches maximizes the information gain.
+            let split_entropy = satisfied as f64
+                * entropy(satisfied_positives, satisfied_negatives)
+                + unsatisfied as f64
+                    * entropy(
+                        positives.len() - satisfied_positives,
+                        negatives.len() - satisfied_negatives,
+                    );
+            if best.is_none_or(|(best_entropy, _)| split_entropy < best_entropy) {
+                best = Some((split_entropy, test));
+            }
+        }
+
+        let Some((_, test)) = best else {
+            // No test tells the traces apart.
+            return DecisionTree::Leaf(positives.len() >= negatives.len());
+        };
+        let (then_positives, otherwise_positives): (Vec<_>, Vec<_>) =
+            positives.iter().partition(|trace| test.eval(trace));
+        let (then_negatives, otherwise_negatives): (Vec<_>, Vec<_>) =
+            negatives.iter().partition(|trace| test.eval(trace));
+        let then = self.split(tests, &then_positives, &then_negatives, depth + 1);
+        let otherwise = self.split(tests, &otherwise_positives, &otherwise_negatives, depth + 1);
+        match (&then, &otherwise) {
+            // Both branches classify the same way, so the test is useless.
+            (DecisionTree::Leaf(then_label), DecisionTree::Leaf(otherwise_label))
+                if then_label == otherwise_label =>
+            {
+                then
+            }
+            _ => DecisionTree::Node {
+                test: test.clone(),
+                then: Box::new(then),
+                otherwise: Box::new(otherwise),
+            },
+        }
+    }
+}
+
+impl<const N: usize> Learner<N> for DecisionTreeLearner {
+    /// Learns a decision tree and encodes it as a formula (see [`DecisionTree::to_formula`]),
+    /// which fails if it exceeds the maximum size.
+    /// The time limit is not enforced, as the tree is built greedily.
+    fn learn(&self, sample: &Sample<N>, budget: Budget) -> LearnResult {
+        if !sample.is_solvable() {
+            return LearnResult::Unsolvable;
+        }
+
+        let Some(formula) = self.learn_tree(sample).to_formula() else {
+            return LearnResult::Exhausted;
+        };
+        if budget.max_size.is_some_and(|max| formula.size() > max) {
+            LearnResult::Exhausted
+        } else if sample.is_consistent(&formula) {
+            LearnResult::Consistent(formula)
+        } else {
+            LearnResult::Approximate(formula)
+        }
+    }
+}
+
+#[cfg(test)]
+mod decision_trees {
+    use super::*;
+
+    #[test]
+    fn learn_tree() {
+        // Positive traces are those where exactly one of x0 and x1 holds at first,
+        // so no atom is consistent with the sample.
+        let sample = Sample::<2> {
+            positive_traces: vec![vec![[true, false]], vec![[false, true], [true, true]]],
+            negative_traces: vec![
+                vec![[true, true]],
+                vec![[false, false], [true, false]],
+                vec![[false, false]],
+            ],
+            ..Sample::default()
+        };
+        let learner = DecisionTreeLearner::default().test_size(1);
+        let tree = learner.learn_tree(&sample);
+        assert_eq!(tree.depth(), 2);
+        assert_eq!(tree.nodes(), 3);
+        assert!(sample.positive_traces.iter().all(|trace| tree.eval(trace)));
+        assert!(!sample.negative_traces.iter().any(|trace| tree.eval(trace)));
+
+        let result = learner.learn(&sample, Budget::default());
+        assert!(result.is_consistent());
+        assert_eq!(result.formula(), tree.to_formula().as_ref());
+
+        // With a depth limit, the tree is only an approximation.
+        let shallow = learner.max_depth(1);
+        assert_eq!(shallow.learn_tree(&sample).depth(), 1);
+        assert!(matches!(
+            shallow.learn(&sample, Budget::default()),
+            LearnResult::Approximate(_)
+        ));
+    }
+}
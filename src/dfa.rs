@@ -0,0 +1,389 @@
+use crate::syntax::*;
+use crate::trace::*;
+use std::collections::{BTreeMap, BTreeSet, HashMap, VecDeque};
+
+/// A deterministic finite automaton over the states of traces over `N` variables,
+/// accepting exactly the (non-empty) finite traces satisfying a formula (see [`SyntaxTree::to_dfa`]),
+/// e.g., to use a learned formula as a classifier or as a runtime monitor.
+///
+/// The letters of the automaton are the `2^N` states of traces,
+/// and the initial state is `0`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Dfa<const N: usize> {
+    /// The successors of each state, indexed by letter (see [`Dfa::letter`]).
+    pub transitions: Vec<Vec<usize>>,
+    /// Whether each state is accepting.
+    pub accepting: Vec<bool>,
+}
+
+impl<const N: usize> Dfa<N> {
+    /// The index of a state of a trace as a letter of the automaton, whose `i`-th bit is the value of `xi`.
+    pub fn letter(state: &[bool; N]) -> usize {
+        state
+            .iter()
+            .enumerate()
+            .map(|(var, val)| (*val as usize) << var)
+            .sum()
+    }
+
+    /// The number of states.
+    pub fn len(&self) -> usize {
+        self.accepting.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.accepting.is_empty()
+    }
+
+    /// The state reached by reading the trace from the initial state.
+    pub fn run(&self, trace: &[[bool; N]]) -> usize {
+        trace.iter().fold(0, |state, letter| {
+            self.transitions[state][Self::letter(letter)]
+        })
+    }
+
+    /// Whether the automaton accepts the trace.
+    pub fn accepts(&self, trace: &[[bool; N]]) -> bool {
+        self.accepting[self.run(trace)]
+    }
+
+    /// The minimal automaton accepting the same traces, by partition refinement (Moore's algorithm).
+    /// All the states are assumed to be reachable, as in the automata built by [`SyntaxTree::to_dfa`].
+    pub fn minimize(&self) -> Dfa<N> {
+        // Two states are in the same class if they cannot be told apart by any trace.
+        let mut classes = self
+            .accepting
+            .iter()
+            .map(|accepting| *accepting as usize)
+            .collect::<Vec<_>>();
+        let mut count = 0;
+        loop {
+            // States stay together only if they were together and go to the same classes on every letter.
+            let mut signatures = HashMap::new();
+            let refined = (0..self.len())
+                .map(|state| {
+                    let signature = (
+                        classes[state],
+                        self.transitions[state]
+                            .iter()
+                            .map(|next| classes[*next])
+                            .collect::<Vec<_>>(),
+                    );
+                    let next_class = signatures.len();
+                    *signatures.entry(signature).or_insert(next_class)
+                })
+                .collect::<Vec<_>>();
+            let refined_count = signatures.len();
+            classes = refined;
+            if refined_count == count {
+                break;
+            }
+            count = refined_count;
+        }
+
+        // Classes are numbered by first occurrence, so the class of the initial state is `0`.
+        let mut transitions = vec![Vec::new(); count];
+        let mut accepting = vec![false; count];
+        for state in 0..self.len() {
+            let class = classes[state];
+            if transitions[class].is_empty() {
+                transitions[class] = self.transitions[state]
+                    .iter()
+                    .map(|next| classes[*next])
+                    .collect();
+                accepting[class] = self.accepting[state];
+            }
+        }
+        Dfa {
+            transitions,
+            accepting,
+        }
+    }
+
+    /// The minimal automaton accepting the same traces, with its states numbered in the order
+    /// they are first reached from the initial state, letter by letter (breadth first),
+    /// so that automata accepting the same traces are equal, e.g., to hash formulas by their semantics.
+    pub fn canonical(&self) -> Dfa<N> {
+        let minimal = self.minimize();
+        let mut numbers = vec![None; minimal.len()];
+        numbers[0] = Some(0);
+        let mut order = vec![0];
+        let mut next = 0;
+        while let Some(&state) = order.get(next) {
+            next += 1;
+            for successor in &minimal.transitions[state] {
+                if numbers[*successor].is_none() {
+                    numbers[*successor] = Some(order.len());
+                    order.push(*successor);
+                }
+            }
+        }
+        let number = |state: usize| numbers[state].expect("reachable state");
+        Dfa {
+            transitions: order
+                .iter()
+                .map(|state| {
+                    minimal.transitions[*state]
+                        .iter()
+                        .map(|successor| number(*successor))
+                        .collect()
+                })
+                .collect(),
+            accepting: order
+                .iter()
+                .map(|state| minimal.accepting[*state])
+                .collect(),
+        }
+    }
+
+    /// Whether the two automata accept the same traces,
+    /// e.g., to verify that a formula is equivalent to a smaller one on finite traces.
+    pub fn equivalent(&self, other: &Dfa<N>) -> bool {
+        let mut visited = BTreeSet::from([(0, 0)]);
+        let mut queue = VecDeque::from([(0, 0)]);
+        while let Some((state, other_state)) = queue.pop_front() {
+            if self.accepting[state] != other.accepting[other_state] {
+                return false;
+            }
+            for letter in 0..1 << N {
+                let next = (
+                    self.transitions[state][letter],
+                    other.transitions[other_state][letter],
+                );
+                if visited.insert(next) {
+                    queue.push_back(next);
+                }
+            }
+        }
+        true
+    }
+}
+
+/// What a formula requires of the rest of the trace, from the next position on.
+#[derive(Debug, Clone, PartialOrd, Ord, PartialEq, Eq)]
+enum Obligation {
+    /// There is a next position, where the formula holds.
+    Strong(SyntaxTree),
+    /// If there is a next position, the formula holds there.
+    Weak(SyntaxTree),
+}
+
+impl Obligation {
+    fn negate(self) -> Obligation {
+        match self {
+            Obligation::Strong(formula) => Obligation::Weak(!formula),
+            Obligation::Weak(formula) => Obligation::Strong(!formula),
+        }
+    }
+
+    /// Whether the obligation holds when the trace ends.
+    fn holds_at_end(&self) -> bool {
+        matches!(self, Obligation::Weak(_))
+    }
+}
+
+/// A Boolean combination of obligations, in disjunctive normal form,
+/// with no conjunction including another one, so that there are finitely many of them.
+type Dnf = BTreeSet<BTreeSet<Obligation>>;
+
+fn dnf_bool(value: bool) -> Dnf {
+    if value {
+        BTreeSet::from([BTreeSet::new()])
+    } else {
+        BTreeSet::new()
+    }
+}
+
+fn dnf_obligation(obligation: Obligation) -> Dnf {
+    BTreeSet::from([BTreeSet::from([obligation])])
+}
+
+/// Removes the conjunctions including other ones, which they imply.
+fn dnf_reduce(dnf: Dnf) -> Dnf {
+    dnf.iter()
+        .filter(|clause| {
+            !dnf.iter()
+                .any(|other| other != *clause && other.is_subset(clause))
+        })
+        .cloned()
+        .collect()
+}
+
+fn dnf_or(left: Dnf, right: Dnf) -> Dnf {
+    dnf_reduce(left.into_iter().chain(right).collect())
+}
+
+fn dnf_and(left: &Dnf, right: &Dnf) -> Dnf {
+    dnf_reduce(
+        left.iter()
+            .flat_map(|left| {
+                right
+                    .iter()
+                    .map(move |right| left.union(right).cloned().collect())
+            })
+            .collect(),
+    )
+}
+
+fn dnf_not(dnf: Dnf) -> Dnf {
+    // ¬(∨ᵢ ∧ⱼ lᵢⱼ) ≡ ∧ᵢ ∨ⱼ ¬lᵢⱼ
+    dnf.into_iter().fold(dnf_bool(true), |negation, clause| {
+        let clause = clause
+            .into_iter()
+            .map(|obligation| dnf_obligation(obligation.negate()))
+            .fold(dnf_bool(false), dnf_or);
+        dnf_and(&negation, &clause)
+    })
+}
+
+impl SyntaxTree {
+    /// What the formula requires of the rest of the trace for it to hold at a position with the given state
+    /// (formula progression).
+    fn progress<S: AtomValuation>(&self, state: &S) -> Dnf {
+        match self {
+            SyntaxTree::Atom(var) => dnf_bool(state.holds(*var)),
+            SyntaxTree::Not(branch) => dnf_not(branch.progress(state)),
+            SyntaxTree::Next(branch) => dnf_obligation(Obligation::Strong(branch.as_ref().clone())),
+            // G φ ≡ φ ∧ X_weak G φ
+            SyntaxTree::Globally(branch) => dnf_and(
+                &branch.progress(state),
+                &dnf_obligation(Obligation::Weak(self.clone())),
+            ),
+            // F φ ≡ φ ∨ X F φ
+            SyntaxTree::Finally(branch) => dnf_or(
+                branch.progress(state),
+                dnf_obligation(Obligation::Strong(self.clone())),
+            ),
+            SyntaxTree::And(left_branch, right_branch) => {
+                dnf_and(&left_branch.progress(state), &right_branch.progress(state))
+            }
+            SyntaxTree::Or(left_branch, right_branch) => {
+                dnf_or(left_branch.progress(state), right_branch.progress(state))
+            }
+            SyntaxTree::Implies(left_branch, right_branch) => dnf_or(
+                dnf_not(left_branch.progress(state)),
+                right_branch.progress(state),
+            ),
+            // φ U ψ ≡ ψ ∨ (φ ∧ X (φ U ψ))
+            SyntaxTree::Until(left_branch, right_branch) => dnf_or(
+                right_branch.progress(state),
+                dnf_and(
+                    &left_branch.progress(state),
+                    &dnf_obligation(Obligation::Strong(self.clone())),
+                ),
+            ),
+        }
+    }
+
+    /// Compiles the formula into a deterministic finite automaton over `N` variables
+    /// accepting exactly the non-empty finite traces satisfying it, as evaluated by [`SyntaxTree::eval`]
+    /// (the empty trace is rejected).
+    ///
+    /// The states of the automaton are what the formula requires of the rest of the trace,
+    /// obtained by formula progression, and they are accepting if the trace can end there.
+    /// The automaton can have exponentially many states, and is not minimal (see [`Dfa::minimize`]).
+    ///
+    /// # Panics
+    ///
+    /// If the formula has variables beyond the first `N`.
+    pub fn to_dfa<const N: usize>(&self) -> Dfa<N> {
+        assert!(self.vars() as usize <= N, "formula variables beyond {N}");
+        let letters = (0..1usize << N)
+            .map(|letter| std::array::from_fn::<bool, N, _>(|var| letter >> var & 1 == 1))
+            .collect::<Vec<_>>();
+
+        let initial = dnf_obligation(Obligation::Strong(self.clone()));
+        let mut states = BTreeMap::from([(initial.clone(), 0)]);
+        let mut queue = VecDeque::from([initial]);
+        let mut dfa = Dfa {
+            transitions: Vec::new(),
+            accepting: Vec::new(),
+        };
+        while let Some(dnf) = queue.pop_front() {
+            dfa.accepting.push(
+                dnf.iter()
+                    .any(|clause| clause.iter().all(Obligation::holds_at_end)),
+            );
+            let successors = letters
+                .iter()
+                .map(|letter| {
+                    // Every obligation holds at the next position if the state is read there.
+                    let next = dnf.iter().fold(dnf_bool(false), |next, clause| {
+                        let clause = clause.iter().fold(dnf_bool(true), |clause, obligation| {
+                            let (Obligation::Strong(formula) | Obligation::Weak(formula)) =
+                                obligation;
+                            dnf_and(&clause, &formula.progress(letter))
+                        });
+                        dnf_or(next, clause)
+                    });
+                    let count = states.len();
+                    *states.entry(next.clone()).or_insert_with(|| {
+                        queue.push_back(next);
+                        count
+                    })
+                })
+                .collect();
+            dfa.transitions.push(successors);
+        }
+        dfa
+    }
+}
+
+#[cfg(test)]
+mod finite_automata {
+    use super::*;
+    use itertools::Itertools;
+
+    #[test]
+    fn to_dfa() {
+        let x0 = SyntaxTree::Atom(0);
+        let x1 = SyntaxTree::Atom(1);
+        let formulas = [
+            x0.clone(),
+            !x0.clone(),
+            x0.clone().next(),
+            (!x0.clone().next()).globally(),
+            x0.clone().globally(),
+            x0.clone().finally().globally(),
+            x0.clone().until(x1.clone()),
+            !x0.clone().until(x1.clone().next()),
+            x0.clone().implies(x1.clone().finally()).globally(),
+            x0.clone().or(x1.clone().globally()),
+        ];
+        let states = [[false, false], [true, false], [false, true], [true, true]];
+        let traces = (1..=4)
+            .flat_map(|len| {
+                (0..len)
+                    .map(|_| states)
+                    .multi_cartesian_product()
+                    .collect_vec()
+            })
+            .collect_vec();
+        for formula in &formulas {
+            let dfa = formula.to_dfa::<2>();
+            let minimal = dfa.minimize();
+            assert!(minimal.len() <= dfa.len());
+            assert!(!dfa.accepts(&[]));
+            for trace in &traces {
+                assert_eq!(
+                    dfa.accepts(trace),
+                    formula.eval(trace),
+                    "{formula} on {trace:?}"
+                );
+                assert_eq!(minimal.accepts(trace), formula.eval(trace));
+            }
+        }
+
+        // ¬X¬x0 ≠ X x0 on finite traces, as Next is strong; G x0 ≡ ¬F¬x0.
+        let weak_next = (!(!x0.clone()).next()).to_dfa::<2>();
+        assert!(!weak_next.equivalent(&x0.clone().next().to_dfa()));
+        let globally = x0.clone().globally().to_dfa::<2>();
+        assert!(globally.equivalent(&(!(!x0.clone()).finally()).to_dfa()));
+        assert_eq!(globally.minimize().len(), 3);
+        assert_eq!(
+            globally.canonical(),
+            (!(!x0.clone()).finally()).to_dfa().canonical()
+        );
+        assert_ne!(globally.canonical(), weak_next.canonical());
+    }
+}
@@ -0,0 +1,183 @@
+use crate::trace::*;
+use itertools::Itertools;
+use std::collections::HashSet;
+use std::fmt;
+use std::hash::Hash;
+
+/// The changes between two collections of labelled items (traces or lassos), ignoring order and repetitions.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diff<T> {
+    /// Items only in the new collection, with their label.
+    pub added: Vec<(Label, T)>,
+    /// Items only in the old collection, with their label.
+    pub removed: Vec<(Label, T)>,
+    /// Items in both collections with different labels, with their new label.
+    pub relabeled: Vec<(T, Label)>,
+}
+
+impl<T> Default for Diff<T> {
+    fn default() -> Self {
+        Diff {
+            added: Vec::new(),
+            removed: Vec::new(),
+            relabeled: Vec::new(),
+        }
+    }
+}
+
+impl<T: Clone + Eq + Hash> Diff<T> {
+    fn new<'a>(
+        old: impl Iterator<Item = (Label, &'a T)> + Clone,
+        new: impl Iterator<Item = (Label, &'a T)> + Clone,
+    ) -> Self
+    where
+        T: 'a,
+    {
+        let old_set = old.clone().collect::<HashSet<_>>();
+        let new_set = new.clone().collect::<HashSet<_>>();
+        let mut added = new
+            .filter(|item| !old_set.contains(item))
+            .unique()
+            .collect::<Vec<_>>();
+        let mut removed = old
+            .filter(|item| !new_set.contains(item))
+            .unique()
+            .collect::<Vec<_>>();
+
+        // An item removed with a label and added with the other was relabeled.
+        let removed_items = removed
+            .iter()
+            .map(|(_, item)| *item)
+            .collect::<HashSet<_>>();
+        let added_items = added.iter().map(|(_, item)| *item).collect::<HashSet<_>>();
+        let relabeled = added
+            .iter()
+            .filter(|(_, item)| removed_items.contains(item))
+            .map(|(label, item)| ((*item).clone(), *label))
+            .collect();
+        added.retain(|(_, item)| !removed_items.contains(item));
+        removed.retain(|(_, item)| !added_items.contains(item));
+
+        let owned = |items: Vec<(Label, &T)>| {
+            items
+                .into_iter()
+                .map(|(label, item)| (label, item.clone()))
+                .collect()
+        };
+        Diff {
+            added: owned(added),
+            removed: owned(removed),
+            relabeled,
+        }
+    }
+}
+
+impl<T> Diff<T> {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.relabeled.is_empty()
+    }
+
+    fn summary(&self) -> String {
+        format!(
+            "{} added, {} removed, {} relabeled",
+            self.added.len(),
+            self.removed.len(),
+            self.relabeled.len()
+        )
+    }
+}
+
+/// The changes between two versions of a sample (see [`Sample::diff`]).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SampleDiff<const N: usize> {
+    /// Whether the variables were renamed.
+    pub var_names_changed: bool,
+    pub traces: Diff<Trace<N>>,
+    pub lassos: Diff<Lasso<N>>,
+}
+
+impl<const N: usize> SampleDiff<N> {
+    /// Whether the samples have the same variable names and (labelled) traces.
+    pub fn is_empty(&self) -> bool {
+        !self.var_names_changed && self.traces.is_empty() && self.lassos.is_empty()
+    }
+}
+
+impl<const N: usize> fmt::Display for SampleDiff<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.var_names_changed {
+            writeln!(f, "Variable names changed")?;
+        }
+        writeln!(f, "Traces: {}", self.traces.summary())?;
+        writeln!(f, "Lassos: {}", self.lassos.summary())?;
+        Ok(())
+    }
+}
+
+fn labelled<'a, T>(
+    positives: &'a [T],
+    negatives: &'a [T],
+) -> impl Iterator<Item = (Label, &'a T)> + Clone {
+    positives
+        .iter()
+        .map(|item| (Label::Positive, item))
+        .chain(negatives.iter().map(|item| (Label::Negative, item)))
+}
+
+impl<const N: usize> Sample<N> {
+    /// Compares the sample to a newer version of it,
+    /// reporting which traces (and lassos) were added, removed, or relabeled,
+    /// regardless of their order and repetitions.
+    pub fn diff(&self, other: &Sample<N>) -> SampleDiff<N> {
+        SampleDiff {
+            var_names_changed: self.var_names != other.var_names,
+            traces: Diff::new(
+                labelled(&self.positive_traces, &self.negative_traces),
+                labelled(&other.positive_traces, &other.negative_traces),
+            ),
+            lassos: Diff::new(
+                labelled(&self.positive_lassos, &self.negative_lassos),
+                labelled(&other.positive_lassos, &other.negative_lassos),
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod sample_diff {
+    use super::*;
+
+    #[test]
+    fn diff() {
+        let old = Sample::<1> {
+            positive_traces: vec![vec![[true]], vec![[true], [false]]],
+            negative_traces: vec![vec![[false]]],
+            ..Sample::default()
+        };
+        let new = Sample::<1> {
+            positive_traces: vec![vec![[true]], vec![[false]], vec![[true]]],
+            negative_traces: vec![vec![[false], [false]]],
+            ..Sample::default()
+        };
+
+        let diff = old.diff(&new);
+        assert_eq!(
+            diff.traces.added,
+            vec![(Label::Negative, vec![[false], [false]])]
+        );
+        assert_eq!(
+            diff.traces.removed,
+            vec![(Label::Positive, vec![[true], [false]])]
+        );
+        assert_eq!(
+            diff.traces.relabeled,
+            vec![(vec![[false]], Label::Positive)]
+        );
+        assert!(diff.lassos.is_empty());
+        assert_eq!(
+            diff.to_string(),
+            "Traces: 1 added, 1 removed, 1 relabeled\nLassos: 0 added, 0 removed, 0 relabeled\n"
+        );
+        assert!(new.diff(&new).is_empty());
+    }
+}
@@ -0,0 +1,207 @@
+use crate::learn::*;
+use crate::syntax::*;
+use crate::trace::*;
+
+/// A classifier made of several formulas, each with a (positive) weight,
+/// classifying a trace as positive if the formulas it satisfies outweigh those it violates.
+/// With equal weights, it is a majority vote (see [`Ensemble::majority`]).
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Ensemble {
+    /// The formulas with their weights.
+    pub members: Vec<(SyntaxTree, f64)>,
+}
+
+impl Ensemble {
+    /// A majority vote of the formulas.
+    pub fn majority(formulas: impl IntoIterator<Item = SyntaxTree>) -> Self {
+        Ensemble {
+            members: formulas.into_iter().map(|formula| (formula, 1.0)).collect(),
+        }
+    }
+
+    /// Adds a formula with the given weight.
+    pub fn member(mut self, formula: SyntaxTree, weight: f64) -> Self {
+        self.members.push((formula, weight));
+        self
+    }
+
+    /// The weight of the formulas satisfied by the trace, minus the weight of those it violates.
+    pub fn score<S: AtomValuation>(&self, trace: &[S]) -> f64 {
+        self.members
+            .iter()
+            .map(|(formula, weight)| {
+                if formula.eval(trace) {
+                    *weight
+                } else {
+                    -weight
+                }
+            })
+            .sum()
+    }
+
+    /// Classifies a trace as positive (`true`) or negative (`false`), breaking ties as negative.
+    pub fn classify<S: AtomValuation>(&self, trace: &[S]) -> bool {
+        self.score(trace) > 0.0
+    }
+
+    /// The fraction of the traces of the sample correctly classified (`1.0` if there are none).
+    /// Lassos are ignored.
+    pub fn accuracy<const N: usize>(&self, sample: &Sample<N>) -> f64 {
+        let total = sample.positive_traces.len() + sample.negative_traces.len();
+        if total == 0 {
+            return 1.0;
+        }
+        let correct = sample
+            .positive_traces
+            .iter()
+            .filter(|trace| self.classify(trace))
+            .count()
+            + sample
+                .negative_traces
+                .iter()
+                .filter(|trace| !self.classify(trace))
+                .count();
+        correct as f64 / total as f64
+    }
+}
+
+/// Learns an [`Ensemble`] by boosting (AdaBoost), for samples that no single small formula classifies well,
+/// e.g., noisy ones: each round adds the formula up to the given size with the least weighted error,
+/// then increases the weight of the traces it misclassifies, so that the next formulas focus on them.
+/// Lassos are ignored.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BoostingLearner {
+    /// The maximum number of formulas of the ensemble.
+    pub rounds: usize,
+    /// The maximum size of the formulas of the ensemble.
+    pub formula_size: usize,
+}
+
+impl Default for BoostingLearner {
+    fn default() -> Self {
+        BoostingLearner {
+            rounds: 10,
+            formula_size: 3,
+        }
+    }
+}
+
+impl BoostingLearner {
+    pub fn rounds(mut self, rounds: usize) -> Self {
+        self.rounds = rounds;
+        self
+    }
+
+    pub fn formula_size(mut self, formula_size: usize) -> Self {
+        self.formula_size = formula_size;
+        self
+    }
+
+    /// Learns an ensemble of up to the configured number of formulas,
+    /// stopping early if a formula classifies every trace correctly (which is then the only member)
+    /// or if no formula does better than chance on the weighted traces.
+    pub fn learn_ensemble<const N: usize>(&self, sample: &Sample<N>) -> Ensemble {
+        let traces = sample
+            .positive_traces
+            .iter()
+            .map(|trace| (trace, true))
+            .chain(sample.negative_traces.iter().map(|trace| (trace, false)))
+            .collect::<Vec<_>>();
+        let mut ensemble = Ensemble::default();
+        if traces.is_empty() {
+            return ensemble;
+        }
+
+        // Whether each candidate formula classifies each trace correctly, computed once.
+        let vars = sample.vars();
+        let candidates = (1..=self.formula_size)
+            .flat_map(|size| gen_formulae::<N>(size, &vars))
+            .map(|formula| {
+                let correct = traces
+                    .iter()
+                    .map(|(trace, label)| formula.eval(trace) == *label)
+                    .collect::<Vec<_>>();
+                (formula, correct)
+            })
+            .collect::<Vec<_>>();
+
+        let mut weights = vec![1.0 / traces.len() as f64; traces.len()];
+        for _ in 0..self.rounds {
+            let error = |correct: &[bool]| {
+                correct
+                    .iter()
+                    .zip(&weights)
+                    .filter(|(correct, _)| !**correct)
+                    .map(|(_, weight)| weight)
+                    .sum::<f64>()
+            };
+            let Some((formula, correct, error)) = candidates
+                .iter()
+                .map(|(formula, correct)| (formula, correct, error(correct)))
+                .min_by(|(_, _, left), (_, _, right)| left.total_cmp(right))
+            else {
+                break;
+            };
+            if error <= 0.0 {
+                return Ensemble::default().member(formula.clone(), 1.0);
+            }
+            if error >= 0.5 {
+                break;
+            }
+
+            let alpha = 0.5 * ((1.0 - error) / error).ln();
+            ensemble = ensemble.member(formula.clone(), alpha);
+            for (weight, correct) in weights.iter_mut().zip(correct) {
+                *weight *= if *correct {
+                    (-alpha).exp()
+                } else {
+                    alpha.exp()
+                };
+            }
+            let total = weights.iter().sum::<f64>();
+            weights.iter_mut().for_each(|weight| *weight /= total);
+        }
+        ensemble
+    }
+}
+
+#[cfg(test)]
+mod ensemble_classification {
+    use super::*;
+
+    #[test]
+    fn boosting() {
+        // Positive traces are those where at least two of x0, x1 and x2 hold at first,
+        // which no formula of size 1 expresses, but a majority vote of x0, x1 and x2 does.
+        let mut sample = Sample::<3>::default();
+        for state in 0..8 {
+            let state = std::array::from_fn(|var| state >> var & 1 == 1);
+            if state.iter().filter(|val| **val).count() >= 2 {
+                sample.positive_traces.push(vec![state]);
+            } else {
+                sample.negative_traces.push(vec![state]);
+            }
+        }
+        let atoms = (0..3).map(SyntaxTree::Atom);
+        assert_eq!(Ensemble::majority(atoms.clone()).accuracy(&sample), 1.0);
+        assert!(atoms
+            .map(|atom| sample.accuracy(&atom))
+            .all(|accuracy| accuracy < 1.0));
+
+        let ensemble = BoostingLearner::default()
+            .formula_size(1)
+            .learn_ensemble(&sample);
+        assert!(ensemble.members.len() > 1);
+        assert_eq!(ensemble.accuracy(&sample), 1.0);
+
+        // A formula classifying every trace correctly is enough.
+        let sample = Sample::<3> {
+            positive_traces: vec![vec![[true, false, false]]],
+            negative_traces: vec![vec![[false, false, false]]],
+            ..Sample::default()
+        };
+        let ensemble = BoostingLearner::default().learn_ensemble(&sample);
+        assert_eq!(ensemble.members.len(), 1);
+        assert!(sample.is_consistent(&ensemble.members[0].0));
+    }
+}
@@ -0,0 +1,238 @@
+use crate::dfa::*;
+use crate::learn::*;
+use crate::source::*;
+use crate::syntax::*;
+use std::collections::HashSet;
+use std::sync::Arc;
+
+/// A set of formulas over `N` variables up to equivalence on finite traces,
+/// decided exactly by comparing their minimal automata (see [`SyntaxTree::to_dfa`] and [`Dfa::canonical`]).
+///
+/// Unlike the syntactic filters of the search, it catches every equivalence,
+/// at the cost of compiling each formula into an automaton, which is exponential in its size.
+/// Lassos are not taken into account: formulas equivalent on finite traces can still differ on them.
+#[derive(Debug, Clone, Default)]
+pub struct EquivalenceFilter<const N: usize> {
+    automata: HashSet<Dfa<N>>,
+}
+
+impl<const N: usize> EquivalenceFilter<N> {
+    /// Adds the formula, returning whether no equivalent formula was in the set.
+    pub fn insert(&mut self, formula: &SyntaxTree) -> bool {
+        self.automata.insert(formula.to_dfa::<N>().canonical())
+    }
+
+    /// Whether an equivalent formula is in the set.
+    pub fn contains(&self, formula: &SyntaxTree) -> bool {
+        self.automata.contains(&formula.to_dfa::<N>().canonical())
+    }
+
+    /// The number of pairwise non-equivalent formulas in the set.
+    pub fn len(&self) -> usize {
+        self.automata.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.automata.is_empty()
+    }
+}
+
+/// Generates the formulas of the next size, built bottom-up from the formulas of the smaller sizes,
+/// keeping only those that pass the syntactic filters and are not equivalent to any formula generated before.
+fn gen_next_distinct<const N: usize>(
+    levels: &mut Vec<Vec<Arc<SyntaxTree>>>,
+    filter: &mut EquivalenceFilter<N>,
+    vars: &[Idx],
+) {
+    let size = levels.len();
+    let mut candidates = Vec::new();
+    if size == 1 {
+        candidates.extend(vars.iter().map(|var| SyntaxTree::Atom(*var)));
+    } else {
+        for child in &levels[size - 1] {
+            push_unary(&mut candidates, child.clone());
+        }
+        for left_size in 1..(size - 1) {
+            for left in &levels[left_size] {
+                for right in &levels[size - 1 - left_size] {
+                    push_binary(&mut candidates, left.clone(), right.clone());
+                }
+            }
+        }
+    }
+    let level = candidates
+        .into_iter()
+        .filter(|formula| filter.insert(formula))
+        .map(Arc::new)
+        .collect();
+    levels.push(level);
+}
+
+/// Generates the formulas up to the given size over the given variables, by size,
+/// such that no two are equivalent on finite traces (see [`EquivalenceFilter`]),
+/// and that every formula is equivalent to one at most as large, but for constant ones (see [`unjustified_pruning`]).
+/// Formulas are built from the previous ones, so equivalent subformulas are never combined twice,
+/// which prunes far more than the syntactic filters of [`gen_formulae`].
+///
+/// The result is indexed by size, starting with an empty `0`-th entry.
+pub fn gen_distinct_formulae<const N: usize>(
+    max_size: usize,
+    vars: &[Idx],
+) -> Vec<Vec<SyntaxTree>> {
+    let mut levels = vec![Vec::new()];
+    let mut filter = EquivalenceFilter::<N>::default();
+    for _ in 1..=max_size {
+        gen_next_distinct(&mut levels, &mut filter, vars);
+    }
+    levels
+        .into_iter()
+        .map(|level| {
+            level
+                .iter()
+                .map(|formula| formula.as_ref().clone())
+                .collect()
+        })
+        .collect()
+}
+
+/// The formulas up to the given size over the given variables that the syntactic filters of the search discard
+/// although no formula it keeps, up to the same size, is equivalent to them on finite traces.
+/// An empty result validates the filters up to that size.
+///
+/// As in [`count_pruned`], only the formulas whose main operator is discarded are checked,
+/// those with discarded subformulas being equivalent to formulas with kept ones.
+pub fn unjustified_pruning<const N: usize>(max_size: usize, vars: &[Idx]) -> Vec<SyntaxTree> {
+    let mut kept = EquivalenceFilter::<N>::default();
+    let mut unjustified = Vec::new();
+    for size in 1..=max_size {
+        for formula in gen_formulae::<N>(size, vars) {
+            kept.insert(&formula);
+        }
+        let mut pruned = Vec::new();
+        for skeleton in SkeletonTree::gen(size) {
+            match skeleton {
+                SkeletonTree::Leaf => {}
+                SkeletonTree::UnaryNode(child) => {
+                    for child in child.gen_formulae::<N>(vars) {
+                        let child = Arc::new(child);
+                        let mut all = vec![
+                            SyntaxTree::Not(child.clone()),
+                            SyntaxTree::Next(child.clone()),
+                            SyntaxTree::Globally(child.clone()),
+                            SyntaxTree::Finally(child.clone()),
+                        ];
+                        let mut filtered = Vec::new();
+                        push_unary(&mut filtered, child);
+                        all.retain(|formula| !filtered.contains(formula));
+                        pruned.extend(all);
+                    }
+                }
+                SkeletonTree::BinaryNode(children) => {
+                    let right_children = children.1.gen_formulae::<N>(vars);
+                    for left in children.0.gen_formulae::<N>(vars) {
+                        let left = Arc::new(left);
+                        for right in &right_children {
+                            let right = Arc::new(right.clone());
+                            let mut all = vec![
+                                SyntaxTree::And(left.clone(), right.clone()),
+                                SyntaxTree::Or(left.clone(), right.clone()),
+                                SyntaxTree::Implies(left.clone(), right.clone()),
+                                SyntaxTree::Until(left.clone(), right.clone()),
+                            ];
+                            let mut filtered = Vec::new();
+                            push_binary(&mut filtered, left.clone(), right);
+                            all.retain(|formula| !filtered.contains(formula));
+                            pruned.extend(all);
+                        }
+                    }
+                }
+            }
+        }
+        unjustified.extend(pruned.into_iter().filter(|formula| !kept.contains(formula)));
+    }
+    unjustified
+}
+
+/// Find a formula consistent with the given sample, which can be any [`SampleSource`],
+/// among formulas pairwise non-equivalent on finite traces (see [`gen_distinct_formulae`]), smallest first.
+/// Solutions are as small as those of [`solve`](crate::solve) if the sample has no lassos.
+///
+/// Searches up to the maximum size if any, otherwise until it finds a formula, which it does if the sample is solvable.
+pub fn solve_distinct<const N: usize, S: SampleSource<N>>(
+    sample: &S,
+    max_size: Option<usize>,
+) -> Option<SyntaxTree> {
+    if !sample.is_solvable() {
+        return None;
+    }
+
+    let vars = sample.vars();
+    let mut levels = vec![Vec::new()];
+    let mut filter = EquivalenceFilter::<N>::default();
+    for size in 1..=max_size.unwrap_or(usize::MAX) {
+        gen_next_distinct(&mut levels, &mut filter, &vars);
+        if let Some(formula) = levels[size]
+            .iter()
+            .find(|formula| sample.is_consistent(formula))
+        {
+            return Some(formula.as_ref().clone());
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod equivalence_filter {
+    use super::*;
+    use crate::trace::*;
+
+    #[test]
+    fn syntactic_filters() {
+        // The filters only discard formulas without an equivalent kept one when they are constant,
+        // e.g., `x0 → x0`, or `G X x0` as Next is strong, which are never useful as subformulas.
+        let x0 = SyntaxTree::Atom(0);
+        let mut constants = EquivalenceFilter::<2>::default();
+        constants.insert(&x0.clone().or(!x0.clone()));
+        constants.insert(&x0.clone().and(!x0.clone()));
+        let unjustified = unjustified_pruning::<2>(5, &[0, 1]);
+        assert!(unjustified.contains(&x0.clone().implies(x0.clone())));
+        for formula in &unjustified {
+            assert!(
+                constants.contains(formula),
+                "pruned without an equivalent formula: {formula}"
+            );
+        }
+    }
+
+    #[test]
+    fn distinct_formulae() {
+        let x0 = SyntaxTree::Atom(0);
+        let mut filter = EquivalenceFilter::<1>::default();
+        assert!(filter.insert(&x0.clone().globally()));
+        assert!(!filter.insert(&(!(!x0.clone()).finally())));
+        assert!(filter.contains(&x0.clone().globally().globally()));
+        assert_eq!(filter.len(), 1);
+
+        let distinct = gen_distinct_formulae::<2>(4, &[0, 1]);
+        let syntactic = (1..=4)
+            .map(|size| gen_formulae::<2>(size, &[0, 1]).len())
+            .sum::<usize>();
+        assert!(distinct.iter().map(Vec::len).sum::<usize>() < syntactic);
+
+        let sample = Sample::<2> {
+            positive_traces: vec![
+                vec![[false, false], [true, true]],
+                vec![[false, true], [true, false]],
+            ],
+            negative_traces: vec![
+                vec![[true, true], [false, true]],
+                vec![[false, false], [true, false]],
+            ],
+            ..Sample::default()
+        };
+        let formula = solve_distinct(&sample, None).expect("solvable");
+        let smallest = solve(&sample, false, false).expect("solvable");
+        assert!(sample.is_consistent(&formula));
+        assert_eq!(formula.size(), smallest.size());
+    }
+}
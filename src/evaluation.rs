@@ -0,0 +1,334 @@
+use crate::metadata::*;
+use crate::syntax::*;
+use crate::trace::*;
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+/// How a formula classifies the traces of a sample, compared to their labels.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Confusion {
+    /// Positive traces satisfying the formula.
+    pub true_positives: usize,
+    /// Positive traces violating the formula.
+    pub false_negatives: usize,
+    /// Negative traces violating the formula.
+    pub true_negatives: usize,
+    /// Negative traces satisfying the formula.
+    pub false_positives: usize,
+}
+
+impl Confusion {
+    /// The number of classified traces.
+    pub fn total(&self) -> usize {
+        self.true_positives + self.false_negatives + self.true_negatives + self.false_positives
+    }
+
+    /// The number of misclassified traces.
+    pub fn misclassified(&self) -> usize {
+        self.false_negatives + self.false_positives
+    }
+
+    /// The fraction of correctly classified traces (`1.0` if there are none).
+    pub fn accuracy(&self) -> f64 {
+        ratio(self.true_positives + self.true_negatives, self.total())
+    }
+
+    /// The fraction of traces satisfying the formula that are positive (`1.0` if there are none).
+    pub fn precision(&self) -> f64 {
+        ratio(
+            self.true_positives,
+            self.true_positives + self.false_positives,
+        )
+    }
+
+    /// The fraction of positive traces satisfying the formula (`1.0` if there are none).
+    pub fn recall(&self) -> f64 {
+        ratio(
+            self.true_positives,
+            self.true_positives + self.false_negatives,
+        )
+    }
+}
+
+fn ratio(num: usize, den: usize) -> f64 {
+    if den == 0 {
+        1.0
+    } else {
+        num as f64 / den as f64
+    }
+}
+
+impl<const N: usize> Sample<N> {
+    /// Classifies the traces of the sample (lassos included) with the formula.
+    pub fn confusion(&self, formula: &SyntaxTree) -> Confusion {
+        let positives = self
+            .positive_traces
+            .iter()
+            .map(|trace| formula.eval(trace))
+            .chain(
+                self.positive_lassos
+                    .iter()
+                    .map(|lasso| formula.eval_lasso(lasso)),
+            );
+        let negatives = self
+            .negative_traces
+            .iter()
+            .map(|trace| formula.eval(trace))
+            .chain(
+                self.negative_lassos
+                    .iter()
+                    .map(|lasso| formula.eval_lasso(lasso)),
+            );
+
+        let mut confusion = Confusion::default();
+        for sat in positives {
+            if sat {
+                confusion.true_positives += 1;
+            } else {
+                confusion.false_negatives += 1;
+            }
+        }
+        for sat in negatives {
+            if sat {
+                confusion.false_positives += 1;
+            } else {
+                confusion.true_negatives += 1;
+            }
+        }
+        confusion
+    }
+
+    /// The fraction of traces correctly classified by the formula.
+    pub fn accuracy(&self, formula: &SyntaxTree) -> f64 {
+        self.confusion(formula).accuracy()
+    }
+
+    /// Randomly splits the sample into a training and a test sample,
+    /// where the training sample gets (about) the given fraction of the traces.
+    /// The split is stratified: positive and negative traces (and lassos) are split separately,
+    /// so that both samples have the same class proportions as the original one.
+    pub fn split<R: Rng + ?Sized>(&self, ratio: f64, rng: &mut R) -> (Sample<N>, Sample<N>) {
+        assert!((0.0..=1.0).contains(&ratio), "ratio must be in [0, 1]");
+
+        let [positives, negatives, positive_lassos, negative_lassos] =
+            self.class_sizes().map(|len| split_indices(len, ratio, rng));
+        let train = self.subsample([
+            &positives.0,
+            &negatives.0,
+            &positive_lassos.0,
+            &negative_lassos.0,
+        ]);
+        let test = self.subsample([
+            &positives.1,
+            &negatives.1,
+            &positive_lassos.1,
+            &negative_lassos.1,
+        ]);
+        (train, test)
+    }
+
+    /// Randomly partitions the sample into `k` folds of (about) the same size,
+    /// and returns, for each fold, the pair of the training sample made of the other folds
+    /// and the validation sample made of the fold itself.
+    /// As for [`Sample::split`], folds are stratified.
+    pub fn kfold<R: Rng + ?Sized>(&self, k: usize, rng: &mut R) -> Vec<(Sample<N>, Sample<N>)> {
+        assert!(k >= 2, "k-fold cross-validation needs at least 2 folds");
+
+        // The fold of each trace is given by its position among the shuffled ones.
+        let shuffled = self.class_sizes().map(|len| {
+            let mut indices = (0..len).collect::<Vec<_>>();
+            indices.shuffle(rng);
+            indices
+        });
+        (0..k)
+            .map(|fold| {
+                let [train, validation] = [false, true].map(|in_fold| {
+                    shuffled.clone().map(|indices| {
+                        indices
+                            .into_iter()
+                            .enumerate()
+                            .filter(|(position, _)| (position % k == fold) == in_fold)
+                            .map(|(_, index)| index)
+                            .collect::<Vec<_>>()
+                    })
+                });
+                (
+                    self.subsample(train.each_ref().map(Vec::as_slice)),
+                    self.subsample(validation.each_ref().map(Vec::as_slice)),
+                )
+            })
+            .collect()
+    }
+
+    /// The number of positive traces, negative traces, positive lassos and negative lassos.
+    fn class_sizes(&self) -> [usize; 4] {
+        [
+            self.positive_traces.len(),
+            self.negative_traces.len(),
+            self.positive_lassos.len(),
+            self.negative_lassos.len(),
+        ]
+    }
+
+    /// The sample made of the positive traces, negative traces, positive lassos and negative lassos
+    /// at the given indices (and their metadata).
+    fn subsample(
+        &self,
+        [positives, negatives, positive_lassos, negative_lassos]: [&[usize]; 4],
+    ) -> Sample<N> {
+        Sample {
+            var_names: self.var_names.clone(),
+            positive_traces: select(&self.positive_traces, positives),
+            negative_traces: select(&self.negative_traces, negatives),
+            positive_lassos: select(&self.positive_lassos, positive_lassos),
+            negative_lassos: select(&self.negative_lassos, negative_lassos),
+            positive_metadata: reindex(&self.positive_metadata, positives.iter().copied()),
+            negative_metadata: reindex(&self.negative_metadata, negatives.iter().copied()),
+            length_policy: self.length_policy,
+            ..Sample::default()
+        }
+    }
+}
+
+/// The outcome of a k-fold cross-validation (see [`cross_validate`]).
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CrossValidation {
+    /// For each fold, the accuracy on the validation sample of the formula learned from the training sample,
+    /// or `None` if the learner found no formula.
+    pub accuracies: Vec<Option<f64>>,
+}
+
+impl CrossValidation {
+    /// The number of folds on which the learner found no formula.
+    pub fn failures(&self) -> usize {
+        self.accuracies.iter().filter(|acc| acc.is_none()).count()
+    }
+
+    /// The mean validation accuracy over the folds on which a formula was found (`NaN` if none).
+    pub fn mean_accuracy(&self) -> f64 {
+        let accuracies = self.accuracies.iter().flatten().collect::<Vec<_>>();
+        accuracies.iter().copied().sum::<f64>() / accuracies.len() as f64
+    }
+
+    /// The (sample) standard deviation of the validation accuracy over the folds on which a formula was found
+    /// (`NaN` if fewer than two).
+    pub fn std_dev(&self) -> f64 {
+        let accuracies = self.accuracies.iter().flatten().collect::<Vec<_>>();
+        let mean = self.mean_accuracy();
+        let variance = accuracies
+            .iter()
+            .map(|acc| (*acc - mean).powi(2))
+            .sum::<f64>()
+            / (accuracies.len() as f64 - 1.0);
+        variance.sqrt()
+    }
+}
+
+/// Runs a learner on each of the `k` folds of the sample (see [`Sample::kfold`]),
+/// measuring the accuracy of the learned formulae on the held-out folds,
+/// to compare learners (or configurations of the same learner) on how well they generalize.
+pub fn cross_validate<const N: usize, R, L>(
+    sample: &Sample<N>,
+    k: usize,
+    rng: &mut R,
+    mut learner: L,
+) -> CrossValidation
+where
+    R: Rng + ?Sized,
+    L: FnMut(&Sample<N>) -> Option<SyntaxTree>,
+{
+    let accuracies = sample
+        .kfold(k, rng)
+        .into_iter()
+        .map(|(train, validation)| learner(&train).map(|formula| validation.accuracy(&formula)))
+        .collect();
+    CrossValidation { accuracies }
+}
+
+/// Shuffles the indices up to `len` and splits them according to the ratio.
+fn split_indices<R: Rng + ?Sized>(len: usize, ratio: f64, rng: &mut R) -> (Vec<usize>, Vec<usize>) {
+    let mut indices = (0..len).collect::<Vec<_>>();
+    indices.shuffle(rng);
+    let test = indices.split_off((len as f64 * ratio).round() as usize);
+    (indices, test)
+}
+
+fn select<T: Clone>(items: &[T], indices: &[usize]) -> Vec<T> {
+    indices.iter().map(|index| items[*index].clone()).collect()
+}
+
+#[cfg(test)]
+mod generalization {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn confusion() {
+        let sample = Sample::<1> {
+            positive_traces: vec![vec![[true]], vec![[false]]],
+            negative_traces: vec![vec![[false], [false]]],
+            ..Sample::default()
+        };
+
+        let confusion = sample.confusion(&SyntaxTree::Atom(0));
+        assert_eq!(confusion.true_positives, 1);
+        assert_eq!(confusion.false_negatives, 1);
+        assert_eq!(confusion.true_negatives, 1);
+        assert_eq!(confusion.misclassified(), 1);
+        assert_eq!(confusion.precision(), 1.0);
+        assert!((sample.accuracy(&SyntaxTree::Atom(0)) - 2.0 / 3.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn stratified_split() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let sample = Sample::<1> {
+            positive_traces: (1..=10).map(|len| vec![[true]; len]).collect(),
+            negative_traces: (1..=20).map(|len| vec![[false]; len]).collect(),
+            ..Sample::default()
+        };
+
+        let (train, test) = sample.split(0.8, &mut rng);
+        assert_eq!(train.positive_traces(), 8);
+        assert_eq!(train.negative_traces(), 16);
+        assert_eq!(test.positive_traces(), 2);
+        assert_eq!(test.negative_traces(), 4);
+        assert!(test
+            .positive_traces
+            .iter()
+            .all(|trace| !train.positive_traces.contains(trace)));
+    }
+
+    #[test]
+    fn kfold() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let sample = Sample::<1> {
+            positive_traces: (1..=10).map(|len| vec![[true]; len]).collect(),
+            negative_traces: (1..=5).map(|len| vec![[false]; len]).collect(),
+            ..Sample::default()
+        };
+
+        let folds = sample.kfold(5, &mut rng);
+        assert_eq!(folds.len(), 5);
+        for (train, validation) in &folds {
+            assert_eq!(train.positive_traces(), 8);
+            assert_eq!(train.negative_traces(), 4);
+            assert_eq!(validation.positive_traces(), 2);
+            assert_eq!(validation.negative_traces(), 1);
+        }
+        // Every negative trace is validated on exactly once.
+        let mut validated = folds
+            .iter()
+            .flat_map(|(_, validation)| validation.negative_traces.clone())
+            .collect::<Vec<_>>();
+        validated.sort();
+        assert_eq!(validated, sample.negative_traces);
+
+        let validation = cross_validate(&sample, 5, &mut rng, |_| Some(SyntaxTree::Atom(0)));
+        assert_eq!(validation.accuracies, vec![Some(1.0); 5]);
+        assert_eq!(validation.mean_accuracy(), 1.0);
+        assert_eq!(validation.std_dev(), 0.0);
+        assert_eq!(cross_validate(&sample, 5, &mut rng, |_| None).failures(), 5);
+    }
+}
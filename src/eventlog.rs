@@ -0,0 +1,258 @@
+use crate::metadata::*;
+use crate::trace::*;
+use itertools::Itertools;
+use std::collections::BTreeMap;
+use std::io::{self, BufRead};
+
+/// An event of a log, occurring at a given time in a given case (i.e., a run of the process).
+#[derive(Debug, Clone, PartialEq)]
+pub struct Event {
+    pub case: String,
+    pub time: f64,
+    pub name: String,
+    pub label: Option<Label>,
+}
+
+/// The names of the columns of an event log in CSV format.
+#[derive(Debug, Clone)]
+pub struct CsvColumns {
+    /// The column identifying the case of the event; if missing, the whole log is a single case.
+    pub case: Option<String>,
+    /// The column with the (numeric) timestamp of the event.
+    pub time: String,
+    /// The column with the name of the event.
+    pub event: String,
+    /// The column with the label of the case (`positive`/`negative`, `true`/`false` or `1`/`0`), if any.
+    pub label: Option<String>,
+}
+
+impl Default for CsvColumns {
+    fn default() -> Self {
+        CsvColumns {
+            case: Some("case".to_string()),
+            time: "time".to_string(),
+            event: "event".to_string(),
+            label: None,
+        }
+    }
+}
+
+/// A log of timestamped events, which can be discretized into propositional traces.
+#[derive(Debug, Clone, Default)]
+pub struct EventLog {
+    pub events: Vec<Event>,
+}
+
+fn invalid_data(msg: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+fn parse_label(value: &str) -> Option<Label> {
+    match value.to_lowercase().as_str() {
+        "positive" | "true" | "1" => Some(Label::Positive),
+        "negative" | "false" | "0" => Some(Label::Negative),
+        _ => None,
+    }
+}
+
+impl EventLog {
+    /// Reads an event log in CSV format, with a header line naming the columns.
+    /// Fields are separated by commas, and can be surrounded by double quotes, but cannot contain commas.
+    /// Timestamps have to be numbers (e.g., seconds since the start of the log).
+    pub fn read_csv<R: BufRead>(reader: R, columns: &CsvColumns) -> io::Result<Self> {
+        let mut lines = reader.lines();
+        let header = lines
+            .next()
+            .ok_or_else(|| invalid_data("missing header".to_string()))??;
+        let fields = split_csv(&header);
+        let column = |name: &str| {
+            fields
+                .iter()
+                .position(|field| field == name)
+                .ok_or_else(|| invalid_data(format!("missing column `{name}`")))
+        };
+        let case_col = columns.case.as_deref().map(column).transpose()?;
+        let time_col = column(&columns.time)?;
+        let event_col = column(&columns.event)?;
+        let label_col = columns.label.as_deref().map(column).transpose()?;
+
+        let mut events = Vec::new();
+        for (line_number, line) in lines.enumerate() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            // The header is line 1.
+            let line_number = line_number + 2;
+            let fields = split_csv(&line);
+            let field = |col: usize| {
+                fields
+                    .get(col)
+                    .ok_or_else(|| invalid_data(format!("line {line_number}: missing field")))
+            };
+
+            let time = field(time_col)?
+                .parse::<f64>()
+                .map_err(|err| invalid_data(format!("line {line_number}: {err}")))?;
+            let label =
+                match label_col {
+                    Some(col) => Some(parse_label(field(col)?).ok_or_else(|| {
+                        invalid_data(format!("line {line_number}: invalid label"))
+                    })?),
+                    None => None,
+                };
+            events.push(Event {
+                case: case_col.map_or(Ok(String::new()), |col| field(col).cloned())?,
+                time,
+                name: field(event_col)?.clone(),
+                label,
+            });
+        }
+
+        Ok(EventLog { events })
+    }
+
+    /// The distinct names of the events of the log, in alphabetical order.
+    pub fn event_names(&self) -> Vec<String> {
+        self.events
+            .iter()
+            .map(|event| event.name.clone())
+            .sorted()
+            .dedup()
+            .collect()
+    }
+
+    /// Discretizes each case of the log into a trace, by splitting time into windows of the given width
+    /// starting from the first event of the case.
+    /// The n-th variable is true in a state if the n-th of the given events occurs during the corresponding window;
+    /// events with other names are ignored.
+    pub fn discretize<const N: usize>(
+        &self,
+        events: &[String; N],
+        window: f64,
+    ) -> BTreeMap<String, Trace<N>> {
+        assert!(window > 0.0, "window width must be positive");
+
+        let mut cases = BTreeMap::<String, Vec<&Event>>::new();
+        for event in &self.events {
+            cases.entry(event.case.clone()).or_default().push(event);
+        }
+
+        cases
+            .into_iter()
+            .map(|(case, case_events)| {
+                let start = case_events
+                    .iter()
+                    .map(|event| event.time)
+                    .fold(f64::INFINITY, f64::min);
+                let window_of = |event: &Event| ((event.time - start) / window).floor() as usize;
+                let length = case_events
+                    .iter()
+                    .map(|event| window_of(event))
+                    .max()
+                    .unwrap_or(0)
+                    + 1;
+
+                let mut trace = vec![[false; N]; length];
+                for event in case_events {
+                    if let Some(var) = events.iter().position(|name| *name == event.name) {
+                        trace[window_of(event)][var] = true;
+                    }
+                }
+                (case, trace)
+            })
+            .collect()
+    }
+
+    /// Discretizes the log (see [`EventLog::discretize`]) into a sample,
+    /// where each case is labelled by the label of its events (cases without a label are discarded).
+    /// The identifier of the case is kept in the metadata of its trace, under `case`.
+    /// Variables are named after the events.
+    pub fn to_sample<const N: usize>(&self, events: &[String; N], window: f64) -> Sample<N> {
+        let labels = self
+            .events
+            .iter()
+            .filter_map(|event| event.label.map(|label| (event.case.clone(), label)))
+            .collect::<BTreeMap<_, _>>();
+
+        let mut sample = Sample {
+            var_names: events.clone(),
+            ..Sample::default()
+        };
+        for (case, trace) in self.discretize(events, window) {
+            let Some(&label) = labels.get(&case) else {
+                continue;
+            };
+            let traces = match label {
+                Label::Positive => &mut sample.positive_traces,
+                Label::Negative => &mut sample.negative_traces,
+            };
+            traces.push(trace);
+            let index = traces.len() - 1;
+            sample.set_metadata(label, index, Metadata::from([("case".to_string(), case)]));
+        }
+        sample
+    }
+}
+
+fn split_csv(line: &str) -> Vec<String> {
+    line.split(',')
+        .map(|field| field.trim().trim_matches('"').to_string())
+        .collect()
+}
+
+#[cfg(test)]
+mod event_log {
+    use super::*;
+
+    const LOG: &str = "case,time,event,outcome
+a,0.0,request,ok
+a,1.5,grant,ok
+b,10,request,fail
+b,13,request,fail
+";
+
+    fn columns() -> CsvColumns {
+        CsvColumns {
+            label: Some("outcome".to_string()),
+            ..CsvColumns::default()
+        }
+    }
+
+    #[test]
+    fn read_csv() {
+        let log = EventLog::read_csv(LOG.as_bytes(), &columns());
+        // `ok` and `fail` are not valid labels
+        assert!(log.is_err());
+
+        let log = LOG
+            .replace(",ok", ",positive")
+            .replace(",fail", ",negative");
+        let log = EventLog::read_csv(log.as_bytes(), &columns()).expect("read log");
+        assert_eq!(log.events.len(), 4);
+        assert_eq!(log.event_names(), vec!["grant", "request"]);
+    }
+
+    #[test]
+    fn to_sample() {
+        let log = LOG.replace(",ok", ",1").replace(",fail", ",0");
+        let log = EventLog::read_csv(log.as_bytes(), &columns()).expect("read log");
+        let events = ["request".to_string(), "grant".to_string()];
+
+        let sample = log.to_sample(&events, 1.0);
+        assert_eq!(
+            sample.positive_traces,
+            vec![vec![[true, false], [false, true]]]
+        );
+        assert_eq!(
+            sample.negative_traces,
+            vec![vec![
+                [true, false],
+                [false, false],
+                [false, false],
+                [true, false]
+            ]]
+        );
+        assert!(sample.metadata(Label::Negative, 0).is_some());
+    }
+}
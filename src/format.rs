@@ -0,0 +1,449 @@
+use crate::syntax::*;
+use std::fmt::{self, Write};
+use std::str::FromStr;
+
+/// The notations available to print formulae.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Notation {
+    /// Infix notation with Unicode symbols, e.g., `□(x0 → ◇x1)`.
+    #[default]
+    Unicode,
+    /// Infix notation with ASCII symbols, e.g., `G(x0 -> F x1)`.
+    Ascii,
+    /// Prefix (Polish) notation with ASCII symbols, e.g., `G -> x0 F x1`.
+    Prefix,
+    /// LaTeX math-mode notation, e.g., `\square(x_{0} \rightarrow \Diamond x_{1})`.
+    Latex,
+    /// Syntax of the [Spot](https://spot.lre.epita.fr) library (e.g., `ltl2tgba`), e.g., `G(x0 -> F x1)`.
+    /// Variable names that Spot would not parse as atomic propositions are double-quoted.
+    Spot,
+    /// Syntax of the [Spin](https://spinroot.com) model checker, e.g., `[](x0 -> <> x1)`.
+    Spin,
+}
+
+impl FromStr for Notation {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "unicode" => Ok(Notation::Unicode),
+            "ascii" => Ok(Notation::Ascii),
+            "prefix" | "polish" => Ok(Notation::Prefix),
+            "latex" => Ok(Notation::Latex),
+            "spot" => Ok(Notation::Spot),
+            "spin" => Ok(Notation::Spin),
+            _ => Err(format!("unknown notation: {s}")),
+        }
+    }
+}
+
+/// The operator symbols of an infix notation.
+/// Unary operators are followed by a space when `spaced` is set,
+/// to separate alphabetic operators from their arguments.
+pub(crate) struct Symbols {
+    pub not: &'static str,
+    pub next: &'static str,
+    pub globally: &'static str,
+    pub finally: &'static str,
+    pub and: &'static str,
+    pub or: &'static str,
+    pub implies: &'static str,
+    pub until: &'static str,
+    pub spaced: bool,
+}
+
+const UNICODE: Symbols = Symbols {
+    not: "¬",
+    next: "○",
+    globally: "□",
+    finally: "◇",
+    and: "∧",
+    or: "∨",
+    implies: "→",
+    until: "U",
+    spaced: false,
+};
+
+const ASCII: Symbols = Symbols {
+    not: "!",
+    next: "X",
+    globally: "G",
+    finally: "F",
+    and: "&",
+    or: "|",
+    implies: "->",
+    until: "U",
+    spaced: true,
+};
+
+const LATEX: Symbols = Symbols {
+    not: "\\neg",
+    next: "\\bigcirc",
+    globally: "\\square",
+    finally: "\\Diamond",
+    and: "\\wedge",
+    or: "\\vee",
+    implies: "\\rightarrow",
+    until: "\\mathbin{\\mathcal{U}}",
+    spaced: true,
+};
+
+const SPIN: Symbols = Symbols {
+    not: "!",
+    next: "X",
+    globally: "[]",
+    finally: "<>",
+    and: "&&",
+    or: "||",
+    implies: "->",
+    until: "U",
+    spaced: true,
+};
+
+/// Prints a formula in infix notation with the given symbols.
+/// Arguments of unary operators are parenthesized only if they are binary formulae,
+/// and arguments of binary operators are parenthesized if they are binary formulae themselves.
+pub(crate) fn write_infix(
+    f: &mut fmt::Formatter<'_>,
+    formula: &SyntaxTree,
+    symbols: &Symbols,
+    atom: &dyn Fn(&mut fmt::Formatter<'_>, Idx) -> fmt::Result,
+) -> fmt::Result {
+    match formula {
+        SyntaxTree::Atom(var) => atom(f, *var),
+        SyntaxTree::Not(branch)
+        | SyntaxTree::Next(branch)
+        | SyntaxTree::Globally(branch)
+        | SyntaxTree::Finally(branch) => {
+            let op = match formula {
+                SyntaxTree::Not(_) => symbols.not,
+                SyntaxTree::Next(_) => symbols.next,
+                SyntaxTree::Globally(_) => symbols.globally,
+                _ => symbols.finally,
+            };
+            write!(f, "{op}")?;
+            if is_binary(branch) {
+                write!(f, "(")?;
+                write_infix(f, branch, symbols, atom)?;
+                write!(f, ")")
+            } else {
+                if symbols.spaced {
+                    write!(f, " ")?;
+                }
+                write_infix(f, branch, symbols, atom)
+            }
+        }
+        SyntaxTree::And(left_branch, right_branch)
+        | SyntaxTree::Or(left_branch, right_branch)
+        | SyntaxTree::Implies(left_branch, right_branch)
+        | SyntaxTree::Until(left_branch, right_branch) => {
+            let op = match formula {
+                SyntaxTree::And(_, _) => symbols.and,
+                SyntaxTree::Or(_, _) => symbols.or,
+                SyntaxTree::Implies(_, _) => symbols.implies,
+                _ => symbols.until,
+            };
+            write_operand(f, left_branch, symbols, atom)?;
+            write!(f, " {op} ")?;
+            write_operand(f, right_branch, symbols, atom)
+        }
+    }
+}
+
+fn write_operand(
+    f: &mut fmt::Formatter<'_>,
+    formula: &SyntaxTree,
+    symbols: &Symbols,
+    atom: &dyn Fn(&mut fmt::Formatter<'_>, Idx) -> fmt::Result,
+) -> fmt::Result {
+    if is_binary(formula) {
+        write!(f, "(")?;
+        write_infix(f, formula, symbols, atom)?;
+        write!(f, ")")
+    } else {
+        write_infix(f, formula, symbols, atom)
+    }
+}
+
+fn is_binary(formula: &SyntaxTree) -> bool {
+    matches!(
+        formula,
+        SyntaxTree::And(_, _)
+            | SyntaxTree::Or(_, _)
+            | SyntaxTree::Implies(_, _)
+            | SyntaxTree::Until(_, _)
+    )
+}
+
+fn write_prefix(
+    f: &mut fmt::Formatter<'_>,
+    formula: &SyntaxTree,
+    atom: &dyn Fn(&mut fmt::Formatter<'_>, Idx) -> fmt::Result,
+) -> fmt::Result {
+    match formula {
+        SyntaxTree::Atom(var) => atom(f, *var),
+        SyntaxTree::Not(branch) => {
+            write!(f, "{} ", ASCII.not)?;
+            write_prefix(f, branch, atom)
+        }
+        SyntaxTree::Next(branch) => {
+            write!(f, "{} ", ASCII.next)?;
+            write_prefix(f, branch, atom)
+        }
+        SyntaxTree::Globally(branch) => {
+            write!(f, "{} ", ASCII.globally)?;
+            write_prefix(f, branch, atom)
+        }
+        SyntaxTree::Finally(branch) => {
+            write!(f, "{} ", ASCII.finally)?;
+            write_prefix(f, branch, atom)
+        }
+        SyntaxTree::And(left_branch, right_branch)
+        | SyntaxTree::Or(left_branch, right_branch)
+        | SyntaxTree::Implies(left_branch, right_branch)
+        | SyntaxTree::Until(left_branch, right_branch) => {
+            let op = match formula {
+                SyntaxTree::And(_, _) => ASCII.and,
+                SyntaxTree::Or(_, _) => ASCII.or,
+                SyntaxTree::Implies(_, _) => ASCII.implies,
+                _ => ASCII.until,
+            };
+            write!(f, "{op} ")?;
+            write_prefix(f, left_branch, atom)?;
+            write!(f, " ")?;
+            write_prefix(f, right_branch, atom)
+        }
+    }
+}
+
+/// Escapes the characters of a variable name that are special in LaTeX.
+fn latex_escape(name: &str) -> String {
+    name.chars()
+        .map(|c| match c {
+            '_' | '&' | '%' | '$' | '#' | '{' | '}' => format!("\\{c}"),
+            _ => c.to_string(),
+        })
+        .collect()
+}
+
+/// Escapes a label for a double-quoted DOT string.
+fn dot_escape(label: &str) -> String {
+    label.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+/// Writes the nodes and edges of the subtree rooted in `formula`, returning the id of its root.
+fn write_dot(
+    out: &mut String,
+    formula: &SyntaxTree,
+    names: &[String],
+    next_id: &mut usize,
+) -> usize {
+    let id = *next_id;
+    *next_id += 1;
+    let (label, children): (String, Vec<&SyntaxTree>) = match formula {
+        SyntaxTree::Atom(var) => (
+            names
+                .get(*var as usize)
+                .cloned()
+                .unwrap_or_else(|| format!("x{var}")),
+            vec![],
+        ),
+        SyntaxTree::Not(branch) => (ASCII.not.to_string(), vec![branch]),
+        SyntaxTree::Next(branch) => (ASCII.next.to_string(), vec![branch]),
+        SyntaxTree::Globally(branch) => (ASCII.globally.to_string(), vec![branch]),
+        SyntaxTree::Finally(branch) => (ASCII.finally.to_string(), vec![branch]),
+        SyntaxTree::And(left_branch, right_branch) => {
+            (ASCII.and.to_string(), vec![left_branch, right_branch])
+        }
+        SyntaxTree::Or(left_branch, right_branch) => {
+            (ASCII.or.to_string(), vec![left_branch, right_branch])
+        }
+        SyntaxTree::Implies(left_branch, right_branch) => {
+            (ASCII.implies.to_string(), vec![left_branch, right_branch])
+        }
+        SyntaxTree::Until(left_branch, right_branch) => {
+            (ASCII.until.to_string(), vec![left_branch, right_branch])
+        }
+    };
+    let shape = if children.is_empty() {
+        "box"
+    } else {
+        "ellipse"
+    };
+    // Writing to a `String` cannot fail.
+    let _ = writeln!(
+        out,
+        "  n{id} [label=\"{}\", shape={shape}];",
+        dot_escape(&label)
+    );
+    for child in children {
+        let child_id = write_dot(out, child, names, next_id);
+        let _ = writeln!(out, "  n{id} -> n{child_id};");
+    }
+    id
+}
+
+/// Double-quotes a variable name unless Spot can parse it as an atomic proposition,
+/// i.e., unless it is an identifier starting with a lowercase letter or underscore that is not a keyword.
+fn spot_quote(name: &str) -> String {
+    let mut chars = name.chars();
+    let is_identifier = matches!(chars.next(), Some(c) if c.is_ascii_lowercase() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if is_identifier && !matches!(name, "true" | "false") {
+        name.to_string()
+    } else {
+        format!("\"{}\"", name.replace('"', "\\\""))
+    }
+}
+
+/// Prints a formula in the chosen [`Notation`], optionally with custom variable names.
+#[derive(Debug, Clone, Copy)]
+pub struct FormulaFormatter<'a> {
+    formula: &'a SyntaxTree,
+    notation: Notation,
+    names: Option<&'a [String]>,
+}
+
+impl<'a> FormulaFormatter<'a> {
+    /// Uses the given names for the propositional variables.
+    /// Variables without a name are printed with their default name.
+    pub fn names(mut self, names: &'a [String]) -> Self {
+        self.names = Some(names);
+        self
+    }
+
+    fn name(&self, var: Idx) -> Option<&'a String> {
+        self.names.and_then(|names| names.get(var as usize))
+    }
+}
+
+impl<'a> fmt::Display for FormulaFormatter<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let atom = |f: &mut fmt::Formatter<'_>, var: Idx| match (self.notation, self.name(var)) {
+            (Notation::Latex, Some(name)) => write!(f, "\\mathit{{{}}}", latex_escape(name)),
+            (Notation::Latex, None) => write!(f, "x_{{{var}}}"),
+            (Notation::Spot, Some(name)) => write!(f, "{}", spot_quote(name)),
+            (_, Some(name)) => write!(f, "{name}"),
+            (_, None) => write!(f, "x{var}"),
+        };
+
+        match self.notation {
+            Notation::Unicode => write_infix(f, self.formula, &UNICODE, &atom),
+            Notation::Ascii => write_infix(f, self.formula, &ASCII, &atom),
+            Notation::Prefix => write_prefix(f, self.formula, &atom),
+            Notation::Latex => write_infix(f, self.formula, &LATEX, &atom),
+            Notation::Spot => write_infix(f, self.formula, &ASCII, &atom),
+            Notation::Spin => write_infix(f, self.formula, &SPIN, &atom),
+        }
+    }
+}
+
+impl SyntaxTree {
+    /// Prints the formula in the given notation.
+    pub fn format(&self, notation: Notation) -> FormulaFormatter<'_> {
+        FormulaFormatter {
+            formula: self,
+            notation,
+            names: None,
+        }
+    }
+
+    /// A [Graphviz](https://graphviz.org) DOT graph of the syntax tree of the formula,
+    /// with operators as inner nodes and variables as leaves.
+    pub fn to_dot(&self) -> String {
+        self.to_dot_w_named_vars(&[])
+    }
+
+    /// Same as [`SyntaxTree::to_dot`], with leaves labelled by the given variable names.
+    pub fn to_dot_w_named_vars(&self, names: &[String]) -> String {
+        // Children are drawn in order, so that the operands of `U` and `->` are not swapped.
+        let mut out = String::from("digraph formula {\n  ordering=out;\n");
+        write_dot(&mut out, self, names, &mut 0);
+        out.push_str("}\n");
+        out
+    }
+
+    /// The formula in the syntax of the [Spot](https://spot.lre.epita.fr) library,
+    /// e.g., to be piped into `ltl2tgba`.
+    pub fn to_spot(&self) -> String {
+        self.format(Notation::Spot).to_string()
+    }
+
+    /// The formula in the syntax of the [Spin](https://spinroot.com) model checker.
+    pub fn to_spin(&self) -> String {
+        self.format(Notation::Spin).to_string()
+    }
+}
+
+#[cfg(test)]
+mod notation {
+    use super::*;
+
+    fn formula() -> SyntaxTree {
+        // G(x0 -> F x1)
+        SyntaxTree::Atom(0)
+            .implies(SyntaxTree::Atom(1).finally())
+            .globally()
+    }
+
+    #[test]
+    fn unicode() {
+        assert_eq!(
+            formula().format(Notation::Unicode).to_string(),
+            "□(x0 → ◇x1)"
+        );
+    }
+
+    #[test]
+    fn ascii() {
+        let names = ["request".to_string(), "grant".to_string()];
+        assert_eq!(
+            formula().format(Notation::Ascii).names(&names).to_string(),
+            "G(request -> F grant)"
+        );
+    }
+
+    #[test]
+    fn prefix() {
+        assert_eq!(
+            formula().format(Notation::Prefix).to_string(),
+            "G -> x0 F x1"
+        );
+    }
+
+    #[test]
+    fn latex() {
+        let names = ["req_1".to_string()];
+        assert_eq!(
+            formula().format(Notation::Latex).names(&names).to_string(),
+            "\\square(\\mathit{req\\_1} \\rightarrow \\Diamond x_{1})"
+        );
+    }
+
+    #[test]
+    fn spot() {
+        let names = ["speed > 3".to_string(), "grant".to_string()];
+        assert_eq!(formula().to_spot(), "G(x0 -> F x1)");
+        assert_eq!(
+            formula().format(Notation::Spot).names(&names).to_string(),
+            "G(\"speed > 3\" -> F grant)"
+        );
+    }
+
+    #[test]
+    fn spin() {
+        let formula = formula().and((!SyntaxTree::Atom(0)).next());
+        assert_eq!(formula.to_spin(), "X ! x0 && [](x0 -> <> x1)");
+    }
+
+    #[test]
+    fn dot() {
+        let names = ["request".to_string()];
+        let dot = formula().to_dot_w_named_vars(&names);
+        assert!(dot.starts_with("digraph formula {"));
+        assert!(dot.contains("n2 [label=\"request\", shape=box];"));
+        assert!(dot.contains("n4 [label=\"x1\", shape=box];"));
+        assert!(dot.contains("n1 -> n3;"));
+        assert_eq!(dot.lines().filter(|line| line.contains(" -> n")).count(), 4);
+    }
+}
@@ -0,0 +1,236 @@
+use crate::syntax::*;
+use crate::trace::*;
+use rand::Rng;
+use std::error::Error;
+use std::fmt;
+
+/// The generator could not find enough traces of some class within the maximum number of attempts,
+/// e.g., because the target formula is (almost) always true or always false.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GenerationError {
+    pub positives_found: usize,
+    pub negatives_found: usize,
+}
+
+impl fmt::Display for GenerationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "only {} positive and {} negative traces could be generated",
+            self.positives_found, self.negatives_found
+        )
+    }
+}
+
+impl Error for GenerationError {}
+
+/// Generates ground-truth samples for a target formula,
+/// labelling random traces of the given length by evaluating the formula on them.
+///
+/// Traces are first drawn uniformly at random (rejection sampling).
+/// If one of the classes is rare (e.g., for formulae like `G x0`, satisfied by few random traces),
+/// further traces of that class are searched for by flipping random values of the traces of that class found so far,
+/// or, if none has been found yet, by a random walk moving towards traces with more suffixes in that class.
+/// This way, the classes have the requested sizes even when random traces are very unbalanced.
+#[derive(Debug, Clone)]
+pub struct SampleGenerator<'a> {
+    formula: &'a SyntaxTree,
+    length: usize,
+    positives: usize,
+    negatives: usize,
+    max_attempts: usize,
+}
+
+impl<'a> SampleGenerator<'a> {
+    /// Creates a generator for traces of the given length, with no positive or negative trace requested.
+    pub fn new(formula: &'a SyntaxTree, length: usize) -> Self {
+        SampleGenerator {
+            formula,
+            length,
+            positives: 0,
+            negatives: 0,
+            max_attempts: 100_000,
+        }
+    }
+
+    /// Sets the number of positive traces.
+    pub fn positives(mut self, positives: usize) -> Self {
+        self.positives = positives;
+        self
+    }
+
+    /// Sets the number of negative traces.
+    pub fn negatives(mut self, negatives: usize) -> Self {
+        self.negatives = negatives;
+        self
+    }
+
+    /// Sets the maximum number of traces drawn before giving up (100 000 by default).
+    pub fn max_attempts(mut self, max_attempts: usize) -> Self {
+        self.max_attempts = max_attempts;
+        self
+    }
+
+    /// Generates a sample with distinct traces, consistent with the target formula.
+    pub fn generate<const N: usize, R: Rng + ?Sized>(
+        &self,
+        rng: &mut R,
+    ) -> Result<Sample<N>, GenerationError> {
+        let mut sample = Sample::<N>::default();
+        // Half of the attempts are spent on pure rejection sampling.
+        let random_attempts = self.max_attempts / 2;
+        // Random walks towards traces of each class, used while no trace of the class has been found.
+        let mut walkers: [Option<Trace<N>>; 2] = [None, None];
+
+        for attempt in 0..self.max_attempts {
+            let missing_positives = sample.positive_traces() < self.positives;
+            let missing_negatives = sample.negative_traces() < self.negatives;
+            if !missing_positives && !missing_negatives {
+                break;
+            }
+
+            let trace = if attempt < random_attempts || N == 0 || self.length == 0 {
+                random_trace::<N, R>(self.length, rng)
+            } else {
+                // Look for a trace of a class that is still missing traces.
+                let positive = match (missing_positives, missing_negatives) {
+                    (true, false) => true,
+                    (false, true) => false,
+                    _ => rng.gen(),
+                };
+                let seeds = if positive {
+                    &sample.positive_traces
+                } else {
+                    &sample.negative_traces
+                };
+                if seeds.is_empty() {
+                    // Only accept steps that do not move away from the class.
+                    let walker = walkers[positive as usize]
+                        .get_or_insert_with(|| random_trace::<N, R>(self.length, rng));
+                    let candidate = self.flip(walker, rng);
+                    if self.score(&candidate, positive) >= self.score(walker, positive) {
+                        *walker = candidate.clone();
+                    }
+                    candidate
+                } else {
+                    let seed = &seeds[rng.gen_range(0..seeds.len())];
+                    self.flip(seed, rng)
+                }
+            };
+
+            if self.formula.eval(&trace) {
+                if missing_positives {
+                    let _ = sample.add_positive_trace(trace);
+                }
+            } else if missing_negatives {
+                let _ = sample.add_negative_trace(trace);
+            }
+        }
+
+        self.check(sample)
+    }
+
+    /// Generates a sample with distinct traces, consistent with the target formula,
+    /// by rejection sampling over traces drawn from a custom distribution
+    /// (e.g., random walks over a [`MarkovChain`](crate::MarkovChain)).
+    /// Traces are not altered, so they all come from the given distribution.
+    pub fn generate_from<const N: usize, R: Rng + ?Sized>(
+        &self,
+        rng: &mut R,
+        mut draw: impl FnMut(&mut R) -> Trace<N>,
+    ) -> Result<Sample<N>, GenerationError> {
+        let mut sample = Sample::<N>::default();
+        for _ in 0..self.max_attempts {
+            let missing_positives = sample.positive_traces() < self.positives;
+            let missing_negatives = sample.negative_traces() < self.negatives;
+            if !missing_positives && !missing_negatives {
+                break;
+            }
+
+            let trace = draw(rng);
+            if trace.is_empty() {
+                continue;
+            }
+            if self.formula.eval(&trace) {
+                if missing_positives {
+                    let _ = sample.add_positive_trace(trace);
+                }
+            } else if missing_negatives {
+                let _ = sample.add_negative_trace(trace);
+            }
+        }
+        self.check(sample)
+    }
+
+    fn check<const N: usize>(&self, sample: Sample<N>) -> Result<Sample<N>, GenerationError> {
+        if sample.positive_traces() < self.positives || sample.negative_traces() < self.negatives {
+            Err(GenerationError {
+                positives_found: sample.positive_traces(),
+                negatives_found: sample.negative_traces(),
+            })
+        } else {
+            Ok(sample)
+        }
+    }
+
+    /// Copies the trace, flipping the value of a random variable at a random time.
+    fn flip<const N: usize, R: Rng + ?Sized>(&self, trace: &Trace<N>, rng: &mut R) -> Trace<N> {
+        let mut trace = trace.clone();
+        let time = rng.gen_range(0..self.length);
+        let var = rng.gen_range(0..N);
+        trace[time][var] = !trace[time][var];
+        trace
+    }
+
+    /// How close the trace is to belonging to the given class,
+    /// measured as the number of its suffixes that already do.
+    fn score<const N: usize>(&self, trace: &Trace<N>, positive: bool) -> usize {
+        (0..trace.len())
+            .filter(|time| self.formula.eval_at_time(trace, *time) == positive)
+            .count()
+    }
+}
+
+#[cfg(test)]
+mod generation {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use std::sync::Arc;
+
+    #[test]
+    fn balanced() {
+        let mut rng = StdRng::seed_from_u64(0);
+        // G x0 is satisfied by a random trace of length 12 with probability 1/4096
+        let formula = SyntaxTree::Globally(Arc::new(SyntaxTree::Atom(0)));
+
+        let sample = SampleGenerator::new(&formula, 12)
+            .positives(1)
+            .negatives(10)
+            .max_attempts(2000)
+            .generate::<2, _>(&mut rng)
+            .expect("generate sample");
+        assert_eq!(sample.positive_traces(), 1);
+        assert_eq!(sample.negative_traces(), 10);
+        assert!(sample.is_consistent(&formula));
+    }
+
+    #[test]
+    fn impossible() {
+        let mut rng = StdRng::seed_from_u64(0);
+        // x0 ∧ ¬x0 is never satisfied
+        let formula = SyntaxTree::And(
+            Arc::new(SyntaxTree::Atom(0)),
+            Arc::new(SyntaxTree::Not(Arc::new(SyntaxTree::Atom(0)))),
+        );
+
+        let err = SampleGenerator::new(&formula, 3)
+            .positives(1)
+            .negatives(1)
+            .max_attempts(100)
+            .generate::<1, _>(&mut rng)
+            .unwrap_err();
+        assert_eq!(err.positives_found, 0);
+        assert_eq!(err.negatives_found, 1);
+    }
+}
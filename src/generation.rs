@@ -0,0 +1,127 @@
+use crate::genetic::*;
+use crate::source::*;
+use crate::syntax::*;
+use std::io::{self, Write};
+use std::time::Duration;
+
+/// Statistics of a generation of the genetic algorithm, to compare the progress of runs,
+/// e.g., with different parameters, written as a row of a CSV file (see [`GenerationStats::write_csv_row`]).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GenerationStats {
+    /// The number of the generation, `0` for the initial population.
+    pub generation: usize,
+    /// The score of the fittest formula (see [`Fitness::score`]).
+    pub best_fitness: i32,
+    /// The mean score of the formulas of the population.
+    pub mean_fitness: f64,
+    /// The median score of the formulas of the population.
+    pub median_fitness: f64,
+    /// The fraction of traces the fittest formula classifies correctly.
+    pub best_accuracy: f64,
+    /// The size of the fittest formula, or its cost (see [`Fitness::size`]).
+    pub best_size: usize,
+    /// The mean distance between two formulas of the population (see [`SyntaxTree::distance`]),
+    /// which drops as the population collapses onto a single shape of formulas.
+    pub diversity: f64,
+    /// The time elapsed since the start of the run.
+    pub elapsed: Duration,
+}
+
+impl GenerationStats {
+    /// The names of the columns of the CSV rows, in order.
+    pub const CSV_HEADER: &'static str = "generation,best_fitness,mean_fitness,median_fitness,best_accuracy,best_size,diversity,elapsed_seconds";
+
+    /// The statistics of a population ranked over the sample, fittest first (see [`GeneticLearner::rank`]),
+    /// which must not be empty.
+    pub fn new<const N: usize, S: SampleSource<N>>(
+        generation: usize,
+        ranked: &[(SyntaxTree, Fitness)],
+        sample: &S,
+        elapsed: Duration,
+    ) -> Self {
+        let (_, best) = ranked.first().expect("non-empty population");
+        let mut scores = ranked
+            .iter()
+            .map(|(_, fitness)| fitness.score())
+            .collect::<Vec<_>>();
+        scores.sort_unstable();
+        let middle = scores.len() / 2;
+        let median_fitness = if scores.len() % 2 == 0 {
+            (scores[middle - 1] + scores[middle]) as f64 / 2.0
+        } else {
+            scores[middle] as f64
+        };
+        let pairs = ranked.len() * (ranked.len() - 1) / 2;
+        let distances = ranked
+            .iter()
+            .enumerate()
+            .flat_map(|(index, (formula, _))| {
+                ranked[index + 1..]
+                    .iter()
+                    .map(|(other, _)| formula.distance(other))
+            })
+            .sum::<usize>();
+        GenerationStats {
+            generation,
+            best_fitness: best.score(),
+            mean_fitness: scores.iter().sum::<i32>() as f64 / scores.len() as f64,
+            median_fitness,
+            best_accuracy: best.accuracy(sample),
+            best_size: best.size,
+            diversity: if pairs == 0 {
+                0.0
+            } else {
+                distances as f64 / pairs as f64
+            },
+            elapsed,
+        }
+    }
+
+    /// Writes the statistics as a line of comma-separated values, in the order of [`GenerationStats::CSV_HEADER`].
+    pub fn write_csv_row<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        writeln!(
+            writer,
+            "{},{},{:.3},{:.3},{:.4},{},{:.3},{:.3}",
+            self.generation,
+            self.best_fitness,
+            self.mean_fitness,
+            self.median_fitness,
+            self.best_accuracy,
+            self.best_size,
+            self.diversity,
+            self.elapsed.as_secs_f64()
+        )
+    }
+}
+
+#[cfg(test)]
+mod generation_stats {
+    use super::*;
+    use crate::trace::*;
+
+    #[test]
+    fn csv_row() {
+        let sample = Sample::always_x0();
+        let x0 = SyntaxTree::Atom(0);
+        let ranked = GeneticLearner::default().rank(
+            &[x0.clone().globally(), x0.clone(), SyntaxTree::Atom(1)],
+            &sample,
+        );
+        let stats = GenerationStats::new(3, &ranked, &sample, Duration::from_millis(1500));
+        assert_eq!(stats.best_fitness, 0);
+        assert_eq!(stats.median_fitness, 0.0);
+        assert_eq!(stats.best_accuracy, 1.0);
+        assert_eq!(stats.best_size, 2);
+        // The distances are 2 between G(x0) and either atom, and 1 between the atoms.
+        assert_eq!(stats.diversity, 5.0 / 3.0);
+
+        let mut csv = Vec::new();
+        stats.write_csv_row(&mut csv).expect("write to memory");
+        let row = String::from_utf8(csv).expect("utf-8");
+        assert_eq!(row, "3,0,-0.667,0.000,1.0000,2,1.667,1.500\n");
+        assert_eq!(
+            row.split(',').count(),
+            GenerationStats::CSV_HEADER.split(',').count()
+        );
+    }
+}
@@ -0,0 +1,1915 @@
+use crate::cost::*;
+use crate::grammar::*;
+use crate::learn::*;
+use crate::observer::*;
+use crate::pareto::*;
+use crate::source::*;
+use crate::syntax::*;
+use rand::distributions::{Distribution, WeightedIndex};
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+use std::sync::Arc;
+
+/// How many of the fittest formulas survive each generation, by default.
+const SURVIVORS: usize = 100;
+
+/// The probability that a formula of the population is mutated in each generation, by default.
+const MUTATION_RATE: f64 = 0.2;
+
+/// The bounds of an adaptive mutation rate (see [`MutationRate::Adaptive`]).
+const MIN_MUTATION_RATE: f64 = 0.05;
+const MAX_MUTATION_RATE: f64 = 0.8;
+
+/// How many pairs of random subformulas [`subtree_crossover`] tries before giving up.
+const CROSSOVER_ATTEMPTS: usize = 10;
+
+impl SyntaxTree {
+    /// The number of nodes (operators and atoms) of the formula.
+    pub fn size(&self) -> usize {
+        match self {
+            SyntaxTree::Atom(_) => 1,
+            SyntaxTree::Not(branch)
+            | SyntaxTree::Next(branch)
+            | SyntaxTree::Globally(branch)
+            | SyntaxTree::Finally(branch) => 1 + branch.size(),
+            SyntaxTree::And(left_branch, right_branch)
+            | SyntaxTree::Or(left_branch, right_branch)
+            | SyntaxTree::Implies(left_branch, right_branch)
+            | SyntaxTree::Until(left_branch, right_branch) => {
+                1 + left_branch.size() + right_branch.size()
+            }
+        }
+    }
+
+    /// The subformula at the given index, in prefix order, where the formula itself is at index 0.
+    pub(crate) fn subformula(&self, index: usize) -> &SyntaxTree {
+        if index == 0 {
+            return self;
+        }
+        let index = index - 1;
+        match self {
+            SyntaxTree::Atom(_) => self,
+            SyntaxTree::Not(branch)
+            | SyntaxTree::Next(branch)
+            | SyntaxTree::Globally(branch)
+            | SyntaxTree::Finally(branch) => branch.subformula(index),
+            SyntaxTree::And(left, right)
+            | SyntaxTree::Or(left, right)
+            | SyntaxTree::Implies(left, right)
+            | SyntaxTree::Until(left, right) => {
+                if index < left.size() {
+                    left.subformula(index)
+                } else {
+                    right.subformula(index - left.size())
+                }
+            }
+        }
+    }
+
+    /// Replaces the subformula at the given index, in prefix order, by the result of `replace` on it.
+    pub(crate) fn replace_subformula(
+        &self,
+        index: usize,
+        replace: &mut impl FnMut(&SyntaxTree) -> SyntaxTree,
+    ) -> SyntaxTree {
+        if index == 0 {
+            return replace(self);
+        }
+        let index = index - 1;
+        let unary = |branch: &SyntaxTree, replace: &mut _| {
+            Arc::new(branch.replace_subformula(index, replace))
+        };
+        let binary = |left: &Arc<SyntaxTree>, right: &Arc<SyntaxTree>, replace: &mut _| {
+            if index < left.size() {
+                (
+                    Arc::new(left.replace_subformula(index, replace)),
+                    right.clone(),
+                )
+            } else {
+                (
+                    left.clone(),
+                    Arc::new(right.replace_subformula(index - left.size(), replace)),
+                )
+            }
+        };
+        match self {
+            SyntaxTree::Atom(_) => self.clone(),
+            SyntaxTree::Not(branch) => SyntaxTree::Not(unary(branch, replace)),
+            SyntaxTree::Next(branch) => SyntaxTree::Next(unary(branch, replace)),
+            SyntaxTree::Globally(branch) => SyntaxTree::Globally(unary(branch, replace)),
+            SyntaxTree::Finally(branch) => SyntaxTree::Finally(unary(branch, replace)),
+            SyntaxTree::And(left, right) => {
+                let (left, right) = binary(left, right, replace);
+                SyntaxTree::And(left, right)
+            }
+            SyntaxTree::Or(left, right) => {
+                let (left, right) = binary(left, right, replace);
+                SyntaxTree::Or(left, right)
+            }
+            SyntaxTree::Implies(left, right) => {
+                let (left, right) = binary(left, right, replace);
+                SyntaxTree::Implies(left, right)
+            }
+            SyntaxTree::Until(left, right) => {
+                let (left, right) = binary(left, right, replace);
+                SyntaxTree::Until(left, right)
+            }
+        }
+    }
+
+    /// The number of nodes on the longest path from the root to a leaf, the root included.
+    fn height(&self) -> usize {
+        match self {
+            SyntaxTree::Atom(_) => 1,
+            SyntaxTree::Not(branch)
+            | SyntaxTree::Next(branch)
+            | SyntaxTree::Globally(branch)
+            | SyntaxTree::Finally(branch) => 1 + branch.height(),
+            SyntaxTree::And(left, right)
+            | SyntaxTree::Or(left, right)
+            | SyntaxTree::Implies(left, right)
+            | SyntaxTree::Until(left, right) => 1 + left.height().max(right.height()),
+        }
+    }
+
+    /// The number of ancestors of the node at the given index, in prefix order, so `0` for the root.
+    fn node_depth(&self, index: usize) -> usize {
+        if index == 0 {
+            return 0;
+        }
+        let index = index - 1;
+        1 + match self {
+            SyntaxTree::Atom(_) => 0,
+            SyntaxTree::Not(branch)
+            | SyntaxTree::Next(branch)
+            | SyntaxTree::Globally(branch)
+            | SyntaxTree::Finally(branch) => branch.node_depth(index),
+            SyntaxTree::And(left, right)
+            | SyntaxTree::Or(left, right)
+            | SyntaxTree::Implies(left, right)
+            | SyntaxTree::Until(left, right) => {
+                if index < left.size() {
+                    left.node_depth(index)
+                } else {
+                    right.node_depth(index - left.size())
+                }
+            }
+        }
+    }
+
+    /// A structural distance between formulas: the number of nodes that differ when overlaying their trees from the root,
+    /// where a subformula with a different operator counts as many nodes as the larger of the two subformulas,
+    /// so `0` only for the same formula.
+    pub fn distance(&self, other: &SyntaxTree) -> usize {
+        match (self, other) {
+            (SyntaxTree::Atom(left), SyntaxTree::Atom(right)) => usize::from(left != right),
+            (SyntaxTree::Not(left), SyntaxTree::Not(right))
+            | (SyntaxTree::Next(left), SyntaxTree::Next(right))
+            | (SyntaxTree::Globally(left), SyntaxTree::Globally(right))
+            | (SyntaxTree::Finally(left), SyntaxTree::Finally(right)) => left.distance(right),
+            (SyntaxTree::And(left1, right1), SyntaxTree::And(left2, right2))
+            | (SyntaxTree::Or(left1, right1), SyntaxTree::Or(left2, right2))
+            | (SyntaxTree::Implies(left1, right1), SyntaxTree::Implies(left2, right2))
+            | (SyntaxTree::Until(left1, right1), SyntaxTree::Until(left2, right2)) => {
+                left1.distance(left2) + right1.distance(right2)
+            }
+            _ => self.size().max(other.size()),
+        }
+    }
+
+    /// Replaces both branches of a binary operator with the given one.
+    fn replace_branch(&self, new_branch: Arc<SyntaxTree>) -> SyntaxTree {
+        match self {
+            SyntaxTree::And(_, _) => SyntaxTree::And(new_branch.clone(), new_branch),
+            SyntaxTree::Or(_, _) => SyntaxTree::Or(new_branch.clone(), new_branch),
+            SyntaxTree::Implies(_, _) => SyntaxTree::Implies(new_branch.clone(), new_branch),
+            SyntaxTree::Until(_, _) => SyntaxTree::Until(new_branch.clone(), new_branch),
+            _ => self.clone(),
+        }
+    }
+
+    /// Combines two branches with a binary operator depending on their shape.
+    fn combine_branches(branch1: Arc<SyntaxTree>, branch2: Arc<SyntaxTree>) -> SyntaxTree {
+        match (&*branch1, &*branch2) {
+            (SyntaxTree::Finally(_), SyntaxTree::Atom(_)) => SyntaxTree::Until(branch1, branch2),
+            (SyntaxTree::Finally(_), _) => SyntaxTree::Implies(branch1, branch2),
+            (_, SyntaxTree::Atom(_)) => SyntaxTree::Implies(branch1, branch2),
+            (_, _) => SyntaxTree::Or(branch1, branch2),
+        }
+    }
+
+    fn branches(&self) -> Option<(Arc<SyntaxTree>, Arc<SyntaxTree>)> {
+        match self {
+            SyntaxTree::And(left, right)
+            | SyntaxTree::Or(left, right)
+            | SyntaxTree::Implies(left, right)
+            | SyntaxTree::Until(left, right) => Some((left.clone(), right.clone())),
+            _ => None,
+        }
+    }
+}
+
+/// Breeds two offspring from two parents, which must both be binary operators,
+/// with a crossover method chosen at random:
+/// swapping their branches, combining their branches, or swapping a random branch of each.
+pub fn crossover<R: Rng + ?Sized>(
+    parent1: &SyntaxTree,
+    parent2: &SyntaxTree,
+    rng: &mut R,
+) -> Option<(SyntaxTree, SyntaxTree)> {
+    let (branch1_p1, branch2_p1) = parent1.branches()?;
+    let (branch1_p2, branch2_p2) = parent2.branches()?;
+
+    match rng.gen_range(0..=2) {
+        0 => Some((
+            parent1.replace_branch(branch2_p2),
+            parent2.replace_branch(branch1_p1),
+        )),
+        1 => Some((
+            SyntaxTree::combine_branches(branch1_p1, branch2_p2),
+            SyntaxTree::combine_branches(branch1_p2, branch2_p1),
+        )),
+        _ => {
+            let random_branch_parent1 = if rng.gen() { branch1_p1 } else { branch2_p1 };
+            let random_branch_parent2 = if rng.gen() { branch1_p2 } else { branch2_p2 };
+            Some((
+                parent1.replace_branch(random_branch_parent2),
+                parent2.replace_branch(random_branch_parent1),
+            ))
+        }
+    }
+}
+
+/// Breeds two offspring from two parents by standard subtree crossover:
+/// picks a uniformly random node in each parent and swaps the subformulas rooted there.
+/// Unlike [`crossover`], any formulas can be parents, and the structure of both is preserved outside the swapped subformulas.
+///
+/// With a maximum size, swaps making either offspring larger are retried a few times,
+/// returning `None` if none fits.
+pub fn subtree_crossover<R: Rng + ?Sized>(
+    parent1: &SyntaxTree,
+    parent2: &SyntaxTree,
+    max_size: Option<usize>,
+    rng: &mut R,
+) -> Option<(SyntaxTree, SyntaxTree)> {
+    let (size1, size2) = (parent1.size(), parent2.size());
+    (0..CROSSOVER_ATTEMPTS).find_map(|_| {
+        let index1 = rng.gen_range(0..size1);
+        let index2 = rng.gen_range(0..size2);
+        let branch1 = parent1.subformula(index1);
+        let branch2 = parent2.subformula(index2);
+        let fits = max_size.is_none_or(|max| {
+            size1 - branch1.size() + branch2.size() <= max
+                && size2 - branch2.size() + branch1.size() <= max
+        });
+        fits.then(|| {
+            (
+                parent1.replace_subformula(index1, &mut |_| branch2.clone()),
+                parent2.replace_subformula(index2, &mut |_| branch1.clone()),
+            )
+        })
+    })
+}
+
+/// How the genetic algorithm breeds offspring from two parents.
+///
+/// Parsed from `subtree` or `branches`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CrossoverMethod {
+    /// Swap random subformulas (see [`subtree_crossover`]).
+    #[default]
+    Subtree,
+    /// Recombine the branches of binary parents (see [`crossover`]).
+    Branches,
+}
+
+impl fmt::Display for CrossoverMethod {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CrossoverMethod::Subtree => write!(f, "subtree"),
+            CrossoverMethod::Branches => write!(f, "branches"),
+        }
+    }
+}
+
+impl FromStr for CrossoverMethod {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "subtree" => Ok(CrossoverMethod::Subtree),
+            "branches" => Ok(CrossoverMethod::Branches),
+            _ => Err(format!("unknown crossover method: {s}")),
+        }
+    }
+}
+
+/// Mutates a formula by replacing its root binary operator with another one chosen at random.
+/// Other formulas are left unchanged.
+pub fn mutate<R: Rng + ?Sized>(formula: &SyntaxTree, rng: &mut R) -> SyntaxTree {
+    let choice = rng.gen_range(0..3);
+    match formula {
+        SyntaxTree::And(left, right) => match choice {
+            0 => SyntaxTree::Or(left.clone(), right.clone()),
+            1 => SyntaxTree::Implies(left.clone(), right.clone()),
+            _ => SyntaxTree::Until(left.clone(), right.clone()),
+        },
+        SyntaxTree::Or(left, right) => match choice {
+            0 => SyntaxTree::And(left.clone(), right.clone()),
+            1 => SyntaxTree::Implies(right.clone(), left.clone()),
+            _ => SyntaxTree::Until(left.clone(), right.clone()),
+        },
+        SyntaxTree::Implies(left, right) => match choice {
+            0 => SyntaxTree::And(left.clone(), right.clone()),
+            1 => SyntaxTree::Or(left.clone(), right.clone()),
+            _ => SyntaxTree::Until(left.clone(), right.clone()),
+        },
+        SyntaxTree::Until(left, right) => match choice {
+            0 => SyntaxTree::And(left.clone(), right.clone()),
+            1 => SyntaxTree::Or(left.clone(), right.clone()),
+            _ => SyntaxTree::Implies(left.clone(), right.clone()),
+        },
+        _ => formula.clone(),
+    }
+}
+
+/// Mutates a formula at a uniformly random node, anywhere in the tree, which is either
+/// replaced by a random atom among the given variables (another one for atoms), negated (or its negation removed),
+/// or given another operator of the same arity (or another variable among the given ones), binary ones by [`mutate`].
+///
+/// The deeper the node, the likelier it is replaced by an atom, so that mutations mostly refine formulas rather than collapse them:
+/// a node with `d` ancestors in a formula with `h` levels is replaced with probability `d / h`,
+/// from never at the root to `(h - 1) / h` at the deepest leaves;
+/// otherwise, the node is negated or given another operator with equal probability.
+/// Atoms are never replaced by an atom as such, but given another variable whenever they are not negated.
+pub fn point_mutation<R: Rng + ?Sized>(
+    formula: &SyntaxTree,
+    vars: &[Idx],
+    rng: &mut R,
+) -> SyntaxTree {
+    let index = rng.gen_range(0..formula.size());
+    let depth = formula.node_depth(index);
+    let atomize = !vars.is_empty() && rng.gen_bool(depth as f64 / formula.height() as f64);
+    let negate = !atomize && rng.gen();
+    formula.replace_subformula(index, &mut |node| match node {
+        SyntaxTree::Not(branch) if negate => branch.as_ref().clone(),
+        _ if negate => SyntaxTree::Not(Arc::new(node.clone())),
+        SyntaxTree::Atom(var) => {
+            let others = vars
+                .iter()
+                .filter(|other| *other != var)
+                .collect::<Vec<_>>();
+            if others.is_empty() {
+                node.clone()
+            } else {
+                SyntaxTree::Atom(*others[rng.gen_range(0..others.len())])
+            }
+        }
+        _ if atomize => SyntaxTree::Atom(vars[rng.gen_range(0..vars.len())]),
+        SyntaxTree::Not(branch)
+        | SyntaxTree::Next(branch)
+        | SyntaxTree::Globally(branch)
+        | SyntaxTree::Finally(branch) => {
+            let branch = branch.clone();
+            let operators: [fn(Arc<SyntaxTree>) -> SyntaxTree; 4] = [
+                SyntaxTree::Not,
+                SyntaxTree::Next,
+                SyntaxTree::Globally,
+                SyntaxTree::Finally,
+            ];
+            let others = operators
+                .into_iter()
+                .map(|operator| operator(branch.clone()))
+                .filter(|other| other != node)
+                .collect::<Vec<_>>();
+            others[rng.gen_range(0..others.len())].clone()
+        }
+        _ => mutate(node, rng),
+    })
+}
+
+/// Mutates a formula by changing its size, so that the population can leave the size it started from:
+/// with equal probability, it either grows, inserting a random unary operator above a random node,
+/// or shrinks at a random operator, deleting it if unary or else replacing it with one of its branches at random ("hoisting" it).
+/// Atoms only grow.
+pub fn structural_mutation<R: Rng + ?Sized>(formula: &SyntaxTree, rng: &mut R) -> SyntaxTree {
+    let operators = (0..formula.size())
+        .filter(|index| !matches!(formula.subformula(*index), SyntaxTree::Atom(_)))
+        .collect::<Vec<_>>();
+    if operators.is_empty() || rng.gen() {
+        let index = rng.gen_range(0..formula.size());
+        return formula.replace_subformula(index, &mut |node| {
+            let node = Arc::new(node.clone());
+            match rng.gen_range(0..4) {
+                0 => SyntaxTree::Not(node),
+                1 => SyntaxTree::Next(node),
+                2 => SyntaxTree::Globally(node),
+                _ => SyntaxTree::Finally(node),
+            }
+        });
+    }
+    let index = operators[rng.gen_range(0..operators.len())];
+    formula.replace_subformula(index, &mut |node| match node {
+        SyntaxTree::Atom(_) => node.clone(),
+        SyntaxTree::Not(branch)
+        | SyntaxTree::Next(branch)
+        | SyntaxTree::Globally(branch)
+        | SyntaxTree::Finally(branch) => branch.as_ref().clone(),
+        SyntaxTree::And(left, right)
+        | SyntaxTree::Or(left, right)
+        | SyntaxTree::Implies(left, right)
+        | SyntaxTree::Until(left, right) => {
+            if rng.gen() {
+                left.as_ref().clone()
+            } else {
+                right.as_ref().clone()
+            }
+        }
+    })
+}
+
+/// How the genetic algorithm mutates formulas.
+///
+/// Parsed from `mixed`, `point`, `structural` or `root`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MutationMethod {
+    /// Either change a random node or the size of the formula, with equal probability.
+    #[default]
+    Mixed,
+    /// Change a random node (see [`point_mutation`]).
+    Point,
+    /// Grow or shrink the formula (see [`structural_mutation`]).
+    Structural,
+    /// Change the root binary operator (see [`mutate`]).
+    Root,
+}
+
+impl fmt::Display for MutationMethod {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MutationMethod::Mixed => write!(f, "mixed"),
+            MutationMethod::Point => write!(f, "point"),
+            MutationMethod::Structural => write!(f, "structural"),
+            MutationMethod::Root => write!(f, "root"),
+        }
+    }
+}
+
+impl FromStr for MutationMethod {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "mixed" => Ok(MutationMethod::Mixed),
+            "point" => Ok(MutationMethod::Point),
+            "structural" => Ok(MutationMethod::Structural),
+            "root" => Ok(MutationMethod::Root),
+            _ => Err(format!("unknown mutation method: {s}")),
+        }
+    }
+}
+
+/// How often the genetic algorithm mutates the formulas of the population.
+///
+/// Parsed from `fixed` or `fixed:<rate>`, e.g., `fixed:0.2`, and `adaptive` or `adaptive:<rate>`,
+/// e.g., `adaptive:0.2` (the default), where the rate is the initial probability of mutating each formula.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MutationRate {
+    /// Each formula is mutated with the given probability, every generation.
+    Fixed(f64),
+    /// Each formula is mutated with a probability starting at the given one,
+    /// which grows by half every generation the fittest formula does not improve, up to 80%,
+    /// to explore further when the population stagnates, and halves whenever it improves, down to 5%,
+    /// to refine the fittest formulas (see [`MutationSchedule`]).
+    Adaptive(f64),
+}
+
+impl Default for MutationRate {
+    fn default() -> Self {
+        MutationRate::Adaptive(MUTATION_RATE)
+    }
+}
+
+impl fmt::Display for MutationRate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MutationRate::Fixed(rate) => write!(f, "fixed:{rate}"),
+            MutationRate::Adaptive(rate) => write!(f, "adaptive:{rate}"),
+        }
+    }
+}
+
+impl FromStr for MutationRate {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (name, arg) = match s.split_once(':') {
+            Some((name, arg)) => (name, Some(arg)),
+            None => (s, None),
+        };
+        let rate = match arg {
+            None => MUTATION_RATE,
+            Some(rate) => match rate.trim().parse() {
+                Ok(rate) if (0.0..=1.0).contains(&rate) => rate,
+                _ => return Err(format!("invalid mutation rate: {rate}")),
+            },
+        };
+        match name.trim().to_lowercase().as_str() {
+            "fixed" => Ok(MutationRate::Fixed(rate)),
+            "adaptive" => Ok(MutationRate::Adaptive(rate)),
+            _ => Err(format!("unknown mutation rate: {s}")),
+        }
+    }
+}
+
+/// The current probability of mutating each formula during a run of the genetic algorithm,
+/// which [`GeneticLearner::breed`] updates every generation as its [`MutationRate`] prescribes.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MutationSchedule {
+    rate: MutationRate,
+    probability: f64,
+    best: Option<i32>,
+}
+
+impl MutationSchedule {
+    pub fn new(rate: MutationRate) -> Self {
+        let probability = match rate {
+            MutationRate::Fixed(rate) | MutationRate::Adaptive(rate) => rate.clamp(0.0, 1.0),
+        };
+        MutationSchedule {
+            rate,
+            probability,
+            best: None,
+        }
+    }
+
+    /// The probability of mutating each formula in the current generation.
+    pub fn probability(&self) -> f64 {
+        self.probability
+    }
+
+    /// Takes the fittest formula of a new generation into account, if any:
+    /// an adaptive probability grows if its score is no better than the best one so far, and shrinks otherwise.
+    /// The first generation sets the best score, leaving the probability unchanged.
+    pub fn update(&mut self, fittest: Option<&Fitness>) {
+        let Some(score) = fittest.map(Fitness::score) else {
+            return;
+        };
+        if let (MutationRate::Adaptive(_), Some(best)) = (self.rate, self.best) {
+            self.probability = if score > best {
+                (self.probability / 2.0).max(MIN_MUTATION_RATE)
+            } else {
+                (self.probability * 1.5).min(MAX_MUTATION_RATE)
+            };
+        }
+        self.best = Some(self.best.map_or(score, |best| best.max(score)));
+    }
+}
+
+/// Whether each of the ranked formulas (see [`GeneticLearner::rank`]) is among the given number of survivors
+/// with the highest shared fitness, where the scores (shifted so that the lowest one is 1) are divided by the niche count
+/// of the formula: the sum over the formulas within the radius (see [`SyntaxTree::distance`]), itself included,
+/// of 1 minus their distance over the radius.
+/// Formulas of a crowded shape thus share their fitness, so that less fit formulas of other shapes survive instead.
+/// The fittest formula always survives.
+fn shared_survivors(ranked: &[(SyntaxTree, Fitness)], radius: usize, count: usize) -> Vec<bool> {
+    let radius = radius.max(1) as f64;
+    let lowest = ranked
+        .iter()
+        .map(|(_, fitness)| fitness.score())
+        .min()
+        .unwrap_or(0);
+    let shared = ranked
+        .iter()
+        .map(|(formula, fitness)| {
+            let niche = ranked
+                .iter()
+                .map(|(other, _)| 1.0 - formula.distance(other) as f64 / radius)
+                .filter(|share| *share > 0.0)
+                .sum::<f64>();
+            (fitness.score() - lowest + 1) as f64 / niche
+        })
+        .collect::<Vec<_>>();
+    let mut order = (1..ranked.len()).collect::<Vec<_>>();
+    order.sort_by(|left, right| shared[*right].total_cmp(&shared[*left]));
+    let mut survivors = vec![false; ranked.len()];
+    for index in std::iter::once(0).chain(order).take(count) {
+        survivors[index] = true;
+    }
+    survivors
+}
+
+/// The progress of the fittest formula over the generations of a run of the genetic algorithm,
+/// to stop it early when it stagnates (see [`GeneticLearner::stops`]).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Stagnation {
+    best: Option<i32>,
+    generations: usize,
+}
+
+impl Stagnation {
+    /// The number of generations since the score of the fittest formula last improved.
+    pub fn generations(&self) -> usize {
+        self.generations
+    }
+}
+
+/// A generation of a run of the genetic algorithm, once ranked (see [`GeneticLearner::evolve_observed`]).
+#[derive(Debug, Clone, Copy)]
+pub struct Generation<'a> {
+    /// The number of the generation, the initial population being generation 0.
+    pub index: usize,
+    /// The formulas bred from the previous generation, with their fitness, fittest first,
+    /// before the survivors are kept (none for the initial population).
+    pub offspring: &'a [(SyntaxTree, Fitness)],
+    /// The surviving formulas with their fitness, fittest first.
+    pub ranked: &'a [(SyntaxTree, Fitness)],
+    /// The formulas evaluated so far.
+    pub cache: &'a FitnessCache,
+    /// The mutation schedule, once updated by breeding the generation.
+    pub schedule: &'a MutationSchedule,
+    /// The progress of the fittest formula, including this generation.
+    pub stagnation: &'a Stagnation,
+    /// Whether the run stops early after this generation (see [`GeneticLearner::stops`]).
+    pub stops: bool,
+}
+
+/// How the genetic algorithm picks the parents of each offspring among the population.
+///
+/// Parsed from `uniform`, `tournament` or `tournament:<size>`, e.g., `tournament:5`, `roulette`,
+/// `linear` or `linear:<pressure>`, e.g., `linear:1.5` (the default), `exponential` or `exponential:<base>`,
+/// e.g., `exponential:0.95` (the default), and `lexicase`.
+/// Ranks start at 0 for the fittest formula, formulas with the same score being ranked in order.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ParentSelection {
+    /// Any formula of the population, with equal probability.
+    Uniform,
+    /// The fittest of that many formulas drawn at random (with replacement),
+    /// so that fitter formulas breed more often while less fit ones still get a chance, keeping the population diverse.
+    /// The larger the tournaments, the stronger the selection pressure; tournaments of size 1 are uniform.
+    Tournament(usize),
+    /// Any formula, with a probability proportional to its scaled fitness (roulette-wheel selection).
+    /// As scores can be negative, they are scaled by subtracting the lowest score of the population and adding one,
+    /// so that even the least fit formula has a chance.
+    /// Scores are used even if formulas are not ranked by them (see [`GeneticLearner::pareto`]).
+    Roulette,
+    /// Any formula, with a probability decreasing linearly with its rank, from the given pressure (between 1 and 2)
+    /// times the average probability for the fittest formula, to 2 minus the pressure times it for the least fit one.
+    /// Unlike [`ParentSelection::Roulette`], the selection pressure does not depend on the scale of the scores,
+    /// which mix numbers of traces and sizes.
+    LinearRank(f64),
+    /// Any formula, with a probability proportional to the given base (between 0 and 1) to the power of its rank,
+    /// so the fittest formulas are picked far more often than with [`ParentSelection::LinearRank`] if the base is small.
+    ExponentialRank(f64),
+    /// A formula classifying correctly the traces considered one after the other in a random order (see [`lexicase`]),
+    /// so that formulas right on traces most others get wrong breed, even if they are not the fittest overall.
+    /// It needs to know which traces each formula classifies correctly, which [`GeneticLearner::breed`] evaluates.
+    Lexicase,
+}
+
+impl Default for ParentSelection {
+    fn default() -> Self {
+        ParentSelection::Tournament(3)
+    }
+}
+
+impl ParentSelection {
+    /// A parent among the ranked population, fittest first (see [`GeneticLearner::rank`]), which must not be empty.
+    /// Lexicase selection picks it by whether each formula passes each case, in the same order
+    /// (see [`FitnessCache::outcomes`]), which the other selections ignore, so they can be given none.
+    ///
+    /// # Panics
+    ///
+    /// Panics with lexicase selection if the cases are not given for every formula.
+    pub fn pick<'a, R: Rng + ?Sized>(
+        &self,
+        ranked: &'a [(SyntaxTree, Fitness)],
+        cases: &[Vec<bool>],
+        rng: &mut R,
+    ) -> &'a SyntaxTree {
+        let index = match self {
+            ParentSelection::Uniform => rng.gen_range(0..ranked.len()),
+            ParentSelection::Lexicase => {
+                assert_eq!(
+                    cases.len(),
+                    ranked.len(),
+                    "lexicase selection needs the cases of every formula"
+                );
+                lexicase(cases, rng)
+            }
+            // The formula ranked first wins, which is the fittest one, or the least crowded one of the best front.
+            ParentSelection::Tournament(size) => (0..(*size).max(1))
+                .map(|_| rng.gen_range(0..ranked.len()))
+                .min()
+                .expect("tournaments are not empty"),
+            ParentSelection::Roulette => {
+                let lowest = ranked
+                    .iter()
+                    .map(|(_, fitness)| fitness.score())
+                    .min()
+                    .unwrap_or(0);
+                let weights = ranked
+                    .iter()
+                    .map(|(_, fitness)| (fitness.score() - lowest) as f64 + 1.0);
+                WeightedIndex::new(weights)
+                    .expect("positive weights")
+                    .sample(rng)
+            }
+            ParentSelection::LinearRank(pressure) => {
+                let last = (ranked.len() - 1).max(1) as f64;
+                let weights = (0..ranked.len())
+                    .map(|rank| pressure - (2.0 * pressure - 2.0) * rank as f64 / last);
+                WeightedIndex::new(weights)
+                    .expect("valid pressure")
+                    .sample(rng)
+            }
+            ParentSelection::ExponentialRank(base) => {
+                let weights = (0..ranked.len()).map(|rank| base.powi(rank as i32));
+                WeightedIndex::new(weights).expect("valid base").sample(rng)
+            }
+        };
+        &ranked[index].0
+    }
+}
+
+/// Lexicase selection: the index of a candidate, given whether each one passes each case (e.g., classifies a trace correctly),
+/// which must be given for at least one candidate.
+/// Considering the cases one after the other in a random order, it keeps the candidates passing the case, if any,
+/// until a single one is left or all cases are considered, and picks one of the remaining candidates at random.
+pub fn lexicase<R: Rng + ?Sized>(cases: &[Vec<bool>], rng: &mut R) -> usize {
+    let mut candidates = (0..cases.len()).collect::<Vec<_>>();
+    let mut order = (0..cases.first().map_or(0, Vec::len)).collect::<Vec<_>>();
+    order.shuffle(rng);
+    for case in order {
+        if candidates.len() <= 1 {
+            break;
+        }
+        let passing = candidates
+            .iter()
+            .copied()
+            .filter(|candidate| cases[*candidate][case])
+            .collect::<Vec<_>>();
+        if !passing.is_empty() {
+            candidates = passing;
+        }
+    }
+    candidates[rng.gen_range(0..candidates.len())]
+}
+
+impl fmt::Display for ParentSelection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParentSelection::Uniform => write!(f, "uniform"),
+            ParentSelection::Tournament(size) => write!(f, "tournament:{size}"),
+            ParentSelection::Roulette => write!(f, "roulette"),
+            ParentSelection::LinearRank(pressure) => write!(f, "linear:{pressure}"),
+            ParentSelection::ExponentialRank(base) => write!(f, "exponential:{base}"),
+            ParentSelection::Lexicase => write!(f, "lexicase"),
+        }
+    }
+}
+
+impl FromStr for ParentSelection {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (name, arg) = match s.split_once(':') {
+            Some((name, arg)) => (name, Some(arg)),
+            None => (s, None),
+        };
+        match (name.trim().to_lowercase().as_str(), arg) {
+            ("uniform", None) => Ok(ParentSelection::Uniform),
+            ("tournament", None) => Ok(ParentSelection::default()),
+            ("roulette", None) => Ok(ParentSelection::Roulette),
+            ("lexicase", None) => Ok(ParentSelection::Lexicase),
+            ("tournament", Some(size)) => match size.trim().parse() {
+                Ok(size) if size > 0 => Ok(ParentSelection::Tournament(size)),
+                _ => Err(format!("invalid tournament size: {size}")),
+            },
+            ("linear", None) => Ok(ParentSelection::LinearRank(1.5)),
+            ("linear", Some(pressure)) => match pressure.trim().parse() {
+                Ok(pressure) if (1.0..=2.0).contains(&pressure) => {
+                    Ok(ParentSelection::LinearRank(pressure))
+                }
+                _ => Err(format!("invalid selection pressure: {pressure}")),
+            },
+            ("exponential", None) => Ok(ParentSelection::ExponentialRank(0.95)),
+            ("exponential", Some(base)) => match base.trim().parse() {
+                Ok(base) if base > 0.0 && base <= 1.0 => Ok(ParentSelection::ExponentialRank(base)),
+                _ => Err(format!("invalid base: {base}")),
+            },
+            _ => Err(format!("unknown parent selection: {s}")),
+        }
+    }
+}
+
+/// How well a formula fits a sample.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Fitness {
+    /// The number of positive traces satisfying the formula, lassos included.
+    pub positives: usize,
+    /// The number of negative traces satisfying the formula, lassos included.
+    pub negatives: usize,
+    /// The size of the formula, or its cost (see [`Fitness::weighted`]).
+    pub size: usize,
+}
+
+impl Fitness {
+    /// Evaluates the formula over the sample, which can be any [`SampleSource`].
+    pub fn new<const N: usize, S: SampleSource<N>>(formula: &SyntaxTree, sample: &S) -> Self {
+        Fitness::weighted(formula, sample, &OperatorCosts::default())
+    }
+
+    /// Evaluates the formula over the sample, penalizing its cost rather than its size.
+    pub fn weighted<const N: usize, S: SampleSource<N>>(
+        formula: &SyntaxTree,
+        sample: &S,
+        costs: &OperatorCosts,
+    ) -> Self {
+        Fitness {
+            positives: sample
+                .iter_positive()
+                .filter(|trace| formula.eval(trace))
+                .count()
+                + sample
+                    .positive_lassos()
+                    .iter()
+                    .filter(|lasso| formula.eval_lasso(lasso))
+                    .count(),
+            negatives: sample
+                .iter_negative()
+                .filter(|trace| formula.eval(trace))
+                .count()
+                + sample
+                    .negative_lassos()
+                    .iter()
+                    .filter(|lasso| formula.eval_lasso(lasso))
+                    .count(),
+            size: costs.cost(formula),
+        }
+    }
+
+    /// The satisfied positive traces, minus the satisfied negative traces, minus the size of the formula as a penalty.
+    pub fn score(&self) -> i32 {
+        self.positives as i32 - self.negatives as i32 - self.size as i32
+    }
+
+    /// The fraction of the traces of the sample the formula was evaluated on, which can be any [`SampleSource`],
+    /// classified correctly by the formula (`1.0` on an empty sample).
+    pub fn accuracy<const N: usize, S: SampleSource<N>>(&self, sample: &S) -> f64 {
+        let negatives = sample.negative_count() + sample.negative_lassos().len();
+        let total = sample.positive_count() + sample.positive_lassos().len() + negatives;
+        if total == 0 {
+            1.0
+        } else {
+            (self.positives + negatives - self.negatives) as f64 / total as f64
+        }
+    }
+}
+
+/// The fitness of the formulas evaluated so far, and whether they classify each trace correctly,
+/// so that each distinct formula is evaluated only once,
+/// although the population and its offspring have many formulas in common from a generation to the next.
+/// A cache is only valid for a given sample and given costs.
+#[derive(Debug, Clone, Default)]
+pub struct FitnessCache {
+    evaluations: HashMap<SyntaxTree, (Fitness, Vec<bool>)>,
+    hits: usize,
+}
+
+impl FitnessCache {
+    /// The fitness of the formula over the sample with the given costs, and whether it classifies each trace correctly,
+    /// positive traces first, each followed by the lassos of the same label, evaluated only if the formula was not evaluated before.
+    fn evaluation<const N: usize, S: SampleSource<N>>(
+        &mut self,
+        formula: &SyntaxTree,
+        sample: &S,
+        costs: &OperatorCosts,
+    ) -> &(Fitness, Vec<bool>) {
+        if self.evaluations.contains_key(formula) {
+            self.hits += 1;
+        } else {
+            let mut outcomes = sample
+                .iter_positive()
+                .map(|trace| formula.eval(&trace))
+                .chain(
+                    sample
+                        .positive_lassos()
+                        .iter()
+                        .map(|lasso| formula.eval_lasso(lasso)),
+                )
+                .collect::<Vec<_>>();
+            let positives = outcomes.iter().filter(|outcome| **outcome).count();
+            let positive_count = outcomes.len();
+            outcomes.extend(sample.iter_negative().map(|trace| !formula.eval(&trace)));
+            outcomes.extend(
+                sample
+                    .negative_lassos()
+                    .iter()
+                    .map(|lasso| !formula.eval_lasso(lasso)),
+            );
+            let fitness = Fitness {
+                positives,
+                negatives: outcomes[positive_count..]
+                    .iter()
+                    .filter(|outcome| !**outcome)
+                    .count(),
+                size: costs.cost(formula),
+            };
+            self.evaluations
+                .insert(formula.clone(), (fitness, outcomes));
+        }
+        &self.evaluations[formula]
+    }
+
+    /// The fitness of the formula over the sample with the given costs (see [`Fitness::weighted`]),
+    /// evaluated only if the formula was not evaluated before.
+    pub fn fitness<const N: usize, S: SampleSource<N>>(
+        &mut self,
+        formula: &SyntaxTree,
+        sample: &S,
+        costs: &OperatorCosts,
+    ) -> Fitness {
+        self.evaluation(formula, sample, costs).0
+    }
+
+    /// Whether the formula classifies each trace of the sample correctly, positive traces first, e.g., for [`lexicase`],
+    /// evaluated only if the formula was not evaluated before.
+    pub fn outcomes<const N: usize, S: SampleSource<N>>(
+        &mut self,
+        formula: &SyntaxTree,
+        sample: &S,
+        costs: &OperatorCosts,
+    ) -> &[bool] {
+        &self.evaluation(formula, sample, costs).1
+    }
+
+    /// A cache of the evaluations of the given formulas only, e.g., for a subpopulation.
+    pub fn subset(&self, formulas: &[SyntaxTree]) -> FitnessCache {
+        FitnessCache {
+            evaluations: formulas
+                .iter()
+                .filter_map(|formula| {
+                    let evaluation = self.evaluations.get(formula)?;
+                    Some((formula.clone(), evaluation.clone()))
+                })
+                .collect(),
+            hits: 0,
+        }
+    }
+
+    /// Adds the evaluations and hits of the other cache, over the same sample and costs.
+    pub fn merge(&mut self, other: FitnessCache) {
+        for (formula, evaluation) in other.evaluations {
+            self.evaluations.entry(formula).or_insert(evaluation);
+        }
+        self.hits += other.hits;
+    }
+
+    /// The number of distinct formulas evaluated.
+    pub fn len(&self) -> usize {
+        self.evaluations.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.evaluations.is_empty()
+    }
+
+    /// The number of times the fitness of a formula evaluated before was asked for.
+    pub fn hits(&self) -> usize {
+        self.hits
+    }
+}
+
+/// Learns formulas with a genetic algorithm.
+/// The initial population is made of all the formulas of a given size, plus optional seeds;
+/// each generation adds the offspring of parents picked by tournaments (see [`ParentSelection`] and [`CrossoverMethod`])
+/// and random mutants (see [`MutationMethod`]),
+/// then keeps the fittest formulas (see [`Fitness`]).
+/// Offspring and mutants larger than the maximum size, if any, are discarded.
+///
+/// With a [`Grammar`], the formulas are restricted to its fragment of LTL:
+/// the initial population is made of the formulas of the fragment up to the given size (as there may be none of that exact size)
+/// and the seeds in the fragment, and offspring and mutants are bred by its typed operators (see [`Grammar::crossover`]).
+///
+/// ```
+/// use learn_ltl::*;
+///
+/// let sample = Sample::<1> {
+///     positive_traces: vec![vec![[true], [true]]],
+///     negative_traces: vec![vec![[false], [true]], vec![[true], [false]]],
+///     ..Sample::default()
+/// };
+/// let formula = GeneticLearner::new(2).iterations(3).learn(&sample);
+/// assert!(sample.is_consistent(&formula));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct GeneticLearner {
+    /// The size of the formulas of the initial population.
+    pub size: usize,
+    /// The number of generations.
+    pub iterations: usize,
+    /// The variables of the formulas of the initial population, or `None` for all those allowed by the sample.
+    pub vars: Option<Vec<Idx>>,
+    /// Formulas added to the initial population, e.g., expert guesses.
+    pub seeds: Vec<SyntaxTree>,
+    /// The costs penalizing formulas in their fitness, instead of their size.
+    pub costs: OperatorCosts,
+    /// The fragment of LTL the formulas are restricted to, if any.
+    pub grammar: Option<Grammar>,
+    /// How offspring are bred, without a grammar.
+    pub crossover: CrossoverMethod,
+    /// How mutants are bred, without a grammar.
+    pub mutation: MutationMethod,
+    /// How often formulas are mutated.
+    pub mutation_rate: MutationRate,
+    /// How the parents of offspring are picked.
+    pub selection: ParentSelection,
+    /// The number of the fittest formulas of the population carried over unchanged into the next generation, if any,
+    /// where they survive whatever the offspring and mutants (see [`GeneticLearner::keep_survivors`]),
+    /// so that the fittest formulas are never lost; the rest of the population is replaced.
+    /// By default, the whole population competes with its offspring and mutants instead.
+    pub elitism: Option<usize>,
+    /// The maximum number of formulas of the initial population, seeds included, if any.
+    /// If there are more formulas of the configured size, an evenly spread subset of them is kept.
+    pub population: Option<usize>,
+    /// The number of offspring bred each generation, if any (fewer if crossovers fail or breed duplicates);
+    /// by default, the population breeds two offspring per formula but one.
+    pub offspring: Option<usize>,
+    /// The number of the fittest formulas surviving each generation.
+    pub survivors: usize,
+    /// The niche radius of fitness sharing, if any: survivors are then the formulas with the highest shared fitness
+    /// (see [`GeneticLearner::keep_survivors`]), so that the population does not collapse onto a single shape of formulas.
+    pub sharing: Option<usize>,
+    /// Whether formulas are ranked by non-dominated sorting over accuracy and size, as in NSGA-II (see [`sort_pareto`]),
+    /// rather than by their score, so that no penalty weighs size against accuracy.
+    pub pareto: bool,
+    /// Whether runs stop as soon as the fittest formula is consistent with the sample,
+    /// rather than after the configured number of generations.
+    pub stop_when_consistent: bool,
+    /// The number of generations without improvement of the fittest formula after which runs stop, if any.
+    pub patience: Option<usize>,
+    /// The seed of the random number generator of [`GeneticLearner::learn`], if any, to make runs reproducible.
+    pub random_seed: Option<u64>,
+    /// The maximum size of the offspring and mutants, if any.
+    pub max_size: Option<usize>,
+}
+
+impl Default for GeneticLearner {
+    fn default() -> Self {
+        GeneticLearner::new(3)
+    }
+}
+
+impl GeneticLearner {
+    /// A learner starting from the formulas of the given size, for 10 generations.
+    pub fn new(size: usize) -> Self {
+        GeneticLearner {
+            size,
+            iterations: 10,
+            vars: None,
+            seeds: Vec::new(),
+            costs: OperatorCosts::default(),
+            grammar: None,
+            crossover: CrossoverMethod::default(),
+            mutation: MutationMethod::default(),
+            mutation_rate: MutationRate::default(),
+            selection: ParentSelection::default(),
+            elitism: None,
+            population: None,
+            offspring: None,
+            survivors: SURVIVORS,
+            sharing: None,
+            pareto: false,
+            stop_when_consistent: true,
+            patience: None,
+            random_seed: None,
+            max_size: None,
+        }
+    }
+
+    pub fn iterations(mut self, iterations: usize) -> Self {
+        self.iterations = iterations;
+        self
+    }
+
+    pub fn vars(mut self, vars: Vec<Idx>) -> Self {
+        self.vars = Some(vars);
+        self
+    }
+
+    pub fn seeds(mut self, seeds: Vec<SyntaxTree>) -> Self {
+        self.seeds = seeds;
+        self
+    }
+
+    pub fn costs(mut self, costs: OperatorCosts) -> Self {
+        self.costs = costs;
+        self
+    }
+
+    pub fn grammar(mut self, grammar: Grammar) -> Self {
+        self.grammar = Some(grammar);
+        self
+    }
+
+    pub fn crossover(mut self, crossover: CrossoverMethod) -> Self {
+        self.crossover = crossover;
+        self
+    }
+
+    pub fn mutation(mut self, mutation: MutationMethod) -> Self {
+        self.mutation = mutation;
+        self
+    }
+
+    pub fn mutation_rate(mut self, mutation_rate: MutationRate) -> Self {
+        self.mutation_rate = mutation_rate;
+        self
+    }
+
+    pub fn selection(mut self, selection: ParentSelection) -> Self {
+        self.selection = selection;
+        self
+    }
+
+    pub fn elitism(mut self, elitism: usize) -> Self {
+        self.elitism = Some(elitism);
+        self
+    }
+
+    pub fn population(mut self, population: usize) -> Self {
+        self.population = Some(population);
+        self
+    }
+
+    pub fn offspring(mut self, offspring: usize) -> Self {
+        self.offspring = Some(offspring);
+        self
+    }
+
+    pub fn survivors(mut self, survivors: usize) -> Self {
+        self.survivors = survivors;
+        self
+    }
+
+    pub fn sharing(mut self, radius: usize) -> Self {
+        self.sharing = Some(radius);
+        self
+    }
+
+    pub fn pareto(mut self, pareto: bool) -> Self {
+        self.pareto = pareto;
+        self
+    }
+
+    pub fn stop_when_consistent(mut self, stop: bool) -> Self {
+        self.stop_when_consistent = stop;
+        self
+    }
+
+    pub fn patience(mut self, patience: usize) -> Self {
+        self.patience = Some(patience);
+        self
+    }
+
+    pub fn random_seed(mut self, seed: u64) -> Self {
+        self.random_seed = Some(seed);
+        self
+    }
+
+    /// A random number generator seeded with the configured seed, or else with entropy from the system.
+    pub fn rng(&self) -> StdRng {
+        self.random_seed
+            .map_or_else(StdRng::from_entropy, StdRng::seed_from_u64)
+    }
+
+    pub fn max_size(mut self, max_size: usize) -> Self {
+        self.max_size = Some(max_size);
+        self
+    }
+
+    /// The schedule of the mutation probability for a new run (see [`GeneticLearner::mutation_rate`]).
+    pub fn mutation_schedule(&self) -> MutationSchedule {
+        MutationSchedule::new(self.mutation_rate)
+    }
+
+    /// All the formulas of the configured size, followed by the seeds,
+    /// or, with a grammar, those of its fragment (see [`GeneticLearner`]),
+    /// the formulas being thinned out to the population size if any.
+    pub fn initial_population<const N: usize, S: SampleSource<N>>(
+        &self,
+        sample: &S,
+    ) -> Vec<SyntaxTree> {
+        let vars = self.vars.clone().unwrap_or_else(|| sample.vars());
+        let (mut population, seeds) = match &self.grammar {
+            None => (gen_formulae::<N>(self.size, &vars), self.seeds.clone()),
+            Some(grammar) => (
+                (grammar.min_size()..=self.size)
+                    .flat_map(|size| gen_formulae_iter::<N>(size, &vars))
+                    .filter(|formula| grammar.contains(formula))
+                    .collect(),
+                self.seeds
+                    .iter()
+                    .filter(|seed| grammar.contains(seed))
+                    .cloned()
+                    .collect(),
+            ),
+        };
+        if let Some(size) = self.population {
+            // Formulas are enumerated by shape, so an evenly spread subset keeps all shapes and variables.
+            let count = size.saturating_sub(seeds.len());
+            if population.len() > count {
+                let len = population.len();
+                population = (0..count)
+                    .map(|index| population[index * len / count].clone())
+                    .collect();
+            }
+        }
+        population.extend(seeds);
+        population
+    }
+
+    /// The elite of the ranked population (see [`GeneticLearner::rank`] and [`GeneticLearner::elitism`]),
+    /// followed by the offspring of parents picked from the whole population (see [`ParentSelection`]) and by random mutants.
+    /// Mutants get variables among the configured ones, or else those up to the highest one of the population.
+    /// The sample the population was ranked on, which can be any [`SampleSource`], is only evaluated for lexicase selection,
+    /// through the cache the population was ranked with, if any (see [`GeneticLearner::rank_cached`]).
+    /// Each call is a generation of the run the schedule belongs to, which is updated with the fittest formula
+    /// before mutating formulas with its probability (see [`MutationSchedule`]).
+    pub fn breed<const N: usize, S: SampleSource<N>, R: Rng + ?Sized>(
+        &self,
+        ranked: &[(SyntaxTree, Fitness)],
+        sample: &S,
+        cache: &mut FitnessCache,
+        schedule: &mut MutationSchedule,
+        rng: &mut R,
+    ) -> Vec<SyntaxTree> {
+        let population = ranked
+            .iter()
+            .map(|(formula, _)| formula.clone())
+            .collect::<Vec<_>>();
+        let breed_pair = |parent1, parent2, rng: &mut R| match (&self.grammar, self.crossover) {
+            (Some(grammar), _) => grammar.crossover(parent1, parent2, rng),
+            (None, CrossoverMethod::Subtree) => {
+                subtree_crossover(parent1, parent2, self.max_size, rng)
+            }
+            (None, CrossoverMethod::Branches) => crossover(parent1, parent2, rng),
+        };
+        let fits = |formula: &SyntaxTree| self.max_size.is_none_or(|max| formula.size() <= max);
+        let (attempts, count) = match self.offspring {
+            Some(count) => (count, count),
+            None => (population.len().saturating_sub(1), usize::MAX),
+        };
+        let cases = if self.selection == ParentSelection::Lexicase {
+            population
+                .iter()
+                .map(|formula| cache.outcomes(formula, sample, &self.costs).to_vec())
+                .collect::<Vec<_>>()
+        } else {
+            Vec::new()
+        };
+        let pick = |rng: &mut R| self.selection.pick(ranked, &cases, rng);
+        let mut offspring: Vec<SyntaxTree> = Vec::new();
+        if !population.is_empty() {
+            for _ in 0..attempts {
+                if offspring.len() >= count {
+                    break;
+                }
+                let parent1 = pick(rng);
+                let parent2 = pick(rng);
+                if let Some((offspring1, offspring2)) = breed_pair(parent1, parent2, rng) {
+                    for child in [offspring1, offspring2] {
+                        if fits(&child) && !offspring.contains(&child) && offspring.len() < count {
+                            offspring.push(child);
+                        }
+                    }
+                }
+            }
+        }
+
+        let vars = self.vars.clone().unwrap_or_else(|| {
+            let vars = population.iter().map(SyntaxTree::vars).max().unwrap_or(0);
+            (0..vars).collect()
+        });
+        schedule.update(ranked.first().map(|(_, fitness)| fitness));
+        let mut mutants = Vec::new();
+        for formula in &population {
+            if rng.gen_bool(schedule.probability()) {
+                let mutant = match (&self.grammar, self.mutation) {
+                    (Some(grammar), _) => grammar.mutate(formula, &vars, rng),
+                    (None, MutationMethod::Mixed) if rng.gen() => {
+                        point_mutation(formula, &vars, rng)
+                    }
+                    (None, MutationMethod::Mixed | MutationMethod::Structural) => {
+                        structural_mutation(formula, rng)
+                    }
+                    (None, MutationMethod::Point) => point_mutation(formula, &vars, rng),
+                    (None, MutationMethod::Root) => mutate(formula, rng),
+                };
+                if fits(&mutant) {
+                    mutants.push(mutant);
+                }
+            }
+        }
+
+        let mut combined =
+            population[..self.elitism.unwrap_or(usize::MAX).min(population.len())].to_vec();
+        combined.extend(offspring);
+        combined.extend(mutants);
+        // Without elites, offspring or mutants, the population carries over, rather than dying out.
+        if combined.is_empty() {
+            return population;
+        }
+        combined
+    }
+
+    /// The formulas with their fitness over the sample, fittest first,
+    /// by score or, with [`GeneticLearner::pareto`], as NSGA-II ranks them (see [`sort_pareto`]).
+    pub fn rank<const N: usize, S: SampleSource<N>>(
+        &self,
+        formulas: &[SyntaxTree],
+        sample: &S,
+    ) -> Vec<(SyntaxTree, Fitness)> {
+        self.rank_cached(formulas, sample, &mut FitnessCache::default())
+    }
+
+    /// Ranks the formulas like [`GeneticLearner::rank`], only evaluating those not in the cache,
+    /// which must have been filled with the same sample and costs.
+    pub fn rank_cached<const N: usize, S: SampleSource<N>>(
+        &self,
+        formulas: &[SyntaxTree],
+        sample: &S,
+        cache: &mut FitnessCache,
+    ) -> Vec<(SyntaxTree, Fitness)> {
+        let mut ranked = formulas
+            .iter()
+            .map(|formula| {
+                let fitness = cache.fitness(formula, sample, &self.costs);
+                (formula.clone(), fitness)
+            })
+            .collect::<Vec<_>>();
+        self.sort_ranked(&mut ranked);
+        ranked
+    }
+
+    /// Sorts formulas already evaluated like [`GeneticLearner::rank`] does, fittest first.
+    pub(crate) fn sort_ranked(&self, ranked: &mut Vec<(SyntaxTree, Fitness)>) {
+        if self.pareto {
+            sort_pareto(ranked);
+        } else {
+            ranked.sort_by_key(|(_, fitness)| Reverse(fitness.score()));
+        }
+    }
+
+    /// Whether a run stops early at the given generation, ranked fittest first (see [`GeneticLearner::rank`]):
+    /// if the fittest formula is consistent with the sample, unless [`GeneticLearner::stop_when_consistent`] is unset,
+    /// or if its score has not improved for the configured patience, if any, which the stagnation keeps track of.
+    pub fn stops<const N: usize, S: SampleSource<N>>(
+        &self,
+        ranked: &[(SyntaxTree, Fitness)],
+        sample: &S,
+        stagnation: &mut Stagnation,
+    ) -> bool {
+        let Some((fittest, fitness)) = ranked.first() else {
+            return true;
+        };
+        if stagnation.best.is_some_and(|best| fitness.score() <= best) {
+            stagnation.generations += 1;
+        } else {
+            stagnation.best = Some(fitness.score());
+            stagnation.generations = 0;
+        }
+        self.patience
+            .is_some_and(|patience| stagnation.generations >= patience)
+            || self.stop_when_consistent
+                && fitness.negatives == 0
+                && fitness.positives == sample.positive_count() + sample.positive_lassos().len()
+                && sample.is_consistent(fittest)
+    }
+
+    /// The formulas of a ranked population surviving to the next generation, without elites
+    /// (see [`GeneticLearner::keep_survivors`]).
+    pub fn select(&self, ranked: &[(SyntaxTree, Fitness)]) -> Vec<SyntaxTree> {
+        let mut survivors = ranked.to_vec();
+        self.keep_survivors(&mut survivors, &[]);
+        survivors.into_iter().map(|(formula, _)| formula).collect()
+    }
+
+    /// The elites of a ranked population (see [`GeneticLearner::elitism`]), which survive the next generation.
+    pub fn elite(&self, ranked: &[(SyntaxTree, Fitness)]) -> Vec<SyntaxTree> {
+        ranked
+            .iter()
+            .take(self.elitism.unwrap_or(0))
+            .map(|(formula, _)| formula.clone())
+            .collect()
+    }
+
+    /// Truncates a ranked population to the formulas surviving to the next generation, keeping their fitness
+    /// to pick parents among them, still fittest first: the elites of the previous generation (see [`GeneticLearner::elite`]),
+    /// whatever their fitness, and the fittest other formulas or, with fitness sharing,
+    /// those with the highest shared fitness, up to the number of survivors.
+    pub fn keep_survivors(&self, ranked: &mut Vec<(SyntaxTree, Fitness)>, elite: &[SyntaxTree]) {
+        let elites = ranked
+            .iter()
+            .map(|(formula, _)| elite.contains(formula))
+            .collect::<Vec<_>>();
+        let others = ranked
+            .iter()
+            .zip(&elites)
+            .filter(|(_, elite)| !**elite)
+            .map(|(entry, _)| entry.clone())
+            .collect::<Vec<_>>();
+        let slots = self
+            .survivors
+            .saturating_sub(elites.iter().filter(|elite| **elite).count());
+        let mut kept = match self.sharing.filter(|_| others.len() > slots) {
+            Some(radius) => shared_survivors(&others, radius, slots),
+            None => (0..others.len()).map(|index| index < slots).collect(),
+        }
+        .into_iter();
+        let mut elites = elites.into_iter();
+        ranked.retain(|_| elites.next().unwrap_or(false) || kept.next().unwrap_or(false));
+    }
+
+    /// Evolves the population for the configured number of generations, unless it stops early (see [`GeneticLearner::stops`]),
+    /// returning the surviving formulas with their fitness, fittest first.
+    pub fn evolve<const N: usize, S: SampleSource<N>, R: Rng + ?Sized>(
+        &self,
+        sample: &S,
+        population: Vec<SyntaxTree>,
+        rng: &mut R,
+    ) -> Vec<(SyntaxTree, Fitness)> {
+        let mut cache = FitnessCache::default();
+        self.evolve_observed(sample, population, &mut cache, rng, &(), |_| {})
+    }
+
+    /// Evolves the population like [`GeneticLearner::evolve`], with the given cache, notifying the observer
+    /// of every generation, of the number of formulas ranked so far,
+    /// and of every fittest formula fitter than the previous ones,
+    /// and calling back after every generation, e.g., to log the run.
+    pub fn evolve_observed<const N: usize, S, R, F>(
+        &self,
+        sample: &S,
+        population: Vec<SyntaxTree>,
+        cache: &mut FitnessCache,
+        rng: &mut R,
+        observer: &dyn SearchObserver,
+        mut on_generation: F,
+    ) -> Vec<(SyntaxTree, Fitness)>
+    where
+        S: SampleSource<N>,
+        R: Rng + ?Sized,
+        F: FnMut(&Generation<'_>),
+    {
+        let mut ranked = self.rank_cached(&population, sample, cache);
+        let mut offspring = Vec::new();
+        let mut checked = ranked.len();
+        let mut best = None;
+        let mut schedule = self.mutation_schedule();
+        let mut stagnation = Stagnation::default();
+        for generation in 0..=self.iterations {
+            if generation > 0 {
+                let elite = self.elite(&ranked);
+                let combined = self.breed(&ranked, sample, cache, &mut schedule, rng);
+                offspring = self.rank_cached(&combined, sample, cache);
+                checked += offspring.len();
+                ranked = offspring.clone();
+                self.keep_survivors(&mut ranked, &elite);
+            }
+            if let Some((fittest, fitness)) = ranked.first() {
+                observer.on_generation(generation, fittest, fitness);
+                if best.is_none_or(|best| fitness.score() > best) {
+                    best = Some(fitness.score());
+                    observer.on_candidate_found(fittest);
+                }
+            }
+            observer.on_progress(checked);
+            let stops = self.stops(&ranked, sample, &mut stagnation);
+            on_generation(&Generation {
+                index: generation,
+                offspring: &offspring,
+                ranked: &ranked,
+                cache,
+                schedule: &schedule,
+                stagnation: &stagnation,
+                stops,
+            });
+            if stops {
+                break;
+            }
+        }
+        ranked
+    }
+
+    /// Learns a formula from the sample, which can be any [`SampleSource`]:
+    /// the fittest formula of the last generation, which is not necessarily consistent with the sample.
+    /// The [`Learner`](crate::Learner) implementation also tells whether it is, and honors a [`Budget`](crate::Budget).
+    ///
+    /// # Panics
+    ///
+    /// If the initial population is empty, i.e., there are no formulas of the configured size and no seeds.
+    pub fn learn<const N: usize, S: SampleSource<N>>(&self, sample: &S) -> SyntaxTree {
+        let population = self.initial_population(sample);
+        self.evolve(sample, population, &mut self.rng())
+            .into_iter()
+            .next()
+            .map(|(formula, _)| formula)
+            .expect("empty initial population")
+    }
+}
+
+#[cfg(test)]
+mod genetic_learning {
+    use super::*;
+    use crate::trace::*;
+
+    const ATOM_0: SyntaxTree = SyntaxTree::Atom(0);
+    const ATOM_1: SyntaxTree = SyntaxTree::Atom(1);
+
+    #[test]
+    fn branch_crossover() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let and = ATOM_0.and(ATOM_1);
+        let until = ATOM_1.until(ATOM_0);
+        assert_eq!(and.size(), 3);
+        let (offspring1, offspring2) = crossover(&and, &until, &mut rng).expect("binary parents");
+        assert!(offspring1.branches().is_some());
+        assert!(offspring2.branches().is_some());
+        assert_eq!(crossover(&ATOM_0, &and, &mut rng), None);
+        assert_eq!("branches".parse(), Ok(CrossoverMethod::Branches));
+    }
+
+    #[test]
+    fn capped_subtree_crossover() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let and = ATOM_0.and(ATOM_1);
+        let globally = ATOM_1.until(ATOM_0).globally();
+        let (offspring1, offspring2) =
+            subtree_crossover(&and, &globally, None, &mut rng).expect("no cap");
+        assert_eq!(offspring1.size() + offspring2.size(), 7);
+        for _ in 0..10 {
+            if let Some((offspring1, offspring2)) =
+                subtree_crossover(&ATOM_0, &globally, Some(3), &mut rng)
+            {
+                assert!(offspring1.size() <= 3 && offspring2.size() <= 3);
+            }
+        }
+        assert_eq!(
+            subtree_crossover(&ATOM_0, &ATOM_1, Some(1), &mut rng),
+            Some((ATOM_1, ATOM_0))
+        );
+    }
+
+    #[test]
+    fn point_mutations() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let deep = !ATOM_1.until(ATOM_0).globally();
+        assert_eq!((deep.height(), deep.node_depth(3)), (4, 3));
+        for _ in 0..10 {
+            let mutant = point_mutation(&deep, &[0, 1], &mut rng);
+            assert_ne!(mutant, deep);
+            assert!(mutant.size() > 1, "the root is never replaced by an atom");
+        }
+        assert_ne!(point_mutation(&ATOM_0, &[0, 1], &mut rng), ATOM_0);
+        assert_eq!("root".parse(), Ok(MutationMethod::Root));
+    }
+
+    #[test]
+    fn structural_mutations() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let deep = !ATOM_1.until(ATOM_0).globally();
+        let (mut grown, mut shrunk) = (false, false);
+        for _ in 0..20 {
+            let mutant = structural_mutation(&deep, &mut rng);
+            let size = mutant.size();
+            assert!(size == deep.size() + 1 || size < deep.size());
+            grown |= size > deep.size();
+            shrunk |= size < deep.size();
+        }
+        assert!(grown && shrunk);
+        assert_eq!(structural_mutation(&ATOM_0, &mut rng).size(), 2);
+    }
+
+    #[test]
+    fn branch_mutation() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let and = ATOM_0.and(ATOM_1);
+        let mutant = mutate(&and, &mut rng);
+        assert_ne!(mutant, and);
+        assert_eq!(mutant.branches(), and.branches());
+        assert_eq!(mutate(&ATOM_0, &mut rng), ATOM_0);
+    }
+
+    /// A sample where x0 holds exactly in positive traces, with x0, x1 and their conjunction,
+    /// ranked as x0, then x1, then x0 ∧ x1.
+    fn ranked() -> (Sample<2>, Vec<(SyntaxTree, Fitness)>) {
+        let sample = Sample::<2> {
+            positive_traces: vec![vec![[true, false]], vec![[true, true]]],
+            negative_traces: vec![vec![[false, true]]],
+            ..Sample::default()
+        };
+        let ranked = GeneticLearner::default().rank(&[ATOM_1, ATOM_0.and(ATOM_1), ATOM_0], &sample);
+        assert_eq!(ranked[0].0, ATOM_0);
+        (sample, ranked)
+    }
+
+    /// How many times out of 300 the selection picks x0, the fittest formula of [`ranked`].
+    fn wins(selection: ParentSelection) -> usize {
+        let (_, ranked) = ranked();
+        let mut rng = StdRng::seed_from_u64(0);
+        (0..300)
+            .filter(|_| *selection.pick(&ranked, &[], &mut rng) == ATOM_0)
+            .count()
+    }
+
+    #[test]
+    fn tournament_selection() {
+        // The fittest formula wins a tournament unless it is not drawn, with probability (2/3)^size.
+        assert!(wins(ParentSelection::Tournament(5)) > 220);
+        assert_eq!("tournament:5".parse(), Ok(ParentSelection::Tournament(5)));
+        assert_eq!("tournament".parse(), Ok(ParentSelection::default()));
+        assert!("tournament:0".parse::<ParentSelection>().is_err());
+    }
+
+    #[test]
+    fn uniform_selection() {
+        assert!(wins(ParentSelection::Uniform) < 150);
+    }
+
+    #[test]
+    fn roulette_selection() {
+        // The scaled scores are 4, 2 and 1.
+        let roulette = wins(ParentSelection::Roulette);
+        assert!(roulette > 130 && roulette < 220);
+    }
+
+    #[test]
+    fn rank_selection() {
+        // The least fit formula is never picked with the highest linear pressure.
+        let (_, ranked) = ranked();
+        let mut rng = StdRng::seed_from_u64(0);
+        let linear = ParentSelection::LinearRank(2.0);
+        assert!((0..100).all(|_| *linear.pick(&ranked, &[], &mut rng) != ATOM_0.and(ATOM_1)));
+        let exponential = ParentSelection::ExponentialRank(0.1);
+        assert!(wins(exponential) > 250);
+        assert_eq!("linear".parse(), Ok(ParentSelection::LinearRank(1.5)));
+        assert_eq!(exponential.to_string().parse(), Ok(exponential));
+        assert!("linear:3".parse::<ParentSelection>().is_err());
+    }
+
+    #[test]
+    fn lexicase_selection() {
+        let mut rng = StdRng::seed_from_u64(0);
+        // The second candidate wins whenever the second case comes first, the first one otherwise,
+        // and the third one never, as it passes no case the first one fails.
+        let cases = [
+            vec![true, false, true],
+            vec![false, true, false],
+            vec![false, false, true],
+        ];
+        let picks = (0..300)
+            .map(|_| lexicase(&cases, &mut rng))
+            .collect::<Vec<_>>();
+        assert!(picks.contains(&0) && picks.contains(&1));
+        assert!(!picks.contains(&2));
+        let (_, ranked) = ranked();
+        let lexicase = ParentSelection::Lexicase;
+        assert!((0..100).all(|_| *lexicase.pick(&ranked, &cases, &mut rng) != ranked[2].0));
+    }
+
+    #[test]
+    fn cached_outcomes() {
+        let (sample, _) = ranked();
+        let mut cache = FitnessCache::default();
+        let costs = OperatorCosts::default();
+        assert_eq!(cache.outcomes(&ATOM_0, &sample, &costs), [true, true, true]);
+        assert_eq!(
+            cache.outcomes(&ATOM_1, &sample, &costs),
+            [false, true, false]
+        );
+        let and = ATOM_0.and(ATOM_1);
+        assert_eq!(cache.outcomes(&and, &sample, &costs), [false, true, true]);
+        assert_eq!(
+            cache.fitness(&and, &sample, &costs),
+            Fitness::new(&and, &sample)
+        );
+        assert_eq!((cache.len(), cache.hits()), (3, 1));
+    }
+
+    #[test]
+    fn mutation_rate() {
+        let fitness = |size| Fitness {
+            positives: 2,
+            negatives: 0,
+            size,
+        };
+        let mut schedule = MutationSchedule::new(MutationRate::default());
+        schedule.update(Some(&fitness(3)));
+        assert_eq!(schedule.probability(), 0.2);
+        // The probability grows while the fittest formula stagnates, up to the maximum, and halves when it improves.
+        for _ in 0..10 {
+            schedule.update(Some(&fitness(3)));
+        }
+        assert_eq!(schedule.probability(), MAX_MUTATION_RATE);
+        schedule.update(Some(&fitness(2)));
+        assert_eq!(schedule.probability(), MAX_MUTATION_RATE / 2.0);
+
+        let mut fixed = MutationSchedule::new(MutationRate::Fixed(0.3));
+        fixed.update(Some(&fitness(3)));
+        fixed.update(Some(&fitness(3)));
+        assert_eq!(fixed.probability(), 0.3);
+        assert_eq!("fixed".parse(), Ok(MutationRate::Fixed(0.2)));
+        assert_eq!(
+            MutationRate::default().to_string().parse(),
+            Ok(MutationRate::default())
+        );
+        assert!("adaptive:2".parse::<MutationRate>().is_err());
+    }
+
+    #[test]
+    fn early_stopping() {
+        let sample = Sample::always_x0();
+        let learner = GeneticLearner::new(2);
+        let consistent = learner.rank(&[ATOM_0.globally(), ATOM_0], &sample);
+        assert!(learner.stops(&consistent, &sample, &mut Stagnation::default()));
+        let inconsistent = learner.rank(&[ATOM_0, ATOM_1], &sample);
+        let learner = learner.patience(2);
+        let mut stagnation = Stagnation::default();
+        assert!(!learner.stops(&inconsistent, &sample, &mut stagnation));
+        assert!(!learner.stops(&inconsistent, &sample, &mut stagnation));
+        assert!(learner.stops(&inconsistent, &sample, &mut stagnation));
+        assert_eq!(stagnation.generations(), 2);
+        let learner = learner.stop_when_consistent(false);
+        assert!(!learner.stops(&consistent, &sample, &mut Stagnation::default()));
+    }
+
+    #[test]
+    fn fitness_sharing() {
+        let fitness = |negatives, size| Fitness {
+            positives: 4,
+            negatives,
+            size,
+        };
+        let until = ATOM_0.until(ATOM_1).finally();
+        assert_eq!(ATOM_0.globally().distance(&ATOM_1.globally()), 1);
+        assert_eq!(ATOM_0.globally().distance(&until), 4);
+        assert_eq!(until.distance(&until), 0);
+
+        let ranked = vec![
+            (ATOM_0.globally(), fitness(0, 2)),
+            (ATOM_1.globally(), fitness(2, 2)),
+            (until.clone(), fitness(0, 4)),
+        ];
+        let learner = GeneticLearner::new(2).survivors(2);
+        assert_eq!(
+            learner.select(&ranked),
+            [ATOM_0.globally(), ATOM_1.globally()]
+        );
+        // Both formulas of the same shape share their fitness, so the formula of another shape survives instead.
+        let mut survivors = ranked.clone();
+        learner.sharing(3).keep_survivors(&mut survivors, &[]);
+        assert_eq!(survivors, [ranked[0].clone(), ranked[2].clone()]);
+    }
+
+    #[test]
+    fn weighted_fitness() {
+        let sample = Sample::<2> {
+            positive_traces: vec![vec![[true, false], [true, true]]],
+            negative_traces: vec![vec![[false, true]]],
+            ..Sample::default()
+        };
+        let costs = OperatorCosts {
+            until: 5,
+            ..OperatorCosts::default()
+        };
+        let until = ATOM_0.until(ATOM_1);
+        let ranked = GeneticLearner::default()
+            .costs(costs)
+            .rank(&[until.clone(), ATOM_0.and(ATOM_1)], &sample);
+        assert_eq!(
+            ranked[1],
+            (until.clone(), Fitness::weighted(&until, &sample, &costs))
+        );
+        assert_eq!(ranked[1].1.size, 7);
+
+        let mut cache = FitnessCache::default();
+        let learner = GeneticLearner::default();
+        let formulas = [until.clone(), ATOM_0, until.clone()];
+        let ranked = learner.rank_cached(&formulas, &sample, &mut cache);
+        assert_eq!(ranked, learner.rank(&formulas, &sample));
+        assert_eq!((cache.len(), cache.hits()), (2, 1));
+        learner.rank_cached(&[ATOM_0], &sample, &mut cache);
+        assert_eq!((cache.len(), cache.hits()), (2, 2));
+    }
+
+    #[test]
+    fn learn() {
+        let sample = Sample::always_x0();
+        let learner = GeneticLearner::new(2).iterations(2);
+        let population = learner.initial_population(&sample);
+        let ranked = learner.evolve(&sample, population, &mut StdRng::seed_from_u64(0));
+        let (formula, fitness) = &ranked[0];
+        assert_eq!(*formula, ATOM_0.globally());
+        assert_eq!(
+            *fitness,
+            Fitness {
+                positives: 2,
+                negatives: 0,
+                size: 2
+            }
+        );
+        assert!(sample.is_consistent(formula));
+    }
+
+    #[test]
+    fn elitism() {
+        // With a single elite, the fittest formula is still never lost.
+        let sample = Sample::always_x0();
+        let elite = GeneticLearner::new(2).iterations(2).elitism(1);
+        let population = elite.initial_population(&sample);
+        let ranked = elite.rank(&population, &sample);
+        let bred = elite.breed(
+            &ranked,
+            &sample,
+            &mut FitnessCache::default(),
+            &mut elite.mutation_schedule(),
+            &mut StdRng::seed_from_u64(0),
+        );
+        assert_eq!(bred[0], ranked[0].0);
+        let ranked = elite.evolve(&sample, population, &mut StdRng::seed_from_u64(0));
+        assert_eq!(ranked[0].0, ATOM_0.globally());
+    }
+
+    #[test]
+    fn survivors() {
+        let sample = Sample::always_x0();
+        let learner = GeneticLearner::new(2).population(5).survivors(3);
+        let population = learner.initial_population(&sample);
+        assert_eq!(population.len(), 5);
+        let ranked = learner.rank(&population, &sample);
+        let bred = learner.clone().offspring(0).elitism(0).breed(
+            &ranked,
+            &sample,
+            &mut FitnessCache::default(),
+            &mut learner.mutation_schedule(),
+            &mut StdRng::seed_from_u64(0),
+        );
+        assert!(bred.len() <= population.len(), "only mutants");
+        let ranked = learner.evolve(&sample, population, &mut StdRng::seed_from_u64(0));
+        assert_eq!(ranked.len(), 3);
+
+        // Elites survive however many fitter offspring there are, and even with fitness sharing.
+        let ranked = learner.rank(&[ATOM_0.globally(), ATOM_0, ATOM_1], &sample);
+        let mut survivors = ranked.clone();
+        learner
+            .survivors(2)
+            .sharing(3)
+            .keep_survivors(&mut survivors, &[ATOM_1]);
+        assert_eq!(survivors, [ranked[0].clone(), ranked[2].clone()]);
+    }
+
+    #[test]
+    fn barren_population() {
+        // Without elites, offspring or mutants, the population carries over rather than dying out.
+        let sample = Sample::always_x0();
+        let barren = GeneticLearner::new(2)
+            .elitism(0)
+            .crossover(CrossoverMethod::Branches)
+            .mutation_rate(MutationRate::Fixed(0.0));
+        let ranked = barren.rank(&barren.initial_population(&sample), &sample);
+        let bred = barren.breed(
+            &ranked,
+            &sample,
+            &mut FitnessCache::default(),
+            &mut barren.mutation_schedule(),
+            &mut StdRng::seed_from_u64(0),
+        );
+        assert_eq!(bred.len(), ranked.len());
+        assert_eq!(barren.learn(&sample).size(), 2);
+    }
+
+    #[test]
+    fn pareto_ranking() {
+        // Without a size penalty, the formula satisfying all positive traces and no negative one ranks first.
+        let sample = Sample::always_x0();
+        let pareto = GeneticLearner::new(2).pareto(true);
+        let ranked = pareto.rank(&[ATOM_0, ATOM_0.globally(), ATOM_1], &sample);
+        assert_eq!(ranked[0].0, ATOM_0.globally());
+    }
+
+    #[test]
+    fn seeded_runs() {
+        // Runs with the same seed are the same.
+        let sample = Sample::always_x0();
+        let learner = GeneticLearner::new(2).iterations(3).random_seed(7);
+        let population = learner.initial_population(&sample);
+        assert_eq!(
+            learner.evolve(&sample, population.clone(), &mut learner.rng()),
+            learner.evolve(&sample, population, &mut learner.rng())
+        );
+    }
+}
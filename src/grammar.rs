@@ -0,0 +1,359 @@
+use crate::sketch::*;
+use crate::syntax::*;
+use rand::Rng;
+use std::str::FromStr;
+use std::sync::Arc;
+
+/// How many random choices the typed genetic operators try before giving up.
+const ATTEMPTS: usize = 10;
+
+/// A production of a nonterminal of a [`Grammar`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Production {
+    /// Any variable, for the built-in `atom` nonterminal.
+    Var,
+    /// A sketch whose holes stand for the nonterminals at their number.
+    Shape(Sketch, Vec<usize>),
+}
+
+/// A context-free grammar of formulas, describing a fragment of LTL,
+/// to guide genetic programming so that offspring stay inside the fragment (see [`GeneticLearner::grammar`](crate::GeneticLearner::grammar)).
+///
+/// A grammar is parsed from rules separated by newlines or `;`, each of the form `name := sketch`,
+/// where the holes of the [`Sketch`] are nonterminals, and rules of the same nonterminal are alternatives.
+/// The first nonterminal is the start symbol, and `atom` is any variable, unless it has rules.
+/// For instance, conjunctions of response properties are
+///
+/// ```text
+/// spec := G ?response
+/// spec := ?spec & ?spec
+/// response := ?atom -> F ?atom
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Grammar {
+    nonterminals: Vec<String>,
+    productions: Vec<Vec<Production>>,
+    /// The size of the smallest formula derived by each nonterminal.
+    min_sizes: Vec<usize>,
+}
+
+impl Grammar {
+    /// Parses a grammar, where variables can be referred to by the given names (see [`Sketch::parse_w_named_vars`]).
+    pub fn parse_w_named_vars(input: &str, names: &[String]) -> Result<Self, String> {
+        let mut rules = Vec::new();
+        for rule in input
+            .split(['\n', ';'])
+            .map(str::trim)
+            .filter(|rule| !rule.is_empty())
+        {
+            let (name, sketch) = rule
+                .split_once(":=")
+                .ok_or_else(|| format!("expected `name := sketch`: {rule}"))?;
+            let (sketch, holes) = Sketch::parse_w_hole_names(sketch, names)
+                .map_err(|err| format!("invalid rule `{rule}`: {err}"))?;
+            rules.push((name.trim().to_string(), sketch, holes));
+        }
+
+        let mut nonterminals: Vec<String> = Vec::new();
+        for (name, _, _) in &rules {
+            if !nonterminals.contains(name) {
+                nonterminals.push(name.clone());
+            }
+        }
+        if nonterminals.is_empty() {
+            return Err("empty grammar".to_string());
+        }
+        let mut productions = vec![Vec::new(); nonterminals.len()];
+        for (name, sketch, holes) in rules {
+            let mut hole_nonterminals = Vec::with_capacity(holes.len());
+            for hole in holes {
+                let nonterminal = match nonterminals.iter().position(|name| *name == hole) {
+                    Some(nonterminal) => nonterminal,
+                    None if hole == "atom" => {
+                        nonterminals.push(hole);
+                        productions.push(vec![Production::Var]);
+                        nonterminals.len() - 1
+                    }
+                    None => return Err(format!("undefined nonterminal `{hole}`")),
+                };
+                hole_nonterminals.push(nonterminal);
+            }
+            let nonterminal = nonterminals
+                .iter()
+                .position(|nonterminal| *nonterminal == name)
+                .expect("defined nonterminal");
+            productions[nonterminal].push(Production::Shape(sketch, hole_nonterminals));
+        }
+
+        // The smallest sizes are the least fixpoint of the sizes of the productions.
+        let mut min_sizes = vec![usize::MAX; nonterminals.len()];
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for (nonterminal, productions) in productions.iter().enumerate() {
+                for production in productions {
+                    let size = min_size(production, &min_sizes);
+                    if size < min_sizes[nonterminal] {
+                        min_sizes[nonterminal] = size;
+                        changed = true;
+                    }
+                }
+            }
+        }
+        if let Some(nonterminal) = min_sizes.iter().position(|size| *size == usize::MAX) {
+            return Err(format!(
+                "nonterminal `{}` derives no formula",
+                nonterminals[nonterminal]
+            ));
+        }
+
+        Ok(Grammar {
+            nonterminals,
+            productions,
+            min_sizes,
+        })
+    }
+
+    /// The size of the smallest formula of the fragment.
+    pub fn min_size(&self) -> usize {
+        self.min_sizes[0]
+    }
+
+    /// Whether the formula belongs to the fragment, i.e., is derived from the start symbol.
+    pub fn contains(&self, formula: &SyntaxTree) -> bool {
+        self.derives(0, formula, self.nonterminals.len())
+    }
+
+    /// Whether the nonterminal derives the formula,
+    /// following at most `units` rules of the form `a := ?b` in a row, as they may form cycles.
+    fn derives(&self, nonterminal: usize, formula: &SyntaxTree, units: usize) -> bool {
+        self.productions[nonterminal]
+            .iter()
+            .any(|production| match production {
+                Production::Var => matches!(formula, SyntaxTree::Atom(_)),
+                Production::Shape(sketch, holes) => self.matches(sketch, holes, formula, units),
+            })
+    }
+
+    fn matches(
+        &self,
+        sketch: &Sketch,
+        holes: &[usize],
+        formula: &SyntaxTree,
+        units: usize,
+    ) -> bool {
+        let branch =
+            |sketch, formula| self.matches(sketch, holes, formula, self.nonterminals.len());
+        match (sketch, formula) {
+            (Sketch::Hole(hole), _) => units > 0 && self.derives(holes[*hole], formula, units - 1),
+            (Sketch::Atom(var), SyntaxTree::Atom(atom)) => var == atom,
+            (Sketch::Not(sketch), SyntaxTree::Not(formula))
+            | (Sketch::Next(sketch), SyntaxTree::Next(formula))
+            | (Sketch::Globally(sketch), SyntaxTree::Globally(formula))
+            | (Sketch::Finally(sketch), SyntaxTree::Finally(formula)) => branch(sketch, formula),
+            (Sketch::And(left_sketch, right_sketch), SyntaxTree::And(left, right))
+            | (Sketch::Or(left_sketch, right_sketch), SyntaxTree::Or(left, right))
+            | (Sketch::Implies(left_sketch, right_sketch), SyntaxTree::Implies(left, right))
+            | (Sketch::Until(left_sketch, right_sketch), SyntaxTree::Until(left, right)) => {
+                branch(left_sketch, left) && branch(right_sketch, right)
+            }
+            _ => false,
+        }
+    }
+
+    /// A random formula of the fragment no larger than the maximum size, with variables among the given ones,
+    /// or `None` if there is none.
+    pub fn generate<R: Rng + ?Sized>(
+        &self,
+        max_size: usize,
+        vars: &[Idx],
+        rng: &mut R,
+    ) -> Option<SyntaxTree> {
+        self.generate_from(0, max_size, vars, rng)
+    }
+
+    fn generate_from<R: Rng + ?Sized>(
+        &self,
+        nonterminal: usize,
+        max_size: usize,
+        vars: &[Idx],
+        rng: &mut R,
+    ) -> Option<SyntaxTree> {
+        let productions = self.productions[nonterminal]
+            .iter()
+            .filter(|production| {
+                min_size(production, &self.min_sizes) <= max_size
+                    && (**production != Production::Var || !vars.is_empty())
+            })
+            .collect::<Vec<_>>();
+        if productions.is_empty() {
+            return None;
+        }
+        match productions[rng.gen_range(0..productions.len())] {
+            Production::Var => Some(SyntaxTree::Atom(vars[rng.gen_range(0..vars.len())])),
+            production @ Production::Shape(sketch, holes) => {
+                let mut spare = max_size - min_size(production, &self.min_sizes);
+                self.expand(sketch, holes, &mut spare, vars, rng)
+            }
+        }
+    }
+
+    /// Fills the holes of the sketch with random derivations, each taking a random part of the spare size.
+    fn expand<R: Rng + ?Sized>(
+        &self,
+        sketch: &Sketch,
+        holes: &[usize],
+        spare: &mut usize,
+        vars: &[Idx],
+        rng: &mut R,
+    ) -> Option<SyntaxTree> {
+        if let Sketch::Hole(hole) = sketch {
+            let min_size = self.min_sizes[holes[*hole]];
+            let max_size = min_size + rng.gen_range(0..=*spare);
+            let formula = self.generate_from(holes[*hole], max_size, vars, rng)?;
+            *spare -= formula.size() - min_size;
+            return Some(formula);
+        }
+
+        let mut expand =
+            |branch: &Sketch| self.expand(branch, holes, spare, vars, rng).map(Arc::new);
+        Some(match sketch {
+            Sketch::Hole(_) => unreachable!("holes are filled above"),
+            Sketch::Atom(var) => SyntaxTree::Atom(*var),
+            Sketch::Not(branch) => SyntaxTree::Not(expand(branch)?),
+            Sketch::Next(branch) => SyntaxTree::Next(expand(branch)?),
+            Sketch::Globally(branch) => SyntaxTree::Globally(expand(branch)?),
+            Sketch::Finally(branch) => SyntaxTree::Finally(expand(branch)?),
+            Sketch::And(left, right) => SyntaxTree::And(expand(left)?, expand(right)?),
+            Sketch::Or(left, right) => SyntaxTree::Or(expand(left)?, expand(right)?),
+            Sketch::Implies(left, right) => SyntaxTree::Implies(expand(left)?, expand(right)?),
+            Sketch::Until(left, right) => SyntaxTree::Until(expand(left)?, expand(right)?),
+        })
+    }
+
+    /// Breeds two offspring from two parents of the fragment by swapping random subformulas,
+    /// such that both offspring are in the fragment, or `None` if no such swap was found.
+    pub fn crossover<R: Rng + ?Sized>(
+        &self,
+        parent1: &SyntaxTree,
+        parent2: &SyntaxTree,
+        rng: &mut R,
+    ) -> Option<(SyntaxTree, SyntaxTree)> {
+        (0..ATTEMPTS).find_map(|_| {
+            let index1 = rng.gen_range(0..parent1.size());
+            let index2 = rng.gen_range(0..parent2.size());
+            let branch1 = parent1.subformula(index1);
+            let branch2 = parent2.subformula(index2);
+            let offspring1 = parent1.replace_subformula(index1, &mut |_| branch2.clone());
+            let offspring2 = parent2.replace_subformula(index2, &mut |_| branch1.clone());
+            (self.contains(&offspring1) && self.contains(&offspring2))
+                .then_some((offspring1, offspring2))
+        })
+    }
+
+    /// Mutates a formula of the fragment by replacing a random subformula with a random derivation
+    /// of a nonterminal deriving it, at most two nodes larger and with variables among the given ones,
+    /// such that the mutant is in the fragment.
+    /// The formula is left unchanged if no such replacement was found.
+    pub fn mutate<R: Rng + ?Sized>(
+        &self,
+        formula: &SyntaxTree,
+        vars: &[Idx],
+        rng: &mut R,
+    ) -> SyntaxTree {
+        (0..ATTEMPTS)
+            .find_map(|_| {
+                let index = rng.gen_range(0..formula.size());
+                let subformula = formula.subformula(index);
+                let nonterminals = (0..self.nonterminals.len())
+                    .filter(|nonterminal| {
+                        self.derives(*nonterminal, subformula, self.nonterminals.len())
+                    })
+                    .collect::<Vec<_>>();
+                if nonterminals.is_empty() {
+                    return None;
+                }
+                let nonterminal = nonterminals[rng.gen_range(0..nonterminals.len())];
+                let replacement =
+                    self.generate_from(nonterminal, subformula.size() + 2, vars, rng)?;
+                let mutant = formula.replace_subformula(index, &mut |_| replacement.clone());
+                self.contains(&mutant).then_some(mutant)
+            })
+            .unwrap_or_else(|| formula.clone())
+    }
+}
+
+/// The size of the smallest formula derived by the production, given those of the nonterminals.
+fn min_size(production: &Production, min_sizes: &[usize]) -> usize {
+    fn sketch_size(sketch: &Sketch, holes: &[usize], min_sizes: &[usize]) -> usize {
+        let size = |branch| sketch_size(branch, holes, min_sizes);
+        match sketch {
+            Sketch::Hole(hole) => min_sizes[holes[*hole]],
+            Sketch::Atom(_) => 1,
+            Sketch::Not(branch)
+            | Sketch::Next(branch)
+            | Sketch::Globally(branch)
+            | Sketch::Finally(branch) => size(branch).saturating_add(1),
+            Sketch::And(left, right)
+            | Sketch::Or(left, right)
+            | Sketch::Implies(left, right)
+            | Sketch::Until(left, right) => {
+                size(left).saturating_add(size(right)).saturating_add(1)
+            }
+        }
+    }
+
+    match production {
+        Production::Var => 1,
+        Production::Shape(sketch, holes) => sketch_size(sketch, holes, min_sizes),
+    }
+}
+
+/// Parses grammars whose rules refer to variables by index (see [`Grammar::parse_w_named_vars`]).
+impl FromStr for Grammar {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Grammar::parse_w_named_vars(s, &[])
+    }
+}
+
+#[cfg(test)]
+mod grammar_guided {
+    use super::*;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    #[test]
+    fn fragment() {
+        let grammar = "spec := G ?response; spec := ?spec & ?spec; response := ?atom -> F ?atom"
+            .parse::<Grammar>()
+            .expect("grammar");
+        assert_eq!(grammar.min_size(), 5);
+        let response = |p, q| {
+            SyntaxTree::Atom(p)
+                .implies(SyntaxTree::Atom(q).finally())
+                .globally()
+        };
+        assert!(grammar.contains(&response(0, 1)));
+        assert!(grammar.contains(&response(0, 1).and(response(1, 0))));
+        assert!(!grammar.contains(&SyntaxTree::Atom(0).finally().globally()));
+
+        let mut rng = StdRng::seed_from_u64(0);
+        let parent1 = response(0, 1).and(response(1, 1));
+        let parent2 = response(1, 0);
+        for _ in 0..20 {
+            let formula = grammar.generate(11, &[0, 1], &mut rng).expect("formula");
+            assert!(formula.size() <= 11);
+            assert!(grammar.contains(&formula));
+            assert!(grammar.contains(&grammar.mutate(&formula, &[0, 1], &mut rng)));
+            if let Some((offspring1, offspring2)) = grammar.crossover(&parent1, &parent2, &mut rng)
+            {
+                assert!(grammar.contains(&offspring1));
+                assert!(grammar.contains(&offspring2));
+            }
+        }
+
+        assert!("spec := G ?prop".parse::<Grammar>().is_err());
+        assert!("spec := ?spec & ?spec".parse::<Grammar>().is_err());
+    }
+}
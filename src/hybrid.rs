@@ -0,0 +1,187 @@
+use crate::genetic::*;
+use crate::learn::*;
+use crate::learner::*;
+use crate::source::*;
+use crate::syntax::*;
+use crate::trace::*;
+use rand::Rng;
+use std::sync::Arc;
+use std::time::Instant;
+
+/// Learns formulas in two phases: first, all the formulas up to a given size are enumerated,
+/// which finds a smallest consistent formula if there is one;
+/// otherwise, they are the building blocks that a genetic algorithm combines with binary operators,
+/// keeping the fittest combinations in each generation (see [`GeneticLearner`] for its parameters).
+#[derive(Debug, Clone, PartialEq)]
+pub struct HybridLearner {
+    /// The maximum size of the building blocks.
+    pub block_size: usize,
+    /// The configuration of the genetic algorithm; its initial size is unused.
+    pub genetic: GeneticLearner,
+}
+
+impl Default for HybridLearner {
+    fn default() -> Self {
+        HybridLearner::new(2)
+    }
+}
+
+impl HybridLearner {
+    /// A learner combining the formulas up to the given size, with the default genetic algorithm.
+    pub fn new(block_size: usize) -> Self {
+        HybridLearner {
+            block_size,
+            genetic: GeneticLearner::default(),
+        }
+    }
+
+    pub fn genetic(mut self, genetic: GeneticLearner) -> Self {
+        self.genetic = genetic;
+        self
+    }
+
+    /// The first phase: all the formulas up to the block size, smallest first, followed by the seeds.
+    pub fn blocks<const N: usize, S: SampleSource<N>>(&self, sample: &S) -> Vec<SyntaxTree> {
+        let vars = self.genetic.vars.clone().unwrap_or_else(|| sample.vars());
+        let mut blocks = (1..=self.block_size)
+            .flat_map(|size| gen_formulae_iter::<N>(size, &vars))
+            .collect::<Vec<_>>();
+        blocks.extend(self.genetic.seeds.iter().cloned());
+        blocks
+    }
+
+    /// The population followed by combinations of random formulas of the population with random blocks,
+    /// under a random binary operator.
+    pub fn combine<R: Rng + ?Sized>(
+        &self,
+        population: &[SyntaxTree],
+        blocks: &[SyntaxTree],
+        rng: &mut R,
+    ) -> Vec<SyntaxTree> {
+        let mut combined = population.to_vec();
+        if population.is_empty() || blocks.is_empty() {
+            return combined;
+        }
+        for _ in 0..population.len() {
+            let formula = Arc::new(population[rng.gen_range(0..population.len())].clone());
+            let block = Arc::new(blocks[rng.gen_range(0..blocks.len())].clone());
+            let (left, right) = if rng.gen() {
+                (formula, block)
+            } else {
+                (block, formula)
+            };
+            let combination = match rng.gen_range(0..4) {
+                0 => SyntaxTree::And(left, right),
+                1 => SyntaxTree::Or(left, right),
+                2 => SyntaxTree::Implies(left, right),
+                _ => SyntaxTree::Until(left, right),
+            };
+            if !combined.contains(&combination) {
+                combined.push(combination);
+            }
+        }
+        combined
+    }
+
+    /// The second phase: evolves combinations of the blocks for the configured number of generations,
+    /// or until the deadline or the genetic algorithm stops early (see [`GeneticLearner::stops`]),
+    /// returning the surviving formulas with their fitness, fittest first.
+    pub fn evolve<const N: usize, S: SampleSource<N>, R: Rng + ?Sized>(
+        &self,
+        sample: &S,
+        blocks: &[SyntaxTree],
+        deadline: Option<Instant>,
+        rng: &mut R,
+    ) -> Vec<(SyntaxTree, Fitness)> {
+        let mut cache = FitnessCache::default();
+        let mut ranked = self.genetic.rank_cached(blocks, sample, &mut cache);
+        let mut population = self.genetic.select(&ranked);
+        let mut stagnation = Stagnation::default();
+        for _ in 0..self.genetic.iterations {
+            if deadline.is_some_and(|deadline| Instant::now() >= deadline)
+                || self.genetic.stops(&ranked, sample, &mut stagnation)
+            {
+                break;
+            }
+            let combined = self.combine(&population, blocks, rng);
+            ranked = self.genetic.rank_cached(&combined, sample, &mut cache);
+            population = self.genetic.select(&ranked);
+        }
+        ranked
+    }
+}
+
+impl<const N: usize> Learner<N> for HybridLearner {
+    /// Returns the first consistent block, if any, which is then a smallest consistent formula;
+    /// otherwise, the fittest combination no larger than the maximum size.
+    fn learn(&self, sample: &Sample<N>, budget: Budget) -> LearnResult {
+        if !sample.is_solvable() {
+            return LearnResult::Unsolvable;
+        }
+
+        let deadline = budget.deadline();
+        let blocks = self.blocks(sample);
+        let fits = |formula: &SyntaxTree| budget.max_size.is_none_or(|max| formula.size() <= max);
+        if let Some(block) = blocks
+            .iter()
+            .find(|block| fits(block) && sample.is_consistent(block))
+        {
+            return LearnResult::Consistent(block.clone());
+        }
+
+        let best = self
+            .evolve(sample, &blocks, deadline, &mut self.genetic.rng())
+            .into_iter()
+            .map(|(formula, _)| formula)
+            .find(fits);
+        match best {
+            Some(formula) if sample.is_consistent(&formula) => LearnResult::Consistent(formula),
+            Some(formula) => LearnResult::Approximate(formula),
+            None => LearnResult::Exhausted,
+        }
+    }
+}
+
+#[cfg(test)]
+mod hybrid_learning {
+    use super::*;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    #[test]
+    fn learn() {
+        // Positive traces are those where x0 holds at first and x1 holds eventually,
+        // which no formula of size up to 2 expresses.
+        let sample = Sample::<2> {
+            positive_traces: vec![
+                vec![[true, false], [false, true]],
+                vec![[true, true]],
+                vec![[true, false], [false, false], [true, true]],
+            ],
+            negative_traces: vec![
+                vec![[false, true], [true, false]],
+                vec![[true, false], [true, false]],
+                vec![[false, false]],
+            ],
+            ..Sample::default()
+        };
+        let learner = HybridLearner::new(2).genetic(GeneticLearner::default().iterations(5));
+        let blocks = learner.blocks(&sample);
+        assert!(!blocks.iter().any(|block| sample.is_consistent(block)));
+
+        let mut rng = StdRng::seed_from_u64(0);
+        let ranked = learner.evolve(&sample, &blocks, None, &mut rng);
+        assert!(ranked
+            .windows(2)
+            .all(|pair| pair[0].1.score() >= pair[1].1.score()));
+        // Combinations of blocks survive.
+        assert!(ranked.iter().any(|(formula, _)| formula.size() > 2));
+
+        // The first phase is enough for simple samples.
+        let simple = Sample {
+            negative_traces: vec![vec![[false, false]]],
+            ..sample
+        };
+        let result = learner.learn(&simple, Budget::default());
+        assert_eq!(result, LearnResult::Consistent(SyntaxTree::Atom(0)));
+    }
+}
@@ -0,0 +1,268 @@
+use crate::builder::*;
+use crate::learn::*;
+use crate::metadata::*;
+use crate::syntax::*;
+use crate::trace::*;
+
+/// The number of traces of each kind of a sample at some point in time,
+/// used to find out which traces have been added since then (see [`Sample::push_positive`]).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Checkpoint {
+    pub positive_traces: usize,
+    pub negative_traces: usize,
+    pub positive_lassos: usize,
+    pub negative_lassos: usize,
+}
+
+impl<const N: usize> Sample<N> {
+    /// Appends a positive trace, returning whether it was added (`false` if it was already in the sample).
+    /// Fails if the trace is already among the negative ones.
+    ///
+    /// Traces are only ever appended, so a learner holding a [`Checkpoint`] taken earlier
+    /// can tell whether the sample changed ([`Sample::changed_since`]),
+    /// and only re-check the traces added since ([`Sample::is_consistent_since`]).
+    /// The contract only holds as long as traces are not removed or reordered by other means
+    /// (e.g., [`Sample::dedup`], or by modifying the fields directly).
+    pub fn push_positive(&mut self, trace: Trace<N>) -> Result<bool, SampleError> {
+        if let Some(negative) = self.negative_traces.iter().position(|neg| *neg == trace) {
+            return Err(SampleError::Contradiction {
+                positive: self.positive_traces.len(),
+                negative,
+            });
+        }
+        if self.positive_traces.contains(&trace) {
+            return Ok(false);
+        }
+        self.positive_traces.push(trace);
+        Ok(true)
+    }
+
+    /// Appends a negative trace, returning whether it was added (`false` if it was already in the sample).
+    /// Fails if the trace is already among the positive ones.
+    /// See [`Sample::push_positive`] for how to observe the changes.
+    pub fn push_negative(&mut self, trace: Trace<N>) -> Result<bool, SampleError> {
+        if let Some(positive) = self.positive_traces.iter().position(|pos| *pos == trace) {
+            return Err(SampleError::Contradiction {
+                positive,
+                negative: self.negative_traces.len(),
+            });
+        }
+        if self.negative_traces.contains(&trace) {
+            return Ok(false);
+        }
+        self.negative_traces.push(trace);
+        Ok(true)
+    }
+
+    /// Records the current number of traces of each kind.
+    pub fn checkpoint(&self) -> Checkpoint {
+        Checkpoint {
+            positive_traces: self.positive_traces.len(),
+            negative_traces: self.negative_traces.len(),
+            positive_lassos: self.positive_lassos.len(),
+            negative_lassos: self.negative_lassos.len(),
+        }
+    }
+
+    /// Whether traces have been added (or removed) since the checkpoint was taken.
+    pub fn changed_since(&self, checkpoint: &Checkpoint) -> bool {
+        self.checkpoint() != *checkpoint
+    }
+
+    /// Returns a sample with only the traces added since the checkpoint was taken.
+    pub fn added_since(&self, checkpoint: &Checkpoint) -> Sample<N> {
+        Sample {
+            var_names: self.var_names.clone(),
+            positive_traces: since(&self.positive_traces, checkpoint.positive_traces).to_vec(),
+            negative_traces: since(&self.negative_traces, checkpoint.negative_traces).to_vec(),
+            positive_lassos: since(&self.positive_lassos, checkpoint.positive_lassos).to_vec(),
+            negative_lassos: since(&self.negative_lassos, checkpoint.negative_lassos).to_vec(),
+            positive_metadata: reindex(
+                &self.positive_metadata,
+                checkpoint.positive_traces..self.positive_traces.len(),
+            ),
+            negative_metadata: reindex(
+                &self.negative_metadata,
+                checkpoint.negative_traces..self.negative_traces.len(),
+            ),
+            length_policy: self.length_policy,
+            ..Sample::default()
+        }
+    }
+
+    /// Checks the formula only on the traces added since the checkpoint was taken,
+    /// assuming it was already consistent with the sample at that time.
+    pub fn is_consistent_since(&self, formula: &SyntaxTree, checkpoint: &Checkpoint) -> bool {
+        since(&self.positive_traces, checkpoint.positive_traces)
+            .iter()
+            .all(|trace| formula.eval(trace))
+            && since(&self.negative_traces, checkpoint.negative_traces)
+                .iter()
+                .all(|trace| !formula.eval(trace))
+            && since(&self.positive_lassos, checkpoint.positive_lassos)
+                .iter()
+                .all(|lasso| formula.eval_lasso(lasso))
+            && since(&self.negative_lassos, checkpoint.negative_lassos)
+                .iter()
+                .all(|lasso| !formula.eval_lasso(lasso))
+    }
+}
+
+/// The items from the given index onward (none, if there are fewer).
+fn since<T>(items: &[T], from: usize) -> &[T] {
+    items.get(from..).unwrap_or_default()
+}
+
+/// Relearns a smallest consistent formula as traces are added to a sample (see [`Sample::push_positive`]),
+/// without restarting the brute-force search of [`solve`] from scratch.
+///
+/// Adding traces can only make formulas inconsistent, so the previous formula is checked first,
+/// on the added traces only; if it no longer fits, the search resumes right after it,
+/// among the formulas of the same size which are kept from the previous search.
+/// The search starts over only if the variables of the sample change.
+/// The learner must always be given the same sample, which is only ever appended to.
+#[derive(Debug, Clone, Default)]
+pub struct IncrementalLearner {
+    max_size: Option<usize>,
+    formula: Option<SyntaxTree>,
+    checkpoint: Checkpoint,
+    vars: Vec<Idx>,
+    /// The size of the formulas searched.
+    size: usize,
+    /// The formulas of the searched size, if already generated, and the position of the next one to check.
+    formulas: Vec<SyntaxTree>,
+    next: usize,
+}
+
+impl IncrementalLearner {
+    pub fn new() -> Self {
+        IncrementalLearner::default()
+    }
+
+    /// Limits the size of the formulas searched, so that the search ends even if no formula fits.
+    pub fn max_size(mut self, max_size: usize) -> Self {
+        self.max_size = Some(max_size);
+        self
+    }
+
+    /// The last formula learned, if any.
+    pub fn formula(&self) -> Option<&SyntaxTree> {
+        self.formula.as_ref()
+    }
+
+    /// The size of the formulas the search is at, which only decreases when the variables change.
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Learns a smallest formula consistent with the sample, taking advantage of the previous searches.
+    /// Returns `None` if no formula (up to the maximum size) is consistent with the sample.
+    pub fn learn<const N: usize>(&mut self, sample: &Sample<N>) -> Option<&SyntaxTree> {
+        let checkpoint = std::mem::replace(&mut self.checkpoint, sample.checkpoint());
+        if self
+            .formula
+            .as_ref()
+            .is_some_and(|formula| sample.is_consistent_since(formula, &checkpoint))
+        {
+            return self.formula.as_ref();
+        }
+        self.formula = None;
+
+        let vars = sample.vars();
+        if vars != self.vars || self.size == 0 {
+            self.vars = vars;
+            self.size = 1;
+            self.formulas.clear();
+            self.next = 0;
+        }
+        if !sample.is_solvable() {
+            return None;
+        }
+
+        while self.max_size.is_none_or(|max| self.size <= max) {
+            if self.formulas.is_empty() {
+                self.formulas = gen_formulae::<N>(self.size, &self.vars);
+                self.next = 0;
+            }
+            if let Some(position) = self.formulas[self.next..]
+                .iter()
+                .position(|formula| sample.is_consistent(formula))
+            {
+                self.next += position;
+                self.formula = Some(self.formulas[self.next].clone());
+                return self.formula.as_ref();
+            }
+            self.size += 1;
+            self.formulas.clear();
+        }
+
+        None
+    }
+}
+
+#[cfg(test)]
+mod incremental_updates {
+    use super::*;
+
+    #[test]
+    fn push() {
+        let mut sample = Sample::<1>::default();
+        assert_eq!(sample.push_positive(vec![[true]]), Ok(true));
+        assert_eq!(sample.push_positive(vec![[true]]), Ok(false));
+        assert_eq!(
+            sample.push_negative(vec![[true]]),
+            Err(SampleError::Contradiction {
+                positive: 0,
+                negative: 0
+            })
+        );
+        assert_eq!(sample.push_negative(vec![[false]]), Ok(true));
+        assert_eq!(sample.positive_traces(), 1);
+        assert_eq!(sample.negative_traces(), 1);
+    }
+
+    #[test]
+    fn checkpoint() {
+        let mut sample = Sample::<1>::default();
+        sample.push_positive(vec![[true]]).expect("push trace");
+        let checkpoint = sample.checkpoint();
+        assert!(!sample.changed_since(&checkpoint));
+
+        sample
+            .push_negative(vec![[true], [false]])
+            .expect("push trace");
+        assert!(sample.changed_since(&checkpoint));
+        assert_eq!(
+            sample.added_since(&checkpoint).negative_traces,
+            vec![vec![[true], [false]]]
+        );
+        // x0 holds on the new negative trace.
+        assert!(!sample.is_consistent_since(&SyntaxTree::Atom(0), &checkpoint));
+        assert!(sample.is_consistent_since(&!SyntaxTree::Atom(0), &checkpoint));
+    }
+
+    #[test]
+    fn relearn() {
+        let mut sample = Sample::<2> {
+            positive_traces: vec![vec![[true, false], [true, true]]],
+            negative_traces: vec![vec![[false, true]]],
+            ..Sample::default()
+        };
+        let mut learner = IncrementalLearner::new().max_size(4);
+        assert_eq!(learner.learn(&sample), Some(&SyntaxTree::Atom(0)));
+
+        // The formula still fits, so no search is needed.
+        sample
+            .push_negative(vec![[false, false]])
+            .expect("push trace");
+        assert_eq!(learner.learn(&sample), Some(&SyntaxTree::Atom(0)));
+
+        // Resuming the search finds the same formula as searching from scratch.
+        sample
+            .push_negative(vec![[true, false], [false, true]])
+            .expect("push trace");
+        let formula = learner.learn(&sample).cloned();
+        assert_eq!(formula, solve(&sample, false, false));
+        assert_eq!(learner.size(), formula.expect("some formula").size());
+    }
+}
@@ -0,0 +1,292 @@
+use crate::load::*;
+use crate::syntax::*;
+use crate::trace::*;
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// Summary of a single trace of an indexed sample file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IndexEntry {
+    /// The sample file containing the trace.
+    pub file: PathBuf,
+    /// The label of the trace in the sample file.
+    pub label: Label,
+    /// The position of the trace among the traces of the same label in the sample file.
+    pub position: usize,
+    /// The length of the trace.
+    pub length: usize,
+    /// For each propositional variable, the number of time instants in which it is true.
+    pub atom_counts: Vec<usize>,
+    /// A hash of the content of the trace.
+    pub hash: u64,
+}
+
+impl IndexEntry {
+    /// Whether the given propositional variable is true at least once along the trace.
+    pub fn mentions(&self, atom: Idx) -> bool {
+        matches!(self.atom_counts.get(atom as usize), Some(count) if *count > 0)
+    }
+}
+
+/// Fingerprint of an indexed file, used to detect whether it has to be indexed again.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct FileRecord {
+    path: PathBuf,
+    size: u64,
+    modified: Option<SystemTime>,
+}
+
+impl FileRecord {
+    fn new(path: &Path) -> io::Result<Self> {
+        let metadata = fs::metadata(path)?;
+        Ok(FileRecord {
+            path: path.to_path_buf(),
+            size: metadata.len(),
+            modified: metadata.modified().ok(),
+        })
+    }
+}
+
+/// A lightweight index over a directory of sample files (in `ron` or `json` format),
+/// recording statistics about each of their traces.
+/// The index is stored in the directory itself, and only files that changed since the last scan are read again.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TraceIndex {
+    files: Vec<FileRecord>,
+    pub entries: Vec<IndexEntry>,
+}
+
+impl TraceIndex {
+    /// The name of the file storing the index inside the indexed directory.
+    pub const FILE_NAME: &'static str = ".trace_index.ron";
+
+    /// Opens the index of the given directory, updating it if sample files were added, changed or removed,
+    /// or builds it from scratch if it does not exist yet.
+    /// Sample files are parsed as samples over `N` variables; files that cannot be parsed are skipped.
+    pub fn open<const N: usize>(dir: &Path) -> io::Result<Self> {
+        let index_path = dir.join(Self::FILE_NAME);
+        let mut index = if index_path.exists() {
+            Self::load(&index_path)?
+        } else {
+            TraceIndex::default()
+        };
+        if index.update::<N>(dir)? {
+            index.save(&index_path)?;
+        }
+        Ok(index)
+    }
+
+    /// Loads an index from file.
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let contents = fs::read(path)?;
+        ron::de::from_bytes(&contents)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+    }
+
+    /// Saves the index to file.
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let contents = ron::ser::to_string(self)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+        fs::write(path, contents)
+    }
+
+    /// Scans the directory and re-indexes the sample files that changed since the last scan.
+    /// Returns whether the index has been modified.
+    pub fn update<const N: usize>(&mut self, dir: &Path) -> io::Result<bool> {
+        let mut records = Vec::new();
+        for dir_entry in fs::read_dir(dir)? {
+            let path = dir_entry?.path();
+            if path.is_file()
+                && path.file_name() != Some(Self::FILE_NAME.as_ref())
+                && SampleFormat::from_path(&path).is_some()
+            {
+                records.push(FileRecord::new(&path)?);
+            }
+        }
+        records.sort_by(|a, b| a.path.cmp(&b.path));
+
+        let mut modified = false;
+
+        // Drop entries of files that have been removed or changed.
+        let stale = self
+            .files
+            .iter()
+            .filter(|record| !records.contains(record))
+            .map(|record| record.path.clone())
+            .collect::<Vec<_>>();
+        if !stale.is_empty() {
+            self.entries.retain(|entry| !stale.contains(&entry.file));
+            self.files.retain(|record| !stale.contains(&record.path));
+            modified = true;
+        }
+
+        // Index new or changed files.
+        for record in records {
+            if !self.files.contains(&record) {
+                if let Ok(sample) = Sample::<N>::load(&record.path) {
+                    self.entries.extend(index_sample(&record.path, &sample));
+                }
+                self.files.push(record);
+                modified = true;
+            }
+        }
+
+        Ok(modified)
+    }
+
+    /// Starts a query over the indexed traces.
+    pub fn query(&self) -> Query<'_> {
+        Query {
+            index: self,
+            filters: Vec::new(),
+        }
+    }
+}
+
+fn index_sample<const N: usize>(file: &Path, sample: &Sample<N>) -> Vec<IndexEntry> {
+    let entry = |label: Label, position: usize, trace: &Trace<N>| IndexEntry {
+        file: file.to_path_buf(),
+        label,
+        position,
+        length: trace.len(),
+        atom_counts: (0..N)
+            .map(|n| trace.iter().filter(|state| state[n]).count())
+            .collect(),
+        hash: trace_hash(trace),
+    };
+
+    sample
+        .positive_traces
+        .iter()
+        .enumerate()
+        .map(|(position, trace)| entry(Label::Positive, position, trace))
+        .chain(
+            sample
+                .negative_traces
+                .iter()
+                .enumerate()
+                .map(|(position, trace)| entry(Label::Negative, position, trace)),
+        )
+        .collect()
+}
+
+/// FNV-1a hash of the trace content, which (unlike `DefaultHasher`) is stable across Rust versions.
+pub(crate) fn trace_hash<const N: usize>(trace: &[[bool; N]]) -> u64 {
+    const OFFSET: u64 = 0xcbf29ce484222325;
+    const PRIME: u64 = 0x100000001b3;
+
+    trace
+        .iter()
+        .flat_map(|state| state.iter().map(|val| *val as u8).chain(std::iter::once(2)))
+        .fold(OFFSET, |hash, byte| {
+            (hash ^ byte as u64).wrapping_mul(PRIME)
+        })
+}
+
+type Filter<'a> = Box<dyn Fn(&IndexEntry) -> bool + 'a>;
+
+/// A query over the traces of a [`TraceIndex`], built by chaining filters.
+pub struct Query<'a> {
+    index: &'a TraceIndex,
+    filters: Vec<Filter<'a>>,
+}
+
+impl<'a> Query<'a> {
+    /// Keeps only the traces satisfying the given predicate.
+    pub fn filter(mut self, filter: impl Fn(&IndexEntry) -> bool + 'a) -> Self {
+        self.filters.push(Box::new(filter));
+        self
+    }
+
+    /// Keeps only the traces with the given label.
+    pub fn label(self, label: Label) -> Self {
+        self.filter(move |entry| entry.label == label)
+    }
+
+    /// Keeps only the traces at least as long as the given length.
+    pub fn min_length(self, length: usize) -> Self {
+        self.filter(move |entry| entry.length >= length)
+    }
+
+    /// Keeps only the traces at most as long as the given length.
+    pub fn max_length(self, length: usize) -> Self {
+        self.filter(move |entry| entry.length <= length)
+    }
+
+    /// Keeps only the traces in which the given propositional variable is true at least once.
+    pub fn mentions(self, atom: Idx) -> Self {
+        self.filter(move |entry| entry.mentions(atom))
+    }
+
+    /// Iterates over the index entries matching the query.
+    pub fn iter(&self) -> impl Iterator<Item = &'a IndexEntry> + '_ {
+        self.index
+            .entries
+            .iter()
+            .filter(|entry| self.filters.iter().all(|filter| filter(entry)))
+    }
+
+    /// Builds a sample out of the traces matching the query, reading only the sample files containing them.
+    pub fn collect_sample<const N: usize>(&self) -> io::Result<Sample<N>> {
+        let mut entries = self.iter().collect::<Vec<_>>();
+        entries.sort_by(|a, b| a.file.cmp(&b.file));
+
+        let mut sample = Sample::default();
+        for (file, entries) in &entries.into_iter().group_by(|entry| &entry.file) {
+            let file_sample = Sample::<N>::load(file)?;
+            for entry in entries {
+                let traces = match entry.label {
+                    Label::Positive => &file_sample.positive_traces,
+                    Label::Negative => &file_sample.negative_traces,
+                };
+                let trace = traces.get(entry.position).cloned().ok_or_else(|| {
+                    io::Error::new(io::ErrorKind::InvalidData, "stale index entry")
+                })?;
+                match entry.label {
+                    Label::Positive => sample.positive_traces.push(trace),
+                    Label::Negative => sample.negative_traces.push(trace),
+                }
+            }
+        }
+        Ok(sample)
+    }
+}
+
+#[cfg(test)]
+mod trace_index {
+    use super::*;
+
+    #[test]
+    fn query() {
+        let dir = std::env::temp_dir().join(format!("learn_ltl_index_{}", std::process::id()));
+        fs::create_dir_all(&dir).expect("create directory");
+
+        let sample = Sample::<2> {
+            var_names: ["x0".to_string(), "x1".to_string()],
+            positive_traces: vec![vec![[true, false]; 3], vec![[false, true]]],
+            negative_traces: vec![vec![[false, false]; 4]],
+            ..Sample::default()
+        };
+        let contents = ron::ser::to_string(&sample).expect("serialize sample");
+        fs::write(dir.join("sample.ron"), contents).expect("write sample");
+
+        let index = TraceIndex::open::<2>(&dir).expect("open index");
+        assert_eq!(index.entries.len(), 3);
+        assert!(dir.join(TraceIndex::FILE_NAME).exists());
+
+        let query = index.query().min_length(2).mentions(0);
+        assert_eq!(query.iter().count(), 1);
+        let collected = query.collect_sample::<2>().expect("collect sample");
+        assert_eq!(collected.positive_traces, vec![vec![[true, false]; 3]]);
+
+        // Nothing changed, so the index is not modified.
+        let mut index = TraceIndex::open::<2>(&dir).expect("open index");
+        assert!(!index.update::<2>(&dir).expect("update index"));
+
+        fs::remove_dir_all(&dir).expect("remove directory");
+    }
+}
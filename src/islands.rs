@@ -0,0 +1,349 @@
+use crate::genetic::*;
+use crate::learner::*;
+use crate::source::*;
+use crate::syntax::*;
+use crate::trace::*;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use std::collections::HashSet;
+use std::time::Instant;
+
+/// A subpopulation of the island model, with its own fitness cache, mutation schedule and random number generator,
+/// so that islands evolve independently.
+struct Island {
+    ranked: Vec<(SyntaxTree, Fitness)>,
+    cache: FitnessCache,
+    schedule: MutationSchedule,
+    stagnation: Stagnation,
+    stopped: bool,
+    rng: StdRng,
+}
+
+/// Learns formulas with a genetic algorithm over several subpopulations, or islands,
+/// which evolve independently on separate threads (see [`GeneticLearner`] for their parameters),
+/// and every few generations send copies of their fittest formulas to the next island, in a ring.
+///
+/// Islands evolving apart keep the population more diverse than a single large one,
+/// while migrations spread good formulas, and each island runs on its own thread.
+#[derive(Debug, Clone, PartialEq)]
+pub struct IslandModel {
+    /// The configuration of the genetic algorithm of each island.
+    pub genetic: GeneticLearner,
+    /// The number of islands.
+    pub islands: usize,
+    /// The number of generations between migrations.
+    pub interval: usize,
+    /// The number of formulas each island sends to the next one at each migration.
+    pub migrants: usize,
+}
+
+impl Default for IslandModel {
+    fn default() -> Self {
+        IslandModel::new(4)
+    }
+}
+
+impl IslandModel {
+    /// A model of the given number of islands with the default genetic algorithm,
+    /// each sending its 2 fittest formulas to the next one every 5 generations.
+    pub fn new(islands: usize) -> Self {
+        IslandModel {
+            genetic: GeneticLearner::default(),
+            islands,
+            interval: 5,
+            migrants: 2,
+        }
+    }
+
+    pub fn genetic(mut self, genetic: GeneticLearner) -> Self {
+        self.genetic = genetic;
+        self
+    }
+
+    pub fn interval(mut self, interval: usize) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    pub fn migrants(mut self, migrants: usize) -> Self {
+        self.migrants = migrants;
+        self
+    }
+
+    /// Deals the population out to the islands, and evolves them for the configured number of generations
+    /// of the genetic algorithm, or until the deadline, migrating formulas between them at the configured interval.
+    /// Each island stops early on its own (see [`GeneticLearner::stops`]), and all of them as soon as one has a consistent
+    /// fittest formula, unless [`GeneticLearner::stop_when_consistent`] is unset.
+    /// Returns the surviving formulas of all islands with their fitness, fittest first, without duplicates.
+    ///
+    /// Islands draw their seeds from the given random number generator, so runs are reproducible with a seeded one.
+    /// Each island evaluates formulas through its own cache, starting from the evaluations of its formulas in the given one,
+    /// and migrants bring their evaluations along, so that each distinct formula is evaluated once per island at most;
+    /// the given cache gets the evaluations of all islands in the end.
+    pub fn evolve<const N: usize, S: SampleSource<N> + Sync, R: Rng + ?Sized>(
+        &self,
+        sample: &S,
+        population: Vec<SyntaxTree>,
+        cache: &mut FitnessCache,
+        deadline: Option<Instant>,
+        rng: &mut R,
+    ) -> Vec<(SyntaxTree, Fitness)> {
+        self.evolve_observed(sample, population, cache, deadline, rng, |_, _| {})
+    }
+
+    /// Evolves the islands like [`IslandModel::evolve`], calling back after every generation, numbered from 1,
+    /// with the surviving formulas of all islands, fittest first, without duplicates, e.g., to log statistics of the run.
+    pub fn evolve_observed<const N: usize, S, R, F>(
+        &self,
+        sample: &S,
+        population: Vec<SyntaxTree>,
+        cache: &mut FitnessCache,
+        deadline: Option<Instant>,
+        rng: &mut R,
+        mut on_generation: F,
+    ) -> Vec<(SyntaxTree, Fitness)>
+    where
+        S: SampleSource<N> + Sync,
+        R: Rng + ?Sized,
+        F: FnMut(usize, &[(SyntaxTree, Fitness)]),
+    {
+        use rayon::prelude::*;
+
+        let count = self.islands.max(1);
+        let mut islands = (0..count)
+            .map(|index| {
+                let formulas = population
+                    .iter()
+                    .skip(index)
+                    .step_by(count)
+                    .cloned()
+                    .collect::<Vec<_>>();
+                let mut cache = cache.subset(&formulas);
+                Island {
+                    ranked: self.genetic.rank_cached(&formulas, sample, &mut cache),
+                    cache,
+                    schedule: self.genetic.mutation_schedule(),
+                    stagnation: Stagnation::default(),
+                    stopped: false,
+                    rng: StdRng::seed_from_u64(rng.gen()),
+                }
+            })
+            .collect::<Vec<_>>();
+        let expired = || deadline.is_some_and(|deadline| Instant::now() >= deadline);
+
+        for generation in 1..=self.genetic.iterations {
+            if expired() {
+                break;
+            }
+            islands.par_iter_mut().for_each(|island| {
+                if island.stopped {
+                    return;
+                }
+                if self
+                    .genetic
+                    .stops(&island.ranked, sample, &mut island.stagnation)
+                {
+                    island.stopped = true;
+                    return;
+                }
+                let elite = self.genetic.elite(&island.ranked);
+                let combined = self.genetic.breed(
+                    &island.ranked,
+                    sample,
+                    &mut island.cache,
+                    &mut island.schedule,
+                    &mut island.rng,
+                );
+                island.ranked = self
+                    .genetic
+                    .rank_cached(&combined, sample, &mut island.cache);
+                self.genetic.keep_survivors(&mut island.ranked, &elite);
+            });
+            on_generation(generation, &self.merge(&islands));
+            // Islands stop early on their own, but a consistent formula stops them all.
+            let consistent = self.genetic.stop_when_consistent
+                && islands.iter().any(|island| {
+                    island
+                        .ranked
+                        .first()
+                        .is_some_and(|(fittest, _)| sample.is_consistent(fittest))
+                });
+            if consistent || islands.iter().all(|island| island.stopped) {
+                break;
+            }
+            if generation % self.interval.max(1) == 0 && generation < self.genetic.iterations {
+                self.migrate(&mut islands, sample);
+            }
+        }
+
+        let mut formulas = Vec::new();
+        for island in islands {
+            for (formula, _) in island.ranked {
+                if !formulas.contains(&formula) {
+                    formulas.push(formula);
+                }
+            }
+            cache.merge(island.cache);
+        }
+        self.genetic.rank_cached(&formulas, sample, cache)
+    }
+
+    /// The surviving formulas of all islands with their fitness, fittest first, without duplicates.
+    fn merge(&self, islands: &[Island]) -> Vec<(SyntaxTree, Fitness)> {
+        let mut seen = HashSet::new();
+        let mut ranked = islands
+            .iter()
+            .flat_map(|island| island.ranked.iter())
+            .filter(|(formula, _)| seen.insert(formula))
+            .cloned()
+            .collect::<Vec<_>>();
+        self.genetic.sort_ranked(&mut ranked);
+        ranked
+    }
+
+    /// Sends copies of the fittest formulas of each island to the next one, with their evaluations,
+    /// where they replace the least fit formulas, unless they are there already.
+    fn migrate<const N: usize, S: SampleSource<N>>(&self, islands: &mut [Island], sample: &S) {
+        let emigrants = islands
+            .iter()
+            .map(|island| {
+                let emigrants = island
+                    .ranked
+                    .iter()
+                    .take(self.migrants)
+                    .map(|(formula, _)| formula.clone())
+                    .collect::<Vec<_>>();
+                let evaluations = island.cache.subset(&emigrants);
+                (emigrants, evaluations)
+            })
+            .collect::<Vec<_>>();
+        for (index, (emigrants, evaluations)) in emigrants.into_iter().enumerate() {
+            let island = &mut islands[(index + 1) % islands.len()];
+            island.cache.merge(evaluations);
+            let mut formulas = island
+                .ranked
+                .iter()
+                .map(|(formula, _)| formula.clone())
+                .collect::<Vec<_>>();
+            let immigrants = emigrants
+                .into_iter()
+                .filter(|formula| !formulas.contains(formula))
+                .collect::<Vec<_>>();
+            formulas.truncate(formulas.len().saturating_sub(immigrants.len()));
+            formulas.extend(immigrants);
+            island.ranked = self
+                .genetic
+                .rank_cached(&formulas, sample, &mut island.cache);
+        }
+    }
+
+    /// Learns a formula from the sample, which can be any [`SampleSource`]:
+    /// the fittest formula of all islands at the last generation, which is not necessarily consistent with the sample.
+    ///
+    /// # Panics
+    ///
+    /// If the initial population is empty, i.e., there are no formulas of the configured size and no seeds.
+    pub fn learn<const N: usize, S: SampleSource<N> + Sync>(&self, sample: &S) -> SyntaxTree {
+        let population = self.genetic.initial_population(sample);
+        let mut cache = FitnessCache::default();
+        self.evolve(
+            sample,
+            population,
+            &mut cache,
+            None,
+            &mut self.genetic.rng(),
+        )
+        .into_iter()
+        .next()
+        .map(|(formula, _)| formula)
+        .expect("empty initial population")
+    }
+}
+
+impl<const N: usize> Learner<N> for IslandModel {
+    /// Evolves the islands for the configured number of generations, or until the time is up,
+    /// and returns the fittest formula no larger than the maximum size.
+    fn learn(&self, sample: &Sample<N>, budget: Budget) -> LearnResult {
+        if !sample.is_solvable() {
+            return LearnResult::Unsolvable;
+        }
+
+        let mut model = self.clone();
+        if let Some(max) = budget.max_size {
+            model.genetic.max_size = Some(self.genetic.max_size.map_or(max, |own| own.min(max)));
+        }
+        let population = model.genetic.initial_population(sample);
+        let best = model
+            .evolve(
+                sample,
+                population,
+                &mut FitnessCache::default(),
+                budget.deadline(),
+                &mut model.genetic.rng(),
+            )
+            .into_iter()
+            .map(|(formula, _)| formula)
+            .find(|formula| budget.max_size.is_none_or(|max| formula.size() <= max));
+        match best {
+            Some(formula) if sample.is_consistent(&formula) => LearnResult::Consistent(formula),
+            Some(formula) => LearnResult::Approximate(formula),
+            None => LearnResult::Exhausted,
+        }
+    }
+}
+
+#[cfg(test)]
+mod island_model {
+    use super::*;
+
+    #[test]
+    fn evolve() {
+        let sample = Sample::always_x0();
+        let model = IslandModel::new(3)
+            .genetic(GeneticLearner::new(2).iterations(6).random_seed(1))
+            .interval(2)
+            .migrants(1);
+        let population = model.genetic.initial_population(&sample);
+        let mut cache = FitnessCache::default();
+        let ranked = model.evolve(
+            &sample,
+            population.clone(),
+            &mut cache,
+            None,
+            &mut model.genetic.rng(),
+        );
+        assert_eq!(ranked[0].0, SyntaxTree::Atom(0).globally());
+        assert!(ranked
+            .iter()
+            .enumerate()
+            .all(|(index, (formula, _))| ranked[..index]
+                .iter()
+                .all(|(other, _)| other != formula)));
+        // The final ranking reuses the evaluations of the islands.
+        assert!(ranked.len() <= cache.len() && cache.hits() >= ranked.len());
+        // Islands are seeded, so runs are reproducible although they evolve in parallel.
+        let mut generations = Vec::new();
+        assert_eq!(
+            ranked,
+            model.evolve_observed(
+                &sample,
+                population,
+                &mut FitnessCache::default(),
+                None,
+                &mut model.genetic.rng(),
+                |generation, ranked| generations.push((generation, ranked.to_vec()))
+            )
+        );
+        // Every generation is reported, the last one with the returned formulas.
+        assert!(generations
+            .iter()
+            .enumerate()
+            .all(|(index, (generation, _))| *generation == index + 1));
+        assert_eq!(generations.last().map(|(_, last)| last), Some(&ranked));
+
+        let result = Learner::<2>::learn(&model, &sample, Budget::default());
+        assert_eq!(
+            result,
+            LearnResult::Consistent(SyntaxTree::Atom(0).globally())
+        );
+    }
+}
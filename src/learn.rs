@@ -1,8 +1,17 @@
+use crate::chance::*;
+use crate::constraint::*;
+use crate::observer::*;
+use crate::order::*;
+use crate::source::*;
 use crate::syntax::*;
-use crate::trace::*;
 use itertools::Itertools;
 
+use std::collections::HashSet;
+use std::error::Error;
+use std::fmt;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 /// A tree structure with unary and binary nodes, but containing no data.
 #[derive(Debug, Clone)]
@@ -64,23 +73,7 @@ impl SkeletonTree {
                 let mut trees = Vec::with_capacity(4 * children.len());
 
                 for child in children {
-                    let child = Arc::new(child);
-
-                    if check_not(child.as_ref()) {
-                        trees.push(SyntaxTree::Not(child.clone()));
-                    }
-
-                    if check_next(child.as_ref()) {
-                        trees.push(SyntaxTree::Next(child.clone()));
-                    }
-
-                    if check_globally(child.as_ref()) {
-                        trees.push(SyntaxTree::Globally(child.clone()));
-                    }
-
-                    if check_finally(child.as_ref()) {
-                        trees.push(SyntaxTree::Finally(child));
-                    }
+                    push_unary(&mut trees, Arc::new(child));
                 }
 
                 trees.shrink_to_fit();
@@ -108,21 +101,7 @@ impl SkeletonTree {
                     .cartesian_product(right_children.into_iter());
 
                 for (left_child, right_child) in children {
-                    if check_and(left_child.as_ref(), right_child.as_ref()) {
-                        trees.push(SyntaxTree::And(left_child.clone(), right_child.clone()));
-                    }
-
-                    if check_or(left_child.as_ref(), right_child.as_ref()) {
-                        trees.push(SyntaxTree::Or(left_child.clone(), right_child.clone()));
-                    }
-
-                    if check_implies(left_child.as_ref(), right_child.as_ref()) {
-                        trees.push(SyntaxTree::Implies(left_child.clone(), right_child.clone()));
-                    }
-
-                    if check_until(left_child.as_ref(), right_child.as_ref()) {
-                        trees.push(SyntaxTree::Until(left_child, right_child));
-                    }
+                    push_binary(&mut trees, left_child, right_child);
                 }
 
                 trees.shrink_to_fit();
@@ -131,6 +110,59 @@ impl SkeletonTree {
             }
         }
     }
+
+    /// Lazily generates the same formulae as [`SkeletonTree::gen_formulae`], in the same order,
+    /// so that a search can stop at the first suitable formula without generating all the others.
+    /// Only the formulae fitting the right branches of binary nodes are generated in advance,
+    /// as each of them is combined with all the formulae fitting the left branch.
+    pub fn gen_formulae_iter<const N: usize>(
+        &self,
+        vars: &[Idx],
+    ) -> Box<dyn Iterator<Item = SyntaxTree> + Send> {
+        self.formulae_iter::<N>(Arc::from(vars))
+    }
+
+    fn formulae_iter<const N: usize>(
+        &self,
+        vars: Arc<[Idx]>,
+    ) -> Box<dyn Iterator<Item = SyntaxTree> + Send> {
+        match self {
+            SkeletonTree::Leaf => Box::new((0..vars.len()).map(move |i| SyntaxTree::Atom(vars[i]))),
+            SkeletonTree::UnaryNode(child) => {
+                Box::new(child.formulae_iter::<N>(vars).flat_map(|child| {
+                    let mut trees = Vec::with_capacity(4);
+                    push_unary(&mut trees, Arc::new(child));
+                    trees
+                }))
+            }
+            SkeletonTree::BinaryNode(child) => {
+                let right_children: Arc<[Arc<SyntaxTree>]> = child
+                    .1
+                    .gen_formulae::<N>(&vars)
+                    .into_iter()
+                    .map(Arc::new)
+                    .collect();
+                Box::new(
+                    child
+                        .0
+                        .formulae_iter::<N>(vars)
+                        .flat_map(move |left_child| {
+                            let left_child = Arc::new(left_child);
+                            let right_children = right_children.clone();
+                            (0..right_children.len()).flat_map(move |i| {
+                                let mut trees = Vec::with_capacity(4);
+                                push_binary(
+                                    &mut trees,
+                                    left_child.clone(),
+                                    right_children[i].clone(),
+                                );
+                                trees
+                            })
+                        }),
+                )
+            }
+        }
+    }
 }
 
 pub fn gen_formulae<const N: usize>(size: usize, vars: &[Idx]) -> Vec<SyntaxTree> {
@@ -140,43 +172,586 @@ pub fn gen_formulae<const N: usize>(size: usize, vars: &[Idx]) -> Vec<SyntaxTree
         .collect_vec()
 }
 
-/// Find a formula consistent with the given `Sample`.
-/// Uses a fundamentally brute-force search algorithm.
+/// Lazily generates the same formulae as [`gen_formulae`] (see [`SkeletonTree::gen_formulae_iter`]).
+pub fn gen_formulae_iter<const N: usize>(
+    size: usize,
+    vars: &[Idx],
+) -> impl Iterator<Item = SyntaxTree> + '_ {
+    SkeletonTree::gen(size)
+        .into_iter()
+        .flat_map(|skeleton| skeleton.gen_formulae_iter::<N>(vars))
+}
+
+/// Generates the formulae of [`gen_formulae`] in parallel, splitting the work by skeleton,
+/// with the formulae of each skeleton generated lazily (see [`SkeletonTree::gen_formulae_iter`]).
+pub fn gen_formulae_par_iter<const N: usize>(
+    size: usize,
+    vars: &[Idx],
+) -> impl rayon::iter::ParallelIterator<Item = SyntaxTree> + '_ {
+    use rayon::prelude::*;
+
+    SkeletonTree::gen(size)
+        .into_par_iter()
+        .flat_map_iter(|skeleton| skeleton.gen_formulae_iter::<N>(vars))
+}
+
+/// Limits on the brute-force search of [`solve_with_budget`].
+/// By default, there is no limit.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SearchBudget {
+    /// The maximum size of the formulas searched.
+    pub max_size: Option<usize>,
+    /// The maximum duration of the search, which is only checked from time to time, so it can be exceeded slightly.
+    pub max_duration: Option<Duration>,
+    /// The maximum number of formulas checked.
+    pub max_formulas: Option<usize>,
+}
+
+impl SearchBudget {
+    pub fn max_size(mut self, max_size: usize) -> Self {
+        self.max_size = Some(max_size);
+        self
+    }
+
+    pub fn max_duration(mut self, max_duration: Duration) -> Self {
+        self.max_duration = Some(max_duration);
+        self
+    }
+
+    pub fn max_formulas(mut self, max_formulas: usize) -> Self {
+        self.max_formulas = Some(max_formulas);
+        self
+    }
+}
+
+/// Why a brute-force search ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SearchEnd {
+    /// A consistent formula was found.
+    Solved,
+    /// No formula is consistent with the sample.
+    Unsolvable,
+    /// No formula up to the maximum size is consistent with the sample.
+    MaxSize,
+    MaxDuration,
+    MaxFormulas,
+}
+
+impl fmt::Display for SearchEnd {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SearchEnd::Solved => write!(f, "solved"),
+            SearchEnd::Unsolvable => write!(f, "sample unsolvable"),
+            SearchEnd::MaxSize => write!(f, "maximum size reached"),
+            SearchEnd::MaxDuration => write!(f, "time limit reached"),
+            SearchEnd::MaxFormulas => write!(f, "formula limit reached"),
+        }
+    }
+}
+
+/// The number of formulas discarded by the search for being equivalent to other formulas,
+/// by their main operator (e.g., `not` counts formulas like `¬¬φ`, equivalent to `φ`).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Pruned {
+    pub not: usize,
+    pub next: usize,
+    pub globally: usize,
+    pub finally: usize,
+    pub and: usize,
+    pub or: usize,
+    pub implies: usize,
+    pub until: usize,
+}
+
+impl Pruned {
+    /// The total number of formulas discarded.
+    pub fn total(&self) -> usize {
+        self.not
+            + self.next
+            + self.globally
+            + self.finally
+            + self.and
+            + self.or
+            + self.implies
+            + self.until
+    }
+}
+
+impl fmt::Display for Pruned {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "¬: {}, X: {}, G: {}, F: {}, ∧: {}, ∨: {}, →: {}, U: {}",
+            self.not,
+            self.next,
+            self.globally,
+            self.finally,
+            self.and,
+            self.or,
+            self.implies,
+            self.until
+        )
+    }
+}
+
+/// Statistics about the search of the formulas of a given size.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SizeStats {
+    pub size: usize,
+    /// The number of formulas of the size checked, i.e., evaluated on the sample.
+    pub formulas: usize,
+    /// The number of formulas of the size discarded before being checked,
+    /// if requested (see [`solve_with_stats`]).
+    pub pruned: Option<Pruned>,
+    pub duration: Duration,
+}
+
+impl fmt::Display for SizeStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "size {}: {} formulas checked in {:.2?}",
+            self.size, self.formulas, self.duration
+        )?;
+        if let Some(pruned) = &self.pruned {
+            write!(f, ", {} pruned ({pruned})", pruned.total())?;
+        }
+        Ok(())
+    }
+}
+
+/// Statistics about a brute-force search.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SearchStats {
+    pub end: SearchEnd,
+    /// The number of formulas checked, i.e., evaluated on the sample.
+    pub formulas: usize,
+    /// The size of the last formulas searched.
+    pub size: usize,
+    pub duration: Duration,
+    /// The statistics of each size searched, in the order searched (increasing by default), including the last one, even if interrupted.
+    pub sizes: Vec<SizeStats>,
+}
+
+impl fmt::Display for SearchStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} after checking {} formulas up to size {} in {:.2?}",
+            self.end, self.formulas, self.size, self.duration
+        )
+    }
+}
+
+/// Find a formula consistent with the given sample, which can be any [`SampleSource`].
+/// Uses a fundamentally brute-force search algorithm, which never ends if no formula is consistent with the sample
+/// but it cannot be told (see [`solve_with_budget`] to limit the search).
 // Parallel search is faster but less consistent then single-threaded search
-pub fn solve<const N: usize>(
-    sample: &Sample<N>,
+pub fn solve<const N: usize, S: SampleSource<N> + Sync>(
+    sample: &S,
     multithread: bool,
     log: bool,
 ) -> Option<SyntaxTree> {
+    solve_with_budget(sample, multithread, log, SearchBudget::default()).0
+}
+
+/// Find a formula consistent with the given sample, which can be any [`SampleSource`], within the budget.
+/// Uses the brute-force search of [`solve`], and returns statistics about it, whether it succeeds or not.
+pub fn solve_with_budget<const N: usize, S: SampleSource<N> + Sync>(
+    sample: &S,
+    multithread: bool,
+    log: bool,
+    budget: SearchBudget,
+) -> (Option<SyntaxTree>, SearchStats) {
+    search(sample, multithread, log, budget, SearchOptions::default())
+}
+
+/// Find a formula consistent with the given sample, which can be any [`SampleSource`], within the budget,
+/// like [`solve_with_budget`], but also counting the formulas of each size searched that are discarded
+/// before being checked (see [`count_pruned`]), which takes about as long as generating them again.
+pub fn solve_with_stats<const N: usize, S: SampleSource<N> + Sync>(
+    sample: &S,
+    multithread: bool,
+    log: bool,
+    budget: SearchBudget,
+) -> (Option<SyntaxTree>, SearchStats) {
+    let options = SearchOptions {
+        count_pruned: true,
+        ..SearchOptions::default()
+    };
+    search(sample, multithread, log, budget, options)
+}
+
+/// Find a formula consistent with the given sample, which can be any [`SampleSource`], within the budget,
+/// like [`solve_with_budget`], but returning the least consistent formula of the smallest size
+/// (in the order of [`SyntaxTree`]), rather than the first one found.
+/// The result is then the same whether the search is parallel or not, and with any number of threads,
+/// at the cost of checking all the formulas of the size of the solution.
+pub fn solve_canonical<const N: usize, S: SampleSource<N> + Sync>(
+    sample: &S,
+    multithread: bool,
+    log: bool,
+    budget: SearchBudget,
+) -> (Option<SyntaxTree>, SearchStats) {
+    let options = SearchOptions {
+        canonical: true,
+        ..SearchOptions::default()
+    };
+    search(sample, multithread, log, budget, options)
+}
+
+/// Find a formula consistent with the given sample, which can be any [`SampleSource`], within the budget,
+/// like [`solve_with_budget`], but only among the formulas respecting the constraints (see [`Constraints`]).
+pub fn solve_constrained<const N: usize, S: SampleSource<N> + Sync>(
+    sample: &S,
+    constraints: &Constraints,
+    multithread: bool,
+    log: bool,
+    budget: SearchBudget,
+) -> (Option<SyntaxTree>, SearchStats) {
+    let options = SearchOptions {
+        constraints: Some(constraints),
+        ..SearchOptions::default()
+    };
+    search(sample, multithread, log, budget, options)
+}
+
+/// Find a formula meeting the chance constraint on the given sample, which can be any [`SampleSource`],
+/// within the budget, like [`solve_with_budget`] does for plain consistency.
+/// The search goes on even if no formula is consistent with the sample, as one may still meet the constraint.
+pub fn solve_chance_constrained<const N: usize, S: SampleSource<N> + Sync>(
+    sample: &S,
+    chance: &ChanceConstraint,
+    multithread: bool,
+    log: bool,
+    budget: SearchBudget,
+) -> (Option<SyntaxTree>, SearchStats) {
+    let options = SearchOptions {
+        chance: Some(chance),
+        ..SearchOptions::default()
+    };
+    search(sample, multithread, log, budget, options)
+}
+
+/// The variants of the brute-force search.
+pub(crate) struct SearchOptions<'a> {
+    /// Constraints that solutions have to respect, if any.
+    pub(crate) constraints: Option<&'a Constraints>,
+    /// Whether to return the least consistent formula of the smallest size, rather than the first one found.
+    pub(crate) canonical: bool,
+    /// The size to start from, when smaller ones are known to have no solution.
+    pub(crate) first_size: usize,
+    /// Called with every size whose formulas have all been checked, with no solution.
+    pub(crate) on_exhausted: Option<&'a mut dyn FnMut(usize)>,
+    /// Notified of the progress of the search.
+    pub(crate) observer: &'a dyn SearchObserver,
+    /// Whether to count the formulas discarded at each size.
+    pub(crate) count_pruned: bool,
+    /// The notion of consistency solutions have to meet, if not plain consistency.
+    pub(crate) chance: Option<&'a ChanceConstraint>,
+    /// The order in which to explore formulas, if not by increasing size.
+    pub(crate) order: Option<&'a SearchOrder>,
+}
+
+impl Default for SearchOptions<'_> {
+    fn default() -> Self {
+        SearchOptions {
+            constraints: None,
+            canonical: false,
+            first_size: 1,
+            on_exhausted: None,
+            observer: &(),
+            count_pruned: false,
+            chance: None,
+            order: None,
+        }
+    }
+}
+
+/// The brute-force search, with the given options.
+pub(crate) fn search<const N: usize, S: SampleSource<N> + Sync>(
+    sample: &S,
+    multithread: bool,
+    log: bool,
+    budget: SearchBudget,
+    options: SearchOptions,
+) -> (Option<SyntaxTree>, SearchStats) {
     use rayon::prelude::*;
 
-    if !sample.is_solvable() {
-        return None;
+    let SearchOptions {
+        constraints,
+        canonical,
+        first_size,
+        mut on_exhausted,
+        observer,
+        count_pruned,
+        chance,
+        order,
+    } = options;
+    let order = order.unwrap_or(&SearchOrder::Size);
+
+    let start = Instant::now();
+    let deadline = budget.max_duration.map(|duration| start + duration);
+    let expired = || deadline.is_some_and(|deadline| Instant::now() >= deadline);
+    let checked = AtomicUsize::new(0);
+    // The formula exceeding the limit is counted, but not checked.
+    let formulas = || {
+        checked
+            .load(Ordering::Relaxed)
+            .min(budget.max_formulas.unwrap_or(usize::MAX))
+    };
+    let stats = |end, size, sizes| SearchStats {
+        end,
+        formulas: formulas(),
+        size,
+        duration: start.elapsed(),
+        sizes,
+    };
+
+    if chance.is_none_or(ChanceConstraint::is_strict) && !sample.is_solvable() {
+        return (None, stats(SearchEnd::Unsolvable, 0, Vec::new()));
     }
 
     let vars = &sample.vars();
+    // Checks a formula, unless the budget is exhausted.
+    let check = |formula: SyntaxTree| {
+        let count = checked.fetch_add(1, Ordering::Relaxed) + 1;
+        if budget.max_formulas.is_some_and(|max| count > max) {
+            return Some(Err(SearchEnd::MaxFormulas));
+        }
+        // Checking the clock is comparatively expensive, so it is done only every so often.
+        if count.is_multiple_of(1024) {
+            observer.on_progress(count);
+            if expired() {
+                return Some(Err(SearchEnd::MaxDuration));
+            }
+        }
+        let found = chance.map_or_else(
+            || sample.is_consistent(&formula),
+            |chance| chance.admits(&formula, sample),
+        ) && constraints.is_none_or(|constraints| constraints.admits(&formula, sample));
+        if found {
+            observer.on_candidate_found(&formula);
+        }
+        found.then_some(Ok(formula))
+    };
 
-    (1..).into_iter().find_map(|size| {
+    let mut sizes = Vec::new();
+    let mut last_size = first_size.max(1) - 1;
+    for size in order.sizes(first_size, budget.max_size) {
+        if expired() {
+            return (None, stats(SearchEnd::MaxDuration, last_size, sizes));
+        }
+        let size_start = Instant::now();
+        let formulas_before = formulas();
         if log {
             println!("Searching formulae of size {}", size);
         }
+        observer.on_size_started(size);
+        let skeletons = order.skeletons(size);
+        let formulae = || {
+            skeletons
+                .iter()
+                .flat_map(|skeleton| skeleton.gen_formulae_iter::<N>(vars))
+        };
+        let par_formulae = || {
+            skeletons
+                .par_iter()
+                .flat_map_iter(|skeleton| skeleton.gen_formulae_iter::<N>(vars))
+        };
         // At small size, the overhead for parallel iterators is not worth it.
         // At larger size, we use parallel iterators for speed.
-        if multithread {
-            SkeletonTree::gen(size)
-                .into_par_iter()
-                .flat_map(|skeleton| skeleton.gen_formulae::<N>(vars))
-                .find_any(|formula| sample.is_consistent(formula))
+        let result = if canonical {
+            // All the consistent formulas of the size are needed to find the least one.
+            let least = |best: Option<SyntaxTree>, formula: Option<SyntaxTree>| {
+                best.into_iter().chain(formula).min()
+            };
+            let checked = |formula| check(formula).transpose();
+            let least = if multithread {
+                par_formulae()
+                    .map(checked)
+                    .try_reduce(|| None, |best, formula| Ok(least(best, formula)))
+            } else {
+                formulae()
+                    .map(checked)
+                    .try_fold(None, |best, formula| Ok(least(best, formula?)))
+            };
+            least.transpose()
+        } else if multithread {
+            par_formulae().find_map_any(check)
         } else {
-            SkeletonTree::gen(size)
-                .into_iter()
-                .flat_map(|skeleton| skeleton.gen_formulae::<N>(vars))
-                .find(|formula| sample.is_consistent(formula))
+            formulae().find_map(check)
+        };
+        sizes.push(SizeStats {
+            size,
+            formulas: formulas() - formulas_before,
+            pruned: count_pruned.then(|| self::count_pruned::<N>(size, vars)),
+            duration: size_start.elapsed(),
+        });
+        match result {
+            Some(Ok(formula)) => return (Some(formula), stats(SearchEnd::Solved, size, sizes)),
+            Some(Err(end)) => return (None, stats(end, size, sizes)),
+            None => {
+                last_size = size;
+                observer.on_progress(checked.load(Ordering::Relaxed));
+                if let Some(on_exhausted) = on_exhausted.as_mut() {
+                    on_exhausted(size);
+                }
+            }
+        }
+    }
+
+    (None, stats(SearchEnd::MaxSize, last_size, sizes))
+}
+
+/// No formula up to the given size is consistent with the sample (see [`brute_solve_bounded`]).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NoFormulaUpTo(pub usize);
+
+impl fmt::Display for NoFormulaUpTo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "no formula up to size {} is consistent with the sample",
+            self.0
+        )
+    }
+}
+
+impl Error for NoFormulaUpTo {}
+
+/// Find a formula consistent with the given sample, which can be any [`SampleSource`], up to the maximum size,
+/// with the single-threaded brute-force search of [`solve`], which always ends.
+/// Returns the number of formulas checked alongside the outcome,
+/// which is an error when the search completed without finding any formula.
+pub fn brute_solve_bounded<const N: usize, S: SampleSource<N> + Sync>(
+    sample: &S,
+    max_size: usize,
+) -> (Result<SyntaxTree, NoFormulaUpTo>, usize) {
+    let budget = SearchBudget::default().max_size(max_size);
+    let (solution, stats) = solve_with_budget(sample, false, false, budget);
+    (solution.ok_or(NoFormulaUpTo(max_size)), stats.formulas)
+}
+
+/// The truth value of the formula at every position of every trace of the sample.
+/// Formulas consistent with the sample agree at the first position of each trace, but can differ on later ones.
+fn behaviour<const N: usize, S: SampleSource<N>>(sample: &S, formula: &SyntaxTree) -> Vec<bool> {
+    sample
+        .iter_positive()
+        .chain(sample.iter_negative())
+        .flat_map(|trace| {
+            (0..trace.len())
+                .map(|time| formula.eval_at_time(&trace, time))
+                .collect_vec()
+        })
+        .collect()
+}
+
+/// Finds up to `k` formulas consistent with the given sample, which can be any [`SampleSource`],
+/// searching formulas by increasing size up to the maximum one, like [`solve`].
+/// The formulas are pairwise distinct on the sample, in that they differ on some position of some trace,
+/// so that each explains the sample differently: of formulas agreeing on every position, only the smallest is kept.
+pub fn learn_top_k<const N: usize, S: SampleSource<N>>(
+    sample: &S,
+    k: usize,
+    max_size: usize,
+) -> Vec<SyntaxTree> {
+    let mut formulas = Vec::new();
+    if k == 0 || !sample.is_solvable() {
+        return formulas;
+    }
+
+    let vars = sample.vars();
+    let mut behaviours = HashSet::new();
+    for size in 1..=max_size {
+        for formula in gen_formulae_iter::<N>(size, &vars) {
+            if sample.is_consistent(&formula) && behaviours.insert(behaviour(sample, &formula)) {
+                formulas.push(formula);
+                if formulas.len() == k {
+                    return formulas;
+                }
+            }
         }
-    })
+    }
+
+    formulas
 }
 
-fn check_not(child: &SyntaxTree) -> bool {
+/// Counts the formulas of the given size over the given variables that the search discards,
+/// by their main operator, as equivalent to other formulas (those with discarded subformulas are not generated at all).
+pub fn count_pruned<const N: usize>(size: usize, vars: &[Idx]) -> Pruned {
+    let mut pruned = Pruned::default();
+    let count = |counter: &mut usize, kept: bool| *counter += usize::from(!kept);
+    for skeleton in SkeletonTree::gen(size) {
+        match skeleton {
+            SkeletonTree::Leaf => {}
+            SkeletonTree::UnaryNode(child) => {
+                for child in child.gen_formulae_iter::<N>(vars) {
+                    count(&mut pruned.not, check_not(&child));
+                    count(&mut pruned.next, check_next(&child));
+                    count(&mut pruned.globally, check_globally(&child));
+                    count(&mut pruned.finally, check_finally(&child));
+                }
+            }
+            SkeletonTree::BinaryNode(children) => {
+                let right_children = children.1.gen_formulae::<N>(vars);
+                for left in children.0.gen_formulae_iter::<N>(vars) {
+                    for right in &right_children {
+                        count(&mut pruned.and, check_and(&left, right));
+                        count(&mut pruned.or, check_or(&left, right));
+                        count(&mut pruned.implies, check_implies(&left, right));
+                        count(&mut pruned.until, check_until(&left, right));
+                    }
+                }
+            }
+        }
+    }
+    pruned
+}
+
+/// Pushes the formulae made of a unary operator applied to the child, unless filtered out.
+pub(crate) fn push_unary(trees: &mut Vec<SyntaxTree>, child: Arc<SyntaxTree>) {
+    if check_not(child.as_ref()) {
+        trees.push(SyntaxTree::Not(child.clone()));
+    }
+
+    if check_next(child.as_ref()) {
+        trees.push(SyntaxTree::Next(child.clone()));
+    }
+
+    if check_globally(child.as_ref()) {
+        trees.push(SyntaxTree::Globally(child.clone()));
+    }
+
+    if check_finally(child.as_ref()) {
+        trees.push(SyntaxTree::Finally(child));
+    }
+}
+
+/// Pushes the formulae made of a binary operator applied to the children, unless filtered out.
+pub(crate) fn push_binary(
+    trees: &mut Vec<SyntaxTree>,
+    left_child: Arc<SyntaxTree>,
+    right_child: Arc<SyntaxTree>,
+) {
+    if check_and(left_child.as_ref(), right_child.as_ref()) {
+        trees.push(SyntaxTree::And(left_child.clone(), right_child.clone()));
+    }
+
+    if check_or(left_child.as_ref(), right_child.as_ref()) {
+        trees.push(SyntaxTree::Or(left_child.clone(), right_child.clone()));
+    }
+
+    if check_implies(left_child.as_ref(), right_child.as_ref()) {
+        trees.push(SyntaxTree::Implies(left_child.clone(), right_child.clone()));
+    }
+
+    if check_until(left_child.as_ref(), right_child.as_ref()) {
+        trees.push(SyntaxTree::Until(left_child, right_child));
+    }
+}
+
+pub(crate) fn check_not(child: &SyntaxTree) -> bool {
     match child {
         // ¬¬φ ≡ φ
         SyntaxTree::Not(_)
@@ -196,7 +771,7 @@ fn check_not(child: &SyntaxTree) -> bool {
     }
 }
 
-fn check_next(child: &SyntaxTree) -> bool {
+pub(crate) fn check_next(child: &SyntaxTree) -> bool {
     !matches!(
         child,
         // X ¬ φ ≡ ¬ X φ // FALSE on finite trace semantics: neXt and Not do not commute on a trace of length 1!
@@ -208,7 +783,7 @@ fn check_next(child: &SyntaxTree) -> bool {
     )
 }
 
-fn check_globally(child: &SyntaxTree) -> bool {
+pub(crate) fn check_globally(child: &SyntaxTree) -> bool {
     !matches!(
         child,
         // G G φ ≡ G φ
@@ -216,7 +791,7 @@ fn check_globally(child: &SyntaxTree) -> bool {
     )
 }
 
-fn check_finally(child: &SyntaxTree) -> bool {
+pub(crate) fn check_finally(child: &SyntaxTree) -> bool {
     !matches!(
         child,
         // F F φ ≡ F φ
@@ -227,7 +802,7 @@ fn check_finally(child: &SyntaxTree) -> bool {
     )
 }
 
-fn check_and(left_child: &SyntaxTree, right_child: &SyntaxTree) -> bool {
+pub(crate) fn check_and(left_child: &SyntaxTree, right_child: &SyntaxTree) -> bool {
     // Commutative law WARNING: CORRECTNESS OF COMM+ASSOC IS NOT PROVEN
     left_child < right_child
     // left_child != right_child
@@ -280,7 +855,7 @@ fn check_and(left_child: &SyntaxTree, right_child: &SyntaxTree) -> bool {
     }
 }
 
-fn check_or(left_child: &SyntaxTree, right_child: &SyntaxTree) -> bool {
+pub(crate) fn check_or(left_child: &SyntaxTree, right_child: &SyntaxTree) -> bool {
     // Commutative law WARNING: CORRECTNESS OF COMM+ASSOC IS NOT PROVEN
     left_child < right_child
     // left_child != right_child
@@ -373,7 +948,7 @@ fn check_or(left_child: &SyntaxTree, right_child: &SyntaxTree) -> bool {
     }
 }
 
-fn check_implies(left_child: &SyntaxTree, right_child: &SyntaxTree) -> bool {
+pub(crate) fn check_implies(left_child: &SyntaxTree, right_child: &SyntaxTree) -> bool {
     left_child != right_child
         && !matches!(
             (left_child, right_child),
@@ -409,7 +984,7 @@ fn check_implies(left_child: &SyntaxTree, right_child: &SyntaxTree) -> bool {
         )
 }
 
-fn check_until(left_child: &SyntaxTree, right_child: &SyntaxTree) -> bool {
+pub(crate) fn check_until(left_child: &SyntaxTree, right_child: &SyntaxTree) -> bool {
     // φ U φ ≡ φ
     left_child != right_child
         && match (left_child, right_child) {
@@ -434,3 +1009,156 @@ fn check_until(left_child: &SyntaxTree, right_child: &SyntaxTree) -> bool {
             _ => true,
         }
 }
+
+#[cfg(test)]
+mod search_budget {
+    use super::*;
+    use crate::trace::*;
+
+    #[test]
+    fn budget() {
+        // Positive traces are those where x0 and x1 eventually hold together,
+        // which no formula of size up to 2 expresses.
+        let sample = Sample::<2> {
+            positive_traces: vec![vec![[true, true]], vec![[false, false], [true, true]]],
+            negative_traces: vec![
+                vec![[true, false], [false, true]],
+                vec![[false, false]],
+                vec![[true, false]],
+                vec![[false, true]],
+            ],
+            ..Sample::default()
+        };
+
+        let (solution, stats) = solve_with_budget(&sample, false, false, SearchBudget::default());
+        assert!(sample.is_consistent(&solution.expect("solvable")));
+        assert_eq!(stats.end, SearchEnd::Solved);
+
+        let (solution, stats) =
+            solve_with_budget(&sample, true, false, SearchBudget::default().max_size(2));
+        assert_eq!(solution, None);
+        assert_eq!((stats.end, stats.size), (SearchEnd::MaxSize, 2));
+
+        let (solution, stats) = solve_with_budget(
+            &sample,
+            false,
+            false,
+            SearchBudget::default().max_formulas(3),
+        );
+        assert_eq!(solution, None);
+        assert_eq!((stats.end, stats.formulas), (SearchEnd::MaxFormulas, 3));
+
+        let mut unsolvable = sample.clone();
+        unsolvable.negative_traces.push(vec![[true, true]]);
+        let (_, stats) = solve_with_budget(&unsolvable, false, false, SearchBudget::default());
+        assert_eq!(stats.end, SearchEnd::Unsolvable);
+    }
+
+    #[test]
+    fn canonical() {
+        let sample = Sample::<2> {
+            positive_traces: vec![vec![[true, true]], vec![[false, false], [true, true]]],
+            negative_traces: vec![vec![[true, false], [false, true]], vec![[false, false]]],
+            ..Sample::default()
+        };
+        let (solution, stats) = solve_canonical(&sample, false, false, SearchBudget::default());
+        let least = gen_formulae::<2>(stats.size, &[0, 1])
+            .into_iter()
+            .filter(|formula| sample.is_consistent(formula))
+            .min();
+        assert_eq!(solution, least);
+        for _ in 0..4 {
+            let (parallel, _) = solve_canonical(&sample, true, false, SearchBudget::default());
+            assert_eq!(parallel, solution);
+        }
+    }
+
+    #[test]
+    fn bounded() {
+        let sample = Sample::<2> {
+            positive_traces: vec![vec![[true, true]], vec![[false, false], [true, true]]],
+            negative_traces: vec![vec![[true, false], [false, true]], vec![[false, false]]],
+            ..Sample::default()
+        };
+        let (result, checked) = brute_solve_bounded(&sample, 1);
+        assert_eq!(result, Err(NoFormulaUpTo(1)));
+        assert_eq!(checked, 2);
+
+        let (result, checked) = brute_solve_bounded(&sample, 3);
+        assert!(sample.is_consistent(&result.expect("solvable")));
+        assert!(checked > 2);
+    }
+
+    #[test]
+    fn search_stats() {
+        let sample = Sample::<2> {
+            positive_traces: vec![vec![[true, true]], vec![[false, false], [true, true]]],
+            negative_traces: vec![vec![[true, false], [false, true]], vec![[false, false]]],
+            ..Sample::default()
+        };
+        let (solution, stats) = solve_with_stats(&sample, false, false, SearchBudget::default());
+        assert!(solution.is_some());
+        assert_eq!(
+            stats.sizes.iter().map(|size| size.size).collect_vec(),
+            (1..=stats.size).collect_vec()
+        );
+        assert_eq!(
+            stats.sizes.iter().map(|size| size.formulas).sum::<usize>(),
+            stats.formulas
+        );
+        assert!(stats.sizes.iter().all(|size| size.pruned.is_some()));
+        let (_, stats) = solve_with_budget(&sample, false, false, SearchBudget::default());
+        assert!(stats.sizes.iter().all(|size| size.pruned.is_none()));
+
+        // Every operator applied to formulas of smaller sizes is either generated or pruned.
+        let vars = [0, 1];
+        let count = |size| gen_formulae::<2>(size, &vars).len();
+        for size in 2..=5 {
+            let candidates = 4 * count(size - 1)
+                + (1..size - 1)
+                    .map(|left| 4 * count(left) * count(size - 1 - left))
+                    .sum::<usize>();
+            assert_eq!(
+                count(size) + count_pruned::<2>(size, &vars).total(),
+                candidates
+            );
+        }
+    }
+
+    #[test]
+    fn lazy_enumeration() {
+        use rayon::prelude::*;
+
+        let vars = [0, 2];
+        for size in 1..=5 {
+            let formulae = gen_formulae::<3>(size, &vars);
+            assert_eq!(gen_formulae_iter::<3>(size, &vars).collect_vec(), formulae);
+            assert_eq!(
+                gen_formulae_par_iter::<3>(size, &vars).collect::<Vec<_>>(),
+                formulae
+            );
+        }
+    }
+
+    #[test]
+    fn top_k() {
+        let sample = Sample::<2> {
+            positive_traces: vec![vec![[true, false], [false, true]]],
+            negative_traces: vec![vec![[false, true], [true, false]]],
+            ..Sample::default()
+        };
+        let formulas = learn_top_k(&sample, 3, 3);
+        assert_eq!(formulas.len(), 3);
+        assert_eq!(formulas.first(), solve(&sample, false, false).as_ref());
+        assert!(formulas.iter().all(|formula| sample.is_consistent(formula)));
+        assert!(formulas
+            .windows(2)
+            .all(|pair| pair[0].size() <= pair[1].size()));
+        assert!(formulas
+            .iter()
+            .tuple_combinations()
+            .all(|(formula, other)| behaviour(&sample, formula) != behaviour(&sample, other)));
+
+        assert!(learn_top_k(&sample, 0, 3).is_empty());
+    }
+}
@@ -0,0 +1,216 @@
+use crate::genetic::*;
+use crate::learn::*;
+use crate::syntax::*;
+use crate::trace::*;
+use std::time::{Duration, Instant};
+
+/// Limits on the effort spent learning a formula.
+/// By default, there is no limit.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Budget {
+    /// The maximum size of the learned formula.
+    pub max_size: Option<usize>,
+    /// The maximum time spent learning, which is only checked from time to time, so it can be exceeded slightly.
+    pub time: Option<Duration>,
+}
+
+impl Budget {
+    pub fn max_size(mut self, max_size: usize) -> Self {
+        self.max_size = Some(max_size);
+        self
+    }
+
+    pub fn time(mut self, time: Duration) -> Self {
+        self.time = Some(time);
+        self
+    }
+
+    pub(crate) fn deadline(&self) -> Option<Instant> {
+        self.time.map(|time| Instant::now() + time)
+    }
+}
+
+fn expired(deadline: Option<Instant>) -> bool {
+    deadline.is_some_and(|deadline| Instant::now() >= deadline)
+}
+
+/// The outcome of learning a formula from a sample.
+#[derive(Debug, Clone, PartialEq)]
+pub enum LearnResult {
+    /// A formula consistent with the sample.
+    Consistent(SyntaxTree),
+    /// The best formula found within the budget, which misclassifies some traces.
+    Approximate(SyntaxTree),
+    /// No formula is consistent with the sample (see [`Sample::check_consistency`]).
+    Unsolvable,
+    /// The budget ran out before finding any formula.
+    Exhausted,
+}
+
+impl LearnResult {
+    /// The learned formula, consistent or not.
+    pub fn formula(&self) -> Option<&SyntaxTree> {
+        match self {
+            LearnResult::Consistent(formula) | LearnResult::Approximate(formula) => Some(formula),
+            LearnResult::Unsolvable | LearnResult::Exhausted => None,
+        }
+    }
+
+    pub fn into_formula(self) -> Option<SyntaxTree> {
+        match self {
+            LearnResult::Consistent(formula) | LearnResult::Approximate(formula) => Some(formula),
+            LearnResult::Unsolvable | LearnResult::Exhausted => None,
+        }
+    }
+
+    pub fn is_consistent(&self) -> bool {
+        matches!(self, LearnResult::Consistent(_))
+    }
+}
+
+/// A learning algorithm, so that applications can swap backends without changing their call sites,
+/// e.g., by holding a `Box<dyn Learner<N>>`.
+pub trait Learner<const N: usize> {
+    /// Learns a formula from the sample, within the budget.
+    fn learn(&self, sample: &Sample<N>, budget: Budget) -> LearnResult;
+}
+
+/// The brute-force search of [`solve`], which finds a smallest consistent formula.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BruteForce {
+    /// Whether to search formulas of each size in parallel.
+    pub multithread: bool,
+}
+
+impl<const N: usize> Learner<N> for BruteForce {
+    fn learn(&self, sample: &Sample<N>, budget: Budget) -> LearnResult {
+        let budget = SearchBudget {
+            max_size: budget.max_size,
+            max_duration: budget.time,
+            max_formulas: None,
+        };
+        match solve_with_budget(sample, self.multithread, false, budget) {
+            (Some(formula), _) => LearnResult::Consistent(formula),
+            (
+                None,
+                SearchStats {
+                    end: SearchEnd::Unsolvable,
+                    ..
+                },
+            ) => LearnResult::Unsolvable,
+            (None, _) => LearnResult::Exhausted,
+        }
+    }
+}
+
+impl<const N: usize> Learner<N> for GeneticLearner {
+    /// Evolves the population for the configured number of generations, or until the time is up or it stops early,
+    /// and returns the fittest formula no larger than the maximum size.
+    fn learn(&self, sample: &Sample<N>, budget: Budget) -> LearnResult {
+        if !sample.is_solvable() {
+            return LearnResult::Unsolvable;
+        }
+
+        // Offspring larger than the maximum size are never bred, so the population is not crowded by them.
+        let mut learner = self.clone();
+        if let Some(max) = budget.max_size {
+            learner.max_size = Some(self.max_size.map_or(max, |own| own.min(max)));
+        }
+        let deadline = budget.deadline();
+        let mut rng = learner.rng();
+        let population = learner.initial_population(sample);
+        let mut cache = FitnessCache::default();
+        let mut ranked = learner.rank_cached(&population, sample, &mut cache);
+        let mut schedule = learner.mutation_schedule();
+        let mut stagnation = Stagnation::default();
+        for _ in 0..learner.iterations {
+            if expired(deadline) || learner.stops(&ranked, sample, &mut stagnation) {
+                break;
+            }
+            let elite = learner.elite(&ranked);
+            let combined = learner.breed(&ranked, sample, &mut cache, &mut schedule, &mut rng);
+            ranked = learner.rank_cached(&combined, sample, &mut cache);
+            learner.keep_survivors(&mut ranked, &elite);
+        }
+
+        let best = ranked
+            .into_iter()
+            .map(|(formula, _)| formula)
+            .find(|formula| budget.max_size.is_none_or(|max| formula.size() <= max));
+        match best {
+            Some(formula) if sample.is_consistent(&formula) => LearnResult::Consistent(formula),
+            Some(formula) => LearnResult::Approximate(formula),
+            None => LearnResult::Exhausted,
+        }
+    }
+}
+
+#[cfg(test)]
+mod learners {
+    use super::*;
+
+    #[test]
+    fn swap_backends() {
+        let sample = Sample::always_x0();
+        let learners: Vec<Box<dyn Learner<2>>> = vec![
+            Box::new(BruteForce { multithread: false }),
+            Box::new(BruteForce { multithread: true }),
+            Box::new(GeneticLearner::new(2).iterations(2)),
+        ];
+        for learner in &learners {
+            let result = learner.learn(&sample, Budget::default());
+            assert!(result.is_consistent());
+            assert_eq!(result.into_formula(), Some(SyntaxTree::Atom(0).globally()));
+
+            // No formula of size 1 is consistent, but the genetic algorithm can shrink formulas to approximate ones.
+            let bounded = learner.learn(&sample, Budget::default().max_size(1));
+            assert!(!bounded.is_consistent());
+            assert!(bounded
+                .into_formula()
+                .is_none_or(|formula| formula.size() <= 1));
+        }
+
+        let mut unsolvable = sample.clone();
+        unsolvable.negative_traces.push(vec![[true, true]]);
+        for learner in &learners {
+            assert_eq!(
+                learner.learn(&unsolvable, Budget::default()),
+                LearnResult::Unsolvable
+            );
+        }
+    }
+
+    #[test]
+    fn lassos() {
+        use crate::annealing::*;
+        use crate::local::*;
+        use rand::{rngs::StdRng, SeedableRng};
+
+        // ¬x0, with enough lassos for consistency to outweigh the size of the formula in its fitness.
+        let sample = Sample::<2> {
+            positive_lassos: vec![
+                Lasso::new(vec![[false, false]], vec![[true, false]]),
+                Lasso::new(vec![[false, true]], vec![[true, true]]),
+                Lasso::new(vec![], vec![[false, false]]),
+            ],
+            negative_lassos: vec![
+                Lasso::new(vec![], vec![[true, false], [false, false]]),
+                Lasso::new(vec![[true, true]], vec![[false, true]]),
+                Lasso::new(vec![[true, false]], vec![[true, false]]),
+            ],
+            ..Sample::default()
+        };
+        let formula = GeneticLearner::new(2).iterations(2).learn(&sample);
+        assert!(sample.is_consistent(&formula));
+        let mut rng = StdRng::seed_from_u64(0);
+        let (formula, _) = AnnealingLearner::new(500)
+            .anneal(&sample, None, &mut rng)
+            .expect("formula");
+        assert!(sample.is_consistent(&formula));
+        let (formula, errors) = LocalSearchLearner::new(200)
+            .walk(&sample, None, &mut rng)
+            .expect("formula");
+        assert_eq!(errors, 0);
+        assert!(sample.is_consistent(&formula));
+    }
+}
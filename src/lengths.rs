@@ -0,0 +1,200 @@
+use crate::syntax::*;
+use crate::trace::*;
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+/// How to deal with the (finite) traces of a sample having different lengths,
+/// as formulae are evaluated differently close to the end of a trace (e.g., `X p` is false at the last position).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum LengthPolicy {
+    /// Traces are kept as they are, with finite-trace semantics at their end.
+    #[default]
+    Natural,
+    /// Traces are padded to the length of the longest one, by repeating their last state.
+    Pad,
+    /// Traces are truncated to the length of the shortest one.
+    Truncate,
+    /// Traces are kept as they are, but formulae looking further ahead than the shortest trace are rejected
+    /// (see [`Sample::check_depth`]).
+    Strict,
+}
+
+impl LengthPolicy {
+    pub fn is_natural(&self) -> bool {
+        *self == LengthPolicy::Natural
+    }
+}
+
+impl FromStr for LengthPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "natural" => Ok(LengthPolicy::Natural),
+            "pad" => Ok(LengthPolicy::Pad),
+            "truncate" => Ok(LengthPolicy::Truncate),
+            "strict" => Ok(LengthPolicy::Strict),
+            _ => Err(format!("unknown length policy: {s}")),
+        }
+    }
+}
+
+/// A formula looks further ahead than the shortest trace of a sample with the strict length policy.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DepthError {
+    /// The temporal depth of the formula.
+    pub depth: usize,
+    /// The length of the shortest trace.
+    pub shortest: usize,
+}
+
+impl fmt::Display for DepthError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "formula has temporal depth {}, but the shortest trace has length {}",
+            self.depth, self.shortest
+        )
+    }
+}
+
+impl Error for DepthError {}
+
+impl SyntaxTree {
+    /// The maximum number of nested temporal operators in the formula.
+    pub fn temporal_depth(&self) -> usize {
+        match self {
+            SyntaxTree::Atom(_) => 0,
+            SyntaxTree::Not(branch) => branch.temporal_depth(),
+            SyntaxTree::Next(branch)
+            | SyntaxTree::Globally(branch)
+            | SyntaxTree::Finally(branch) => branch.temporal_depth() + 1,
+            SyntaxTree::And(left_branch, right_branch)
+            | SyntaxTree::Or(left_branch, right_branch)
+            | SyntaxTree::Implies(left_branch, right_branch) => left_branch
+                .temporal_depth()
+                .max(right_branch.temporal_depth()),
+            SyntaxTree::Until(left_branch, right_branch) => {
+                left_branch
+                    .temporal_depth()
+                    .max(right_branch.temporal_depth())
+                    + 1
+            }
+        }
+    }
+}
+
+impl<const N: usize> Sample<N> {
+    /// The length of the shortest (finite) trace, if any.
+    pub fn shortest_trace(&self) -> Option<usize> {
+        self.positive_traces
+            .iter()
+            .chain(self.negative_traces.iter())
+            .map(Vec::len)
+            .min()
+    }
+
+    /// Applies the length policy of the sample, returning a copy where traces are padded or truncated as required.
+    /// Learning algorithms only see the traces as they are, so this is to be called before learning.
+    /// Padding or truncating can make a positive and a negative trace identical, and the sample unsolvable.
+    pub fn apply_length_policy(&self) -> Sample<N> {
+        let mut sample = self.clone();
+        let traces = sample
+            .positive_traces
+            .iter_mut()
+            .chain(sample.negative_traces.iter_mut());
+        match self.length_policy {
+            LengthPolicy::Natural | LengthPolicy::Strict => {}
+            LengthPolicy::Pad => {
+                let longest = self
+                    .positive_traces
+                    .iter()
+                    .chain(self.negative_traces.iter())
+                    .map(Vec::len)
+                    .max()
+                    .unwrap_or(0);
+                for trace in traces {
+                    if let Some(last) = trace.last().copied() {
+                        trace.resize(longest, last);
+                    }
+                }
+            }
+            LengthPolicy::Truncate => {
+                let shortest = self.shortest_trace().unwrap_or(0);
+                for trace in traces {
+                    trace.truncate(shortest);
+                }
+            }
+        }
+        sample
+    }
+
+    /// Under the strict length policy, checks that the temporal depth of the formula
+    /// does not exceed the length of the shortest trace,
+    /// so that its truth value does not depend on the implicit semantics at the end of traces.
+    /// Other policies accept every formula.
+    pub fn check_depth(&self, formula: &SyntaxTree) -> Result<(), DepthError> {
+        if self.length_policy != LengthPolicy::Strict {
+            return Ok(());
+        }
+        let depth = formula.temporal_depth();
+        match self.shortest_trace() {
+            Some(shortest) if depth > shortest => Err(DepthError { depth, shortest }),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod length_policies {
+    use super::*;
+    use std::sync::Arc;
+
+    fn sample(length_policy: LengthPolicy) -> Sample<1> {
+        Sample {
+            positive_traces: vec![vec![[true], [false]]],
+            negative_traces: vec![vec![[false], [true], [true]]],
+            length_policy,
+            ..Sample::default()
+        }
+    }
+
+    #[test]
+    fn pad_and_truncate() {
+        let padded = sample(LengthPolicy::Pad).apply_length_policy();
+        assert_eq!(padded.positive_traces, vec![vec![[true], [false], [false]]]);
+        assert_eq!(
+            padded.negative_traces,
+            sample(LengthPolicy::Pad).negative_traces
+        );
+
+        let truncated = sample(LengthPolicy::Truncate).apply_length_policy();
+        assert_eq!(truncated.negative_traces, vec![vec![[false], [true]]]);
+        assert_eq!(
+            sample(LengthPolicy::Natural)
+                .apply_length_policy()
+                .negative_traces,
+            sample(LengthPolicy::Natural).negative_traces
+        );
+    }
+
+    #[test]
+    fn strict() {
+        // X X x0
+        let formula = SyntaxTree::Next(Arc::new(SyntaxTree::Next(Arc::new(SyntaxTree::Atom(0)))));
+        assert_eq!(formula.temporal_depth(), 2);
+        assert!(sample(LengthPolicy::Strict).check_depth(&formula).is_ok());
+
+        let deeper = SyntaxTree::Finally(Arc::new(formula));
+        assert_eq!(
+            sample(LengthPolicy::Strict).check_depth(&deeper),
+            Err(DepthError {
+                depth: 3,
+                shortest: 2
+            })
+        );
+        assert!(sample(LengthPolicy::Natural).check_depth(&deeper).is_ok());
+    }
+}
@@ -39,26 +39,229 @@
 //! use learn_ltl::Sample;
 //!
 //! let sample = Sample {
-//!     var_names: Sample::var_names(),
 //!     positive_traces: vec![vec![[true, true]]],
 //!     negative_traces: vec![
 //!         vec![[false, true]],
 //!         vec![[true, false]],
 //!         vec![[false, false]],
 //!     ],
+//!     ..Sample::default()
 //! };
 //!
 //! assert!(sample.is_consistent(&and));
 //! assert!(!sample.is_consistent(&and));
 //! ```
 
+mod active;
+
+mod analysis;
+
+mod annealing;
+
+mod anytime;
+
+mod augment;
+
+mod balance;
+
+mod beam;
+
+mod binary;
+
+mod builder;
+
+mod cache;
+
+mod chance;
+
+mod common;
+
+mod config;
+
+mod consistency;
+
+mod constraint;
+
+mod contrast;
+
+mod cost;
+
+mod decision;
+
+mod dfa;
+
+mod diff;
+
+mod ensemble;
+
+#[cfg(feature = "equivalence")]
+mod equivalence;
+
+mod evaluation;
+
+mod eventlog;
+
+mod format;
+
+mod generate;
+
+mod generation;
+
+mod genetic;
+
+mod grammar;
+
+mod hybrid;
+
+mod incremental;
+
+mod index;
+
+mod islands;
+
 mod learn;
 
+mod learner;
+
+mod lengths;
+
+mod load;
+
+mod local;
+
+#[cfg(all(feature = "mmap", unix))]
+mod mapped;
+
+mod markov;
+
+mod mcts;
+
+mod merge;
+
+mod metadata;
+
+mod migrate;
+
+mod minimize;
+
+mod mining;
+
+mod noise;
+
+mod observer;
+
+mod order;
+
+mod pareto;
+
+mod parser;
+
+mod past;
+
+mod positive;
+
+mod predicate;
+
+mod pruning;
+
+mod refine;
+
+mod relevance;
+
+mod rename;
+
+mod rle;
+
+#[cfg(feature = "sat")]
+mod sat;
+
+mod sketch;
+
+mod soft;
+
+mod source;
+
+mod stats;
+
+mod stream;
+
 /// This module contains the definition of
 mod syntax;
 
+mod timed;
+
 mod trace;
 
+mod tracefile;
+
+mod window;
+
+pub use active::*;
+pub use analysis::*;
+pub use annealing::*;
+pub use anytime::*;
+pub use augment::*;
+pub use balance::*;
+pub use beam::*;
+pub use binary::*;
+pub use builder::*;
+pub use cache::*;
+pub use chance::*;
+pub use common::*;
+pub use config::*;
+pub use constraint::*;
+pub use contrast::*;
+pub use cost::*;
+pub use decision::*;
+pub use dfa::*;
+pub use diff::*;
+pub use ensemble::*;
+#[cfg(feature = "equivalence")]
+pub use equivalence::*;
+pub use evaluation::*;
+pub use eventlog::*;
+pub use format::*;
+pub use generate::*;
+pub use generation::*;
+pub use genetic::*;
+pub use grammar::*;
+pub use hybrid::*;
+pub use incremental::*;
+pub use index::*;
+pub use islands::*;
 pub use learn::*;
+pub use learner::*;
+pub use lengths::*;
+pub use load::*;
+pub use local::*;
+#[cfg(all(feature = "mmap", unix))]
+pub use mapped::*;
+pub use markov::*;
+pub use mcts::*;
+pub use merge::*;
+pub use metadata::*;
+pub use migrate::*;
+pub use mining::*;
+pub use noise::*;
+pub use observer::*;
+pub use order::*;
+pub use pareto::*;
+pub use parser::*;
+pub use past::*;
+pub use positive::*;
+pub use predicate::*;
+pub use pruning::*;
+pub use rename::*;
+pub use rle::*;
+#[cfg(feature = "sat")]
+pub use sat::*;
+pub use sketch::*;
+pub use soft::*;
+pub use source::*;
+pub use stats::*;
+pub use stream::*;
 pub use syntax::*;
+pub use timed::*;
 pub use trace::*;
+pub use tracefile::*;
+pub use window::*;
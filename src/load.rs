@@ -0,0 +1,437 @@
+use crate::binary::*;
+use crate::builder::*;
+use crate::config::*;
+use crate::syntax::*;
+use crate::trace::*;
+use crate::tracefile::*;
+use serde::de::{self, DeserializeOwned, Deserializer, SeqAccess, Visitor};
+use serde::Deserialize;
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// The maximum number of propositional variables of a sample loaded through [`visit_sample`].
+pub const MAX_VARS: usize = 38;
+
+/// The file formats supported for samples.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SampleFormat {
+    Ron,
+    Json,
+    /// The plain-text format of the benchmarks of other LTL learners (see [`Sample::read_trace_file`]),
+    /// which can only hold samples.
+    Trace,
+}
+
+impl SampleFormat {
+    /// Detects the format of a sample file from its extension.
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension() {
+            Some(ext) if ext == "ron" => Some(SampleFormat::Ron),
+            Some(ext) if ext == "json" => Some(SampleFormat::Json),
+            Some(ext) if ext == "trace" => Some(SampleFormat::Trace),
+            _ => None,
+        }
+    }
+
+    /// Deserializes the contents of a file in this format.
+    /// Errors are reported as [`SyntaxError`]s, with the position where they occur.
+    /// Trace files are not a serialization format, so they cannot be parsed this way.
+    pub fn parse<T: DeserializeOwned>(self, contents: &[u8]) -> io::Result<T> {
+        let err = match self {
+            SampleFormat::Ron => match ron::de::from_bytes(contents) {
+                Ok(value) => return Ok(value),
+                Err(err) => SyntaxError {
+                    line: err.position.line,
+                    column: err.position.col,
+                    message: err.code.to_string(),
+                },
+            },
+            SampleFormat::Json => match serde_json::from_slice(contents) {
+                Ok(value) => return Ok(value),
+                Err(err) => {
+                    // The message of JSON errors ends with their position.
+                    let position = format!(" at line {} column {}", err.line(), err.column());
+                    let message = err.to_string();
+                    SyntaxError {
+                        line: err.line(),
+                        column: err.column(),
+                        message: message
+                            .strip_suffix(&position)
+                            .unwrap_or(&message)
+                            .to_string(),
+                    }
+                }
+            },
+            SampleFormat::Trace => {
+                return Err(io::Error::new(
+                    io::ErrorKind::Unsupported,
+                    "trace files can only be read as samples",
+                ))
+            }
+        };
+        Err(io::Error::new(io::ErrorKind::InvalidData, err))
+    }
+}
+
+/// A file could not be parsed, because it is malformed or does not have the expected structure.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SyntaxError {
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+impl fmt::Display for SyntaxError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "line {}, column {}: {}",
+            self.line, self.column, self.message
+        )
+    }
+}
+
+impl Error for SyntaxError {}
+
+fn format_of(path: &Path) -> io::Result<SampleFormat> {
+    SampleFormat::from_path(path).ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::Unsupported,
+            "file format unknown or not supported",
+        )
+    })
+}
+
+impl<const N: usize> Sample<N> {
+    /// Loads a sample from file, in the format given by the file extension.
+    /// Samples in binary format (see [`Sample::write_binary`]) are recognized whatever the extension.
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let contents = fs::read(path)?;
+        if is_binary_sample(&contents) {
+            return Sample::read_binary(contents.as_slice());
+        }
+        let format = format_of(path)?;
+        if format == SampleFormat::Trace {
+            return Sample::read_trace_file(&contents);
+        }
+        format
+            .parse(&contents)
+            .map_err(|err| {
+                // Look for traces of the wrong width, which are reported more clearly than parsing errors.
+                match format
+                    .parse::<SampleWidth>(&contents)
+                    .map(|width| width.validate(Some(N)))
+                {
+                    Ok(Err(invalid)) => invalid_sample(invalid),
+                    _ => err,
+                }
+            })
+            .and_then(Sample::check_cycles)
+    }
+
+    /// Parses a sample in the given format, or in binary format if recognized as such.
+    fn parse(contents: &[u8], format: SampleFormat) -> io::Result<Self> {
+        if is_binary_sample(contents) {
+            Sample::read_binary(contents)
+        } else if format == SampleFormat::Trace {
+            Sample::read_trace_file(contents)
+        } else {
+            format.parse(contents).and_then(Sample::check_cycles)
+        }
+    }
+
+    /// Rejects lassos with an empty cycle, which deserialization lets through.
+    fn check_cycles(self) -> io::Result<Self> {
+        for (label, lassos) in [
+            (Label::Positive, &self.positive_lassos),
+            (Label::Negative, &self.negative_lassos),
+        ] {
+            if let Some(lasso) = lassos.iter().position(|lasso| lasso.cycle.is_empty()) {
+                return Err(invalid_sample(SampleError::EmptyCycle { label, lasso }));
+            }
+        }
+        Ok(self)
+    }
+}
+
+/// Loads a list of formulae from file, in the format (`ron` or `json`) given by the file extension.
+/// Files with `ltl` extension are read as plain text, with a formula per line in standard LTL syntax
+/// (as parsed by `SyntaxTree::from_str`); empty lines and lines starting with `#` are skipped.
+pub fn load_formulas(path: &Path) -> io::Result<Vec<SyntaxTree>> {
+    if matches!(path.extension(), Some(ext) if ext == "ltl") {
+        return fs::read_to_string(path)?
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(|line| {
+                line.parse()
+                    .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+            })
+            .collect();
+    }
+    let contents = fs::read(path)?;
+    format_of(path)?.parse(&contents)
+}
+
+impl GeneticConfig {
+    /// Loads the parameters of a genetic algorithm from file, in the format given by the file extension (RON or JSON).
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let contents = fs::read(path)?;
+        format_of(path)?.parse(&contents)
+    }
+}
+
+/// An operation over a sample whose number of variables is only known at runtime.
+/// As `Sample<N>` is generic over the (`const`) number of variables,
+/// the operation has to be generic over it as well.
+pub trait SampleVisitor {
+    type Output;
+
+    fn visit<const N: usize>(self, sample: Sample<N>) -> Self::Output;
+}
+
+/// Loads a sample from file, in the format given by the file extension,
+/// and applies the visitor to it.
+/// The number of variables is read from the file, up to [`MAX_VARS`].
+/// Samples in binary format (see [`Sample::write_binary`]) are recognized whatever the extension.
+pub fn visit_sample<V: SampleVisitor>(path: &Path, visitor: V) -> io::Result<V::Output> {
+    let contents = fs::read(path)?;
+    if is_binary_sample(&contents) {
+        return visit_sample_contents(&contents, SampleFormat::Ron, visitor);
+    }
+    visit_sample_contents(&contents, format_of(path)?, visitor)
+}
+
+/// Parses a sample and applies the visitor to it.
+/// The number of variables is read from the sample, up to [`MAX_VARS`].
+/// Samples in binary format are recognized, and parsed as such regardless of the given format.
+pub fn visit_sample_contents<V: SampleVisitor>(
+    contents: &[u8],
+    format: SampleFormat,
+    visitor: V,
+) -> io::Result<V::Output> {
+    let vars = if is_binary_sample(contents) {
+        binary_sample_vars(contents)?
+    } else if format == SampleFormat::Trace {
+        trace_file_vars(contents)?
+    } else {
+        format
+            .parse::<SampleWidth>(contents)?
+            .validate(None)
+            .map_err(invalid_sample)?
+    };
+
+    // Dispatch to the monomorphized version for the right number of variables.
+    macro_rules! dispatch {
+        ($($n:literal)*) => {
+            match vars {
+                $($n => Ok(visitor.visit(Sample::<$n>::parse(contents, format)?)),)*
+                _ => Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("sample has {vars} variables, but at most {MAX_VARS} are supported"),
+                )),
+            }
+        };
+    }
+
+    dispatch!(0 1 2 3 4 5 6 7 8 9 10 11 12 13 14 15 16 17 18 19 20 21 22 23 24 25 26 27 28 29 30 31 32 33 34 35 36 37 38)
+}
+
+fn invalid_sample(err: SampleError) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, format!("invalid sample: {err}"))
+}
+
+/// Skims a sample, recording only the number of variables of its traces.
+#[derive(Debug, Default, Deserialize)]
+struct SampleWidth {
+    #[serde(default)]
+    var_names: Option<Width>,
+    positive_traces: Vec<Vec<Width>>,
+    negative_traces: Vec<Vec<Width>>,
+    #[serde(default)]
+    positive_lassos: Vec<LassoWidth>,
+    #[serde(default)]
+    negative_lassos: Vec<LassoWidth>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct LassoWidth {
+    prefix: Vec<Width>,
+    cycle: Vec<Width>,
+}
+
+impl SampleWidth {
+    /// Checks that all states have the same number of variables, that no trace is empty,
+    /// and that no lasso has an empty cycle, returning the number of variables.
+    /// The expected number of variables is the given one, if any,
+    /// or that declared by the variable names, if given,
+    /// or that of the first state of the sample.
+    fn validate(&self, expected: Option<usize>) -> Result<usize, SampleError> {
+        let first_state = self
+            .positive_traces
+            .iter()
+            .chain(self.negative_traces.iter())
+            .flatten()
+            .chain(
+                self.positive_lassos
+                    .iter()
+                    .chain(self.negative_lassos.iter())
+                    .flat_map(|lasso| lasso.prefix.iter().chain(lasso.cycle.iter())),
+            )
+            .next();
+        let expected = expected
+            .or(self.var_names.as_ref().map(|names| names.0))
+            .or(first_state.map(|state| state.0))
+            .unwrap_or(0);
+
+        if let Some(names) = &self.var_names {
+            if names.0 != expected {
+                return Err(SampleError::VarNamesMismatch {
+                    expected,
+                    found: names.0,
+                });
+            }
+        }
+
+        for (label, traces) in [
+            (Label::Positive, &self.positive_traces),
+            (Label::Negative, &self.negative_traces),
+        ] {
+            for (trace, states) in traces.iter().enumerate() {
+                if states.is_empty() {
+                    return Err(SampleError::EmptyTrace { label, trace });
+                }
+                if let Some((time, state)) = states
+                    .iter()
+                    .enumerate()
+                    .find(|(_, state)| state.0 != expected)
+                {
+                    return Err(SampleError::StateWidth {
+                        label,
+                        trace,
+                        time,
+                        expected,
+                        found: state.0,
+                    });
+                }
+            }
+        }
+        for (label, lassos) in [
+            (Label::Positive, &self.positive_lassos),
+            (Label::Negative, &self.negative_lassos),
+        ] {
+            for (lasso, states) in lassos.iter().enumerate() {
+                if states.cycle.is_empty() {
+                    return Err(SampleError::EmptyCycle { label, lasso });
+                }
+                if let Some((position, state)) = states
+                    .prefix
+                    .iter()
+                    .chain(states.cycle.iter())
+                    .enumerate()
+                    .find(|(_, state)| state.0 != expected)
+                {
+                    return Err(SampleError::LassoStateWidth {
+                        label,
+                        lasso,
+                        position,
+                        expected,
+                        found: state.0,
+                    });
+                }
+            }
+        }
+
+        Ok(expected)
+    }
+}
+
+/// The number of elements of a sequence, ignoring their values.
+#[derive(Debug, Default)]
+struct Width(usize);
+
+impl<'de> Deserialize<'de> for Width {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct WidthVisitor;
+
+        impl<'de> Visitor<'de> for WidthVisitor {
+            type Value = Width;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                write!(formatter, "a sequence")
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Width, A::Error> {
+                let mut width = 0;
+                while seq.next_element::<de::IgnoredAny>()?.is_some() {
+                    width += 1;
+                }
+                Ok(Width(width))
+            }
+        }
+
+        deserializer.deserialize_any(WidthVisitor)
+    }
+}
+
+#[cfg(test)]
+mod dispatch {
+    use super::*;
+
+    struct CountVars;
+
+    impl SampleVisitor for CountVars {
+        type Output = usize;
+
+        fn visit<const N: usize>(self, _sample: Sample<N>) -> Self::Output {
+            N
+        }
+    }
+
+    #[test]
+    fn ron() {
+        let contents =
+            b"(positive_traces: [[(true, false, true)]], negative_traces: [[(false, false, true)]])";
+        let vars = visit_sample_contents(contents, SampleFormat::Ron, CountVars).expect("visit");
+        assert_eq!(vars, 3);
+    }
+
+    #[test]
+    fn json() {
+        let contents = br#"{"positive_traces": [[[true, false]]], "negative_traces": []}"#;
+        let vars = visit_sample_contents(contents, SampleFormat::Json, CountVars).expect("visit");
+        assert_eq!(vars, 2);
+    }
+
+    #[test]
+    fn diagnostics() {
+        let contents = b"(positive_traces: [[(true, false)]], negative_traces: [[(false, false)], [(true, false, true)]])";
+        let err = visit_sample_contents(contents, SampleFormat::Ron, CountVars).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "invalid sample: Negative trace 1 has 3 variables at time 0, but 2 were expected"
+        );
+
+        let contents =
+            b"(positive_traces: [[(true, false)]],\n negative_traces: [[(false false)]])";
+        let err = visit_sample_contents(contents, SampleFormat::Ron, CountVars).unwrap_err();
+        let err = err
+            .into_inner()
+            .expect("error source")
+            .downcast::<SyntaxError>()
+            .expect("syntax error");
+        assert_eq!((err.line, err.column), (2, 28));
+
+        let contents = b"(positive_traces: [[(true, false)]], negative_traces: [], positive_lassos: [(prefix: [(true, true)], cycle: [])])";
+        let err = visit_sample_contents(contents, SampleFormat::Ron, CountVars).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "invalid sample: Positive lasso 0 has an empty cycle"
+        );
+        let err = Sample::<2>::parse(contents, SampleFormat::Ron).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+}
@@ -0,0 +1,270 @@
+use crate::annealing::*;
+use crate::learn::*;
+use crate::learner::*;
+use crate::syntax::*;
+use crate::trace::*;
+use rand::Rng;
+use std::time::Instant;
+
+/// A finite or lasso trace of a sample.
+enum Example<'a, const N: usize> {
+    Finite(&'a Trace<N>),
+    Lasso(&'a Lasso<N>),
+}
+
+/// The traces of a sample with their labels, positive ones first, to evaluate formulas on them in any order.
+struct Labelled<'a, const N: usize> {
+    traces: Vec<(Example<'a, N>, bool)>,
+}
+
+impl<const N: usize> Labelled<'_, N> {
+    /// The indices of the traces the formula misclassifies.
+    fn misclassified(&self, formula: &SyntaxTree) -> Vec<usize> {
+        (0..self.traces.len())
+            .filter(|index| !self.classifies(formula, *index))
+            .collect()
+    }
+
+    /// Whether the formula classifies the trace at the given index correctly.
+    fn classifies(&self, formula: &SyntaxTree, index: usize) -> bool {
+        let (example, label) = &self.traces[index];
+        match example {
+            Example::Finite(trace) => formula.eval(trace) == *label,
+            Example::Lasso(lasso) => formula.eval_lasso(lasso) == *label,
+        }
+    }
+
+    /// The number of traces the formula misclassifies, or `None` as soon as it exceeds the limit,
+    /// so that hopeless candidates are discarded without evaluating them on every trace.
+    fn errors_up_to(&self, formula: &SyntaxTree, limit: usize) -> Option<usize> {
+        let mut errors = 0;
+        for index in 0..self.traces.len() {
+            if !self.classifies(formula, index) {
+                errors += 1;
+                if errors > limit {
+                    return None;
+                }
+            }
+        }
+        Some(errors)
+    }
+}
+
+/// Learns formulas by stochastic local search, in the style of WalkSAT:
+/// starting from a random formula of a given size, each flip picks a trace the current formula misclassifies,
+/// and moves to the neighbour (see [`neighbour`]) classifying it correctly with the fewest misclassifications overall,
+/// or, with the probability given by the noise, to a random neighbour, to escape local optima.
+/// The search restarts from a new random formula after a number of flips without finding a consistent formula.
+///
+/// Unlike [`AnnealingLearner`], moves are guided by the misclassified traces rather than by random trials.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LocalSearchLearner {
+    /// The size of the initial formulas.
+    pub size: usize,
+    /// The number of flips before restarting.
+    pub flips: usize,
+    /// The number of restarts, the first search included.
+    pub restarts: usize,
+    /// The probability of moving to a random neighbour instead of the best one.
+    pub noise: f64,
+    /// The number of random neighbours considered at each flip.
+    pub neighbours: usize,
+    /// The maximum size of the formulas, if any: larger neighbours are never visited.
+    pub max_size: Option<usize>,
+}
+
+impl Default for LocalSearchLearner {
+    fn default() -> Self {
+        LocalSearchLearner::new(1000)
+    }
+}
+
+impl LocalSearchLearner {
+    /// A learner restarting 10 times after the given number of flips, from formulas of size 3.
+    pub fn new(flips: usize) -> Self {
+        LocalSearchLearner {
+            size: 3,
+            flips,
+            restarts: 10,
+            noise: 0.2,
+            neighbours: 20,
+            max_size: None,
+        }
+    }
+
+    pub fn size(mut self, size: usize) -> Self {
+        self.size = size;
+        self
+    }
+
+    pub fn restarts(mut self, restarts: usize) -> Self {
+        self.restarts = restarts;
+        self
+    }
+
+    pub fn noise(mut self, noise: f64) -> Self {
+        self.noise = noise;
+        self
+    }
+
+    pub fn neighbours(mut self, neighbours: usize) -> Self {
+        self.neighbours = neighbours;
+        self
+    }
+
+    pub fn max_size(mut self, max_size: usize) -> Self {
+        self.max_size = Some(max_size);
+        self
+    }
+
+    /// Searches for the configured number of flips and restarts, or until the deadline,
+    /// returning the formula visited with the fewest misclassified traces (the smallest one among those),
+    /// with their number, or `None` if there is no initial formula, e.g., because the sample allows no variables.
+    /// The search stops at the first formula misclassifying no trace.
+    pub fn walk<const N: usize, R: Rng + ?Sized>(
+        &self,
+        sample: &Sample<N>,
+        deadline: Option<Instant>,
+        rng: &mut R,
+    ) -> Option<(SyntaxTree, usize)> {
+        let labelled = Labelled {
+            traces: sample
+                .positive_traces
+                .iter()
+                .map(|trace| (Example::Finite(trace), true))
+                .chain(
+                    sample
+                        .positive_lassos
+                        .iter()
+                        .map(|lasso| (Example::Lasso(lasso), true)),
+                )
+                .chain(
+                    sample
+                        .negative_traces
+                        .iter()
+                        .map(|trace| (Example::Finite(trace), false)),
+                )
+                .chain(
+                    sample
+                        .negative_lassos
+                        .iter()
+                        .map(|lasso| (Example::Lasso(lasso), false)),
+                )
+                .collect(),
+        };
+        let vars = sample.vars();
+        let size = self.max_size.map_or(self.size, |max| self.size.min(max));
+        let initial = gen_formulae::<N>(size, &vars);
+        if initial.is_empty() {
+            return None;
+        }
+        let fits = |formula: &SyntaxTree| self.max_size.is_none_or(|max| formula.size() <= max);
+        let expired = || deadline.is_some_and(|deadline| Instant::now() >= deadline);
+
+        let mut best: Option<(SyntaxTree, usize)> = None;
+        for _ in 0..self.restarts.max(1) {
+            let mut current = initial[rng.gen_range(0..initial.len())].clone();
+            let mut misclassified = labelled.misclassified(&current);
+            for _ in 0..=self.flips {
+                let errors = misclassified.len();
+                if best.as_ref().is_none_or(|(formula, best_errors)| {
+                    (errors, current.size()) < (*best_errors, formula.size())
+                }) {
+                    best = Some((current.clone(), errors));
+                }
+                if errors == 0 || expired() {
+                    return best;
+                }
+
+                let target = misclassified[rng.gen_range(0..misclassified.len())];
+                let mut next = None;
+                if !rng.gen_bool(self.noise.clamp(0.0, 1.0)) {
+                    // Greedy move: among the neighbours fixing the target trace, the one with the fewest errors.
+                    let mut fewest = usize::MAX;
+                    for _ in 0..self.neighbours {
+                        let candidate = neighbour(&current, &vars, rng);
+                        if !fits(&candidate) || !labelled.classifies(&candidate, target) {
+                            continue;
+                        }
+                        if let Some(errors) = labelled.errors_up_to(&candidate, fewest) {
+                            if errors < fewest || next.is_none() {
+                                fewest = errors;
+                                next = Some(candidate);
+                            }
+                        }
+                    }
+                }
+                // Random walk, also when no neighbour considered fixes the target trace.
+                let next = next.unwrap_or_else(|| neighbour(&current, &vars, rng));
+                if fits(&next) {
+                    misclassified = labelled.misclassified(&next);
+                    current = next;
+                }
+            }
+        }
+        best
+    }
+}
+
+impl<const N: usize> Learner<N> for LocalSearchLearner {
+    /// Searches for the configured number of flips and restarts, or until the time is up,
+    /// never visiting formulas larger than the maximum size, and returns the best formula visited.
+    fn learn(&self, sample: &Sample<N>, budget: Budget) -> LearnResult {
+        if !sample.is_solvable() {
+            return LearnResult::Unsolvable;
+        }
+
+        let mut learner = *self;
+        if let Some(max) = budget.max_size {
+            learner.max_size = Some(self.max_size.map_or(max, |own| own.min(max)));
+        }
+        match learner.walk(sample, budget.deadline(), &mut rand::thread_rng()) {
+            Some((formula, _)) if sample.is_consistent(&formula) => {
+                LearnResult::Consistent(formula)
+            }
+            Some((formula, _)) => LearnResult::Approximate(formula),
+            None => LearnResult::Exhausted,
+        }
+    }
+}
+
+#[cfg(test)]
+mod local_search {
+    use super::*;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    #[test]
+    fn walk() {
+        // Positive traces are those where x1 eventually holds, and x0 holds until then.
+        let sample = Sample::<2> {
+            positive_traces: vec![
+                vec![[true, false], [true, true]],
+                vec![[false, true]],
+                vec![[true, false], [false, true], [false, false]],
+            ],
+            negative_traces: vec![
+                vec![[true, false], [false, false], [false, true]],
+                vec![[false, false], [false, true]],
+                vec![[true, false]],
+            ],
+            ..Sample::default()
+        };
+        let labelled = Labelled {
+            traces: vec![
+                (Example::Finite(&sample.positive_traces[0]), true),
+                (Example::Finite(&sample.negative_traces[0]), false),
+            ],
+        };
+        let x0 = SyntaxTree::Atom(0);
+        assert_eq!(labelled.misclassified(&x0), vec![1]);
+        assert_eq!(labelled.errors_up_to(&x0, 0), None);
+        assert_eq!(labelled.errors_up_to(&x0, 1), Some(1));
+
+        let mut rng = StdRng::seed_from_u64(0);
+        let learner = LocalSearchLearner::new(200).max_size(5);
+        let (formula, errors) = learner.walk(&sample, None, &mut rng).expect("formula");
+        assert!(formula.size() <= 5);
+        assert_eq!(errors, 0);
+        assert!(sample.is_consistent(&formula));
+    }
+}
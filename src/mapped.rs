@@ -0,0 +1,206 @@
+use crate::binary::*;
+use crate::source::*;
+use crate::syntax::*;
+use crate::trace::*;
+use std::borrow::Cow;
+use std::fs::File;
+use std::io;
+use std::os::unix::io::AsRawFd;
+use std::path::Path;
+
+/// A read-only memory mapping of a whole file.
+struct Mapping {
+    ptr: *mut libc::c_void,
+    len: usize,
+}
+
+// The mapping is read-only, so it can be shared between threads.
+unsafe impl Send for Mapping {}
+unsafe impl Sync for Mapping {}
+
+impl Mapping {
+    fn new(file: &File) -> io::Result<Self> {
+        let len = usize::try_from(file.metadata()?.len())
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "file too large to map"))?;
+        if len == 0 {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "empty file"));
+        }
+        // SAFETY: the file is mapped read-only and privately, and the mapping is checked for failure.
+        let ptr = unsafe {
+            libc::mmap(
+                std::ptr::null_mut(),
+                len,
+                libc::PROT_READ,
+                libc::MAP_PRIVATE,
+                file.as_raw_fd(),
+                0,
+            )
+        };
+        if ptr == libc::MAP_FAILED {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(Mapping { ptr, len })
+    }
+
+    fn bytes(&self) -> &[u8] {
+        // SAFETY: the mapping is valid for `len` bytes until dropped.
+        unsafe { std::slice::from_raw_parts(self.ptr as *const u8, self.len) }
+    }
+}
+
+impl Drop for Mapping {
+    fn drop(&mut self) {
+        // SAFETY: the mapping was created by `mmap` with the same length, and is not used anymore.
+        unsafe {
+            libc::munmap(self.ptr, self.len);
+        }
+    }
+}
+
+/// A sample in binary format (see [`Sample::write_binary`]) mapped in memory,
+/// whose traces are decoded one at a time every time they are iterated over.
+/// Only the position of each trace is kept in memory,
+/// so that learning algorithms (e.g., [`solve`](crate::solve)) can run on samples larger than the available memory,
+/// as the operating system loads and evicts the pages of the file as needed.
+///
+/// The file is validated when opened, and must not change afterwards.
+/// Lassos are not part of [`SampleSource`]s, so they are ignored.
+pub struct MappedSample<const N: usize> {
+    mapping: Mapping,
+    var_names: [String; N],
+    /// The byte offsets of the positive traces within the file.
+    positive_offsets: Vec<usize>,
+    /// The byte offsets of the negative traces within the file.
+    negative_offsets: Vec<usize>,
+}
+
+/// Skips the traces at the beginning of the given bytes, returning their offsets relative to `start`.
+fn scan_traces<const N: usize>(bytes: &mut &[u8], start: usize) -> io::Result<Vec<usize>> {
+    let total = bytes.len();
+    let count = read_u64(bytes)?;
+    let mut offsets = Vec::with_capacity(count.min(1 << 20));
+    for _ in 0..count {
+        offsets.push(start + total - bytes.len());
+        let len = read_u64(bytes)?;
+        let size = len
+            .checked_mul(state_bytes(N))
+            .filter(|size| *size <= bytes.len())
+            .ok_or_else(|| io::Error::new(io::ErrorKind::UnexpectedEof, "truncated trace"))?;
+        *bytes = &bytes[size..];
+    }
+    Ok(offsets)
+}
+
+impl<const N: usize> MappedSample<N> {
+    /// Maps a sample in binary format, scanning it once to validate it and find its traces.
+    pub fn open(path: &Path) -> io::Result<Self> {
+        let mapping = Mapping::new(&File::open(path)?)?;
+        let contents = mapping.bytes();
+        let mut bytes = contents;
+        let (_, vars) = read_header(&mut bytes)?;
+        if vars != N {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("sample has {vars} variables, but {N} were expected"),
+            ));
+        }
+        let mut var_names = Sample::<N>::var_names();
+        for name in var_names.iter_mut() {
+            *name = read_string(&mut bytes)?;
+        }
+        let start = contents.len() - bytes.len();
+        let positive_offsets = scan_traces::<N>(&mut bytes, start)?;
+        let start = contents.len() - bytes.len();
+        let negative_offsets = scan_traces::<N>(&mut bytes, start)?;
+
+        Ok(MappedSample {
+            mapping,
+            var_names,
+            positive_offsets,
+            negative_offsets,
+        })
+    }
+
+    pub fn var_names(&self) -> &[String; N] {
+        &self.var_names
+    }
+
+    fn trace(&self, offset: usize) -> Trace<N> {
+        read_trace(&mut &self.mapping.bytes()[offset..]).expect("trace validated when opened")
+    }
+}
+
+impl<const N: usize> SampleSource<N> for MappedSample<N> {
+    type Traces<'a> = Box<dyn Iterator<Item = Cow<'a, Trace<N>>> + 'a>;
+
+    fn iter_positive(&self) -> Self::Traces<'_> {
+        Box::new(
+            self.positive_offsets
+                .iter()
+                .map(|offset| Cow::Owned(self.trace(*offset))),
+        )
+    }
+
+    fn iter_negative(&self) -> Self::Traces<'_> {
+        Box::new(
+            self.negative_offsets
+                .iter()
+                .map(|offset| Cow::Owned(self.trace(*offset))),
+        )
+    }
+
+    fn positive_count(&self) -> usize {
+        self.positive_offsets.len()
+    }
+
+    fn negative_count(&self) -> usize {
+        self.negative_offsets.len()
+    }
+
+    fn vars(&self) -> Vec<Idx> {
+        // Variables whose name starts with `~` are discarded, as for in-memory samples.
+        (0..N)
+            .filter(|var| !self.var_names[*var].starts_with('~'))
+            .map(|var| var as Idx)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod mapped_samples {
+    use super::*;
+    use std::fs;
+    use std::sync::Arc;
+
+    #[test]
+    fn mapped_sample() {
+        let sample = Sample::<2> {
+            positive_traces: vec![vec![[true, false], [false, true]]],
+            negative_traces: vec![vec![[true, false], [true, false]], vec![[false, false]]],
+            ..Sample::default()
+        };
+        let path =
+            std::env::temp_dir().join(format!("learn_ltl_mapped_{}.bin", std::process::id()));
+        sample
+            .write_binary(fs::File::create(&path).expect("create file"))
+            .expect("write sample");
+
+        let mapped = MappedSample::<2>::open(&path).expect("map sample");
+        assert_eq!(mapped.positive_count(), 1);
+        assert_eq!(mapped.negative_count(), 2);
+        assert_eq!(
+            mapped
+                .iter_negative()
+                .map(Cow::into_owned)
+                .collect::<Vec<_>>(),
+            sample.negative_traces
+        );
+
+        // F x1
+        let formula = SyntaxTree::Finally(Arc::new(SyntaxTree::Atom(1)));
+        assert!(SampleSource::is_consistent(&mapped, &formula));
+        assert!(MappedSample::<3>::open(&path).is_err());
+
+        fs::remove_file(&path).expect("remove file");
+    }
+}
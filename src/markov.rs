@@ -0,0 +1,133 @@
+use crate::trace::*;
+use rand::distributions::{Distribution, WeightedIndex};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use serde_with::*;
+
+/// A Markov chain whose states are labelled with a valuation of the propositional variables,
+/// used to generate realistic (correlated) traces by random walks.
+/// A labelled transition system is a Markov chain where all transitions have the same weight.
+///
+/// States are given by their index, and transitions by adjacency lists of `(target, weight)` pairs,
+/// where weights need not be normalized.
+#[serde_as]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MarkovChain<const N: usize> {
+    /// The label of each state.
+    #[serde_as(as = "Vec<[_; N]>")]
+    pub labels: Vec<[bool; N]>,
+    /// For each state, the weighted transitions leaving it.
+    pub transitions: Vec<Vec<(usize, f64)>>,
+    /// The weighted initial states; if empty, walks start from a state chosen uniformly at random.
+    #[serde(default)]
+    pub initial: Vec<(usize, f64)>,
+}
+
+impl<const N: usize> MarkovChain<N> {
+    /// Adds a state with the given label and no transitions, returning its index.
+    pub fn add_state(&mut self, label: [bool; N]) -> usize {
+        self.labels.push(label);
+        self.transitions.push(Vec::new());
+        self.labels.len() - 1
+    }
+
+    /// Adds a transition between two existing states.
+    pub fn add_transition(&mut self, from: usize, to: usize, weight: f64) {
+        assert!(to < self.labels.len(), "unknown target state {to}");
+        assert!(weight > 0.0, "transition weights must be positive");
+        self.transitions[from].push((to, weight));
+    }
+
+    /// Makes the given state initial, with the given weight.
+    pub fn add_initial(&mut self, state: usize, weight: f64) {
+        assert!(state < self.labels.len(), "unknown initial state {state}");
+        assert!(weight > 0.0, "initial weights must be positive");
+        self.initial.push((state, weight));
+    }
+
+    /// Generates a trace by a random walk of the given length.
+    /// The walk stops early if it reaches a state with no outgoing transitions,
+    /// so the trace can be shorter than requested (and is empty if the chain has no states).
+    pub fn random_walk<R: Rng + ?Sized>(&self, length: usize, rng: &mut R) -> Trace<N> {
+        if self.labels.is_empty() || length == 0 {
+            return Vec::new();
+        }
+
+        let mut state = if self.initial.is_empty() {
+            rng.gen_range(0..self.labels.len())
+        } else {
+            weighted_choice(&self.initial, rng)
+        };
+        let mut trace = vec![self.labels[state]];
+        while trace.len() < length && !self.transitions[state].is_empty() {
+            state = weighted_choice(&self.transitions[state], rng);
+            trace.push(self.labels[state]);
+        }
+        trace
+    }
+}
+
+fn weighted_choice<R: Rng + ?Sized>(choices: &[(usize, f64)], rng: &mut R) -> usize {
+    let dist = WeightedIndex::new(choices.iter().map(|(_, weight)| *weight))
+        .expect("weights must be positive");
+    choices[dist.sample(rng)].0
+}
+
+#[cfg(test)]
+mod random_walks {
+    use super::*;
+    use crate::generate::*;
+    use crate::syntax::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use std::sync::Arc;
+
+    /// A request is always followed by a grant, which is followed by idling.
+    fn chain() -> MarkovChain<2> {
+        let mut chain = MarkovChain::default();
+        let idle = chain.add_state([false, false]);
+        let request = chain.add_state([true, false]);
+        let grant = chain.add_state([false, true]);
+        chain.add_initial(idle, 1.0);
+        chain.add_transition(idle, idle, 3.0);
+        chain.add_transition(idle, request, 1.0);
+        chain.add_transition(request, grant, 1.0);
+        chain.add_transition(grant, idle, 1.0);
+        chain
+    }
+
+    #[test]
+    fn random_walk() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let chain = chain();
+        // G(x0 -> X x1)
+        let formula = SyntaxTree::Globally(Arc::new(SyntaxTree::Implies(
+            Arc::new(SyntaxTree::Atom(0)),
+            Arc::new(SyntaxTree::Next(Arc::new(SyntaxTree::Atom(1)))),
+        )));
+
+        for _ in 0..10 {
+            let trace = chain.random_walk(10, &mut rng);
+            assert_eq!(trace.len(), 10);
+            assert_eq!(trace[0], [false, false]);
+            // A request at the very end of the trace cannot be followed by a grant.
+            assert!(formula.eval(&trace) || trace[9][0]);
+        }
+    }
+
+    #[test]
+    fn labelled_sample() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let chain = chain();
+        // F x1
+        let formula = SyntaxTree::Finally(Arc::new(SyntaxTree::Atom(1)));
+
+        let sample = SampleGenerator::new(&formula, 6)
+            .positives(3)
+            .negatives(1)
+            .generate_from(&mut rng, |rng| chain.random_walk(6, rng))
+            .expect("generate sample");
+        assert!(sample.is_consistent(&formula));
+        assert_eq!(sample.negative_traces, vec![vec![[false, false]; 6]]);
+    }
+}
@@ -0,0 +1,287 @@
+use crate::learner::*;
+use crate::syntax::*;
+use crate::trace::*;
+use rand::Rng;
+use std::sync::Arc;
+use std::time::Instant;
+
+/// Learns formulas by Monte Carlo Tree Search, building them top-down:
+/// a partial formula is the prefix of a formula in prefix notation, each step choosing the next operator or variable,
+/// and every partial formula is evaluated by the accuracy on the sample of its random completions (rollouts).
+/// The search tree grows towards the most rewarding choices, balancing them with unexplored ones (UCT),
+/// rather than enumerating every formula, so it reaches sizes out of reach of exhaustive enumeration,
+/// but with no guarantee of finding a solution, let alone the smallest one.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MctsLearner {
+    /// The number of rollouts.
+    pub iterations: usize,
+    /// The maximum size of the formulas, which the budget can lower.
+    pub max_size: usize,
+    /// The weight of exploration against exploitation in the choice of the next step.
+    pub exploration: f64,
+}
+
+impl Default for MctsLearner {
+    fn default() -> Self {
+        MctsLearner {
+            iterations: 10_000,
+            max_size: 10,
+            exploration: std::f64::consts::SQRT_2,
+        }
+    }
+}
+
+/// A step of the top-down construction of a formula.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Symbol {
+    Atom(Idx),
+    Not,
+    Next,
+    Globally,
+    Finally,
+    And,
+    Or,
+    Implies,
+    Until,
+}
+
+impl Symbol {
+    const OPERATORS: [Symbol; 8] = [
+        Symbol::Not,
+        Symbol::Next,
+        Symbol::Globally,
+        Symbol::Finally,
+        Symbol::And,
+        Symbol::Or,
+        Symbol::Implies,
+        Symbol::Until,
+    ];
+
+    fn arity(self) -> usize {
+        match self {
+            Symbol::Atom(_) => 0,
+            Symbol::Not | Symbol::Next | Symbol::Globally | Symbol::Finally => 1,
+            Symbol::And | Symbol::Or | Symbol::Implies | Symbol::Until => 2,
+        }
+    }
+}
+
+/// A partial formula, i.e., a node of the search tree.
+struct Node {
+    symbols: Vec<Symbol>,
+    /// The number of subformulas still missing.
+    holes: usize,
+    untried: Vec<Symbol>,
+    children: Vec<usize>,
+    visits: usize,
+    reward: f64,
+}
+
+impl Node {
+    fn new(symbols: Vec<Symbol>, holes: usize, max_size: usize, vars: &[Idx]) -> Self {
+        let untried = steps(symbols.len(), holes, max_size, vars);
+        Node {
+            symbols,
+            holes,
+            untried,
+            children: Vec::new(),
+            visits: 0,
+            reward: 0.0,
+        }
+    }
+}
+
+/// The possible next steps of a partial formula, such that it can still be completed within the maximum size.
+fn steps(len: usize, holes: usize, max_size: usize, vars: &[Idx]) -> Vec<Symbol> {
+    if holes == 0 {
+        return Vec::new();
+    }
+    let mut steps = vars
+        .iter()
+        .map(|var| Symbol::Atom(*var))
+        .collect::<Vec<_>>();
+    // Completing the formula takes at least one more variable per hole.
+    steps.extend(
+        Symbol::OPERATORS
+            .into_iter()
+            .filter(|symbol| len + holes + symbol.arity() <= max_size),
+    );
+    steps
+}
+
+/// Builds the formula whose prefix notation the symbols start with.
+fn build(symbols: &mut impl Iterator<Item = Symbol>) -> SyntaxTree {
+    let symbol = symbols.next().expect("complete formula");
+    let mut branch = || Arc::new(build(symbols));
+    match symbol {
+        Symbol::Atom(var) => SyntaxTree::Atom(var),
+        Symbol::Not => SyntaxTree::Not(branch()),
+        Symbol::Next => SyntaxTree::Next(branch()),
+        Symbol::Globally => SyntaxTree::Globally(branch()),
+        Symbol::Finally => SyntaxTree::Finally(branch()),
+        Symbol::And => SyntaxTree::And(branch(), branch()),
+        Symbol::Or => SyntaxTree::Or(branch(), branch()),
+        Symbol::Implies => SyntaxTree::Implies(branch(), branch()),
+        Symbol::Until => SyntaxTree::Until(branch(), branch()),
+    }
+}
+
+impl MctsLearner {
+    pub fn iterations(mut self, iterations: usize) -> Self {
+        self.iterations = iterations;
+        self
+    }
+
+    pub fn max_size(mut self, max_size: usize) -> Self {
+        self.max_size = max_size;
+        self
+    }
+
+    pub fn exploration(mut self, exploration: f64) -> Self {
+        self.exploration = exploration;
+        self
+    }
+
+    /// Searches for the configured number of rollouts, or until the deadline,
+    /// stopping at the first consistent formula, and returns the most accurate (and then smallest) formula found,
+    /// or `None` if the sample allows no variables.
+    pub fn search<const N: usize, R: Rng + ?Sized>(
+        &self,
+        sample: &Sample<N>,
+        deadline: Option<Instant>,
+        rng: &mut R,
+    ) -> Option<SyntaxTree> {
+        let vars = sample.vars();
+        if vars.is_empty() || self.max_size == 0 {
+            return None;
+        }
+
+        let mut nodes = vec![Node::new(Vec::new(), 1, self.max_size, &vars)];
+        let mut best: Option<(f64, SyntaxTree)> = None;
+        for _ in 0..self.iterations {
+            if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+                break;
+            }
+
+            // Selection of a partial formula with untried steps, following the best steps so far.
+            let mut path = vec![0];
+            let mut node = 0;
+            while nodes[node].untried.is_empty() && !nodes[node].children.is_empty() {
+                let parent_visits = (nodes[node].visits as f64).ln();
+                let uct = |child: &Node| {
+                    child.reward / child.visits as f64
+                        + self.exploration * (parent_visits / child.visits as f64).sqrt()
+                };
+                node = *nodes[node]
+                    .children
+                    .iter()
+                    .max_by(|&&left, &&right| uct(&nodes[left]).total_cmp(&uct(&nodes[right])))
+                    .expect("some child");
+                path.push(node);
+            }
+
+            // Expansion with an untried step.
+            if !nodes[node].untried.is_empty() {
+                let untried = &mut nodes[node].untried;
+                let symbol = untried.swap_remove(rng.gen_range(0..untried.len()));
+                let mut symbols = nodes[node].symbols.clone();
+                symbols.push(symbol);
+                let holes = nodes[node].holes - 1 + symbol.arity();
+                nodes.push(Node::new(symbols, holes, self.max_size, &vars));
+                let child = nodes.len() - 1;
+                nodes[node].children.push(child);
+                node = child;
+                path.push(node);
+            }
+
+            // Rollout, completing the formula at random.
+            let mut symbols = nodes[node].symbols.clone();
+            let mut holes = nodes[node].holes;
+            while holes > 0 {
+                let steps = steps(symbols.len(), holes, self.max_size, &vars);
+                let symbol = steps[rng.gen_range(0..steps.len())];
+                symbols.push(symbol);
+                holes = holes - 1 + symbol.arity();
+            }
+            let formula = build(&mut symbols.into_iter());
+            let reward = sample.accuracy(&formula);
+            let consistent = reward == 1.0 && sample.is_consistent(&formula);
+            if best.as_ref().is_none_or(|(best_reward, best_formula)| {
+                reward > *best_reward
+                    || (reward == *best_reward && formula.size() < best_formula.size())
+            }) {
+                best = Some((reward, formula));
+            }
+            if consistent {
+                break;
+            }
+
+            // Backpropagation.
+            for node in path {
+                nodes[node].visits += 1;
+                nodes[node].reward += reward;
+            }
+        }
+
+        best.map(|(_, formula)| formula)
+    }
+}
+
+impl<const N: usize> Learner<N> for MctsLearner {
+    /// Searches for the configured number of rollouts, or until the time is up,
+    /// and returns the most accurate formula found.
+    fn learn(&self, sample: &Sample<N>, budget: Budget) -> LearnResult {
+        if !sample.is_solvable() {
+            return LearnResult::Unsolvable;
+        }
+
+        let learner = budget
+            .max_size
+            .map_or(*self, |max| self.max_size(max.min(self.max_size)));
+        match learner.search(sample, budget.deadline(), &mut rand::thread_rng()) {
+            Some(formula) if sample.is_consistent(&formula) => LearnResult::Consistent(formula),
+            Some(formula) => LearnResult::Approximate(formula),
+            None => LearnResult::Exhausted,
+        }
+    }
+}
+
+#[cfg(test)]
+mod monte_carlo {
+    use super::*;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    #[test]
+    fn search() {
+        let symbols = [
+            Symbol::And,
+            Symbol::Atom(0),
+            Symbol::Finally,
+            Symbol::Atom(1),
+        ];
+        assert_eq!(
+            build(&mut symbols.into_iter()),
+            SyntaxTree::Atom(0).and(SyntaxTree::Atom(1).finally())
+        );
+
+        // Positive traces are those where x0 holds at first and x1 holds eventually.
+        let sample = Sample::<2> {
+            positive_traces: vec![
+                vec![[true, false], [false, true]],
+                vec![[true, true]],
+                vec![[true, false], [false, false], [true, true]],
+            ],
+            negative_traces: vec![
+                vec![[false, true], [true, false]],
+                vec![[true, false], [true, false]],
+                vec![[false, false]],
+            ],
+            ..Sample::default()
+        };
+        let mut rng = StdRng::seed_from_u64(0);
+        let learner = MctsLearner::default().max_size(5);
+        let formula = learner.search(&sample, None, &mut rng).expect("formula");
+        assert!(formula.size() <= 5);
+        assert!(sample.is_consistent(&formula));
+    }
+}
@@ -0,0 +1,181 @@
+use crate::metadata::*;
+use crate::trace::*;
+use std::collections::HashSet;
+use std::error::Error;
+use std::fmt;
+use std::hash::Hash;
+
+/// An error preventing two samples from being merged.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MergeError<const N: usize> {
+    /// The samples name their variables differently, so their traces are not comparable.
+    VarNamesMismatch,
+    /// Some traces (or lassos) are positive in one sample and negative in the other.
+    Conflicts {
+        traces: Vec<Trace<N>>,
+        lassos: Vec<Lasso<N>>,
+    },
+}
+
+impl<const N: usize> fmt::Display for MergeError<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MergeError::VarNamesMismatch => write!(f, "samples have different variable names"),
+            MergeError::Conflicts { traces, lassos } => write!(
+                f,
+                "{} traces and {} lassos are both positive and negative",
+                traces.len(),
+                lassos.len()
+            ),
+        }
+    }
+}
+
+impl<const N: usize> Error for MergeError<N> {}
+
+impl<const N: usize> Sample<N> {
+    /// Adds the traces and lassos of another sample to this one, skipping those already present with the same label.
+    /// If the samples have different variable names,
+    /// or some traces are positive in one sample and negative in the other,
+    /// the sample is left unchanged and the conflicts are returned.
+    pub fn merge(&mut self, other: &Sample<N>) -> Result<(), MergeError<N>> {
+        if self.var_names != other.var_names {
+            return Err(MergeError::VarNamesMismatch);
+        }
+
+        let traces = conflicts(
+            self.positive_traces.iter().chain(&other.positive_traces),
+            self.negative_traces.iter().chain(&other.negative_traces),
+        );
+        let lassos = conflicts(
+            self.positive_lassos.iter().chain(&other.positive_lassos),
+            self.negative_lassos.iter().chain(&other.negative_lassos),
+        );
+        if !traces.is_empty() || !lassos.is_empty() {
+            return Err(MergeError::Conflicts { traces, lassos });
+        }
+
+        let offset = |metadata: &MetadataMap, offset: usize| {
+            metadata
+                .iter()
+                .map(|(index, data)| (index + offset, data.clone()))
+                .collect::<Vec<_>>()
+        };
+        self.positive_metadata
+            .extend(offset(&other.positive_metadata, self.positive_traces.len()));
+        self.negative_metadata
+            .extend(offset(&other.negative_metadata, self.negative_traces.len()));
+        self.positive_traces
+            .extend(other.positive_traces.iter().cloned());
+        self.negative_traces
+            .extend(other.negative_traces.iter().cloned());
+        self.positive_lassos
+            .extend(other.positive_lassos.iter().cloned());
+        self.negative_lassos
+            .extend(other.negative_lassos.iter().cloned());
+        self.dedup();
+        Ok(())
+    }
+
+    /// Removes repeated traces (and lassos) with the same label, keeping their first occurrence
+    /// (and its metadata).
+    /// Returns how many were removed.
+    pub fn dedup(&mut self) -> usize {
+        let len = |sample: &Self| {
+            sample.positive_traces.len()
+                + sample.negative_traces.len()
+                + sample.positive_lassos.len()
+                + sample.negative_lassos.len()
+        };
+        let before = len(self);
+
+        let kept = dedup(&mut self.positive_traces);
+        self.positive_metadata = reindex(&self.positive_metadata, kept);
+        let kept = dedup(&mut self.negative_traces);
+        self.negative_metadata = reindex(&self.negative_metadata, kept);
+        dedup(&mut self.positive_lassos);
+        dedup(&mut self.negative_lassos);
+
+        before - len(self)
+    }
+}
+
+/// The distinct items appearing both among the positives and the negatives, in order of appearance.
+fn conflicts<'a, T: Clone + Eq + Hash + 'a>(
+    positives: impl Iterator<Item = &'a T>,
+    negatives: impl Iterator<Item = &'a T>,
+) -> Vec<T> {
+    let positives = positives.collect::<HashSet<_>>();
+    let mut found = HashSet::new();
+    negatives
+        .filter(|item| positives.contains(item) && found.insert(*item))
+        .cloned()
+        .collect()
+}
+
+/// Removes repeated items, returning the original indices of those kept.
+fn dedup<T: Clone + Eq + Hash>(items: &mut Vec<T>) -> Vec<usize> {
+    let mut seen = HashSet::new();
+    let kept = items
+        .iter()
+        .enumerate()
+        .filter(|(_, item)| seen.insert(*item))
+        .map(|(index, _)| index)
+        .collect::<Vec<_>>();
+    *items = kept.iter().map(|index| items[*index].clone()).collect();
+    kept
+}
+
+#[cfg(test)]
+mod merging {
+    use super::*;
+
+    #[test]
+    fn merge() {
+        let mut sample = Sample::<1> {
+            positive_traces: vec![vec![[true]], vec![[true]]],
+            negative_traces: vec![vec![[false]]],
+            ..Sample::default()
+        };
+        assert_eq!(sample.dedup(), 1);
+
+        let other = Sample::<1> {
+            positive_traces: vec![vec![[true]], vec![[true], [true]]],
+            negative_traces: vec![vec![[false], [false]]],
+            ..Sample::default()
+        };
+        sample.merge(&other).expect("merge samples");
+        assert_eq!(
+            sample.positive_traces,
+            vec![vec![[true]], vec![[true], [true]]]
+        );
+        assert_eq!(
+            sample.negative_traces,
+            vec![vec![[false]], vec![[false], [false]]]
+        );
+    }
+
+    #[test]
+    fn conflicts() {
+        let mut sample = Sample::<1> {
+            positive_traces: vec![vec![[true]]],
+            ..Sample::default()
+        };
+        let other = Sample::<1> {
+            positive_traces: vec![vec![[false]]],
+            negative_traces: vec![vec![[true]]],
+            ..Sample::default()
+        };
+
+        let err = sample.merge(&other).unwrap_err();
+        assert_eq!(
+            err,
+            MergeError::Conflicts {
+                traces: vec![vec![[true]]],
+                lassos: Vec::new(),
+            }
+        );
+        // The sample is unchanged.
+        assert_eq!(sample.positive_traces, vec![vec![[true]]]);
+    }
+}
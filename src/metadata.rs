@@ -0,0 +1,154 @@
+use crate::syntax::*;
+use crate::trace::*;
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// Free-form information about a trace, such as the file or run it comes from, or when it was recorded.
+pub type Metadata = BTreeMap<String, String>;
+
+/// The metadata of some traces, by trace index.
+pub type MetadataMap = BTreeMap<usize, Metadata>;
+
+/// Rearranges metadata after the traces have been rearranged,
+/// where the trace at index `i` is now the one that was at index `old_indices[i]`.
+pub(crate) fn reindex(
+    metadata: &MetadataMap,
+    old_indices: impl IntoIterator<Item = usize>,
+) -> MetadataMap {
+    if metadata.is_empty() {
+        return MetadataMap::new();
+    }
+    old_indices
+        .into_iter()
+        .enumerate()
+        .filter_map(|(new, old)| metadata.get(&old).map(|data| (new, data.clone())))
+        .collect()
+}
+
+/// A trace misclassified by a formula, with its metadata, to find out where it comes from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Counterexample<'a> {
+    pub label: Label,
+    pub index: usize,
+    pub metadata: Option<&'a Metadata>,
+}
+
+impl fmt::Display for Counterexample<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?} trace {}", self.label, self.index)?;
+        if let Some(metadata) = self.metadata {
+            let entries = metadata
+                .iter()
+                .map(|(key, value)| format!("{key}: {value}"))
+                .collect::<Vec<_>>();
+            write!(f, " ({})", entries.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+impl<const N: usize> Sample<N> {
+    /// The metadata of the given trace, if any.
+    pub fn metadata(&self, label: Label, index: usize) -> Option<&Metadata> {
+        match label {
+            Label::Positive => self.positive_metadata.get(&index),
+            Label::Negative => self.negative_metadata.get(&index),
+        }
+    }
+
+    /// Attaches metadata to the given trace, which must exist, replacing any previous one.
+    pub fn set_metadata(&mut self, label: Label, index: usize, metadata: Metadata) {
+        let (traces, map) = match label {
+            Label::Positive => (&self.positive_traces, &mut self.positive_metadata),
+            Label::Negative => (&self.negative_traces, &mut self.negative_metadata),
+        };
+        assert!(index < traces.len(), "no {label:?} trace {index}");
+        map.insert(index, metadata);
+    }
+
+    /// Rearranges the traces with the given label (and their metadata),
+    /// so that the trace at index `i` is the one that was at index `indices[i]`.
+    pub(crate) fn select_traces(&mut self, label: Label, indices: &[usize]) {
+        let (traces, metadata) = match label {
+            Label::Positive => (&mut self.positive_traces, &mut self.positive_metadata),
+            Label::Negative => (&mut self.negative_traces, &mut self.negative_metadata),
+        };
+        *traces = indices.iter().map(|index| traces[*index].clone()).collect();
+        *metadata = reindex(metadata, indices.iter().copied());
+    }
+
+    /// The (finite) traces misclassified by the formula, with their metadata.
+    pub fn counterexamples(&self, formula: &SyntaxTree) -> Vec<Counterexample<'_>> {
+        let positives = self
+            .positive_traces
+            .iter()
+            .enumerate()
+            .filter(|(_, trace)| !formula.eval(trace))
+            .map(|(index, _)| (Label::Positive, index));
+        let negatives = self
+            .negative_traces
+            .iter()
+            .enumerate()
+            .filter(|(_, trace)| formula.eval(trace))
+            .map(|(index, _)| (Label::Negative, index));
+
+        positives
+            .chain(negatives)
+            .map(|(label, index)| Counterexample {
+                label,
+                index,
+                metadata: self.metadata(label, index),
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod trace_metadata {
+    use super::*;
+
+    #[test]
+    fn counterexamples() {
+        let mut sample = Sample::<1> {
+            positive_traces: vec![vec![[true]], vec![[false]]],
+            negative_traces: vec![vec![[false]]],
+            ..Sample::default()
+        };
+        sample.set_metadata(
+            Label::Positive,
+            1,
+            Metadata::from([
+                ("source".to_string(), "run_7.log".to_string()),
+                ("run".to_string(), "7".to_string()),
+            ]),
+        );
+
+        let counterexamples = sample.counterexamples(&SyntaxTree::Atom(0));
+        assert_eq!(counterexamples.len(), 1);
+        assert_eq!(
+            counterexamples[0].to_string(),
+            "Positive trace 1 (run: 7, source: run_7.log)"
+        );
+
+        // Metadata survives serialization.
+        let contents = ron::ser::to_string(&sample).expect("serialize sample");
+        let sample: Sample<1> = ron::de::from_str(&contents).expect("deserialize sample");
+        assert_eq!(
+            sample.metadata(Label::Positive, 1).map(|data| data.len()),
+            Some(2)
+        );
+    }
+
+    #[test]
+    fn reindexing() {
+        let metadata = MetadataMap::from([(0, Metadata::new()), (2, Metadata::new())]);
+        assert_eq!(
+            reindex(&metadata, [2, 1, 0]).keys().collect::<Vec<_>>(),
+            vec![&0, &2]
+        );
+        assert_eq!(
+            reindex(&metadata, [1, 2]).keys().collect::<Vec<_>>(),
+            vec![&1]
+        );
+    }
+}
@@ -0,0 +1,150 @@
+use crate::load::*;
+use crate::trace::*;
+use serde::Serializer;
+use serde_json::{Map, Value};
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// The current version of the layout of serialized samples, written along with them.
+///
+/// Version history:
+/// 1. Samples with `var_names` (optional), `positive_traces` and `negative_traces`,
+///    and optionally `positive_lassos` and `negative_lassos`, with no version field.
+/// 2. Same layout, with the `version` field.
+/// 3. Optional `positive_metadata` and `negative_metadata` fields.
+/// 4. Optional `length_policy` field.
+///
+/// When the layout of [`Sample`] changes, the version has to be bumped
+/// and a migration from the previous version added to [`MIGRATIONS`].
+pub const SAMPLE_FORMAT_VERSION: u32 = 4;
+
+/// A migration converts a sample from a version of the layout to the next one.
+type Migration = fn(&mut Map<String, Value>) -> io::Result<()>;
+
+/// The migrations from each version to the next, starting from version 1.
+const MIGRATIONS: [Migration; SAMPLE_FORMAT_VERSION as usize - 1] =
+    [migrate_v1, migrate_v2, migrate_v3];
+
+/// Version 2 only introduced the version field, so nothing else has to change.
+fn migrate_v1(_sample: &mut Map<String, Value>) -> io::Result<()> {
+    Ok(())
+}
+
+/// Version 3 only introduced optional fields, which are missing in older samples.
+fn migrate_v2(_sample: &mut Map<String, Value>) -> io::Result<()> {
+    Ok(())
+}
+
+/// Version 4 only introduced an optional field as well.
+fn migrate_v3(_sample: &mut Map<String, Value>) -> io::Result<()> {
+    Ok(())
+}
+
+pub(crate) fn legacy_version() -> u32 {
+    1
+}
+
+pub(crate) fn serialize_version<S: Serializer>(
+    _version: &u32,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.serialize_u32(SAMPLE_FORMAT_VERSION)
+}
+
+fn invalid_data(msg: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+/// Deserializes the contents of a file into a generic representation.
+fn parse_value(format: SampleFormat, contents: &[u8]) -> io::Result<Value> {
+    match format {
+        // RON tuples cannot be deserialized directly into generic values.
+        SampleFormat::Ron => format
+            .parse::<ron::Value>(contents)?
+            .into_rust()
+            .map_err(|err| invalid_data(err.to_string())),
+        SampleFormat::Json => format.parse(contents),
+        SampleFormat::Trace => Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "trace files are not versioned, load them with Sample::load",
+        )),
+    }
+}
+
+impl<const N: usize> Sample<N> {
+    /// Loads a sample from file, in the format given by the file extension,
+    /// migrating it to the current layout if it was written with an older version of the format.
+    /// Unlike [`Sample::load`], the whole file is first read into a generic representation,
+    /// so it takes more memory.
+    pub fn load_any(path: &Path) -> io::Result<Self> {
+        let contents = fs::read(path)?;
+        let format = SampleFormat::from_path(path).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::Unsupported,
+                "file format unknown or not supported",
+            )
+        })?;
+        Self::migrate(parse_value(format, &contents)?)
+    }
+
+    /// Converts a sample in a generic representation, in any version of the layout, to the current one.
+    pub fn migrate(sample: Value) -> io::Result<Self> {
+        let Value::Object(mut sample) = sample else {
+            return Err(invalid_data("sample is not a struct".to_string()));
+        };
+
+        let version = match sample.get("version") {
+            None => legacy_version(),
+            Some(version) => version
+                .as_u64()
+                .and_then(|version| u32::try_from(version).ok())
+                .filter(|version| *version >= 1)
+                .ok_or_else(|| invalid_data(format!("invalid sample format version {version}")))?,
+        };
+        if version > SAMPLE_FORMAT_VERSION {
+            return Err(invalid_data(format!(
+                "sample format version {version} is newer than the supported version {SAMPLE_FORMAT_VERSION}"
+            )));
+        }
+
+        for migration in &MIGRATIONS[version as usize - 1..] {
+            migration(&mut sample)?;
+        }
+        sample.insert("version".to_string(), Value::from(SAMPLE_FORMAT_VERSION));
+
+        let mut sample: Sample<N> = serde_json::from_value(Value::Object(sample))
+            .map_err(|err| invalid_data(err.to_string()))?;
+        sample.version = SAMPLE_FORMAT_VERSION;
+        Ok(sample)
+    }
+}
+
+#[cfg(test)]
+mod migration {
+    use super::*;
+
+    #[test]
+    fn legacy() {
+        let contents = b"(positive_traces: [[(true, false)]], negative_traces: [[(false, false)]])";
+        let value = parse_value(SampleFormat::Ron, contents).expect("parse sample");
+        let sample = Sample::<2>::migrate(value).expect("migrate sample");
+        assert_eq!(sample.version, SAMPLE_FORMAT_VERSION);
+        assert_eq!(sample.positive_traces, vec![vec![[true, false]]]);
+
+        // Loading a legacy sample directly records its version.
+        let sample = SampleFormat::Ron
+            .parse::<Sample<2>>(contents)
+            .expect("parse sample");
+        assert_eq!(sample.version, 1);
+        let contents = ron::ser::to_string(&sample).expect("serialize sample");
+        assert!(contents.starts_with(&format!("(version:{SAMPLE_FORMAT_VERSION},")));
+    }
+
+    #[test]
+    fn newer() {
+        let contents = br#"{"version": 1000, "positive_traces": [], "negative_traces": []}"#;
+        let value = parse_value(SampleFormat::Json, contents).expect("parse sample");
+        assert!(Sample::<2>::migrate(value).is_err());
+    }
+}
@@ -0,0 +1,85 @@
+use crate::genetic::*;
+use crate::learn::*;
+use crate::source::*;
+use crate::syntax::*;
+
+/// The largest formulas tried as replacements of subformulas, besides their own subformulas.
+const MAX_CANDIDATE_SIZE: usize = 3;
+
+impl SyntaxTree {
+    /// A formula at most as large that fits the sample, which can be any [`SampleSource`], at least as well,
+    /// i.e., satisfying at least as many positive traces and at most as many negative ones,
+    /// so that a consistent formula stays consistent.
+    ///
+    /// Subformulas are repeatedly replaced, from the root down, by strictly smaller formulas:
+    /// their own subformulas, or formulas over the allowed variables up to size 3,
+    /// until no replacement preserves the fit.
+    /// This is meant to clean up the output of approximate learners, e.g., the genetic algorithm,
+    /// and does not guarantee a minimal formula.
+    pub fn minimize<const N: usize, S: SampleSource<N>>(&self, sample: &S) -> SyntaxTree {
+        let vars = sample.vars();
+        let candidates = (1..=MAX_CANDIDATE_SIZE.min(self.size() - 1))
+            .flat_map(|size| gen_formulae::<N>(size, &vars))
+            .collect::<Vec<_>>();
+        let fitness = Fitness::new(self, sample);
+        let fits = |formula: &SyntaxTree| {
+            let other = Fitness::new(formula, sample);
+            other.positives >= fitness.positives && other.negatives <= fitness.negatives
+        };
+
+        let mut formula = self.clone();
+        'minimize: loop {
+            for index in 0..formula.size() {
+                let subformula = formula.subformula(index);
+                let size = subformula.size();
+                let replacements = (1..size)
+                    .map(|descendant| subformula.subformula(descendant))
+                    .chain(&candidates)
+                    .filter(|replacement| replacement.size() < size)
+                    .cloned()
+                    .collect::<Vec<_>>();
+                for replacement in replacements {
+                    let replaced = formula.replace_subformula(index, &mut |_| replacement.clone());
+                    if fits(&replaced) {
+                        formula = replaced;
+                        continue 'minimize;
+                    }
+                }
+            }
+            return formula;
+        }
+    }
+}
+
+#[cfg(test)]
+mod semantic_minimization {
+    use super::*;
+    use crate::trace::*;
+
+    #[test]
+    fn minimize() {
+        // Positive traces are those where x0 holds at first.
+        let sample = Sample::<2> {
+            positive_traces: vec![vec![[true, false]], vec![[true, true], [false, false]]],
+            negative_traces: vec![vec![[false, true]], vec![[false, false], [true, true]]],
+            ..Sample::default()
+        };
+        let x0 = SyntaxTree::Atom(0);
+        let x1 = SyntaxTree::Atom(1);
+        let bloated = x0
+            .clone()
+            .and(x0.clone().or(x1.clone().globally()))
+            .or(x0.clone().and(x1.clone().next()));
+        assert!(sample.is_consistent(&bloated));
+        assert_eq!(bloated.minimize(&sample), x0);
+
+        // An inconsistent formula fits the sample no worse once minimized.
+        let inconsistent = x1.clone().or(x1.clone().finally().next());
+        let fitness = Fitness::new(&inconsistent, &sample);
+        let minimized = inconsistent.minimize(&sample);
+        let minimized_fitness = Fitness::new(&minimized, &sample);
+        assert!(minimized.size() < inconsistent.size());
+        assert!(minimized_fitness.positives >= fitness.positives);
+        assert!(minimized_fitness.negatives <= fitness.negatives);
+    }
+}
@@ -0,0 +1,170 @@
+use crate::learn::*;
+use crate::learner::*;
+use crate::syntax::*;
+use crate::trace::*;
+use std::time::Instant;
+
+/// Mines a specification as a conjunction of small formulas, each satisfied by all positive traces,
+/// which together exclude all negative traces, rather than searching for one large formula.
+///
+/// The conjuncts are chosen greedily among the formulas up to a given size, as in set cover:
+/// each excludes the most negative traces not yet excluded (the smallest formula, on ties).
+/// Lassos are ignored while choosing the conjuncts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ConjunctiveLearner {
+    /// The maximum size of each conjunct.
+    pub conjunct_size: usize,
+    /// The maximum number of conjuncts, if any.
+    pub max_conjuncts: Option<usize>,
+}
+
+impl Default for ConjunctiveLearner {
+    fn default() -> Self {
+        ConjunctiveLearner {
+            conjunct_size: 3,
+            max_conjuncts: None,
+        }
+    }
+}
+
+impl ConjunctiveLearner {
+    pub fn conjunct_size(mut self, conjunct_size: usize) -> Self {
+        self.conjunct_size = conjunct_size;
+        self
+    }
+
+    pub fn max_conjuncts(mut self, max_conjuncts: usize) -> Self {
+        self.max_conjuncts = Some(max_conjuncts);
+        self
+    }
+
+    /// Chooses the conjuncts, stopping when they exclude all negative traces,
+    /// when no formula excludes any more of them, or at the maximum number of conjuncts.
+    pub fn mine<const N: usize>(&self, sample: &Sample<N>) -> Vec<SyntaxTree> {
+        self.mine_until(sample, None)
+    }
+
+    fn mine_until<const N: usize>(
+        &self,
+        sample: &Sample<N>,
+        deadline: Option<Instant>,
+    ) -> Vec<SyntaxTree> {
+        let vars = sample.vars();
+        // The formulas satisfied by all positive traces, with the negative traces they exclude.
+        let mut candidates = (1..=self.conjunct_size)
+            .flat_map(|size| gen_formulae_iter::<N>(size, &vars))
+            .filter(|formula| {
+                sample
+                    .positive_traces
+                    .iter()
+                    .all(|trace| formula.eval(trace))
+            })
+            .map(|formula| {
+                let excluded = sample
+                    .negative_traces
+                    .iter()
+                    .map(|trace| !formula.eval(trace))
+                    .collect::<Vec<_>>();
+                (formula, excluded)
+            })
+            .collect::<Vec<_>>();
+
+        let mut conjuncts = Vec::new();
+        let mut remaining = vec![true; sample.negative_traces.len()];
+        while remaining.contains(&true)
+            && self.max_conjuncts.is_none_or(|max| conjuncts.len() < max)
+            && deadline.is_none_or(|deadline| Instant::now() < deadline)
+        {
+            let newly_excluded = |excluded: &[bool]| {
+                excluded
+                    .iter()
+                    .zip(&remaining)
+                    .filter(|(&excluded, &remaining)| excluded && remaining)
+                    .count()
+            };
+            // The first of the best candidates is the smallest, as candidates are sorted by size.
+            let Some((best, count)) = candidates
+                .iter()
+                .enumerate()
+                .map(|(n, (_, excluded))| (n, newly_excluded(excluded)))
+                .rev()
+                .max_by_key(|(_, count)| *count)
+            else {
+                break;
+            };
+            if count == 0 {
+                break;
+            }
+            let (formula, excluded) = candidates.remove(best);
+            for (remaining, excluded) in remaining.iter_mut().zip(excluded) {
+                *remaining &= !excluded;
+            }
+            conjuncts.push(formula);
+        }
+        conjuncts
+    }
+}
+
+impl<const N: usize> Learner<N> for ConjunctiveLearner {
+    /// Mines the conjuncts, within the time limit, and returns their conjunction,
+    /// which fails if it exceeds the maximum size.
+    fn learn(&self, sample: &Sample<N>, budget: Budget) -> LearnResult {
+        if !sample.is_solvable() {
+            return LearnResult::Unsolvable;
+        }
+
+        let Some(formula) = self
+            .mine_until(sample, budget.deadline())
+            .into_iter()
+            .reduce(|conjunction, conjunct| conjunction.and(conjunct))
+        else {
+            return LearnResult::Exhausted;
+        };
+        if budget.max_size.is_some_and(|max| formula.size() > max) {
+            LearnResult::Exhausted
+        } else if sample.is_consistent(&formula) {
+            LearnResult::Consistent(formula)
+        } else {
+            LearnResult::Approximate(formula)
+        }
+    }
+}
+
+#[cfg(test)]
+mod specification_mining {
+    use super::*;
+
+    #[test]
+    fn mine() {
+        // Positive traces are those where x0 holds at first and x1 holds eventually.
+        let sample = Sample::<2> {
+            positive_traces: vec![
+                vec![[true, false], [false, true]],
+                vec![[true, true]],
+                vec![[true, false], [false, false], [true, true]],
+            ],
+            negative_traces: vec![
+                vec![[false, true], [true, false]],
+                vec![[true, false], [true, false]],
+                vec![[false, false]],
+                vec![[true, false]],
+            ],
+            ..Sample::default()
+        };
+        let learner = ConjunctiveLearner::default().conjunct_size(2);
+        let conjuncts = learner.mine(&sample);
+        assert_eq!(conjuncts.len(), 2);
+        for conjunct in &conjuncts {
+            assert!(sample
+                .positive_traces
+                .iter()
+                .all(|trace| conjunct.eval(trace)));
+        }
+
+        let result = learner.learn(&sample, Budget::default());
+        assert!(result.is_consistent());
+
+        let result = learner.max_conjuncts(1).learn(&sample, Budget::default());
+        assert!(matches!(result, LearnResult::Approximate(_)));
+    }
+}
@@ -0,0 +1,96 @@
+use crate::anytime::*;
+use crate::learner::*;
+use crate::trace::*;
+use std::ops::ControlFlow;
+use std::time::Instant;
+
+/// A learner for noisy samples, where no formula might be consistent with all the traces:
+/// it finds the smallest formula misclassifying at most the given fraction of the traces.
+///
+/// If the budget runs out first, it returns the most accurate formula found,
+/// so that a zero fraction and a maximum size give the most accurate formula up to that size.
+/// Without a budget, it runs until it finds a good enough formula, which might never happen.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct NoiseTolerantLearner {
+    /// The fraction of traces that the formula is allowed to misclassify.
+    pub max_error: f64,
+}
+
+impl NoiseTolerantLearner {
+    /// A learner allowing the given fraction of misclassified traces, panicking if it is not in `[0, 1]`.
+    pub fn new(max_error: f64) -> Self {
+        assert!(
+            (0.0..=1.0).contains(&max_error),
+            "error fraction must be in [0, 1]"
+        );
+        NoiseTolerantLearner { max_error }
+    }
+}
+
+impl<const N: usize> Learner<N> for NoiseTolerantLearner {
+    fn learn(&self, sample: &Sample<N>, budget: Budget) -> LearnResult {
+        let deadline = budget.deadline();
+        let total = sample.positive_traces() + sample.negative_traces();
+        let allowed = (self.max_error * total as f64).floor() as usize;
+
+        let best = learn_anytime(sample, |progress| match progress {
+            Progress::Improved(candidate) if candidate.total - candidate.correct <= allowed => {
+                ControlFlow::Break(())
+            }
+            Progress::Searching { size, .. }
+                if budget.max_size.is_some_and(|max| size > max)
+                    || deadline.is_some_and(|deadline| Instant::now() >= deadline) =>
+            {
+                ControlFlow::Break(())
+            }
+            _ => ControlFlow::Continue(()),
+        });
+
+        match best {
+            // Lassos are not taken into account by the search.
+            Some(candidate) if sample.is_consistent(&candidate.formula) => {
+                LearnResult::Consistent(candidate.formula)
+            }
+            Some(candidate) => LearnResult::Approximate(candidate.formula),
+            None => LearnResult::Exhausted,
+        }
+    }
+}
+
+#[cfg(test)]
+mod noise_tolerance {
+    use super::*;
+    use crate::syntax::*;
+
+    #[test]
+    fn noisy_sample() {
+        // Positive traces are those where x0 always holds, but for a mislabelled one.
+        // Tolerating one error out of eight, the noise is ignored.
+        let sample = Sample::<2> {
+            positive_traces: vec![
+                vec![[true, false], [true, true]],
+                vec![[true, true]],
+                vec![[true, false], [true, false], [true, true]],
+                vec![[false, true]],
+            ],
+            negative_traces: vec![
+                vec![[true, true], [false, true]],
+                vec![[false, false]],
+                vec![[false, true], [true, false]],
+                vec![[true, false], [false, false]],
+            ],
+            ..Sample::default()
+        };
+        let result = NoiseTolerantLearner::new(0.2).learn(&sample, Budget::default());
+        assert_eq!(
+            result,
+            LearnResult::Approximate(SyntaxTree::Atom(0).globally())
+        );
+
+        // The most accurate formula up to size 1 misclassifies 3 traces.
+        let result = NoiseTolerantLearner::new(0.0).learn(&sample, Budget::default().max_size(1));
+        let formula = result.formula().expect("some formula");
+        assert_eq!(formula.size(), 1);
+        assert_eq!(sample.confusion(formula).misclassified(), 3);
+    }
+}
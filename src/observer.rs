@@ -0,0 +1,131 @@
+use crate::genetic::*;
+use crate::learn::*;
+use crate::source::*;
+use crate::syntax::*;
+
+/// Callbacks notified of the progress of a search, e.g., to report it to a dashboard,
+/// by the brute-force search (see [`solve_observed`]) and by the genetic algorithm
+/// (see [`GeneticLearner::evolve_observed`]).
+/// All of them do nothing by default.
+///
+/// The parallel brute-force search notifies the observer from several threads at once,
+/// so observers keep their state behind atomics or locks.
+pub trait SearchObserver: Sync {
+    /// The brute-force search starts checking the formulas of the given size.
+    fn on_size_started(&self, _size: usize) {}
+
+    /// The search found a candidate formula:
+    /// a consistent one for the brute-force search, a new fittest one for the genetic algorithm.
+    fn on_candidate_found(&self, _formula: &SyntaxTree) {}
+
+    /// The search checked the given number of formulas so far.
+    /// The brute-force search reports it every 1024 formulas and at the end of every size,
+    /// the genetic algorithm at the end of every generation.
+    fn on_progress(&self, _checked: usize) {}
+
+    /// The genetic algorithm ranked a generation (the initial population being generation 0),
+    /// whose fittest formula is given with its fitness.
+    fn on_generation(&self, _generation: usize, _fittest: &SyntaxTree, _fitness: &Fitness) {}
+}
+
+/// Observes nothing.
+impl SearchObserver for () {}
+
+/// Find a formula consistent with the given sample, which can be any [`SampleSource`], within the budget,
+/// like [`solve_with_budget`], notifying the observer of the progress of the search.
+pub fn solve_observed<const N: usize, S: SampleSource<N> + Sync>(
+    sample: &S,
+    multithread: bool,
+    log: bool,
+    budget: SearchBudget,
+    observer: &dyn SearchObserver,
+) -> (Option<SyntaxTree>, SearchStats) {
+    let options = SearchOptions {
+        observer,
+        ..SearchOptions::default()
+    };
+    search(sample, multithread, log, budget, options)
+}
+
+#[cfg(test)]
+mod search_observer {
+    use super::*;
+    use crate::trace::*;
+    use rand::{rngs::StdRng, SeedableRng};
+    use std::sync::Mutex;
+
+    #[derive(Default)]
+    struct Recorder {
+        sizes: Mutex<Vec<usize>>,
+        candidates: Mutex<Vec<SyntaxTree>>,
+        checked: Mutex<usize>,
+        generations: Mutex<usize>,
+    }
+
+    impl SearchObserver for Recorder {
+        fn on_size_started(&self, size: usize) {
+            self.sizes.lock().unwrap().push(size);
+        }
+
+        fn on_candidate_found(&self, formula: &SyntaxTree) {
+            self.candidates.lock().unwrap().push(formula.clone());
+        }
+
+        fn on_progress(&self, checked: usize) {
+            let mut last = self.checked.lock().unwrap();
+            *last = checked.max(*last);
+        }
+
+        fn on_generation(&self, _generation: usize, _fittest: &SyntaxTree, _fitness: &Fitness) {
+            *self.generations.lock().unwrap() += 1;
+        }
+    }
+
+    #[test]
+    fn observe() {
+        // Positive traces are those where x0 holds next and x1 holds eventually.
+        let sample = Sample::<2> {
+            positive_traces: vec![
+                vec![[false, false], [true, true]],
+                vec![[false, true], [true, false]],
+            ],
+            negative_traces: vec![
+                vec![[true, true], [false, true]],
+                vec![[false, false], [true, false]],
+            ],
+            ..Sample::default()
+        };
+        let recorder = Recorder::default();
+        let (formula, stats) =
+            solve_observed(&sample, true, false, SearchBudget::default(), &recorder);
+        let formula = formula.expect("solvable");
+        assert_eq!(
+            *recorder.sizes.lock().unwrap(),
+            (1..=formula.size()).collect::<Vec<_>>()
+        );
+        assert!(recorder.candidates.lock().unwrap().contains(&formula));
+        assert!(*recorder.checked.lock().unwrap() <= stats.formulas);
+
+        let recorder = Recorder::default();
+        let learner = GeneticLearner::new(2).iterations(3);
+        let population = learner.initial_population(&sample);
+        let mut generations = Vec::new();
+        let ranked = learner.evolve_observed(
+            &sample,
+            population,
+            &mut FitnessCache::default(),
+            &mut StdRng::seed_from_u64(0),
+            &recorder,
+            |generation| generations.push((generation.index, generation.ranked.len())),
+        );
+        assert_eq!(*recorder.generations.lock().unwrap(), 4);
+        assert_eq!(generations.len(), 4);
+        assert_eq!(generations.last(), Some(&(3, ranked.len())));
+        // The last candidate is as fit as the fittest formula of the last generation.
+        let candidates = recorder.candidates.lock().unwrap();
+        let last = candidates.last().expect("some candidate");
+        let fitness = Fitness::weighted(last, &sample, &learner.costs);
+        assert_eq!(fitness.score(), ranked[0].1.score());
+        assert!(*recorder.checked.lock().unwrap() >= ranked.len());
+    }
+}
@@ -0,0 +1,208 @@
+use crate::learn::*;
+use crate::source::*;
+use crate::syntax::*;
+use rand::{rngs::StdRng, seq::SliceRandom, SeedableRng};
+use std::fmt;
+use std::str::FromStr;
+
+impl SkeletonTree {
+    /// The number of nodes on the longest path from the root to a leaf, the root included.
+    pub fn depth(&self) -> usize {
+        match self {
+            SkeletonTree::Leaf => 1,
+            SkeletonTree::UnaryNode(child) => 1 + child.depth(),
+            SkeletonTree::BinaryNode(children) => 1 + children.0.depth().max(children.1.depth()),
+        }
+    }
+}
+
+/// The order in which the brute-force search explores formulas (see [`solve_ordered`]).
+///
+/// Parsed from `size`, `depth`, `restarts` or `restarts:<seed>`, and `schedule:<sizes>`, e.g., `schedule:5,3,7`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum SearchOrder {
+    /// By increasing size, so that solutions are smallest.
+    #[default]
+    Size,
+    /// By increasing size, and the formulas of each size by decreasing depth,
+    /// so that deep unary chains such as `G F X x0` are explored before wide binary trees.
+    /// Solutions are still smallest, but which one of a given size is found can differ.
+    Depth,
+    /// By rounds over doubling ranges of sizes (1, 2, 3 to 4, 5 to 8, and so on),
+    /// the sizes of each range being explored in a random order given by the seed,
+    /// so that a large solution can be found before all the smaller sizes are exhausted.
+    /// Solutions are less than twice as large as smallest ones.
+    Restarts(u64),
+    /// The given sizes, in the given order, and no others.
+    Schedule(Vec<usize>),
+}
+
+impl SearchOrder {
+    /// The sizes explored, from the given one, up to the maximum one if any.
+    pub fn sizes(
+        &self,
+        first_size: usize,
+        max_size: Option<usize>,
+    ) -> Box<dyn Iterator<Item = usize> + '_> {
+        let max_size = max_size.unwrap_or(usize::MAX);
+        let first_size = first_size.max(1);
+        match self {
+            SearchOrder::Size | SearchOrder::Depth => Box::new(first_size..=max_size),
+            SearchOrder::Restarts(seed) => {
+                let mut rng = StdRng::seed_from_u64(*seed);
+                let ranges = std::iter::successors(Some((1, 1)), |&(_, end): &(usize, usize)| {
+                    end.checked_mul(2).map(|next_end| (end + 1, next_end))
+                });
+                Box::new(
+                    ranges
+                        .take_while(move |(start, _)| *start <= max_size)
+                        .flat_map(move |(start, end)| {
+                            let mut sizes =
+                                (start.max(first_size)..=end.min(max_size)).collect::<Vec<_>>();
+                            sizes.shuffle(&mut rng);
+                            sizes
+                        }),
+                )
+            }
+            SearchOrder::Schedule(sizes) => Box::new(
+                sizes
+                    .iter()
+                    .copied()
+                    .filter(move |size| (first_size..=max_size).contains(size)),
+            ),
+        }
+    }
+
+    /// The skeletons of the formulas of the given size, in the order they are explored.
+    pub fn skeletons(&self, size: usize) -> Vec<SkeletonTree> {
+        let mut skeletons = SkeletonTree::gen(size);
+        if *self == SearchOrder::Depth {
+            skeletons.sort_by_key(|skeleton| std::cmp::Reverse(skeleton.depth()));
+        }
+        skeletons
+    }
+}
+
+impl fmt::Display for SearchOrder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SearchOrder::Size => write!(f, "size"),
+            SearchOrder::Depth => write!(f, "depth"),
+            SearchOrder::Restarts(seed) => write!(f, "restarts:{seed}"),
+            SearchOrder::Schedule(sizes) => {
+                let sizes = sizes.iter().map(ToString::to_string).collect::<Vec<_>>();
+                write!(f, "schedule:{}", sizes.join(","))
+            }
+        }
+    }
+}
+
+impl FromStr for SearchOrder {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (name, arg) = match s.split_once(':') {
+            Some((name, arg)) => (name, Some(arg)),
+            None => (s, None),
+        };
+        match (name.trim().to_lowercase().as_str(), arg) {
+            ("size", None) => Ok(SearchOrder::Size),
+            ("depth", None) => Ok(SearchOrder::Depth),
+            ("restarts", None) => Ok(SearchOrder::Restarts(0)),
+            ("restarts", Some(seed)) => seed
+                .trim()
+                .parse()
+                .map(SearchOrder::Restarts)
+                .map_err(|_| format!("invalid seed: {seed}")),
+            ("schedule", Some(sizes)) => sizes
+                .split(',')
+                .map(|size| match size.trim().parse() {
+                    Ok(size) if size > 0 => Ok(size),
+                    _ => Err(format!("invalid size: {size}")),
+                })
+                .collect::<Result<_, _>>()
+                .map(SearchOrder::Schedule),
+            _ => Err(format!("unknown search order: {s}")),
+        }
+    }
+}
+
+/// Find a formula consistent with the given sample, which can be any [`SampleSource`], within the budget,
+/// like [`solve_with_budget`], but exploring formulas in the given order.
+/// The statistics list the sizes in the order they were searched.
+pub fn solve_ordered<const N: usize, S: SampleSource<N> + Sync>(
+    sample: &S,
+    order: &SearchOrder,
+    multithread: bool,
+    log: bool,
+    budget: SearchBudget,
+) -> (Option<SyntaxTree>, SearchStats) {
+    let options = SearchOptions {
+        order: Some(order),
+        ..SearchOptions::default()
+    };
+    search(sample, multithread, log, budget, options)
+}
+
+#[cfg(test)]
+mod search_order {
+    use super::*;
+    use crate::trace::*;
+
+    #[test]
+    fn orders() {
+        assert_eq!("depth".parse(), Ok(SearchOrder::Depth));
+        assert_eq!("restarts:7".parse(), Ok(SearchOrder::Restarts(7)));
+        let schedule: SearchOrder = "schedule:5, 3".parse().expect("valid order");
+        assert_eq!(schedule, SearchOrder::Schedule(vec![5, 3]));
+        assert_eq!(schedule.to_string().parse(), Ok(schedule.clone()));
+        assert!("schedule:0".parse::<SearchOrder>().is_err());
+        assert!("breadth".parse::<SearchOrder>().is_err());
+
+        let sizes = SearchOrder::Restarts(1)
+            .sizes(1, Some(10))
+            .collect::<Vec<_>>();
+        assert_eq!(sizes[..2], [1, 2]);
+        let mut range = sizes[4..8].to_vec();
+        range.sort();
+        assert_eq!(range, [5, 6, 7, 8]);
+        assert_eq!(sizes.len(), 10);
+        let depths = SearchOrder::Depth
+            .skeletons(4)
+            .iter()
+            .map(SkeletonTree::depth)
+            .collect::<Vec<_>>();
+        assert_eq!(depths.first(), Some(&4));
+        assert!(depths.is_sorted_by(|left, right| left >= right));
+
+        // Positive traces are those where x0 holds next and x1 holds eventually.
+        let sample = Sample::<2> {
+            positive_traces: vec![
+                vec![[false, false], [true, true]],
+                vec![[false, true], [true, false]],
+            ],
+            negative_traces: vec![
+                vec![[true, true], [false, true]],
+                vec![[false, false], [true, false]],
+            ],
+            ..Sample::default()
+        };
+        let smallest = solve(&sample, false, false).expect("solvable");
+        let (formula, _) = solve_ordered(
+            &sample,
+            &SearchOrder::Depth,
+            false,
+            false,
+            SearchBudget::default(),
+        );
+        assert_eq!(formula.map(|formula| formula.size()), Some(smallest.size()));
+
+        let schedule = SearchOrder::Schedule(vec![smallest.size() + 2, 1]);
+        let (formula, stats) =
+            solve_ordered(&sample, &schedule, false, false, SearchBudget::default());
+        let formula = formula.expect("solvable at a larger size");
+        assert!(sample.is_consistent(&formula));
+        assert_eq!(formula.size(), smallest.size() + 2);
+        assert_eq!(stats.sizes.len(), 1);
+    }
+}
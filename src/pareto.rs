@@ -0,0 +1,232 @@
+use crate::genetic::*;
+use crate::source::*;
+use crate::syntax::*;
+use serde::{Deserialize, Serialize};
+
+impl Fitness {
+    /// The number of positive traces satisfying the formula minus the number of negative ones satisfying it,
+    /// which orders formulas by accuracy over a given sample.
+    fn correctness(&self) -> i64 {
+        self.positives as i64 - self.negatives as i64
+    }
+
+    /// Whether the formula is at least as accurate and as small as the other one, and more accurate or smaller,
+    /// so that it is better for both objectives, accuracy and size, rather than for some trade-off between them.
+    pub fn dominates(&self, other: &Fitness) -> bool {
+        self.correctness() >= other.correctness()
+            && self.size <= other.size
+            && (self.correctness() > other.correctness() || self.size < other.size)
+    }
+}
+
+/// Sorts the fitnesses into successive Pareto fronts over accuracy and size, as in NSGA-II:
+/// the first front is made of the indices of the fitnesses no other one dominates (see [`Fitness::dominates`]),
+/// the second of those only dominated by the first front, and so on.
+pub fn pareto_fronts(fitnesses: &[Fitness]) -> Vec<Vec<usize>> {
+    let mut dominated = vec![Vec::new(); fitnesses.len()];
+    let mut dominators = vec![0; fitnesses.len()];
+    for (index, fitness) in fitnesses.iter().enumerate() {
+        for (other, other_fitness) in fitnesses.iter().enumerate() {
+            if fitness.dominates(other_fitness) {
+                dominated[index].push(other);
+            } else if other_fitness.dominates(fitness) {
+                dominators[index] += 1;
+            }
+        }
+    }
+    let mut fronts = Vec::new();
+    let mut front = (0..fitnesses.len())
+        .filter(|index| dominators[*index] == 0)
+        .collect::<Vec<_>>();
+    while !front.is_empty() {
+        let mut next = Vec::new();
+        for index in &front {
+            for other in &dominated[*index] {
+                dominators[*other] -= 1;
+                if dominators[*other] == 0 {
+                    next.push(*other);
+                }
+            }
+        }
+        next.sort_unstable();
+        fronts.push(front);
+        front = next;
+    }
+    fronts
+}
+
+/// The crowding distance of each fitness of a front (given by indices, see [`pareto_fronts`]), in order:
+/// the sum over both objectives of the normalized gap between its neighbours in the front,
+/// infinite for the most accurate and the smallest fitnesses, so that these are preferred.
+/// Fitnesses in sparse regions of the front have larger distances, which keeps the population diverse.
+pub fn crowding_distances(fitnesses: &[Fitness], front: &[usize]) -> Vec<f64> {
+    let mut distances = vec![0.0; front.len()];
+    let objectives: [fn(&Fitness) -> f64; 2] = [
+        |fitness| fitness.correctness() as f64,
+        |fitness| fitness.size as f64,
+    ];
+    for objective in objectives {
+        let mut order = (0..front.len()).collect::<Vec<_>>();
+        order.sort_by(|left, right| {
+            objective(&fitnesses[front[*left]]).total_cmp(&objective(&fitnesses[front[*right]]))
+        });
+        let (Some(first), Some(last)) = (order.first(), order.last()) else {
+            continue;
+        };
+        let range = objective(&fitnesses[front[*last]]) - objective(&fitnesses[front[*first]]);
+        distances[*first] = f64::INFINITY;
+        distances[*last] = f64::INFINITY;
+        if range > 0.0 {
+            for window in order.windows(3) {
+                let gap = objective(&fitnesses[front[window[2]]])
+                    - objective(&fitnesses[front[window[0]]]);
+                distances[window[1]] += gap / range;
+            }
+        }
+    }
+    distances
+}
+
+/// Sorts the formulas as NSGA-II does: by Pareto front over accuracy and size (see [`pareto_fronts`]),
+/// and by decreasing crowding distance within each front (see [`crowding_distances`]),
+/// so that keeping the first formulas keeps the best trade-offs, spread along the front,
+/// without weighing size against accuracy.
+/// Formulas with the same distance are sorted by decreasing accuracy, so the most accurate formula comes first.
+pub fn sort_pareto(ranked: &mut Vec<(SyntaxTree, Fitness)>) {
+    let fitnesses = ranked
+        .iter()
+        .map(|(_, fitness)| *fitness)
+        .collect::<Vec<_>>();
+    let mut keys = vec![(0, 0.0); ranked.len()];
+    for (rank, front) in pareto_fronts(&fitnesses).iter().enumerate() {
+        for (index, distance) in front.iter().zip(crowding_distances(&fitnesses, front)) {
+            keys[*index] = (rank, distance);
+        }
+    }
+    let mut keyed = ranked.drain(..).zip(keys).collect::<Vec<_>>();
+    keyed.sort_by(|((_, left), left_key), ((_, right), right_key)| {
+        left_key
+            .0
+            .cmp(&right_key.0)
+            .then(right_key.1.total_cmp(&left_key.1))
+            .then(right.correctness().cmp(&left.correctness()))
+    });
+    ranked.extend(keyed.into_iter().map(|(entry, _)| entry));
+}
+
+/// A formula of a Pareto front over accuracy and size (see [`pareto_front`]).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ParetoPoint {
+    pub formula: SyntaxTree,
+    /// The fraction of traces correctly classified by the formula.
+    pub accuracy: f64,
+    /// The size of the formula, or its cost (see [`Fitness::size`]).
+    pub size: usize,
+}
+
+/// The formulas that no other one dominates (see [`Fitness::dominates`]) over the sample they were evaluated on,
+/// which can be any [`SampleSource`], from the smallest to the most accurate, without duplicates:
+/// the best formula for every trade-off between accuracy and size.
+pub fn pareto_front<const N: usize, S: SampleSource<N>>(
+    ranked: &[(SyntaxTree, Fitness)],
+    sample: &S,
+) -> Vec<ParetoPoint> {
+    let fitnesses = ranked
+        .iter()
+        .map(|(_, fitness)| *fitness)
+        .collect::<Vec<_>>();
+    let mut front = pareto_fronts(&fitnesses)
+        .into_iter()
+        .next()
+        .unwrap_or_default();
+    front.sort_by_key(|index| (fitnesses[*index].size, &ranked[*index].0));
+    front.dedup_by_key(|index| &ranked[*index].0);
+    front
+        .into_iter()
+        .map(|index| {
+            let (formula, fitness) = &ranked[index];
+            ParetoPoint {
+                formula: formula.clone(),
+                accuracy: fitness.accuracy(sample),
+                size: fitness.size,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod pareto_sorting {
+    use super::*;
+    use crate::trace::*;
+
+    fn fitness(positives: usize, negatives: usize, size: usize) -> Fitness {
+        Fitness {
+            positives,
+            negatives,
+            size,
+        }
+    }
+
+    #[test]
+    fn fronts() {
+        let fitnesses = [
+            fitness(3, 0, 7),
+            fitness(2, 0, 3),
+            fitness(2, 1, 3),
+            fitness(1, 0, 1),
+            fitness(3, 0, 9),
+        ];
+        assert!(fitnesses[1].dominates(&fitnesses[2]));
+        assert!(!fitnesses[0].dominates(&fitnesses[1]));
+        assert!(!fitnesses[0].dominates(&fitnesses[0]));
+        assert_eq!(pareto_fronts(&fitnesses), vec![vec![0, 1, 3], vec![2, 4]]);
+
+        let distances = crowding_distances(&fitnesses, &[0, 1, 3]);
+        assert_eq!(distances[0], f64::INFINITY);
+        assert!(distances[1].is_finite() && distances[1] > 0.0);
+
+        // The most accurate and the smallest formulas come first, although the size penalty would rank the former fourth.
+        let mut ranked = fitnesses
+            .iter()
+            .enumerate()
+            .map(|(var, fitness)| (SyntaxTree::Atom(var as Idx), *fitness))
+            .collect::<Vec<_>>();
+        sort_pareto(&mut ranked);
+        let order = ranked
+            .iter()
+            .map(|(formula, _)| formula.clone())
+            .collect::<Vec<_>>();
+        assert_eq!(order[..2], [SyntaxTree::Atom(0), SyntaxTree::Atom(3)]);
+        assert_eq!(order[2], SyntaxTree::Atom(1));
+        assert_eq!(order[3..], [SyntaxTree::Atom(4), SyntaxTree::Atom(2)]);
+    }
+
+    #[test]
+    fn front() {
+        let sample = Sample::always_x0();
+        let x0 = SyntaxTree::Atom(0);
+        let formulas = [
+            x0.clone().globally(),
+            x0.clone(),
+            SyntaxTree::Atom(1),
+            x0.clone().globally(),
+        ];
+        let ranked = GeneticLearner::default().rank(&formulas, &sample);
+        let front = pareto_front(&ranked, &sample);
+        assert_eq!(
+            front,
+            vec![
+                ParetoPoint {
+                    formula: x0.clone(),
+                    accuracy: 0.8,
+                    size: 1
+                },
+                ParetoPoint {
+                    formula: x0.globally(),
+                    accuracy: 1.0,
+                    size: 2
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,439 @@
+use crate::sketch::*;
+use crate::syntax::*;
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+use std::sync::Arc;
+
+/// An error occurred while parsing a formula.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    /// The position (in characters) in the input string where the error occurred.
+    pub position: usize,
+    pub message: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at position {}", self.message, self.position)
+    }
+}
+
+impl Error for ParseError {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    Ident(String),
+    Hole(String),
+    Not,
+    Next,
+    Globally,
+    Finally,
+    And,
+    Or,
+    Implies,
+    Until,
+    LParen,
+    RParen,
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Token::Ident(ident) => write!(f, "`{ident}`"),
+            Token::Hole(name) => write!(f, "hole `?{name}`"),
+            Token::Not => write!(f, "negation"),
+            Token::Next => write!(f, "next"),
+            Token::Globally => write!(f, "globally"),
+            Token::Finally => write!(f, "finally"),
+            Token::And => write!(f, "conjunction"),
+            Token::Or => write!(f, "disjunction"),
+            Token::Implies => write!(f, "implication"),
+            Token::Until => write!(f, "until"),
+            Token::LParen => write!(f, "`(`"),
+            Token::RParen => write!(f, "`)`"),
+        }
+    }
+}
+
+/// Splits the input into tokens, each with its position.
+/// Alphabetic operators (`X`, `G`, `F`, `U`) have to be separated from variable names,
+/// e.g., `G p0` or `G(p0)` rather than `Gp0`.
+/// Holes are written as `?` followed by their name, e.g., `?1`.
+fn tokenize(input: &str) -> Result<Vec<(usize, Token)>, ParseError> {
+    // Multi-character symbols have to come before their prefixes.
+    const SYMBOLS: [(&str, Token); 22] = [
+        ("->", Token::Implies),
+        ("=>", Token::Implies),
+        ("→", Token::Implies),
+        ("&&", Token::And),
+        ("/\\", Token::And),
+        ("&", Token::And),
+        ("∧", Token::And),
+        ("||", Token::Or),
+        ("\\/", Token::Or),
+        ("|", Token::Or),
+        ("∨", Token::Or),
+        ("!", Token::Not),
+        ("~", Token::Not),
+        ("¬", Token::Not),
+        ("○", Token::Next),
+        ("[]", Token::Globally),
+        ("□", Token::Globally),
+        ("<>", Token::Finally),
+        ("◇", Token::Finally),
+        ("◊", Token::Finally),
+        ("(", Token::LParen),
+        (")", Token::RParen),
+    ];
+
+    let chars = input.chars().collect::<Vec<_>>();
+    let mut tokens = Vec::new();
+    let mut pos = 0;
+    'outer: while pos < chars.len() {
+        let c = chars[pos];
+        if c.is_whitespace() {
+            pos += 1;
+            continue;
+        }
+
+        let is_ident = |c: char| c.is_alphanumeric() || c == '_';
+        if c == '?' {
+            let start = pos;
+            pos += 1;
+            while pos < chars.len() && is_ident(chars[pos]) {
+                pos += 1;
+            }
+            if pos == start + 1 {
+                return Err(ParseError {
+                    position: pos,
+                    message: "expected hole name".to_string(),
+                });
+            }
+            let name = chars[start + 1..pos].iter().collect::<String>();
+            tokens.push((start, Token::Hole(name)));
+            continue;
+        }
+
+        if is_ident(c) {
+            let start = pos;
+            while pos < chars.len() && is_ident(chars[pos]) {
+                pos += 1;
+            }
+            let ident = chars[start..pos].iter().collect::<String>();
+            let token = match ident.as_str() {
+                "X" => Token::Next,
+                "G" => Token::Globally,
+                "F" => Token::Finally,
+                "U" => Token::Until,
+                _ => Token::Ident(ident),
+            };
+            tokens.push((start, token));
+            continue;
+        }
+
+        for (symbol, token) in SYMBOLS.iter() {
+            let len = symbol.chars().count();
+            if pos + len <= chars.len() && chars[pos..pos + len].iter().copied().eq(symbol.chars())
+            {
+                tokens.push((pos, token.clone()));
+                pos += len;
+                continue 'outer;
+            }
+        }
+
+        return Err(ParseError {
+            position: pos,
+            message: format!("unexpected character `{c}`"),
+        });
+    }
+
+    Ok(tokens)
+}
+
+/// Recursive-descent parser.
+/// Operators are listed from the loosest to the tightest binding:
+/// implication (right-associative), disjunction, conjunction, until (right-associative), unary operators.
+struct Parser<'a> {
+    tokens: Vec<(usize, Token)>,
+    pos: usize,
+    end: usize,
+    names: &'a [String],
+    /// The names of the holes met so far, if holes are allowed.
+    holes: Option<Vec<String>>,
+}
+
+impl<'a> Parser<'a> {
+    fn new(input: &str, names: &'a [String], holes: bool) -> Result<Self, ParseError> {
+        Ok(Parser {
+            tokens: tokenize(input)?,
+            pos: 0,
+            end: input.chars().count(),
+            names,
+            holes: holes.then(Vec::new),
+        })
+    }
+
+    fn parse(&mut self) -> Result<Sketch, ParseError> {
+        let sketch = self.implication()?;
+        match self.peek() {
+            None => Ok(sketch),
+            Some(token) => Err(self.error(format!("unexpected {token}"))),
+        }
+    }
+
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos).map(|(_, token)| token)
+    }
+
+    fn position(&self) -> usize {
+        self.tokens
+            .get(self.pos)
+            .map_or(self.end, |(position, _)| *position)
+    }
+
+    fn error(&self, message: String) -> ParseError {
+        ParseError {
+            position: self.position(),
+            message,
+        }
+    }
+
+    fn eat(&mut self, token: &Token) -> bool {
+        if self.peek() == Some(token) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn implication(&mut self) -> Result<Sketch, ParseError> {
+        let left = self.disjunction()?;
+        if self.eat(&Token::Implies) {
+            let right = self.implication()?;
+            Ok(Sketch::Implies(Arc::new(left), Arc::new(right)))
+        } else {
+            Ok(left)
+        }
+    }
+
+    fn disjunction(&mut self) -> Result<Sketch, ParseError> {
+        let mut left = self.conjunction()?;
+        while self.eat(&Token::Or) {
+            let right = self.conjunction()?;
+            left = Sketch::Or(Arc::new(left), Arc::new(right));
+        }
+        Ok(left)
+    }
+
+    fn conjunction(&mut self) -> Result<Sketch, ParseError> {
+        let mut left = self.until()?;
+        while self.eat(&Token::And) {
+            let right = self.until()?;
+            left = Sketch::And(Arc::new(left), Arc::new(right));
+        }
+        Ok(left)
+    }
+
+    fn until(&mut self) -> Result<Sketch, ParseError> {
+        let left = self.unary()?;
+        if self.eat(&Token::Until) {
+            let right = self.until()?;
+            Ok(Sketch::Until(Arc::new(left), Arc::new(right)))
+        } else {
+            Ok(left)
+        }
+    }
+
+    fn unary(&mut self) -> Result<Sketch, ParseError> {
+        let token = self
+            .peek()
+            .cloned()
+            .ok_or_else(|| self.error("unexpected end of formula".to_string()))?;
+        match token {
+            Token::Not => {
+                self.pos += 1;
+                Ok(Sketch::Not(Arc::new(self.unary()?)))
+            }
+            Token::Next => {
+                self.pos += 1;
+                Ok(Sketch::Next(Arc::new(self.unary()?)))
+            }
+            Token::Globally => {
+                self.pos += 1;
+                Ok(Sketch::Globally(Arc::new(self.unary()?)))
+            }
+            Token::Finally => {
+                self.pos += 1;
+                Ok(Sketch::Finally(Arc::new(self.unary()?)))
+            }
+            Token::LParen => {
+                self.pos += 1;
+                let formula = self.implication()?;
+                if self.eat(&Token::RParen) {
+                    Ok(formula)
+                } else {
+                    Err(self.error("expected `)`".to_string()))
+                }
+            }
+            Token::Ident(ident) => {
+                let atom = self.atom(&ident)?;
+                self.pos += 1;
+                Ok(Sketch::Atom(atom))
+            }
+            Token::Hole(name) if self.holes.is_some() => {
+                let holes = self.holes.as_mut().expect("holes allowed");
+                let hole = holes
+                    .iter()
+                    .position(|hole| *hole == name)
+                    .unwrap_or_else(|| {
+                        holes.push(name);
+                        holes.len() - 1
+                    });
+                self.pos += 1;
+                Ok(Sketch::Hole(hole))
+            }
+            token => Err(self.error(format!("unexpected {token}"))),
+        }
+    }
+
+    /// Variables are either given by name, or by index as `x<n>` or `p<n>`.
+    fn atom(&self, ident: &str) -> Result<Idx, ParseError> {
+        if let Some(idx) = self.names.iter().position(|name| name == ident) {
+            return Idx::try_from(idx)
+                .map_err(|_| self.error(format!("too many variables for `{ident}`")));
+        }
+
+        ident
+            .strip_prefix('x')
+            .or_else(|| ident.strip_prefix('p'))
+            .and_then(|idx| idx.parse::<Idx>().ok())
+            .ok_or_else(|| self.error(format!("unknown variable `{ident}`")))
+    }
+}
+
+impl SyntaxTree {
+    /// Parses a formula, where variables can be referred to by the given names
+    /// (as well as by their index, as `x<n>` or `p<n>`).
+    pub fn parse_w_named_vars(input: &str, names: &[String]) -> Result<Self, ParseError> {
+        // Without holes, the sketch is a formula.
+        Ok(Parser::new(input, names, false)?.parse()?.fill(&[]))
+    }
+}
+
+impl Sketch {
+    /// Parses a formula with holes, written as `?` followed by a name (e.g., `G(?1 -> F ?2)`),
+    /// where variables can be referred to by the given names (see [`SyntaxTree::parse_w_named_vars`]).
+    /// Holes are numbered in order of first appearance, and holes with the same name are the same hole.
+    pub fn parse_w_named_vars(input: &str, names: &[String]) -> Result<Self, ParseError> {
+        Parser::new(input, names, true)?.parse()
+    }
+
+    /// Parses a formula with holes as [`Sketch::parse_w_named_vars`] does, also returning the names of the holes by number.
+    pub(crate) fn parse_w_hole_names(
+        input: &str,
+        names: &[String],
+    ) -> Result<(Self, Vec<String>), ParseError> {
+        let mut parser = Parser::new(input, names, true)?;
+        let sketch = parser.parse()?;
+        Ok((sketch, parser.holes.unwrap_or_default()))
+    }
+}
+
+/// Parses formulae in standard LTL syntax, such as `G(p0 -> F p1)`.
+/// Operators can be written in ASCII (`!`, `X`, `G`, `F`, `&`, `|`, `->`, `U`),
+/// SPIN-style (`[]`, `<>`, `&&`, `||`) or Unicode (`¬`, `○`, `□`, `◇`, `∧`, `∨`, `→`) notation.
+/// Variables are referred to by index, as `x<n>` or `p<n>`.
+impl FromStr for SyntaxTree {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        SyntaxTree::parse_w_named_vars(s, &[])
+    }
+}
+
+/// Parses formulae with holes, with the same syntax as [`SyntaxTree`] (see [`Sketch::parse_w_named_vars`]).
+impl FromStr for Sketch {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Sketch::parse_w_named_vars(s, &[])
+    }
+}
+
+#[cfg(test)]
+mod parsing {
+    use super::*;
+    use crate::format::*;
+
+    fn formula() -> SyntaxTree {
+        // G(x0 -> F x1)
+        SyntaxTree::Globally(Arc::new(SyntaxTree::Implies(
+            Arc::new(SyntaxTree::Atom(0)),
+            Arc::new(SyntaxTree::Finally(Arc::new(SyntaxTree::Atom(1)))),
+        )))
+    }
+
+    #[test]
+    fn notations() {
+        assert_eq!("G(p0 -> F p1)".parse::<SyntaxTree>(), Ok(formula()));
+        assert_eq!("[](x0 -> <> x1)".parse::<SyntaxTree>(), Ok(formula()));
+        assert_eq!("□(x0 → ◇x1)".parse::<SyntaxTree>(), Ok(formula()));
+    }
+
+    #[test]
+    fn round_trip() {
+        let formula = formula();
+        assert_eq!(
+            formula.to_string().parse::<SyntaxTree>(),
+            Ok(formula.clone())
+        );
+        for notation in [Notation::Unicode, Notation::Ascii] {
+            let printed = formula.format(notation).to_string();
+            assert_eq!(printed.parse::<SyntaxTree>(), Ok(formula.clone()));
+        }
+    }
+
+    #[test]
+    fn precedence() {
+        let a = || Arc::new(SyntaxTree::Atom(0));
+        let b = || Arc::new(SyntaxTree::Atom(1));
+        let c = || Arc::new(SyntaxTree::Atom(2));
+
+        assert_eq!(
+            "x0 | x1 & x2".parse::<SyntaxTree>(),
+            Ok(SyntaxTree::Or(a(), Arc::new(SyntaxTree::And(b(), c()))))
+        );
+        assert_eq!(
+            "x0 -> x1 -> x2".parse::<SyntaxTree>(),
+            Ok(SyntaxTree::Implies(
+                a(),
+                Arc::new(SyntaxTree::Implies(b(), c()))
+            ))
+        );
+        assert_eq!(
+            "!x0 U x1".parse::<SyntaxTree>(),
+            Ok(SyntaxTree::Until(Arc::new(SyntaxTree::Not(a())), b()))
+        );
+    }
+
+    #[test]
+    fn named_vars() {
+        let names = ["request".to_string(), "grant".to_string()];
+        assert_eq!(
+            SyntaxTree::parse_w_named_vars("G(request -> F grant)", &names),
+            Ok(formula())
+        );
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(
+            "G(x0 -> F x1".parse::<SyntaxTree>().unwrap_err().position,
+            12
+        );
+        assert!("G(y0)".parse::<SyntaxTree>().is_err());
+        assert!("x0 x1".parse::<SyntaxTree>().is_err());
+    }
+}
@@ -0,0 +1,148 @@
+use crate::learner::*;
+use crate::syntax::*;
+use crate::trace::*;
+use std::fmt;
+
+impl<const N: usize> Sample<N> {
+    /// Returns the sample with every trace reversed, so that learning a formula on it
+    /// learns a pure-past formula on the original sample (see [`PastFormula`]).
+    /// Lassos have no end, so they are dropped.
+    pub fn reversed(&self) -> Sample<N> {
+        let reverse = |traces: &[Trace<N>]| {
+            traces
+                .iter()
+                .map(|trace| trace.iter().rev().copied().collect())
+                .collect()
+        };
+        Sample {
+            positive_traces: reverse(&self.positive_traces),
+            negative_traces: reverse(&self.negative_traces),
+            positive_lassos: Vec::new(),
+            negative_lassos: Vec::new(),
+            ..self.clone()
+        }
+    }
+}
+
+/// A formula of the pure-past fragment of LTL, evaluated at the end of traces, which is natural for logs
+/// and can be monitored by looking at each state only once.
+///
+/// It is represented by its mirror image in the future fragment, where
+/// yesterday (`Y`) is next, historically (`H`) is globally, once (`O`) is finally, and since (`S`) is until:
+/// the past formula holds at the end of a trace exactly when its mirror holds at the start of the reversed trace.
+/// Like next, yesterday is false at the first position.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PastFormula(pub SyntaxTree);
+
+impl PastFormula {
+    /// Evaluates the formula at the end of the trace.
+    pub fn eval<S: AtomValuation + Clone>(&self, trace: &[S]) -> bool {
+        let reversed = trace.iter().rev().cloned().collect::<Vec<_>>();
+        !reversed.is_empty() && self.0.eval(&reversed)
+    }
+
+    pub fn print_w_named_vars(&self, names: &[String]) -> String {
+        NamedPastFormula {
+            formula: &self.0,
+            names,
+        }
+        .to_string()
+    }
+}
+
+impl fmt::Display for PastFormula {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        NamedPastFormula {
+            formula: &self.0,
+            names: &[],
+        }
+        .fmt(f)
+    }
+}
+
+/// A past formula displayed with custom names for its propositional variables, as [`NamedSyntaxTree`].
+struct NamedPastFormula<'a> {
+    formula: &'a SyntaxTree,
+    names: &'a [String],
+}
+
+impl<'a> fmt::Display for NamedPastFormula<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let named = |formula| NamedPastFormula {
+            formula,
+            names: self.names,
+        };
+        match self.formula {
+            SyntaxTree::Atom(var) => match self.names.get(*var as usize) {
+                Some(name) => write!(f, "{}", name),
+                None => write!(f, "x{}", var),
+            },
+            SyntaxTree::Not(branch) => write!(f, "¬({})", named(branch)),
+            SyntaxTree::Next(branch) => write!(f, "Y({})", named(branch)),
+            SyntaxTree::Globally(branch) => write!(f, "H({})", named(branch)),
+            SyntaxTree::Finally(branch) => write!(f, "O({})", named(branch)),
+            SyntaxTree::And(left_branch, right_branch) => {
+                write!(f, "({})∧({})", named(left_branch), named(right_branch))
+            }
+            SyntaxTree::Or(left_branch, right_branch) => {
+                write!(f, "({})∨({})", named(left_branch), named(right_branch))
+            }
+            SyntaxTree::Implies(left_branch, right_branch) => {
+                write!(f, "({})→({})", named(left_branch), named(right_branch))
+            }
+            SyntaxTree::Until(left_branch, right_branch) => {
+                write!(f, "({})S({})", named(left_branch), named(right_branch))
+            }
+        }
+    }
+}
+
+/// Learns a pure-past formula with any learner, by learning on the reversed sample (see [`Sample::reversed`]).
+/// The formula of the result is the mirror image of the past formula, to be wrapped in a [`PastFormula`].
+pub fn learn_past<const N: usize, L: Learner<N> + ?Sized>(
+    learner: &L,
+    sample: &Sample<N>,
+    budget: Budget,
+) -> LearnResult {
+    learner.learn(&sample.reversed(), budget)
+}
+
+#[cfg(test)]
+mod past_formulas {
+    use super::*;
+
+    #[test]
+    fn learn() {
+        // Positive traces are those where x1 holds at the end, and x0 held at some point before.
+        let sample = Sample::<2> {
+            positive_traces: vec![
+                vec![[true, false], [false, true]],
+                vec![[false, false], [true, false], [false, false], [false, true]],
+            ],
+            negative_traces: vec![
+                vec![[false, false], [false, true]],
+                vec![[true, false], [false, false]],
+                vec![[true, true], [true, false]],
+            ],
+            ..Sample::default()
+        };
+        let formula = learn_past(&BruteForce::default(), &sample, Budget::default())
+            .into_formula()
+            .map(PastFormula)
+            .expect("solvable");
+        assert!(sample
+            .positive_traces
+            .iter()
+            .all(|trace| formula.eval(trace)));
+        assert!(!sample
+            .negative_traces
+            .iter()
+            .any(|trace| formula.eval(trace)));
+
+        let once = PastFormula(SyntaxTree::Atom(1).and(SyntaxTree::Atom(0).next().finally()));
+        assert_eq!(
+            once.print_w_named_vars(&["p".to_string()]),
+            "(x1)∧(O(Y(p)))"
+        );
+    }
+}
@@ -0,0 +1,136 @@
+use crate::learn::*;
+use crate::learner::*;
+use crate::syntax::*;
+use crate::trace::*;
+use rand::Rng;
+use std::time::Instant;
+
+/// Learns formulas from positive traces alone, e.g., when no negative traces are available.
+/// Without negative traces, any formula satisfied by all positive traces, such as `x0 ∨ ¬x0`, is consistent,
+/// so the learner prefers the tightest one: the least likely to be satisfied by a random trace
+/// (see [`satisfaction_probability`](crate::satisfaction_probability)), as a proxy for the size of its language.
+/// A penalty proportional to the size of the formulas keeps them from describing the positive traces verbatim.
+///
+/// Negative traces, if any, have to be excluded too.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PositiveOnlyLearner {
+    /// The maximum size of the formulas, which the budget can lower.
+    pub max_size: usize,
+    /// The number of random traces estimating how likely formulas are to be satisfied.
+    pub samples: usize,
+    /// The penalty for each node of a formula, added to its probability of being satisfied.
+    pub size_penalty: f64,
+}
+
+impl Default for PositiveOnlyLearner {
+    fn default() -> Self {
+        PositiveOnlyLearner {
+            max_size: 5,
+            samples: 1000,
+            size_penalty: 0.01,
+        }
+    }
+}
+
+impl PositiveOnlyLearner {
+    pub fn max_size(mut self, max_size: usize) -> Self {
+        self.max_size = max_size;
+        self
+    }
+
+    pub fn samples(mut self, samples: usize) -> Self {
+        self.samples = samples;
+        self
+    }
+
+    pub fn size_penalty(mut self, size_penalty: f64) -> Self {
+        self.size_penalty = size_penalty;
+        self
+    }
+
+    /// The tightest formula consistent with the sample, with its estimated probability of being satisfied,
+    /// or `None` if there is none up to the maximum size, or no positive trace.
+    /// All the formulas are estimated on the same random traces, whose lengths are those of the positive traces.
+    pub fn tightest<const N: usize, R: Rng + ?Sized>(
+        &self,
+        sample: &Sample<N>,
+        rng: &mut R,
+    ) -> Option<(SyntaxTree, f64)> {
+        self.tightest_until(sample, None, rng)
+    }
+
+    fn tightest_until<const N: usize, R: Rng + ?Sized>(
+        &self,
+        sample: &Sample<N>,
+        deadline: Option<Instant>,
+        rng: &mut R,
+    ) -> Option<(SyntaxTree, f64)> {
+        if sample.positive_traces.is_empty() {
+            return None;
+        }
+        let traces = sample
+            .positive_traces
+            .iter()
+            .cycle()
+            .take(self.samples)
+            .map(|trace| random_trace::<N, R>(trace.len(), rng))
+            .collect::<Vec<_>>();
+
+        let vars = sample.vars();
+        let mut best: Option<(SyntaxTree, f64, f64)> = None;
+        for size in 1..=self.max_size {
+            if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+                break;
+            }
+            for formula in gen_formulae_iter::<N>(size, &vars) {
+                if !sample.is_consistent(&formula) {
+                    continue;
+                }
+                let satisfied = traces.iter().filter(|trace| formula.eval(trace)).count();
+                let probability = satisfied as f64 / traces.len().max(1) as f64;
+                let score = probability + self.size_penalty * size as f64;
+                if best.as_ref().is_none_or(|(_, _, best)| score < *best) {
+                    best = Some((formula, probability, score));
+                }
+            }
+        }
+        best.map(|(formula, probability, _)| (formula, probability))
+    }
+}
+
+impl<const N: usize> Learner<N> for PositiveOnlyLearner {
+    /// Returns the tightest formula consistent with the sample, searching up to the maximum size or until the time is up.
+    fn learn(&self, sample: &Sample<N>, budget: Budget) -> LearnResult {
+        if !sample.is_solvable() {
+            return LearnResult::Unsolvable;
+        }
+
+        let learner = budget
+            .max_size
+            .map_or(*self, |max| self.max_size(max.min(self.max_size)));
+        match learner.tightest_until(sample, budget.deadline(), &mut rand::thread_rng()) {
+            Some((formula, _)) => LearnResult::Consistent(formula),
+            None => LearnResult::Exhausted,
+        }
+    }
+}
+
+#[cfg(test)]
+mod positive_only {
+    use super::*;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    #[test]
+    fn tightest() {
+        let sample = Sample {
+            negative_traces: Vec::new(),
+            ..Sample::always_x0()
+        };
+        let learner = PositiveOnlyLearner::default().max_size(3).samples(200);
+        let (formula, probability) = learner
+            .tightest(&sample, &mut StdRng::seed_from_u64(0))
+            .expect("some formula");
+        assert_eq!(formula, SyntaxTree::Atom(0).globally());
+        assert!(probability < 0.5);
+    }
+}
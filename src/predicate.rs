@@ -0,0 +1,236 @@
+use crate::builder::*;
+use crate::trace::*;
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// A trace over numeric signals: at each time instant, it records the value of every signal.
+pub type NumericTrace = Vec<Vec<f64>>;
+
+/// The comparison operator of a [`Predicate`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Comparison {
+    Less,
+    Greater,
+}
+
+/// A propositional atom defined over a numeric signal, such as `x > 3.5`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Predicate {
+    pub signal: usize,
+    pub comparison: Comparison,
+    pub threshold: f64,
+}
+
+impl Predicate {
+    pub fn new(signal: usize, comparison: Comparison, threshold: f64) -> Self {
+        Predicate {
+            signal,
+            comparison,
+            threshold,
+        }
+    }
+
+    /// Whether the predicate holds in the given state of a numeric trace.
+    ///
+    /// # Panics
+    ///
+    /// If the state has no value for the signal of the predicate,
+    /// which [`NumericSample::discretize`] checks beforehand.
+    pub fn holds(&self, state: &[f64]) -> bool {
+        let value = state[self.signal];
+        match self.comparison {
+            Comparison::Less => value < self.threshold,
+            Comparison::Greater => value > self.threshold,
+        }
+    }
+
+    /// Prints the predicate using the given signal names.
+    pub fn print_w_named_signals(&self, signals: &[String]) -> String {
+        let op = match self.comparison {
+            Comparison::Less => "<",
+            Comparison::Greater => ">",
+        };
+        format!("{} {} {}", signals[self.signal], op, self.threshold)
+    }
+}
+
+impl fmt::Display for Predicate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let op = match self.comparison {
+            Comparison::Less => "<",
+            Comparison::Greater => ">",
+        };
+        write!(f, "s{} {} {}", self.signal, op, self.threshold)
+    }
+}
+
+/// A sample of positive and negative traces over numeric signals.
+/// It is turned into a (propositional) [`Sample`] by discretizing it over a set of [`Predicate`]s.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct NumericSample {
+    #[serde(default)]
+    pub signal_names: Vec<String>,
+    pub positive_traces: Vec<NumericTrace>,
+    pub negative_traces: Vec<NumericTrace>,
+}
+
+impl NumericSample {
+    /// The number of signals recorded in the traces of the sample.
+    pub fn signals(&self) -> usize {
+        self.positive_traces
+            .iter()
+            .chain(self.negative_traces.iter())
+            .flat_map(|trace| trace.iter())
+            .map(|state| state.len())
+            .max()
+            .unwrap_or(0)
+    }
+
+    fn signal_name(&self, signal: usize) -> String {
+        self.signal_names
+            .get(signal)
+            .cloned()
+            .unwrap_or_else(|| format!("s{signal}"))
+    }
+
+    /// Candidate thresholds for the given signal,
+    /// given by the midpoints between consecutive distinct values appearing in the sample.
+    /// If there are more than `max_thresholds` candidates, they are subsampled evenly.
+    pub fn thresholds(&self, signal: usize, max_thresholds: usize) -> Vec<f64> {
+        let values = self
+            .positive_traces
+            .iter()
+            .chain(self.negative_traces.iter())
+            .flat_map(|trace| trace.iter())
+            .filter_map(|state| state.get(signal).copied())
+            .filter(|value| !value.is_nan())
+            .sorted_by(|a, b| a.total_cmp(b))
+            .dedup()
+            .collect_vec();
+
+        let midpoints = values
+            .iter()
+            .tuple_windows()
+            .map(|(low, high)| (low + high) / 2.0)
+            .collect_vec();
+
+        if midpoints.len() <= max_thresholds {
+            midpoints
+        } else {
+            (0..max_thresholds)
+                .map(|n| midpoints[(n * midpoints.len()) / max_thresholds])
+                .collect()
+        }
+    }
+
+    /// Candidate predicates of the form `signal > threshold` for every signal of the sample,
+    /// with thresholds given by [`NumericSample::thresholds`].
+    /// Predicates of the form `signal < threshold` are not needed, as they are expressible by negation.
+    pub fn candidate_predicates(&self, max_thresholds: usize) -> Vec<Predicate> {
+        (0..self.signals())
+            .flat_map(|signal| {
+                self.thresholds(signal, max_thresholds)
+                    .into_iter()
+                    .map(move |threshold| Predicate::new(signal, Comparison::Greater, threshold))
+            })
+            .collect()
+    }
+
+    /// Discretizes the sample over the given predicates,
+    /// so that the n-th propositional variable is true whenever the n-th predicate holds.
+    /// Note that distinct numeric traces can become identical once discretized,
+    /// possibly making the resulting sample unsolvable.
+    /// Fails if a state has no value for the signal of some predicate.
+    pub fn discretize<const N: usize>(
+        &self,
+        predicates: &[Predicate; N],
+    ) -> Result<Sample<N>, SampleError> {
+        for (label, traces) in [
+            (Label::Positive, &self.positive_traces),
+            (Label::Negative, &self.negative_traces),
+        ] {
+            for (trace, states) in traces.iter().enumerate() {
+                for (time, state) in states.iter().enumerate() {
+                    if let Some(predicate) = predicates
+                        .iter()
+                        .find(|predicate| predicate.signal >= state.len())
+                    {
+                        return Err(SampleError::MissingSignal {
+                            label,
+                            trace,
+                            time,
+                            signal: predicate.signal,
+                        });
+                    }
+                }
+            }
+        }
+
+        let discretize_trace = |trace: &NumericTrace| -> Trace<N> {
+            trace
+                .iter()
+                .map(|state| predicates.map(|predicate| predicate.holds(state)))
+                .collect()
+        };
+        let signal_names = (0..self.signals())
+            .map(|signal| self.signal_name(signal))
+            .collect_vec();
+
+        Ok(Sample {
+            var_names: predicates.map(|predicate| predicate.print_w_named_signals(&signal_names)),
+            positive_traces: self.positive_traces.iter().map(discretize_trace).collect(),
+            negative_traces: self.negative_traces.iter().map(discretize_trace).collect(),
+            ..Sample::default()
+        })
+    }
+}
+
+#[cfg(test)]
+mod discretization {
+    use super::*;
+    use crate::syntax::*;
+    use std::ops::Not;
+
+    fn sample() -> NumericSample {
+        NumericSample {
+            signal_names: vec!["speed".to_string()],
+            positive_traces: vec![vec![vec![1.0], vec![2.0]], vec![vec![0.5]]],
+            negative_traces: vec![vec![vec![1.0], vec![4.0]]],
+        }
+    }
+
+    #[test]
+    fn thresholds() {
+        let sample = sample();
+        assert_eq!(sample.thresholds(0, 10), vec![0.75, 1.5, 3.0]);
+        assert_eq!(sample.thresholds(0, 1), vec![0.75]);
+    }
+
+    #[test]
+    fn discretize() {
+        let sample = sample();
+        let predicate = Predicate::new(0, Comparison::Greater, 3.0);
+        let discretized = sample
+            .discretize(&[predicate])
+            .expect("signal 0 is recorded");
+
+        assert_eq!(discretized.var_names, ["speed > 3".to_string()]);
+        // G ¬(speed > 3)
+        let formula = SyntaxTree::Atom(0).not().globally();
+        assert!(discretized.is_consistent(&formula));
+
+        let mut ragged = sample;
+        ragged.negative_traces[0].push(vec![]);
+        assert_eq!(ragged.thresholds(0, 10), vec![0.75, 1.5, 3.0]);
+        assert_eq!(
+            ragged.discretize(&[predicate]).unwrap_err(),
+            SampleError::MissingSignal {
+                label: Label::Negative,
+                trace: 0,
+                time: 2,
+                signal: 0
+            }
+        );
+    }
+}
@@ -0,0 +1,269 @@
+use crate::source::*;
+use crate::syntax::*;
+use crate::trace::*;
+use std::collections::HashSet;
+use std::sync::Arc;
+
+/// The truth values of a formula at every position of every trace of a sample, one trace after the other.
+/// The positions of a lasso are those of its prefix, followed by those of its cycle.
+type Behaviour = Vec<bool>;
+
+/// The traces of a sample, positive ones first, as positions in behaviours.
+struct Positions {
+    /// The first position of each trace, followed by the total number of positions.
+    starts: Vec<usize>,
+    /// For each trace, the first position of its cycle if it is a lasso,
+    /// which follows its last position.
+    cycles: Vec<Option<usize>>,
+    positives: usize,
+}
+
+impl Positions {
+    fn traces(&self) -> impl Iterator<Item = std::ops::Range<usize>> + '_ {
+        self.starts.windows(2).map(|pair| pair[0]..pair[1])
+    }
+
+    fn is_consistent(&self, behaviour: &[bool]) -> bool {
+        self.traces()
+            .enumerate()
+            .all(|(n, trace)| behaviour[trace.start] == (n < self.positives))
+    }
+
+    /// The values of the formula at the next position of each position, false past the end of finite traces.
+    fn next(&self, values: &[bool]) -> Behaviour {
+        let mut behaviour = vec![false; values.len()];
+        for (trace, cycle) in self.traces().zip(&self.cycles) {
+            if trace.is_empty() {
+                continue;
+            }
+            behaviour[trace.start..trace.end - 1]
+                .copy_from_slice(&values[trace.start + 1..trace.end]);
+            if let Some(cycle) = cycle {
+                behaviour[trace.end - 1] = values[*cycle];
+            }
+        }
+        behaviour
+    }
+
+    /// Computes a temporal operator backward along each trace,
+    /// from its value at the last position and from its value at the next position otherwise.
+    /// Along lassos, it is computed as a fixpoint from the given value around the cycle, like [`SyntaxTree::eval_lasso`]:
+    /// `true` for greatest fixpoints (e.g., `G`), `false` for least ones (e.g., `F` and `U`).
+    fn backward(
+        &self,
+        last: impl Fn(usize) -> bool,
+        step: impl Fn(usize, bool) -> bool,
+        fixpoint: bool,
+    ) -> Behaviour {
+        let mut behaviour = vec![false; *self.starts.last().unwrap_or(&0)];
+        for (trace, cycle) in self.traces().zip(&self.cycles) {
+            if trace.is_empty() {
+                continue;
+            }
+            match cycle {
+                None => {
+                    behaviour[trace.end - 1] = last(trace.end - 1);
+                    for pos in trace.clone().rev().skip(1) {
+                        behaviour[pos] = step(pos, behaviour[pos + 1]);
+                    }
+                }
+                // Two backward sweeps are enough to propagate values around the cycle.
+                Some(cycle) => {
+                    behaviour[trace.clone()].fill(fixpoint);
+                    for _ in 0..2 {
+                        for pos in trace.clone().rev() {
+                            let next = if pos + 1 == trace.end {
+                                *cycle
+                            } else {
+                                pos + 1
+                            };
+                            behaviour[pos] = step(pos, behaviour[next]);
+                        }
+                    }
+                }
+            }
+        }
+        behaviour
+    }
+}
+
+/// Find a formula consistent with the given sample, which can be any [`SampleSource`], by enumerating formulas bottom-up,
+/// keeping only the first (and smallest) formula of each behaviour on the sample,
+/// i.e., its truth values at every position of every trace, lassos included.
+/// Formulas with the same behaviour are interchangeable as subformulas, so pruning the others loses no solution,
+/// and typically reduces the search space by orders of magnitude compared to [`solve`](crate::solve).
+///
+/// Searches up to the maximum size if any, otherwise until it finds a formula, which it does if the sample is solvable.
+/// The whole sample is kept in memory, as well as all the formulas with distinct behaviours.
+pub fn solve_pruned<const N: usize, S: SampleSource<N>>(
+    sample: &S,
+    max_size: Option<usize>,
+) -> Option<SyntaxTree> {
+    if !sample.is_solvable() {
+        return None;
+    }
+
+    // Each trace with the length of its prefix if it is a lasso, positive ones first.
+    let positive_lassos = sample.positive_lassos();
+    let negative_lassos = sample.negative_lassos();
+    let lasso_trace = |lasso: &Lasso<N>| {
+        let states = lasso.prefix.iter().chain(&lasso.cycle).copied().collect();
+        (states, Some(lasso.prefix.len()))
+    };
+    let traces: Vec<(Trace<N>, Option<usize>)> = sample
+        .iter_positive()
+        .map(|trace| (trace.into_owned(), None))
+        .chain(positive_lassos.iter().map(lasso_trace))
+        .chain(
+            sample
+                .iter_negative()
+                .map(|trace| (trace.into_owned(), None)),
+        )
+        .chain(negative_lassos.iter().map(lasso_trace))
+        .collect();
+    let mut starts = vec![0];
+    starts.extend(traces.iter().scan(0, |end, (trace, _)| {
+        *end += trace.len();
+        Some(*end)
+    }));
+    let positions = Positions {
+        cycles: traces
+            .iter()
+            .zip(&starts)
+            .map(|((_, prefix), start)| prefix.map(|prefix| start + prefix))
+            .collect(),
+        starts,
+        positives: sample.positive_count() + positive_lassos.len(),
+    };
+    let states: Vec<&[bool; N]> = traces.iter().flat_map(|(trace, _)| trace).collect();
+
+    // The formulas of each size with new behaviours.
+    let mut levels: Vec<Vec<(Arc<SyntaxTree>, Behaviour)>> = vec![Vec::new()];
+    let mut seen: HashSet<Behaviour> = HashSet::new();
+    for size in 1..=max_size.unwrap_or(usize::MAX) {
+        let mut candidates: Vec<(SyntaxTree, Behaviour)> = Vec::new();
+
+        if size == 1 {
+            for var in sample.vars() {
+                let behaviour = states.iter().map(|state| state.holds(var)).collect();
+                candidates.push((SyntaxTree::Atom(var), behaviour));
+            }
+        } else {
+            for (child, values) in &levels[size - 1] {
+                candidates.push((
+                    SyntaxTree::Not(child.clone()),
+                    values.iter().map(|value| !value).collect(),
+                ));
+                candidates.push((SyntaxTree::Next(child.clone()), positions.next(values)));
+                candidates.push((
+                    SyntaxTree::Globally(child.clone()),
+                    positions.backward(|pos| values[pos], |pos, next| values[pos] && next, true),
+                ));
+                candidates.push((
+                    SyntaxTree::Finally(child.clone()),
+                    positions.backward(|pos| values[pos], |pos, next| values[pos] || next, false),
+                ));
+            }
+            for left_size in 1..(size - 1) {
+                for (left, left_values) in &levels[left_size] {
+                    for (right, right_values) in &levels[size - 1 - left_size] {
+                        let pointwise = |op: fn(bool, bool) -> bool| {
+                            left_values
+                                .iter()
+                                .zip(right_values)
+                                .map(|(&left, &right)| op(left, right))
+                                .collect()
+                        };
+                        candidates.push((
+                            SyntaxTree::And(left.clone(), right.clone()),
+                            pointwise(|l, r| l && r),
+                        ));
+                        candidates.push((
+                            SyntaxTree::Or(left.clone(), right.clone()),
+                            pointwise(|l, r| l || r),
+                        ));
+                        candidates.push((
+                            SyntaxTree::Implies(left.clone(), right.clone()),
+                            pointwise(|l, r| !l || r),
+                        ));
+                        candidates.push((
+                            SyntaxTree::Until(left.clone(), right.clone()),
+                            positions.backward(
+                                |pos| right_values[pos],
+                                |pos, next| right_values[pos] || (left_values[pos] && next),
+                                false,
+                            ),
+                        ));
+                    }
+                }
+            }
+        }
+
+        let mut level = Vec::new();
+        for (formula, behaviour) in candidates {
+            if seen.contains(&behaviour) {
+                continue;
+            }
+            // Behaviours decide consistency, which the sample confirms in case they missed some of its traces.
+            if positions.is_consistent(&behaviour) && sample.is_consistent(&formula) {
+                return Some(formula);
+            }
+            seen.insert(behaviour.clone());
+            level.push((Arc::new(formula), behaviour));
+        }
+        levels.push(level);
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod pruned_search {
+    use super::*;
+    use crate::learn::*;
+
+    #[test]
+    fn same_size_as_brute_force() {
+        let samples = [
+            Sample::always_x0(),
+            Sample::<2> {
+                positive_traces: vec![vec![[true, true]], vec![[false, false], [true, true]]],
+                negative_traces: vec![
+                    vec![[true, false], [false, true]],
+                    vec![[false, false]],
+                    vec![[false, true], [true, false], [false, false]],
+                ],
+                ..Sample::default()
+            },
+            // Lassos, where x0 eventually holds forever on both, but initially only on the negative one.
+            Sample::<2> {
+                positive_lassos: vec![Lasso::new(vec![[false, false]], vec![[true, false]])],
+                negative_lassos: vec![Lasso::new(vec![], vec![[true, false], [false, false]])],
+                ..Sample::default()
+            },
+            // G F x1, which only the positive lassos satisfy.
+            Sample::<2> {
+                positive_lassos: vec![
+                    Lasso::new(vec![[false, false]], vec![[false, true], [true, false]]),
+                    Lasso::new(vec![], vec![[false, true]]),
+                ],
+                negative_lassos: vec![
+                    Lasso::new(vec![[false, true]], vec![[false, false]]),
+                    Lasso::new(vec![[false, true], [true, true]], vec![[true, false]]),
+                ],
+                ..Sample::default()
+            },
+        ];
+        for sample in &samples {
+            let formula = solve_pruned(sample, None).expect("solvable");
+            assert!(sample.is_consistent(&formula));
+            let brute_force = solve(sample, false, false).expect("solvable");
+            assert_eq!(formula.size(), brute_force.size());
+        }
+
+        let mut unsolvable = samples[0].clone();
+        unsolvable.negative_traces.push(vec![[true, true]]);
+        assert_eq!(solve_pruned(&unsolvable, None), None);
+        assert_eq!(solve_pruned(&samples[1], Some(2)), None);
+    }
+}
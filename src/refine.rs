@@ -0,0 +1,145 @@
+use crate::builder::*;
+use crate::stream::*;
+use crate::syntax::*;
+use crate::trace::*;
+
+/// All the traces of the given length over `N` variables, in lexicographic order.
+fn traces_of_length<const N: usize>(length: usize) -> impl Iterator<Item = Trace<N>> {
+    let states = 1usize
+        .checked_shl(N as u32)
+        .expect("too many variables to enumerate states");
+    let mut digits = Some(vec![0usize; length]);
+    std::iter::from_fn(move || {
+        let current = digits.clone()?;
+        // Advance the odometer, where each digit is a state.
+        let next = digits.as_mut().expect("digits");
+        match next.iter().rposition(|digit| digit + 1 < states) {
+            Some(pos) => {
+                next[pos] += 1;
+                next[pos + 1..].iter_mut().for_each(|digit| *digit = 0);
+            }
+            None => digits = None,
+        }
+        Some(
+            current
+                .into_iter()
+                .map(|state| std::array::from_fn(|var| state & (1 << var) != 0))
+                .collect(),
+        )
+    })
+}
+
+impl SyntaxTree {
+    /// Searches for traces, shortest first, that the formula misclassifies according to the user's intent,
+    /// i.e., that it accepts but `intended` labels as negative, or that it rejects but `intended` labels as positive.
+    /// `intended` returns `None` for traces the user does not care about.
+    /// Returns at most `limit` traces, with their intended label,
+    /// which can be added to the sample to learn a better formula (see [`Sample::refine`]).
+    ///
+    /// The search is exhaustive up to `max_length`, so it checks up to `2^(N * max_length)` traces:
+    /// keep both small, or discard irrelevant variables beforehand.
+    pub fn search_counterexamples<const N: usize, F>(
+        &self,
+        max_length: usize,
+        limit: usize,
+        mut intended: F,
+    ) -> Vec<LabelledTrace<N>>
+    where
+        F: FnMut(&[[bool; N]]) -> Option<Label>,
+    {
+        (1..=max_length)
+            .flat_map(traces_of_length::<N>)
+            .filter_map(|trace| {
+                let label = intended(&trace)?;
+                let accepted = self.eval(&trace);
+                (accepted != (label == Label::Positive)).then_some(LabelledTrace { label, trace })
+            })
+            .take(limit)
+            .collect()
+    }
+}
+
+impl<const N: usize> Sample<N> {
+    /// Adds to the sample the counterexamples to a learned formula (see [`SyntaxTree::search_counterexamples`]),
+    /// so that learning again gives a formula closer to the user's intent.
+    /// Returns how many traces were added, failing if some of them contradict the sample.
+    pub fn refine<F>(
+        &mut self,
+        formula: &SyntaxTree,
+        max_length: usize,
+        limit: usize,
+        intended: F,
+    ) -> Result<usize, SampleError>
+    where
+        F: FnMut(&[[bool; N]]) -> Option<Label>,
+    {
+        let mut added = 0;
+        for LabelledTrace { label, trace } in
+            formula.search_counterexamples(max_length, limit, intended)
+        {
+            let pushed = match label {
+                Label::Positive => self.push_positive(trace)?,
+                Label::Negative => self.push_negative(trace)?,
+            };
+            added += pushed as usize;
+        }
+        Ok(added)
+    }
+}
+
+#[cfg(test)]
+mod refinement {
+    use super::*;
+    use std::sync::Arc;
+
+    #[test]
+    fn enumerate() {
+        let traces = (1..=2).flat_map(traces_of_length::<1>).collect::<Vec<_>>();
+        assert_eq!(
+            traces,
+            vec![
+                vec![[false]],
+                vec![[true]],
+                vec![[false], [false]],
+                vec![[false], [true]],
+                vec![[true], [false]],
+                vec![[true], [true]],
+            ]
+        );
+    }
+
+    #[test]
+    fn refine() {
+        // The user wants x1 to eventually hold, but `F x0` was learned.
+        let learned = SyntaxTree::Finally(Arc::new(SyntaxTree::Atom(0)));
+        let intended = |trace: &[[bool; 2]]| {
+            Some(if trace.iter().any(|state| state[1]) {
+                Label::Positive
+            } else {
+                Label::Negative
+            })
+        };
+
+        let counterexamples = learned.search_counterexamples(1, 10, intended);
+        assert_eq!(
+            counterexamples,
+            vec![
+                LabelledTrace {
+                    label: Label::Negative,
+                    trace: vec![[true, false]]
+                },
+                LabelledTrace {
+                    label: Label::Positive,
+                    trace: vec![[false, true]]
+                },
+            ]
+        );
+
+        let mut sample = Sample::<2> {
+            positive_traces: vec![vec![[true, true]]],
+            ..Sample::default()
+        };
+        assert_eq!(sample.refine(&learned, 2, 3, intended), Ok(3));
+        assert!(!sample.is_consistent(&learned));
+    }
+}
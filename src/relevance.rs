@@ -0,0 +1,145 @@
+use crate::syntax::*;
+use crate::trace::*;
+use std::collections::HashSet;
+
+impl<const N: usize> Sample<N> {
+    /// How differently each variable behaves in positive and negative traces:
+    /// the largest difference between the classes in the fraction of traces where it holds at first,
+    /// and in the fraction of states where it holds, from `0.0` (no difference) to `1.0`.
+    /// Lassos are ignored.
+    pub fn relevance(&self) -> [f64; N] {
+        let frequencies = |traces: &[Trace<N>], var: usize| {
+            let first = traces
+                .iter()
+                .filter(|trace| trace.first().is_some_and(|state| state[var]))
+                .count() as f64
+                / traces.len().max(1) as f64;
+            let states = traces.iter().map(Vec::len).sum::<usize>();
+            let holds = traces.iter().flatten().filter(|state| state[var]).count() as f64
+                / states.max(1) as f64;
+            (first, holds)
+        };
+        std::array::from_fn(|var| {
+            let (positive_first, positive_holds) = frequencies(&self.positive_traces, var);
+            let (negative_first, negative_holds) = frequencies(&self.negative_traces, var);
+            (positive_first - negative_first)
+                .abs()
+                .max((positive_holds - negative_holds).abs())
+        })
+    }
+
+    /// The allowed variables (see [`Sample::vars`]) that formulas likely need to tell positive and negative traces apart:
+    /// those whose value changes somewhere in the sample and whose sequences of values along the traces
+    /// are not the same in both classes, plus, if no positive trace is identical to a negative one
+    /// over all the allowed variables but some are over those (see [`Sample::is_solvable`]),
+    /// the next most relevant variables (see [`Sample::relevance`]) until none is.
+    ///
+    /// This is a heuristic: a variable mattering only in combination with others can be left out.
+    pub fn relevant_vars(&self) -> Vec<Idx> {
+        let vars = self.vars();
+        let relevance = self.relevance();
+        let values = |traces: &[Trace<N>], var: Idx| {
+            traces
+                .iter()
+                .map(|trace| {
+                    trace
+                        .iter()
+                        .map(|state| state[var as usize])
+                        .collect::<Vec<_>>()
+                })
+                .collect::<HashSet<_>>()
+        };
+        let varies = |var: Idx| {
+            let mut values = self
+                .positive_traces
+                .iter()
+                .chain(&self.negative_traces)
+                .flatten()
+                .map(|state| state[var as usize]);
+            values
+                .next()
+                .is_some_and(|first| values.any(|value| value != first))
+        };
+        let (mut relevant, mut others): (Vec<Idx>, Vec<Idx>) = vars.iter().partition(|var| {
+            varies(**var)
+                && values(&self.positive_traces, **var) != values(&self.negative_traces, **var)
+        });
+
+        // The other variables are added back from the most relevant one, as long as needed.
+        others.sort_by(|left, right| {
+            relevance[*right as usize].total_cmp(&relevance[*left as usize])
+        });
+        if self.is_solvable() {
+            let mut restricted = self.clone();
+            for var in &others {
+                let name = &mut restricted.var_names[*var as usize];
+                *name = format!("~{name}");
+            }
+            for var in others {
+                if restricted.is_solvable() {
+                    break;
+                }
+                restricted.var_names[var as usize] = self.var_names[var as usize].clone();
+                relevant.push(var);
+            }
+        }
+        relevant.sort();
+        relevant
+    }
+
+    /// The sample with only the relevant variables allowed (see [`Sample::relevant_vars`]),
+    /// the others being prefixed by `~`, so that searches do not enumerate formulas over them.
+    pub fn restrict_to_relevant(&self) -> Sample<N> {
+        let relevant = self.relevant_vars();
+        let mut sample = self.clone();
+        for var in self.vars() {
+            if !relevant.contains(&var) {
+                let name = &mut sample.var_names[var as usize];
+                *name = format!("~{name}");
+            }
+        }
+        sample
+    }
+}
+
+#[cfg(test)]
+mod relevant_atoms {
+    use super::*;
+    use crate::learn::*;
+
+    #[test]
+    fn relevant_vars() {
+        // Positive traces are those where x1 holds at first; x0 and x2 behave the same in both classes.
+        let sample = Sample::<3> {
+            positive_traces: vec![
+                vec![[true, true, false], [false, false, true]],
+                vec![[false, true, true]],
+                vec![[true, true, true]],
+            ],
+            negative_traces: vec![
+                vec![[true, false, false], [false, true, true]],
+                vec![[false, false, true]],
+                vec![[true, false, true]],
+            ],
+            ..Sample::default()
+        };
+        let relevance = sample.relevance();
+        assert_eq!(relevance[1], 1.0);
+        assert!(relevance[0] < 1.0 && relevance[2] < 1.0);
+        assert_eq!(sample.relevant_vars(), vec![1]);
+
+        let restricted = sample.restrict_to_relevant();
+        assert_eq!(restricted.vars(), vec![1]);
+        assert_eq!(restricted.var_names[0], "~x0");
+        assert_eq!(solve(&restricted, false, false), Some(SyntaxTree::Atom(1)));
+
+        // When positive and negative traces differ by the combination of x0 and x1, both are needed,
+        // but not x2, which never changes.
+        let xor = Sample::<3> {
+            positive_traces: vec![vec![[true, false, false]], vec![[false, true, false]]],
+            negative_traces: vec![vec![[true, true, false]], vec![[false, false, false]]],
+            ..Sample::default()
+        };
+        assert_eq!(xor.relevant_vars(), vec![0, 1]);
+    }
+}
@@ -0,0 +1,152 @@
+use crate::parser::*;
+use crate::syntax::*;
+use crate::trace::*;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::error::Error;
+use std::fmt;
+
+/// A renaming of the variables of a sample, recording both the old and the new names,
+/// so that formulae written with one set of names can be read with the other (see [`AtomRenaming::rename_formula`]).
+/// It can be saved (e.g., in RON format) and kept private when sharing an anonymized sample.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AtomRenaming {
+    /// The names before renaming, by variable index.
+    pub from: Vec<String>,
+    /// The names after renaming, by variable index.
+    pub to: Vec<String>,
+}
+
+/// An error preventing the variables of a sample from being renamed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RenameError {
+    /// The mapping renames a variable that is not in the sample.
+    UnknownAtom(String),
+    /// After renaming, two variables would have the same name.
+    DuplicateName(String),
+}
+
+impl fmt::Display for RenameError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RenameError::UnknownAtom(name) => write!(f, "no variable named `{name}`"),
+            RenameError::DuplicateName(name) => {
+                write!(f, "more than one variable would be named `{name}`")
+            }
+        }
+    }
+}
+
+impl Error for RenameError {}
+
+impl AtomRenaming {
+    /// The renaming undoing this one, e.g., to go back from anonymized names to the real ones.
+    pub fn inverse(&self) -> AtomRenaming {
+        AtomRenaming {
+            from: self.to.clone(),
+            to: self.from.clone(),
+        }
+    }
+
+    /// Rewrites a formula written with the old names (e.g., learned from an anonymized sample)
+    /// with the new names.
+    pub fn rename_formula(&self, formula: &str) -> Result<String, ParseError> {
+        SyntaxTree::parse_w_named_vars(formula, &self.from)
+            .map(|formula| formula.print_w_named_vars(&self.to))
+    }
+}
+
+impl<const N: usize> Sample<N> {
+    /// Renames the variables of the sample according to the mapping from old to new names,
+    /// returning the renaming that was applied.
+    /// Variables missing from the mapping keep their name.
+    /// On error, the sample is left unchanged.
+    pub fn rename_atoms(
+        &mut self,
+        mapping: &BTreeMap<String, String>,
+    ) -> Result<AtomRenaming, RenameError> {
+        if let Some(unknown) = mapping.keys().find(|name| !self.var_names.contains(name)) {
+            return Err(RenameError::UnknownAtom(unknown.clone()));
+        }
+        let renamed = self
+            .var_names
+            .clone()
+            .map(|name| mapping.get(&name).cloned().unwrap_or(name));
+        let mut seen = HashSet::new();
+        if let Some(duplicate) = renamed.iter().find(|name| !seen.insert(*name)) {
+            return Err(RenameError::DuplicateName(duplicate.clone()));
+        }
+
+        let renaming = AtomRenaming {
+            from: self.var_names.to_vec(),
+            to: renamed.to_vec(),
+        };
+        self.var_names = renamed;
+        Ok(renaming)
+    }
+
+    /// Replaces the names of the variables with the default ones (`x0`, `x1`, …),
+    /// returning the renaming needed to restore them (through [`AtomRenaming::inverse`]).
+    /// Discarded variables (whose name starts with `~`) stay discarded.
+    pub fn anonymize(&mut self) -> AtomRenaming {
+        let mapping = self
+            .var_names
+            .iter()
+            .enumerate()
+            .map(|(var, name)| {
+                let prefix = if name.starts_with('~') { "~" } else { "" };
+                (name.clone(), format!("{prefix}x{var}"))
+            })
+            .collect();
+        self.rename_atoms(&mapping)
+            .expect("default names are distinct")
+    }
+}
+
+#[cfg(test)]
+mod renaming {
+    use super::*;
+
+    fn sample() -> Sample<2> {
+        Sample {
+            var_names: ["brake_pressure".to_string(), "~wheel_speed".to_string()],
+            ..Sample::default()
+        }
+    }
+
+    #[test]
+    fn anonymize() {
+        let mut sample = sample();
+        let renaming = sample.anonymize();
+        assert_eq!(sample.var_names, ["x0".to_string(), "~x1".to_string()]);
+
+        // A formula learned on the anonymized sample, read with the real names.
+        let formula = renaming
+            .inverse()
+            .rename_formula("G (x0 -> F x1)")
+            .expect("rename formula");
+        assert_eq!(
+            formula,
+            SyntaxTree::parse_w_named_vars("G (brake_pressure -> F x1)", &renaming.from)
+                .expect("parse formula")
+                .print_w_named_vars(&renaming.from)
+        );
+        assert!(formula.contains("brake_pressure"));
+    }
+
+    #[test]
+    fn rename_atoms() {
+        let mut sample = sample();
+        let mapping = BTreeMap::from([("brake_pressure".to_string(), "~wheel_speed".to_string())]);
+        assert_eq!(
+            sample.rename_atoms(&mapping),
+            Err(RenameError::DuplicateName("~wheel_speed".to_string()))
+        );
+        let mapping = BTreeMap::from([("speed".to_string(), "s".to_string())]);
+        assert_eq!(
+            sample.rename_atoms(&mapping),
+            Err(RenameError::UnknownAtom("speed".to_string()))
+        );
+        assert_eq!(sample.var_names, self::sample().var_names);
+    }
+}
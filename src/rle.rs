@@ -0,0 +1,244 @@
+use crate::syntax::*;
+use crate::trace::*;
+use serde::{Deserialize, Serialize};
+use serde_with::*;
+
+/// A run-length encoded trace, storing each maximal stretch of identical states once, with its length.
+/// Traces with long constant stretches (e.g., from control systems) take much less memory,
+/// and formulae are evaluated run by run rather than state by state (see [`SyntaxTree::eval_rle`]).
+#[serde_as]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct RleTrace<const N: usize> {
+    /// The runs of the trace, as pairs of a state and its (positive) number of repetitions.
+    #[serde_as(as = "Vec<([_; N], _)>")]
+    runs: Vec<([bool; N], usize)>,
+}
+
+impl<const N: usize> RleTrace<N> {
+    /// Appends a state repeated the given number of times, merging it with the last run if equal.
+    pub fn push(&mut self, state: [bool; N], repetitions: usize) {
+        if repetitions == 0 {
+            return;
+        }
+        match self.runs.last_mut() {
+            Some((last, length)) if *last == state => *length += repetitions,
+            _ => self.runs.push((state, repetitions)),
+        }
+    }
+
+    /// The runs of the trace, as pairs of a state and its number of repetitions.
+    pub fn runs(&self) -> &[([bool; N], usize)] {
+        &self.runs
+    }
+
+    /// The length of the (decompressed) trace.
+    pub fn len(&self) -> usize {
+        self.runs.iter().map(|(_, length)| length).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.runs.is_empty()
+    }
+
+    /// Decompresses the trace.
+    pub fn to_trace(&self) -> Trace<N> {
+        self.runs
+            .iter()
+            .flat_map(|(state, length)| std::iter::repeat_n(*state, *length))
+            .collect()
+    }
+}
+
+impl<const N: usize> From<&[[bool; N]]> for RleTrace<N> {
+    fn from(trace: &[[bool; N]]) -> Self {
+        let mut rle = RleTrace::default();
+        for state in trace {
+            rle.push(*state, 1);
+        }
+        rle
+    }
+}
+
+impl<const N: usize> FromIterator<[bool; N]> for RleTrace<N> {
+    fn from_iter<I: IntoIterator<Item = [bool; N]>>(iter: I) -> Self {
+        let mut rle = RleTrace::default();
+        for state in iter {
+            rle.push(state, 1);
+        }
+        rle
+    }
+}
+
+impl SyntaxTree {
+    /// Evaluate a formula on a run-length encoded trace, giving the same result as [`SyntaxTree::eval`]
+    /// on the decompressed trace.
+    ///
+    /// A formula with `d` nested Next operators cannot distinguish the positions of a run
+    /// that are more than `d` steps away from its end,
+    /// so only the last `d + 1` positions of each run are evaluated,
+    /// and the cost does not depend on the length of the runs.
+    pub fn eval_rle<const N: usize>(&self, trace: &RleTrace<N>) -> bool {
+        assert!(!trace.is_empty());
+
+        let width = self.next_depth() + 1;
+        self.eval_runs(trace, width)[0][0]
+    }
+
+    /// The maximum number of nested Next operators in the formula.
+    fn next_depth(&self) -> usize {
+        match self {
+            SyntaxTree::Atom(_) => 0,
+            SyntaxTree::Next(branch) => branch.next_depth() + 1,
+            SyntaxTree::Not(branch)
+            | SyntaxTree::Globally(branch)
+            | SyntaxTree::Finally(branch) => branch.next_depth(),
+            SyntaxTree::And(left_branch, right_branch)
+            | SyntaxTree::Or(left_branch, right_branch)
+            | SyntaxTree::Implies(left_branch, right_branch)
+            | SyntaxTree::Until(left_branch, right_branch) => {
+                left_branch.next_depth().max(right_branch.next_depth())
+            }
+        }
+    }
+
+    /// Computes, for each run, the truth values of the formula at its last `width` positions (or all of them, if fewer).
+    /// The first of these values also holds at all earlier positions of the run.
+    fn eval_runs<const N: usize>(&self, trace: &RleTrace<N>, width: usize) -> Vec<Vec<bool>> {
+        match self {
+            SyntaxTree::Atom(var) => trace
+                .runs
+                .iter()
+                .map(|(state, length)| vec![state[*var as usize]; width.min(*length)])
+                .collect(),
+            SyntaxTree::Not(branch) => {
+                let mut vals = branch.eval_runs(trace, width);
+                vals.iter_mut().flatten().for_each(|val| *val = !*val);
+                vals
+            }
+            SyntaxTree::Next(branch) => {
+                let vals = branch.eval_runs(trace, width);
+                (0..vals.len())
+                    .map(|run| {
+                        let mut next = vals[run][1..].to_vec();
+                        // Next is false at the end of the trace.
+                        next.push(vals.get(run + 1).is_some_and(|next_run| next_run[0]));
+                        next
+                    })
+                    .collect()
+            }
+            SyntaxTree::And(left_branch, right_branch)
+            | SyntaxTree::Or(left_branch, right_branch)
+            | SyntaxTree::Implies(left_branch, right_branch) => {
+                let mut left_vals = left_branch.eval_runs(trace, width);
+                let right_vals = right_branch.eval_runs(trace, width);
+                for (left, right) in left_vals
+                    .iter_mut()
+                    .flatten()
+                    .zip(right_vals.iter().flatten())
+                {
+                    *left = match self {
+                        SyntaxTree::And(_, _) => *left && *right,
+                        SyntaxTree::Or(_, _) => *left || *right,
+                        _ => !*left || *right,
+                    };
+                }
+                left_vals
+            }
+            // Temporal operators are computed by a backwards sweep,
+            // where each value depends on the current values of the arguments and on the next value of the operator.
+            SyntaxTree::Globally(branch) => {
+                let mut vals = branch.eval_runs(trace, width);
+                let mut next = true;
+                for val in vals.iter_mut().rev().flat_map(|run| run.iter_mut().rev()) {
+                    *val = *val && next;
+                    next = *val;
+                }
+                vals
+            }
+            SyntaxTree::Finally(branch) => {
+                let mut vals = branch.eval_runs(trace, width);
+                let mut next = false;
+                for val in vals.iter_mut().rev().flat_map(|run| run.iter_mut().rev()) {
+                    *val = *val || next;
+                    next = *val;
+                }
+                vals
+            }
+            SyntaxTree::Until(left_branch, right_branch) => {
+                let left_vals = left_branch.eval_runs(trace, width);
+                let mut vals = right_branch.eval_runs(trace, width);
+                let mut next = false;
+                for (val, left) in vals
+                    .iter_mut()
+                    .rev()
+                    .flat_map(|run| run.iter_mut().rev())
+                    .zip(left_vals.iter().rev().flat_map(|run| run.iter().rev()))
+                {
+                    *val = *val || (*left && next);
+                    next = *val;
+                }
+                vals
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod run_length {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+    use std::sync::Arc;
+
+    #[test]
+    fn compression() {
+        let trace = vec![[true], [true], [false], [true], [true], [true]];
+        let rle = RleTrace::from(trace.as_slice());
+        assert_eq!(rle.runs(), &[([true], 2), ([false], 1), ([true], 3)]);
+        assert_eq!(rle.len(), 6);
+        assert_eq!(rle.to_trace(), trace);
+    }
+
+    #[test]
+    fn eval_rle() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let atom = |var| Arc::new(SyntaxTree::Atom(var));
+        let formulae = [
+            // G(x0 -> X x1)
+            SyntaxTree::Globally(Arc::new(SyntaxTree::Implies(
+                atom(0),
+                Arc::new(SyntaxTree::Next(atom(1))),
+            ))),
+            // x0 U X X ¬x1
+            SyntaxTree::Until(
+                atom(0),
+                Arc::new(SyntaxTree::Next(Arc::new(SyntaxTree::Next(Arc::new(
+                    SyntaxTree::Not(atom(1)),
+                ))))),
+            ),
+            // F(x1 ∧ X G x0)
+            SyntaxTree::Finally(Arc::new(SyntaxTree::And(
+                atom(1),
+                Arc::new(SyntaxTree::Next(Arc::new(SyntaxTree::Globally(atom(0))))),
+            ))),
+        ];
+
+        for _ in 0..200 {
+            // Traces with long constant stretches.
+            let rle = (0..rng.gen_range(1..5))
+                .flat_map(|_| {
+                    let state = [rng.gen(), rng.gen()];
+                    std::iter::repeat_n(state, rng.gen_range(1..5))
+                })
+                .collect::<RleTrace<2>>();
+            let trace = rle.to_trace();
+            for formula in &formulae {
+                assert_eq!(
+                    formula.eval_rle(&rle),
+                    formula.eval(&trace),
+                    "{formula} on {trace:?}"
+                );
+            }
+        }
+    }
+}
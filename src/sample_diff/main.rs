@@ -0,0 +1,98 @@
+use learn_ltl::*;
+
+use std::path::Path;
+
+use clap::Parser;
+
+/// Compare two versions of a sample, reporting which traces were added, removed, or relabeled.
+/// Supported file types: ron, json, trace, binary.
+#[derive(Parser, Debug)]
+#[clap(name = "sample_diff")]
+struct SampleDiffArgs {
+    /// The old version of the sample
+    old: String,
+    /// The new version of the sample
+    new: String,
+    /// Print every changed trace, besides the counts
+    #[arg(short, long, default_value_t = false)]
+    verbose: bool,
+    /// Also print the formulas up to the given size whose satisfaction differs the most between the traces of the two versions
+    #[arg(long)]
+    contrast: Option<usize>,
+}
+
+/// Compares a sample with any number of variables to another one with the same number.
+struct Compare<'a> {
+    new: &'a Path,
+    verbose: bool,
+    contrast: Option<usize>,
+}
+
+impl SampleVisitor for Compare<'_> {
+    type Output = std::io::Result<bool>;
+
+    fn visit<const N: usize>(self, old: Sample<N>) -> Self::Output {
+        let new = Sample::<N>::load(self.new)?;
+        let diff = old.diff(&new);
+        print!("{diff}");
+        if self.verbose {
+            for (label, trace) in &diff.traces.added {
+                println!("+ {label:?} {trace:?}");
+            }
+            for (label, trace) in &diff.traces.removed {
+                println!("- {label:?} {trace:?}");
+            }
+            for (trace, label) in &diff.traces.relabeled {
+                println!("~ {label:?} {trace:?}");
+            }
+            for (label, lasso) in &diff.lassos.added {
+                println!("+ {label:?} {lasso:?}");
+            }
+            for (label, lasso) in &diff.lassos.removed {
+                println!("- {label:?} {lasso:?}");
+            }
+            for (lasso, label) in &diff.lassos.relabeled {
+                println!("~ {label:?} {lasso:?}");
+            }
+        }
+        if let Some(max_size) = self.contrast {
+            // Labels are ignored, as the versions are told apart by their traces as a whole.
+            let traces = |sample: &Sample<N>| {
+                sample
+                    .positive_traces
+                    .iter()
+                    .chain(&sample.negative_traces)
+                    .cloned()
+                    .collect::<Vec<_>>()
+            };
+            let contrasts = ContrastiveLearner::default()
+                .max_size(max_size)
+                .contrasts(&traces(&old), &traces(&new));
+            for contrast in contrasts {
+                println!(
+                    "{} holds on {:.1}% of the old traces and {:.1}% of the new ones",
+                    contrast.formula.named(&old.var_names),
+                    100.0 * contrast.first,
+                    100.0 * contrast.second
+                );
+            }
+        }
+        Ok(diff.is_empty())
+    }
+}
+
+fn main() -> std::io::Result<()> {
+    let args = SampleDiffArgs::parse();
+
+    let compare = Compare {
+        new: Path::new(&args.new),
+        verbose: args.verbose,
+        contrast: args.contrast,
+    };
+    if !visit_sample(Path::new(&args.old), compare)?? {
+        // Like `diff`, exit with an error status if the samples differ.
+        std::process::exit(1);
+    }
+
+    Ok(())
+}
@@ -1,15 +1,11 @@
 use learn_ltl::*;
-use clap::Parser;
-use ron::de::from_reader;
+use clap::parser::ValueSource;
+use clap::{ArgMatches, CommandFactory, FromArgMatches, Parser};
+use rand::{rngs::StdRng, SeedableRng};
 use std::fs::File;
-use std::io::Write;
-use std::io::{BufReader, Read};
-use learn_ltl::SyntaxTree as ImportedSyntaxTree;
-use ron;
-use rand::Rng;
-use std::sync::Arc;
-use rand::seq::SliceRandom;
-use rand::prelude::*;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use std::time::Instant;
 
 
 #[derive(Parser, Debug)]
@@ -26,401 +22,402 @@ struct Args {
     #[clap(short = 'i', long, default_value_t = 10)]
     iterations: usize, // taking command line argument for number of iterations
 
+    /// File of formulas (ron, json, or ltl with a formula per line) added to the initial population
+    #[clap(long)]
+    seeds: Option<String>,
+
+    /// Formulas satisfied by at least this fraction of random traces are discarded as near-tautologies
+    #[clap(long, default_value_t = 0.95)]
+    triviality_threshold: f64,
+
+    /// Number of random traces used to estimate the triviality score of a formula
+    #[clap(long, default_value_t = 1000)]
+    triviality_samples: usize,
+
+    /// Balance positive and negative traces before evolving formulas: undersample or oversample
+    #[clap(long)]
+    balance: Option<BalanceStrategy>,
+
+    /// Operator costs penalizing formulas in their fitness instead of their size, e.g., until=3,not=0
+    #[clap(long)]
+    costs: Option<OperatorCosts>,
+
+    /// File of grammar rules (e.g., `spec := G ?spec`) restricting formulas to a fragment of LTL
+    #[clap(long)]
+    grammar: Option<String>,
+
+    /// Fractions of positive traces to satisfy at least and of negative traces to satisfy at most, e.g., 0.9,0.1 (default 1,0)
+    #[clap(long)]
+    chance: Option<ChanceConstraint>,
+
+    /// Evolve formulas over all the variables, rather than only those needed to tell positive and negative traces apart
+    #[clap(long, default_value_t = false)]
+    all_vars: bool,
+
+    /// How offspring are bred: subtree (swap random subformulas) or branches (recombine the branches of binary parents)
+    #[clap(long, default_value_t = CrossoverMethod::Subtree)]
+    crossover: CrossoverMethod,
+
+    /// How mutants are bred: point (change a random node), structural (grow or shrink the formula),
+    /// mixed (either of them) or root (change the root binary operator)
+    #[clap(long, default_value_t = MutationMethod::Mixed)]
+    mutation: MutationMethod,
+
+    /// How often formulas are mutated: fixed:<RATE> (with probability RATE every iteration)
+    /// or adaptive:<RATE> (starting at RATE, more often while the fittest formula stagnates, less often after it improves)
+    #[clap(long, default_value_t = MutationRate::default())]
+    mutation_rate: MutationRate,
+
+    /// How the parents of offspring are picked: uniform, tournament:<SIZE> (the fittest of SIZE random formulas),
+    /// roulette (with a probability proportional to their fitness), linear:<PRESSURE> or exponential:<BASE> (by rank),
+    /// or lexicase (right on the traces considered in a random order)
+    #[clap(long, default_value_t = ParentSelection::default())]
+    selection: ParentSelection,
+
+    /// Number of the fittest formulas carried over unchanged into each generation, the rest being replaced (default all)
+    #[clap(long)]
+    elitism: Option<usize>,
+
+    /// Maximum number of formulas of the initial population, seeds included (default all the formulas of the given size)
+    #[clap(long)]
+    population: Option<usize>,
+
+    /// Number of offspring bred each generation (default two per formula of the population)
+    #[clap(long)]
+    offspring: Option<usize>,
+
+    /// Number of the fittest formulas surviving each generation
+    #[clap(long, default_value_t = 100)]
+    survivors: usize,
+
+    /// Niche radius of fitness sharing: survivors are picked by their fitness divided among the formulas of a similar shape,
+    /// i.e., differing in fewer nodes than the radius, so that the population keeps formulas of different shapes
+    #[clap(long)]
+    sharing: Option<usize>,
+
+    /// Rank formulas by non-dominated sorting over accuracy and size (NSGA-II), rather than by a fitness penalizing size
+    #[clap(long, default_value_t = false)]
+    pareto: bool,
+
+    /// Stop after this many iterations without improvement of the fittest formula
+    #[clap(long)]
+    patience: Option<usize>,
+
+    /// Run all the iterations, even once the fittest formula is consistent with the sample
+    #[clap(long, default_value_t = false)]
+    keep_going: bool,
+
+    /// Seed of the random number generator, to make runs reproducible (default a random seed)
+    #[clap(long)]
+    seed: Option<u64>,
+
+    /// Offspring and mutants larger than this size are discarded
+    #[clap(long)]
+    max_size: Option<usize>,
+
+    /// Number of subpopulations evolving in parallel, exchanging their fittest formulas (default a single population)
+    #[clap(long)]
+    islands: Option<usize>,
+
+    /// Number of generations between migrations of formulas from each island to the next one
+    #[clap(long, default_value_t = 5)]
+    migration_interval: usize,
+
+    /// Number of the fittest formulas of each island migrating to the next one
+    #[clap(long, default_value_t = 2)]
+    migrants: usize,
+
+    /// File of parameters of the genetic algorithm (ron or json), overridden by the options given on the command line
+    #[clap(long)]
+    config: Option<String>,
+
+    /// CSV file to write statistics of every iteration to: fitness of the fittest formula, mean and median fitness,
+    /// accuracy and size of the fittest formula, diversity and elapsed time
+    #[clap(long)]
+    csv: Option<String>,
 }
 
-const N: usize = 2; // number of propositional variables
-
-fn calculate_formula_size(tree: &SyntaxTree) -> usize {
-    match tree {
-        SyntaxTree::Atom(_) => 1,
-        SyntaxTree::Not(subtree) => 1 + calculate_formula_size(subtree),
-        SyntaxTree::Next(subtree) => 1 + calculate_formula_size(subtree),
-        SyntaxTree::Globally(subtree) => 1 + calculate_formula_size(subtree),
-        SyntaxTree::Finally(subtree) => 1 + calculate_formula_size(subtree),
-        SyntaxTree::And(left, right)
-        | SyntaxTree::Or(left, right)
-        | SyntaxTree::Implies(left, right)
-        | SyntaxTree::Until(left, right) => 1 + calculate_formula_size(left) + calculate_formula_size(right),
+/// The parameters of the genetic algorithm given by the options, or only those given on the command line
+fn args_config(args: &Args, matches: Option<&ArgMatches>) -> GeneticConfig {
+    let given = |id| matches.is_none_or(|matches| matches.value_source(id) == Some(ValueSource::CommandLine));
+    GeneticConfig {
+        size: given("size").then_some(args.size),
+        iterations: given("iterations").then_some(args.iterations),
+        population: args.population.filter(|_| given("population")),
+        offspring: args.offspring.filter(|_| given("offspring")),
+        survivors: given("survivors").then_some(args.survivors),
+        elitism: args.elitism.filter(|_| given("elitism")),
+        crossover: given("crossover").then_some(args.crossover),
+        mutation: given("mutation").then_some(args.mutation),
+        mutation_rate: given("mutation_rate").then_some(args.mutation_rate),
+        selection: given("selection").then_some(args.selection),
+        sharing: args.sharing.filter(|_| given("sharing")),
+        pareto: given("pareto").then_some(args.pareto),
+        max_size: args.max_size.filter(|_| given("max_size")),
+        stop_when_consistent: given("keep_going").then_some(!args.keep_going),
+        patience: args.patience.filter(|_| given("patience")),
+        random_seed: args.seed.filter(|_| given("seed")),
+        seeds: None,
     }
 }
 
-fn calculate_fitness(positive_count: usize, negative_count: usize, size: usize) -> i32 {
-    // Calculate the net gain in positive traces and net loss in negative traces
-    let net_fitness = (positive_count as i32) - (negative_count as i32);
-    // Introduce a penalty for the size of the formula
-    let size_penalty = size as i32;
-    // Calculate the final fitness by subtracting the size penalty
-    net_fitness - size_penalty
-}
-
-fn evaluate_formulas(
-    contents: &[u8],
-    multithread: bool,
-    formulas: &[SyntaxTree],
+// Statistics of the population are written as a CSV row, if the population is not empty
+fn log_generation<const N: usize, W: Write>(
+    csv: &mut Option<W>,
+    generation: usize,
+    ranked: &[(SyntaxTree, Fitness)],
     sample: &Sample<N>,
-) -> Option<(usize, usize)> {
-    let mut total_positive_count = 0;
-    let mut total_negative_count = 0;
-
-    for formula in formulas {
-        let mut positive_count = 0;
-        let mut negative_count = 0;
-
-        for content in contents.chunks(10000000) {
-            // Deserialize the content
-            if let Ok(deserialized_sample) = ron::de::from_bytes::<Sample<N>>(content) {
-                // Count the number of satisfied positive traces
-                positive_count += deserialized_sample.positive_traces
-                    .iter()
-                    .filter(|&trace| formula.eval(trace.as_slice()))
-                    .count();
-
-                // Count the number of satisfied negative traces
-                negative_count += deserialized_sample.negative_traces
-                    .iter()
-                    .skip(deserialized_sample.negative_traces.len() - deserialized_sample.positive_traces.len())
-                    .filter(|&trace| formula.eval(trace.as_slice()))
-                    .count();
-            }
-        }
-
-        total_positive_count += positive_count;
-        total_negative_count += negative_count;
+    start: Instant,
+) -> std::io::Result<()> {
+    match csv {
+        Some(csv) if !ranked.is_empty() => GenerationStats::new(generation, ranked, sample, start.elapsed()).write_csv_row(csv),
+        _ => Ok(()),
     }
-
-    Some((total_positive_count, total_negative_count))
 }
 
-// Define a trait to handle operations on SyntaxTree
-trait SyntaxTreeOperations {
-    fn replace_branch(&self, new_branch: Arc<SyntaxTree>) -> SyntaxTree;
-    fn combine_branches(branch1: Arc<SyntaxTree>, branch2: Arc<SyntaxTree>) -> SyntaxTree;
-}
-
-impl SyntaxTreeOperations for SyntaxTree {
-    fn replace_branch(&self, new_branch: Arc<SyntaxTree>) -> SyntaxTree {
-        match self {
-            SyntaxTree::And(_, _) => SyntaxTree::And(new_branch.clone(), new_branch.clone()),
-            SyntaxTree::Or(_, _) => SyntaxTree::Or(new_branch.clone(), new_branch.clone()),
-            SyntaxTree::Implies(_, _) => SyntaxTree::Implies(new_branch.clone(), new_branch.clone()),
-            SyntaxTree::Until(_, _) => SyntaxTree::Until(new_branch.clone(), new_branch.clone()),
-            _ => self.clone(),
-        }
-    }
+fn save_formulas_to_file(formulas: &[SyntaxTree], filename: &str) -> Result<(), Box<dyn std::error::Error>> {
+    // Formulas are saved in RON format, so that they can be loaded back (e.g., as seeds)
+    let file = File::create(filename)?;
+    ron::ser::to_writer_pretty(BufWriter::new(file), formulas, ron::ser::PrettyConfig::default())?;
 
-    fn combine_branches(branch1: Arc<SyntaxTree>, branch2: Arc<SyntaxTree>) -> SyntaxTree {
-        match (&*branch1, &*branch2) {
-            (SyntaxTree::Finally(left), SyntaxTree::Atom(right)) => SyntaxTree::Until(branch1, branch2),
-            (SyntaxTree::Finally(left), _) => SyntaxTree::Implies(branch1, branch2),
-            (_, SyntaxTree::Atom(right)) => SyntaxTree::Implies(branch1, branch2),
-            (_, _) => SyntaxTree::Or(branch1, branch2),
-        }
-    }
+    Ok(())
 }
 
-fn get_branches(tree: &SyntaxTree) -> (Option<Arc<SyntaxTree>>, Option<Arc<SyntaxTree>>) {
-    match tree {
-        SyntaxTree::And(left, right)
-        | SyntaxTree::Or(left, right)
-        | SyntaxTree::Implies(left, right)
-        | SyntaxTree::Until(left, right) => (Some(left.clone()), Some(right.clone())),
-        _ => (None, None),
-    }
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let matches = Args::command().get_matches();
+    let args = Args::from_arg_matches(&matches)?;
+
+    // Options given on the command line override the configuration file, which overrides the defaults of the options
+    let config = match &args.config {
+        Some(config_file) => GeneticConfig::load(Path::new(config_file))?,
+        None => GeneticConfig::default(),
+    };
+    let config = args_config(&args, Some(&matches)).or(config).or(args_config(&args, None));
+
+    // The number of propositional variables is read from the sample file
+    let sample_path = Path::new(&args.sample_file);
+    visit_sample(sample_path, GeneticRun { args: &args, config })?
 }
 
-fn crossover(parent1: &SyntaxTree, parent2: &SyntaxTree) -> Option<(SyntaxTree, SyntaxTree)> {
-    //println!("Formula is {} {}", parent1, parent2); // Print the parents
-
-    // Check if both parents have exactly two branches
-    if let (Some(branch1_p1), Some(branch2_p1)) = get_branches(parent1) {
-        if let (Some(branch1_p2), Some(branch2_p2)) = get_branches(parent2) {
-
-            // println!("Formula is {} {}", parent1, parent2);
-
-            let mut offspring1 = None;
-            let mut offspring2 = None;
-
-            // Randomly select a crossover method
-            let crossover_method = rand::thread_rng().gen_range(0..=2);
+struct GeneticRun<'a> {
+    args: &'a Args,
+    config: GeneticConfig,
+}
 
-            match crossover_method {
-                // Method 1: Swap subtrees between parents
-                0 => {
-                    offspring1 = Some(parent1.replace_branch(branch2_p2.clone()));
-                    offspring2 = Some(parent2.replace_branch(branch1_p1.clone()));
-                }
-                // Method 2: Combine branches from both parents
-                1 => {
-                    offspring1 = Some(SyntaxTree::combine_branches(branch1_p1.clone(), branch2_p2.clone()));
-                    offspring2 = Some(SyntaxTree::combine_branches(branch1_p2.clone(), branch2_p1.clone()));
-                }
-                // Method 3: Randomly select a branch from each parent
-                2 => {
-                    let random_branch_parent1 = if rand::random() { branch1_p1.clone() } else { branch2_p1.clone() };
-                    let random_branch_parent2 = if rand::random() { branch1_p2.clone() } else { branch2_p2.clone() };
-                    offspring1 = Some(parent1.replace_branch(random_branch_parent2));
-                    offspring2 = Some(parent2.replace_branch(random_branch_parent1));
-                }
-                _ => {}
-            }
+impl SampleVisitor for GeneticRun<'_> {
+    type Output = Result<(), Box<dyn std::error::Error>>;
 
-            // If both offspring are successfully created, return them
-            if let (Some(off1), Some(off2)) = (offspring1, offspring2) {
-                return Some((off1, off2));
-            }
-        }
+    fn visit<const N: usize>(self, sample: Sample<N>) -> Self::Output {
+        run(self.args, &self.config, sample)
     }
-
-    // If parents do not meet the criteria, return None
-    None
 }
 
-fn mutate_formula(formula: &SyntaxTree) -> SyntaxTree {
-    match formula {
-        SyntaxTree::Atom(_) => formula.clone(),
-        SyntaxTree::Not(subtree) => SyntaxTree::Not(subtree.clone()),
-        SyntaxTree::Next(subtree) => SyntaxTree::Next(subtree.clone()),
-        SyntaxTree::Globally(subtree) => SyntaxTree::Globally(subtree.clone()),
-        SyntaxTree::Finally(subtree) => SyntaxTree::Finally(subtree.clone()),
-        SyntaxTree::And(left, right) => {
-            match (rand::random::<usize>() % 3) {
-                0 => SyntaxTree::Or(left.clone(), right.clone()),
-                1 => SyntaxTree::Implies(left.clone(), right.clone()),
-                2 => SyntaxTree::Until(left.clone(), right.clone()),
-                _ => unreachable!("Unexpected random value for And mutation"),
-            }
-        }
-        SyntaxTree::Or(left, right) => {
-            match (rand::random::<usize>() % 3) {
-                0 => SyntaxTree::And(left.clone(), right.clone()),
-                1 => SyntaxTree::Implies(right.clone(), left.clone()),
-                2 => SyntaxTree::Until(left.clone(), right.clone()),
-                _ => unreachable!("Unexpected random value for Or mutation"),
-            }
-        }
-        SyntaxTree::Implies(left, right) => {
-            match (rand::random::<usize>() % 3) {
-                0 => SyntaxTree::And(left.clone(), right.clone()),
-                1 => SyntaxTree::Or(left.clone(), right.clone()),
-                2 => SyntaxTree::Until(left.clone(), right.clone()),
-                _ => unreachable!("Unexpected random value for Implies mutation"),
-            }
-        }
-        SyntaxTree::Until(left, right) => {
-            match (rand::random::<usize>() % 3) {
-                0 => SyntaxTree::And(left.clone(), right.clone()),
-                1 => SyntaxTree::Or(left.clone(), right.clone()),
-                2 => SyntaxTree::Implies(left.clone(), right.clone()),
-                _ => unreachable!("Unexpected random value for Until mutation"),
-            }
+fn run<const N: usize>(args: &Args, config: &GeneticConfig, sample: Sample<N>) -> Result<(), Box<dyn std::error::Error>> {
+    // All the random choices are drawn from a single generator, so that runs with the same seed are the same
+    let mut rng = match config.random_seed {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => StdRng::from_entropy(),
+    };
+
+    // Balance the classes, so that the fitness is not dominated by the larger one
+    let sample = match args.balance {
+        Some(strategy) => sample.balance(strategy, &mut rng),
+        None => sample,
+    };
+    if let Err(contradictions) = sample.check_consistency() {
+        // No formula can be consistent with the sample, so evolving one would be pointless.
+        for contradiction in contradictions {
+            println!("Inconsistent sample: {contradiction}");
         }
+        return Err("no formula is consistent with the sample".into());
     }
-}
 
-fn save_formulas_to_file(formulas: &[SyntaxTree], filename: &str) -> Result<(), Box<dyn std::error::Error>> {
-    let mut file = File::create(filename)?;
+    let vars = if args.all_vars { sample.vars() } else { sample.relevant_vars() };
+    let mut learner = config.apply(GeneticLearner::default()).vars(vars.clone());
 
-    for formula in formulas {
-        writeln!(file, "{:?}", formula)?;
+    // Add the seed formulas to the initial population, after those of the configuration file
+    let mut seeds = learner.seeds.clone();
+    if let Some(seeds_file) = &args.seeds {
+        seeds.extend(load_formulas(Path::new(seeds_file))?);
     }
-
-    Ok(())
-}
-
-fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let args = Args::parse();
-
-    let multithread: bool = true; // Initialize multithread with a value
-    let size = args.size; // size of the formula
-    let iterations = args.iterations; // number of iterations
-
-    let vars = vec![0, N-1];
-
-    // Convert Vec<i32> into Vec<u8>
-    let vars_vec: Vec<u8> = vars.iter().map(|&x| x as u8).collect();
-
-    // Convert Vec<u8> into &[u8] slice
-    let vars_slice: &[u8] = &vars_vec;
-
-    // Start a new vector
-    let mut formulas: Vec<SyntaxTree> = Vec::new();
-
-    // Using learn module function
-    for skeleton in SkeletonTree::gen(size) {
-        let generated_formulas = skeleton.gen_formulae::<N>(vars_slice);
-        formulas.extend(generated_formulas);
+    let (seeds, discarded): (Vec<_>, Vec<_>) =
+        seeds.into_iter().partition(|seed| seed.vars() as usize <= N);
+    if !discarded.is_empty() {
+        println!("Discarded {} seed formulas using more than {} variables", discarded.len(), N);
+    }
+    learner = learner.seeds(seeds);
+    if let Some(costs) = args.costs {
+        learner = learner.costs(costs);
+    }
+    if let Some(grammar_file) = &args.grammar {
+        let rules = std::fs::read_to_string(grammar_file)?;
+        let grammar = Grammar::parse_w_named_vars(&rules, &sample.var_names)
+            .map_err(|err| format!("invalid grammar: {err}"))?;
+        learner = learner.grammar(grammar);
     }
 
-    // Deserialize the sample of traces from a .ron file
-    let sample_filename = &args.sample_file;
-    let file = File::open(sample_filename)?;
-    let mut buf_reader = BufReader::new(file);
-    let mut content = Vec::new();
-    buf_reader.read_to_end(&mut content)?;
-
-    let sample: Sample<N> = from_reader(&content[..])?;
-
-    // Evaluate formulas
-    let (positive_count, negative_count) = evaluate_formulas(&content, multithread, &formulas, &sample)
-        .expect("Evaluation failed");
-
-    // Saving the list of formulas in a txt file
-    let filename = "formulas.txt";
-    let mut file = File::create(filename)?;
-    //println!("Generated Formula: {:?}", formulas);
+    let mut formulas = learner.initial_population(&sample);
 
-    for formula in &formulas {
-        //println!(" PARENTTTTTTTTTTTTTTTTT 1111111111111111111 isssssssssssss {}", formula);
-        writeln!(file, "{:?}", formula)?;
-    }
+    // Saving the list of formulas in a ron file
+    save_formulas_to_file(&formulas, "formulas.ron")?;
 
     // Count the total number of formulas and print
-    let total_formulas = formulas.len();
-    println!("size of the formula is {}", args.size);
+    println!("size of the formula is {}", learner.size);
     println!("propositional variables are {:?}", vars);
-    println!("Total number of formulas generated: {}", total_formulas);
-
-    let mut rng = rand::thread_rng();
-
-    for iteration in 0..iterations {
-        println!("\nIteration {}", iteration + 1);
-    let total_formulas = formulas.len();
-        println!("Total number of initial formulas: {}", total_formulas);
-
-    // Perform crossover
-    let mut new_population: Vec<SyntaxTree> = Vec::new(); // Declare and initialize new_population
-
-    // Combine initial formulas with crossover and mutated formulas
-    let mut combined_formulas = formulas.clone();
-
-    // Assuming you have some parent1, parent2, and crossover_point values
-    // let mut parent1; // Accessing the first formula as parent1 for example
-    // println!("size of the parent1 is {}", parent1);
-    // let mut parent2; // Accessing the second formula as parent2 for example
-    // println!("size of the parent2 is {}", parent2);
-    // let crossover_point = 5; // Example crossover point
-
-    let mut crossoverFormulas: Vec<SyntaxTree> = Vec::new();
-
-    for i in 1..total_formulas {
-
-        let parent1_index = rng.gen_range(0..total_formulas);
-        let parent2_index = rng.gen_range(0..total_formulas);
-
-        let parent1 = &formulas[parent1_index];
-        let parent2 = &formulas[parent2_index];
-        // println!("Number: {}", i);
-        // parent1 = &formulas[i - 1];
-        // parent2 = &formulas[i];
-        // println!(" parents are {} {}", parent1, parent2);
-        //println!(" PARENTTTTTTTTTTTTTTTTT 1111111111111111111 isssssssssssss {}", parent1);
-        if let Some((mut offspring1, mut offspring2)) = crossover(parent1, parent2) {
-            //println!(" offspring1 is {}", offspring1);
-            //println!(" offspring2 is {}", offspring2);
-            let offspring_vec1 = vec![offspring1.clone()]; // Wrap offspring1 in a vector
-            let offspring_vec2 = vec![offspring2.clone()]; // Wrap offspring2 in a vector
-
-            if !crossoverFormulas.contains(&offspring1) {
-                crossoverFormulas.extend(offspring_vec1);
+    println!("Total number of formulas generated: {}", formulas.len());
+
+    let chance = args.chance.unwrap_or_default();
+    // Each distinct formula is evaluated once, although formulas survive many generations
+    let mut cache = FitnessCache::default();
+    let start = Instant::now();
+
+    // The statistics of each iteration are written as they come, so that long runs can be followed
+    let mut csv = match &args.csv {
+        Some(csv_file) => {
+            let mut csv = BufWriter::new(File::create(csv_file)?);
+            writeln!(csv, "{}", GenerationStats::CSV_HEADER)?;
+            Some(csv)
+        }
+        None => None,
+    };
+
+    // The islands evolve for all the iterations at once, on separate threads, logging the formulas of all islands
+    if let Some(islands) = args.islands {
+        let ranked = learner.rank_cached(&formulas, &sample, &mut cache);
+        log_generation(&mut csv, 0, &ranked, &sample, start)?;
+        let model = IslandModel::new(islands)
+            .genetic(learner.clone())
+            .interval(args.migration_interval)
+            .migrants(args.migrants);
+        let mut logged = Ok(());
+        let ranked = model.evolve_observed(&sample, formulas, &mut cache, None, &mut rng, |generation, ranked| {
+            if logged.is_ok() {
+                logged = log_generation(&mut csv, generation, ranked, &sample, start);
             }
+        });
+        logged?;
+        formulas = learner.select(&ranked);
+        println!(
+            "\nEvolved {} islands for {} iterations, migrating {} formulas every {} iterations",
+            islands, learner.iterations, args.migrants, args.migration_interval
+        );
+    } else {
+        // Every generation is printed, saved and logged as soon as it is ranked
+        let mut population = formulas.len();
+        let mut report = |generation: &Generation| -> Result<(), Box<dyn std::error::Error>> {
+            if generation.index > 0 {
+                println!("\nIteration {}", generation.index);
+                println!("Total number of initial formulas: {}", population);
+                println!("Mutation rate: {:.0}%", 100.0 * generation.schedule.probability());
+                let sorted_formulas: Vec<SyntaxTree> =
+                    generation.offspring.iter().map(|(formula, _)| formula.clone()).collect();
+                save_formulas_to_file(&sorted_formulas, "combined_formulas.ron")?;
+
+                // The parents with their offspring and mutants, by fitness score in descending order
+                println!("Formulas sorted by fitness:");
+                for (i, (_, fitness)) in generation.offspring.iter().enumerate() {
+                    println!(
+                        "Formula {} satisfied {} positive traces and {} negative traces, fitness is {:.2}{}",
+                        i + 1, fitness.positives, fitness.negatives, fitness.score(),
+                        if chance.admits_fitness(fitness, &sample) { " (accepted)" } else { "" }
+                    );
+                }
+                let accepted = generation
+                    .offspring
+                    .iter()
+                    .filter(|(_, fitness)| chance.admits_fitness(fitness, &sample))
+                    .count();
+                println!("{} formulas satisfy {}", accepted, chance);
+                save_formulas_to_file(&sorted_formulas, "sorted_formulas.ron")?;
 
-            if !crossoverFormulas.contains(&offspring2) {
-                crossoverFormulas.extend(offspring_vec2);
+                println!("Iteration {} completed", generation.index);
             }
-
-        }
+            population = generation.ranked.len();
+            log_generation(&mut csv, generation.index, generation.ranked, &sample, start)?;
+            if let Some(csv) = &mut csv {
+                csv.flush()?;
+            }
+            if generation.stops {
+                let stagnation = generation.stagnation.generations();
+                if learner.patience.is_some_and(|patience| stagnation >= patience) {
+                    println!("\nStopped after {} iterations without improvement", stagnation);
+                } else {
+                    println!("\nStopped after {} iterations, as the fittest formula is consistent with the sample", generation.index);
+                }
+            }
+            Ok(())
+        };
+        let mut logged = Ok(());
+        let ranked = learner.evolve_observed(&sample, formulas, &mut cache, &mut rng, &(), |generation| {
+            if logged.is_ok() {
+                logged = report(generation);
+            }
+        });
+        logged?;
+        // The fittest formulas of the last generation
+        formulas = ranked.into_iter().map(|(formula, _)| formula).collect();
     }
 
-    // Add crossover formulas to combined formulas
-    combined_formulas.extend(crossoverFormulas.clone());
-
-    //println!("After Applying corssover on valid expressions");
-    //for formula in &crossoverFormulas {
-
-    //    println!(" formula is {}", formula);
-    //}
+    println!(
+        "\nEvaluated {} distinct formulas ({} evaluations saved)",
+        cache.len(),
+        cache.hits()
+    );
 
-    // Perform mutation on all formulas with 10% probability
-    let mut mutated_formulas: Vec<SyntaxTree> = Vec::new();
-    for formula in &mut formulas {
-        // Apply mutation with 20% probability
-        if rand::thread_rng().gen_range(0..=99) < 20 {
-            let mutated_formula = mutate_formula(formula);
-            mutated_formulas.push(mutated_formula);
+    // Replace subformulas of the final population by smaller ones fitting the sample as well
+    let population_size = formulas.iter().map(SyntaxTree::size).sum::<usize>();
+    let mut minimized: Vec<SyntaxTree> = Vec::new();
+    for formula in &formulas {
+        let formula = formula.minimize(&sample);
+        if !minimized.contains(&formula) {
+            minimized.push(formula);
         }
     }
-
-    // Add mutated formulas to combined formulas
-    combined_formulas.extend(mutated_formulas.clone());
-
-    // Save the combined set of formulas to a new file
-    let combined_filename = "combined_formulas.txt";
-    save_formulas_to_file(&combined_formulas, combined_filename)?;
-
-    // Print the combined formulas after crossover and mutation
-    //println!("Combined formulas after crossover and mutation: {:?}", combined_formulas);
-
-    // Calculate the fitness scores for all formulas
-    let mut formula_fitness: Vec<(SyntaxTree, i32)> = Vec::new();
-    for (i, formula) in combined_formulas.iter().enumerate() {
-        let (positive_count, negative_count) = evaluate_formulas(&content, multithread, &[formula.clone()], &sample)
-            .expect("Evaluation failed");
-        let size = calculate_formula_size(formula);
-        let fitness = calculate_fitness(positive_count, negative_count, size);
-        formula_fitness.push((formula.clone(), fitness));
-
-        /* Print the evaluation results for the current formula
-        println!(
-            "Formula {} satisfied {} positive traces and {} negative traces, fitness is {:.2}",
-            i + 1, positive_count, negative_count, fitness
-        ); */
-    }
-
-    // Evaluate formulas
-    let (positive_count, negative_count) = evaluate_formulas(&content, multithread, &formulas, &sample)
-        .expect("Evaluation failed");
-
-    // Calculate and print the size of each formula in combined_formulas
-    for formula in &combined_formulas {
-        let size = calculate_formula_size(formula);
-        // println!("Formula: {:?}, Size: {}", formula, size);
+    println!(
+        "\nMinimized the final formulas from {} to {} nodes in total ({} duplicates removed)",
+        population_size,
+        minimized.iter().map(SyntaxTree::size).sum::<usize>(),
+        formulas.len() - minimized.len()
+    );
+    let formulas = minimized;
+
+    // Save the best formula for every trade-off between accuracy and size, rather than only the fittest one
+    if learner.pareto {
+        let front = pareto_front(&learner.rank(&formulas, &sample), &sample);
+        println!("\nPareto front of {} formulas:", front.len());
+        for point in &front {
+            println!(
+                "{}: accuracy is {:.3}, size is {}",
+                point.formula.named(&sample.var_names), point.accuracy, point.size
+            );
+        }
+        let file = File::create("pareto_front.ron")?;
+        ron::ser::to_writer_pretty(BufWriter::new(file), &front, ron::ser::PrettyConfig::default())?;
     }
 
-    // Sort the formulas based on fitness score in descending order
-    formula_fitness.sort_by(|a, b| b.1.cmp(&a.1));
-
-    // Print the formulas with their fitness for the sorted formulas
-    println!("Formulas sorted by fitness:");
-    for (i, (formula, fitness)) in formula_fitness.iter().enumerate() {
-        let (positive_count, negative_count) = evaluate_formulas(&content, multithread, &[formula.clone()], &sample)
-            .expect("Evaluation failed");
+    // Filter near-tautologies out of the final population
+    let trace_length = (sample.time_lenght() as usize).max(1);
+    let scored = filter_trivial::<N, _>(
+        &formulas,
+        trace_length,
+        args.triviality_samples,
+        args.triviality_threshold,
+        &mut rng,
+    );
+    println!(
+        "\nDiscarded {} near-tautologies (triviality score >= {})",
+        formulas.len() - scored.len(),
+        args.triviality_threshold
+    );
+    for (i, triviality) in scored.iter().enumerate() {
         println!(
-            "Formula {} satisfied {} positive traces and {} negative traces, fitness is {:.2}",
-            i + 1, positive_count, negative_count, fitness
+            "Formula {}: {}, triviality score is {:.3}",
+            i + 1, triviality.formula.named(&sample.var_names), triviality.score
         );
     }
 
-    // Extract the sorted formulas from the tuples
-    let sorted_formulas: Vec<SyntaxTree> = formula_fitness.iter().map(|(formula, _)| formula.clone()).collect();
-
-    // Save the sorted formulas to a new file
-    let sorted_filename = "sorted_formulas.txt";
-    save_formulas_to_file(&sorted_formulas, sorted_filename)?;
-
-    // Extract the top 100 sorted formulas
-    let top_n = 100;
-    let sorted_formulas: Vec<SyntaxTree> = formula_fitness
-        .iter()
-        .take(top_n.min(formula_fitness.len()))
-        .map(|(formula, _)| formula.clone())
-        .collect();
-
-    println!("Iteration {} completed", iteration + 1);
-
-    // Update formulas with the combined formulas
-    formulas.clear();
-    formulas.extend(sorted_formulas);
-    }
+    let final_formulas: Vec<SyntaxTree> = scored.into_iter().map(|triviality| triviality.formula).collect();
+    save_formulas_to_file(&final_formulas, "final_formulas.ron")?;
 
     Ok(())
 }
@@ -24,6 +24,19 @@ struct Sampler {
     /// Length of traces
     #[arg(short, long)]
     length: usize,
+
+    /// Comma-separated names of the propositional variables (e.g., `request,grant`)
+    #[arg(long, value_delimiter = ',')]
+    names: Option<Vec<String>>,
+
+    /// Filename of a Markov chain (in ron format) generating the traces by random walks,
+    /// instead of drawing them uniformly at random
+    #[arg(long)]
+    chain: Option<String>,
+
+    /// Write the sample in the compact binary format, faster to load for large samples
+    #[arg(long, default_value_t = false)]
+    binary: bool,
 }
 
 fn main() -> std::io::Result<()> {
@@ -36,7 +49,24 @@ fn main() -> std::io::Result<()> {
     let formula = ron::de::from_bytes::<SyntaxTree>(&contents).expect("formula");
     let vars = formula.vars();
 
-    let name = format!("sample_{}.ron", formula);
+    if let Some(names) = &sampler.names {
+        if names.len() != vars as usize {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!(
+                    "the formula has {} variables, but {} names were given",
+                    vars,
+                    names.len()
+                ),
+            ));
+        }
+    }
+
+    let extension = if sampler.binary { "bin" } else { "ron" };
+    let name = match &sampler.names {
+        Some(names) => format!("sample_{}.{extension}", formula.named(names)),
+        None => format!("sample_{}.{extension}", formula),
+    };
     let file = File::create(name).expect("open sample file");
     let buf_writer = BufWriter::new(file);
 
@@ -48,9 +78,11 @@ fn main() -> std::io::Result<()> {
                 sampler.positives,
                 sampler.negatives,
                 sampler.length,
-            );
+                sampler.names.as_deref(),
+                sampler.chain.as_deref(),
+            )?;
             assert!(sample.is_consistent(&formula));
-            ron::ser::to_writer(buf_writer, &sample).expect("serialize sample");
+            save(buf_writer, &sample, sampler.binary)?;
         }
         1 => {
             let sample = sample::<1>(
@@ -58,9 +90,11 @@ fn main() -> std::io::Result<()> {
                 sampler.positives,
                 sampler.negatives,
                 sampler.length,
-            );
+                sampler.names.as_deref(),
+                sampler.chain.as_deref(),
+            )?;
             assert!(sample.is_consistent(&formula));
-            ron::ser::to_writer(buf_writer, &sample).expect("serialize sample");
+            save(buf_writer, &sample, sampler.binary)?;
         }
         2 => {
             let sample = sample::<2>(
@@ -68,9 +102,11 @@ fn main() -> std::io::Result<()> {
                 sampler.positives,
                 sampler.negatives,
                 sampler.length,
-            );
+                sampler.names.as_deref(),
+                sampler.chain.as_deref(),
+            )?;
             assert!(sample.is_consistent(&formula));
-            ron::ser::to_writer(buf_writer, &sample).expect("serialize sample");
+            save(buf_writer, &sample, sampler.binary)?;
         }
         3 => {
             let sample = sample::<3>(
@@ -78,9 +114,11 @@ fn main() -> std::io::Result<()> {
                 sampler.positives,
                 sampler.negatives,
                 sampler.length,
-            );
+                sampler.names.as_deref(),
+                sampler.chain.as_deref(),
+            )?;
             assert!(sample.is_consistent(&formula));
-            ron::ser::to_writer(buf_writer, &sample).expect("serialize sample");
+            save(buf_writer, &sample, sampler.binary)?;
         }
         4 => {
             let sample = sample::<4>(
@@ -88,9 +126,11 @@ fn main() -> std::io::Result<()> {
                 sampler.positives,
                 sampler.negatives,
                 sampler.length,
-            );
+                sampler.names.as_deref(),
+                sampler.chain.as_deref(),
+            )?;
             assert!(sample.is_consistent(&formula));
-            ron::ser::to_writer(buf_writer, &sample).expect("serialize sample");
+            save(buf_writer, &sample, sampler.binary)?;
         }
         5 => {
             let sample = sample::<5>(
@@ -98,9 +138,11 @@ fn main() -> std::io::Result<()> {
                 sampler.positives,
                 sampler.negatives,
                 sampler.length,
-            );
+                sampler.names.as_deref(),
+                sampler.chain.as_deref(),
+            )?;
             assert!(sample.is_consistent(&formula));
-            ron::ser::to_writer(buf_writer, &sample).expect("serialize sample");
+            save(buf_writer, &sample, sampler.binary)?;
         }
         6 => {
             let sample = sample::<6>(
@@ -108,9 +150,11 @@ fn main() -> std::io::Result<()> {
                 sampler.positives,
                 sampler.negatives,
                 sampler.length,
-            );
+                sampler.names.as_deref(),
+                sampler.chain.as_deref(),
+            )?;
             assert!(sample.is_consistent(&formula));
-            ron::ser::to_writer(buf_writer, &sample).expect("serialize sample");
+            save(buf_writer, &sample, sampler.binary)?;
         }
         7 => {
             let sample = sample::<7>(
@@ -118,9 +162,11 @@ fn main() -> std::io::Result<()> {
                 sampler.positives,
                 sampler.negatives,
                 sampler.length,
-            );
+                sampler.names.as_deref(),
+                sampler.chain.as_deref(),
+            )?;
             assert!(sample.is_consistent(&formula));
-            ron::ser::to_writer(buf_writer, &sample).expect("serialize sample");
+            save(buf_writer, &sample, sampler.binary)?;
         }
         8 => {
             let sample = sample::<8>(
@@ -128,9 +174,11 @@ fn main() -> std::io::Result<()> {
                 sampler.positives,
                 sampler.negatives,
                 sampler.length,
-            );
+                sampler.names.as_deref(),
+                sampler.chain.as_deref(),
+            )?;
             assert!(sample.is_consistent(&formula));
-            ron::ser::to_writer(buf_writer, &sample).expect("serialize sample");
+            save(buf_writer, &sample, sampler.binary)?;
         }
         9 => {
             let sample = sample::<9>(
@@ -138,9 +186,11 @@ fn main() -> std::io::Result<()> {
                 sampler.positives,
                 sampler.negatives,
                 sampler.length,
-            );
+                sampler.names.as_deref(),
+                sampler.chain.as_deref(),
+            )?;
             assert!(sample.is_consistent(&formula));
-            ron::ser::to_writer(buf_writer, &sample).expect("serialize sample");
+            save(buf_writer, &sample, sampler.binary)?;
         }
         10 => {
             let sample = sample::<10>(
@@ -148,9 +198,11 @@ fn main() -> std::io::Result<()> {
                 sampler.positives,
                 sampler.negatives,
                 sampler.length,
-            );
+                sampler.names.as_deref(),
+                sampler.chain.as_deref(),
+            )?;
             assert!(sample.is_consistent(&formula));
-            ron::ser::to_writer(buf_writer, &sample).expect("serialize sample");
+            save(buf_writer, &sample, sampler.binary)?;
         }
         _ => panic!("out-of-bound parameter"),
     }
@@ -163,27 +215,39 @@ fn sample<const N: usize>(
     positives: usize,
     negatives: usize,
     length: usize,
-) -> Sample<N> {
-    let mut sample = Sample::default();
-    while sample.positive_traces() < positives || sample.negative_traces() < negatives {
-        let trace = Vec::from_iter((0..length).map(|_| gen_bools()));
-        let satisfaction = formula.eval(&trace);
-        if satisfaction && sample.positive_traces() < positives {
-            sample
-                .add_positive_trace(trace)
-                .expect("add positive trace");
-        } else if !satisfaction && sample.negative_traces() < negatives {
-            sample
-                .add_negative_trace(trace)
-                .expect("add negative trace");
+    names: Option<&[String]>,
+    chain: Option<&str>,
+) -> std::io::Result<Sample<N>> {
+    let generator = SampleGenerator::new(formula, length)
+        .positives(positives)
+        .negatives(negatives)
+        .max_attempts(1000 * (positives + negatives).max(100));
+    let mut rng = rand::thread_rng();
+    let sample = match chain {
+        Some(chain) => {
+            let contents = std::fs::read(chain)?;
+            let chain = ron::de::from_bytes::<MarkovChain<N>>(&contents)
+                .map_err(|err| std::io::Error::new(std::io::ErrorKind::InvalidData, err))?;
+            generator.generate_from(&mut rng, |rng| chain.random_walk(length, rng))
         }
+        None => generator.generate(&mut rng),
+    };
+    let mut sample = sample.map_err(std::io::Error::other)?;
+    if let Some(names) = names {
+        sample.var_names = names.to_vec().try_into().expect("one name per variable");
     }
-    sample
+    Ok(sample)
 }
 
-fn gen_bools<const N: usize>() -> [bool; N] {
-    use rand::prelude::*;
-    let mut values = [true; N];
-    rand::thread_rng().fill(&mut values[..]);
-    values
+fn save<const N: usize>(
+    buf_writer: BufWriter<File>,
+    sample: &Sample<N>,
+    binary: bool,
+) -> std::io::Result<()> {
+    if binary {
+        sample.write_binary(buf_writer)
+    } else {
+        ron::ser::to_writer(buf_writer, sample).expect("serialize sample");
+        Ok(())
+    }
 }
@@ -0,0 +1,497 @@
+use crate::learner::*;
+use crate::syntax::*;
+use crate::trace::*;
+use std::ops::Not;
+use std::sync::Arc;
+use std::time::Instant;
+use varisat::ExtendFormula;
+
+/// A literal, i.e., a propositional variable or its negation,
+/// encoded as twice the variable index, plus 1 if negated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+struct Lit(usize);
+
+impl Lit {
+    fn var(self) -> usize {
+        self.0 >> 1
+    }
+
+    fn is_negated(self) -> bool {
+        self.0 & 1 == 1
+    }
+}
+
+impl Not for Lit {
+    type Output = Lit;
+
+    fn not(self) -> Lit {
+        Lit(self.0 ^ 1)
+    }
+}
+
+/// The outcome of a satisfiability check.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Satisfiability {
+    /// A satisfying assignment, indexed by variable.
+    Sat(Vec<bool>),
+    Unsat,
+    /// The deadline passed before deciding, or the solver failed.
+    Unknown,
+}
+
+/// A propositional formula in conjunctive normal form.
+#[derive(Debug, Clone, Default)]
+struct Cnf {
+    vars: usize,
+    clauses: Vec<Vec<Lit>>,
+}
+
+impl Cnf {
+    fn new_var(&mut self) -> Lit {
+        self.vars += 1;
+        Lit(2 * (self.vars - 1))
+    }
+
+    /// Adds a clause, dropping repeated literals and skipping tautologies.
+    fn add(&mut self, mut clause: Vec<Lit>) {
+        clause.sort();
+        clause.dedup();
+        if clause.windows(2).all(|pair| pair[0] != !pair[1]) {
+            self.clauses.push(clause);
+        }
+    }
+
+    fn exactly_one(&mut self, lits: &[Lit]) {
+        self.add(lits.to_vec());
+        for (i, &lit) in lits.iter().enumerate() {
+            for &other in &lits[i + 1..] {
+                self.add(vec![!lit, !other]);
+            }
+        }
+    }
+
+    /// Adds the clauses for `guard -> (lit <-> gate)`, where the guard is a conjunction of literals.
+    fn gate(&mut self, guard: &[Lit], lit: Lit, gate: Gate) {
+        let mut guarded = |lits: &[Lit]| {
+            self.add(
+                guard
+                    .iter()
+                    .map(|&guard| !guard)
+                    .chain(lits.iter().copied())
+                    .collect(),
+            )
+        };
+        match gate {
+            Gate::Const(true) => guarded(&[lit]),
+            Gate::Const(false) => guarded(&[!lit]),
+            Gate::Lit(other) => {
+                guarded(&[!lit, other]);
+                guarded(&[lit, !other]);
+            }
+            Gate::And(left, right) => {
+                guarded(&[!lit, left]);
+                guarded(&[!lit, right]);
+                guarded(&[lit, !left, !right]);
+            }
+            Gate::Or(left, right) => {
+                guarded(&[lit, !left]);
+                guarded(&[lit, !right]);
+                guarded(&[!lit, left, right]);
+            }
+            // lit <-> right | (left & next) = (right | left) & (right | next)
+            Gate::Until { left, right, next } => {
+                guarded(&[!lit, right, left]);
+                guarded(&[!lit, right, next]);
+                guarded(&[lit, !right]);
+                guarded(&[lit, !left, !next]);
+            }
+        }
+    }
+
+    /// Solves the formula with varisat, which cannot be interrupted, so that the deadline is only checked beforehand.
+    fn solve(&self, deadline: Option<Instant>) -> Satisfiability {
+        if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+            return Satisfiability::Unknown;
+        }
+        let mut solver = varisat::Solver::new();
+        for clause in &self.clauses {
+            let clause = clause
+                .iter()
+                .map(|lit| {
+                    varisat::Lit::from_var(varisat::Var::from_index(lit.var()), !lit.is_negated())
+                })
+                .collect::<Vec<_>>();
+            solver.add_clause(&clause);
+        }
+        match solver.solve() {
+            Ok(true) => {
+                // Variables in no clause are left out of the model, and can be false.
+                let mut model = vec![false; self.vars];
+                for lit in solver.model().expect("satisfiable") {
+                    model[lit.var().index()] = lit.is_positive();
+                }
+                Satisfiability::Sat(model)
+            }
+            Ok(false) => Satisfiability::Unsat,
+            Err(_) => Satisfiability::Unknown,
+        }
+    }
+}
+
+/// A boolean function of literals, whose value a literal is constrained to take.
+#[derive(Debug, Clone, Copy)]
+enum Gate {
+    Const(bool),
+    Lit(Lit),
+    And(Lit, Lit),
+    Or(Lit, Lit),
+    Until { left: Lit, right: Lit, next: Lit },
+}
+
+/// The labels of the nodes of a formula.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Node {
+    Atom(Idx),
+    Not,
+    Next,
+    Globally,
+    Finally,
+    And,
+    Or,
+    Implies,
+    Until,
+}
+
+const OPERATORS: [Node; 8] = [
+    Node::Not,
+    Node::Next,
+    Node::Globally,
+    Node::Finally,
+    Node::And,
+    Node::Or,
+    Node::Implies,
+    Node::Until,
+];
+
+impl Node {
+    fn is_binary(self) -> bool {
+        matches!(self, Node::And | Node::Or | Node::Implies | Node::Until)
+    }
+}
+
+/// The encoding into SAT of the existence of a formula with a given number of distinct subformulas
+/// consistent with a sample (see [Neider, Gavran - Learning Linear Temporal Properties (2018)](https://doi.org/10.23919/FMCAD.2018.8603016)).
+/// The formula is a DAG of nodes, where the children of a node come before it, and the last node is the root.
+struct Encoding {
+    cnf: Cnf,
+    labels: Vec<Node>,
+    /// Whether the node has the label, for each node and label.
+    label: Vec<Vec<Lit>>,
+    /// Whether the node has the (earlier) node as left child, for each node but the first.
+    left: Vec<Vec<Lit>>,
+    /// Whether the node has the (earlier) node as right child, for each node but the first.
+    right: Vec<Vec<Lit>>,
+}
+
+impl Encoding {
+    fn new<const N: usize>(sample: &Sample<N>, vars: &[Idx], size: usize) -> Self {
+        let mut cnf = Cnf::default();
+        let labels = vars
+            .iter()
+            .map(|&var| Node::Atom(var))
+            .chain(OPERATORS)
+            .collect::<Vec<_>>();
+
+        let label = (0..size)
+            .map(|_| labels.iter().map(|_| cnf.new_var()).collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        let mut children = || {
+            (0..size)
+                .map(|node| (0..node).map(|_| cnf.new_var()).collect::<Vec<_>>())
+                .collect::<Vec<_>>()
+        };
+        let left = children();
+        let right = children();
+
+        for node in 0..size {
+            cnf.exactly_one(&label[node]);
+            if node == 0 {
+                // The first node has no children, so it can only be an atom.
+                for (index, &node_label) in labels.iter().enumerate() {
+                    if !matches!(node_label, Node::Atom(_)) {
+                        cnf.add(vec![!label[0][index]]);
+                    }
+                }
+                continue;
+            }
+            cnf.exactly_one(&left[node]);
+            cnf.exactly_one(&right[node]);
+            // Unused children are fixed to the first node, to avoid equivalent assignments.
+            for (index, &node_label) in labels.iter().enumerate() {
+                if !node_label.is_binary() {
+                    cnf.add(vec![!label[node][index], right[node][0]]);
+                }
+                if matches!(node_label, Node::Atom(_)) {
+                    cnf.add(vec![!label[node][index], left[node][0]]);
+                }
+            }
+        }
+
+        let mut encoding = Encoding {
+            cnf,
+            labels,
+            label,
+            left,
+            right,
+        };
+        for trace in &sample.positive_traces {
+            encoding.trace(trace, true);
+        }
+        for trace in &sample.negative_traces {
+            encoding.trace(trace, false);
+        }
+        encoding
+    }
+
+    /// Constrains the root to be satisfied (or not) by the trace.
+    fn trace<const N: usize>(&mut self, trace: &[[bool; N]], satisfied: bool) {
+        let size = self.label.len();
+        let length = trace.len();
+        // Whether each node is satisfied at each time.
+        let holds = (0..size)
+            .map(|_| (0..length).map(|_| self.cnf.new_var()).collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+
+        for node in 0..size {
+            for (index, &node_label) in self.labels.iter().enumerate() {
+                let is_label = self.label[node][index];
+                if let Node::Atom(var) = node_label {
+                    for time in 0..length {
+                        let gate = Gate::Const(trace[time][var as usize]);
+                        self.cnf.gate(&[is_label], holds[node][time], gate);
+                    }
+                    continue;
+                }
+                for left in 0..node {
+                    let is_left = self.left[node][left];
+                    if !node_label.is_binary() {
+                        for time in 0..length {
+                            let child = holds[left][time];
+                            let next = holds[node].get(time + 1).copied();
+                            let gate = match (node_label, next) {
+                                (Node::Not, _) => Gate::Lit(!child),
+                                (Node::Next, Some(_)) => Gate::Lit(holds[left][time + 1]),
+                                (Node::Next, None) => Gate::Const(false),
+                                (Node::Globally, Some(next)) => Gate::And(child, next),
+                                (Node::Finally, Some(next)) => Gate::Or(child, next),
+                                (_, _) => Gate::Lit(child),
+                            };
+                            self.cnf.gate(&[is_label, is_left], holds[node][time], gate);
+                        }
+                        continue;
+                    }
+                    for right in 0..node {
+                        let is_right = self.right[node][right];
+                        for time in 0..length {
+                            let left_child = holds[left][time];
+                            let right_child = holds[right][time];
+                            let gate = match (node_label, holds[node].get(time + 1)) {
+                                (Node::And, _) => Gate::And(left_child, right_child),
+                                (Node::Or, _) => Gate::Or(left_child, right_child),
+                                (Node::Implies, _) => Gate::Or(!left_child, right_child),
+                                (_, Some(&next)) => Gate::Until {
+                                    left: left_child,
+                                    right: right_child,
+                                    next,
+                                },
+                                (_, None) => Gate::Lit(right_child),
+                            };
+                            self.cnf
+                                .gate(&[is_label, is_left, is_right], holds[node][time], gate);
+                        }
+                    }
+                }
+            }
+        }
+
+        let root = holds[size - 1][0];
+        self.cnf.add(vec![if satisfied { root } else { !root }]);
+    }
+
+    /// Reads the formula off a satisfying assignment.
+    fn formula(&self, model: &[bool]) -> SyntaxTree {
+        let is_true = |lit: Lit| model[lit.var()] != lit.is_negated();
+        let mut formulas: Vec<Arc<SyntaxTree>> = Vec::with_capacity(self.label.len());
+        for node in 0..self.label.len() {
+            let index = self.label[node]
+                .iter()
+                .position(|&lit| is_true(lit))
+                .expect("every node has a label");
+            let child = |children: &[Vec<Lit>]| {
+                let child = children[node]
+                    .iter()
+                    .position(|&lit| is_true(lit))
+                    .expect("every node has children");
+                formulas[child].clone()
+            };
+            let formula = match self.labels[index] {
+                Node::Atom(var) => SyntaxTree::Atom(var),
+                Node::Not => SyntaxTree::Not(child(&self.left)),
+                Node::Next => SyntaxTree::Next(child(&self.left)),
+                Node::Globally => SyntaxTree::Globally(child(&self.left)),
+                Node::Finally => SyntaxTree::Finally(child(&self.left)),
+                Node::And => SyntaxTree::And(child(&self.left), child(&self.right)),
+                Node::Or => SyntaxTree::Or(child(&self.left), child(&self.right)),
+                Node::Implies => SyntaxTree::Implies(child(&self.left), child(&self.right)),
+                Node::Until => SyntaxTree::Until(child(&self.left), child(&self.right)),
+            };
+            formulas.push(Arc::new(formula));
+        }
+        formulas.pop().expect("at least one node").as_ref().clone()
+    }
+
+    /// Excludes the structure of the formula of a satisfying assignment from further solutions.
+    fn block(&mut self, model: &[bool]) {
+        let clause = self
+            .label
+            .iter()
+            .chain(self.left.iter())
+            .chain(self.right.iter())
+            .flatten()
+            .filter(|&&lit| model[lit.var()] != lit.is_negated())
+            .map(|&lit| !lit)
+            .collect();
+        self.cnf.add(clause);
+    }
+}
+
+/// An exact learner, which encodes the existence of a formula of a given size consistent with the sample into SAT
+/// and increases the size until the encoding is satisfiable.
+/// It solves the encoding with [varisat](https://docs.rs/varisat), and requires the `sat` feature.
+///
+/// The size is the number of distinct subformulas, as subformulas can be shared,
+/// so the learned formula can be larger than those found by [`BruteForce`] when written as a tree.
+/// Lassos are not part of the encoding: formulas misclassifying them are excluded one by one.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SatLearner;
+
+impl SatLearner {
+    /// Learns a consistent formula of the given size, if any,
+    /// or runs out of time.
+    fn learn_size<const N: usize>(
+        &self,
+        sample: &Sample<N>,
+        vars: &[Idx],
+        size: usize,
+        deadline: Option<Instant>,
+    ) -> Option<LearnResult> {
+        let mut encoding = Encoding::new(sample, vars, size);
+        loop {
+            match encoding.cnf.solve(deadline) {
+                Satisfiability::Sat(model) => {
+                    let formula = encoding.formula(&model);
+                    if sample.is_consistent(&formula) {
+                        return Some(LearnResult::Consistent(formula));
+                    }
+                    encoding.block(&model);
+                }
+                Satisfiability::Unsat => return None,
+                Satisfiability::Unknown => return Some(LearnResult::Exhausted),
+            }
+        }
+    }
+}
+
+impl<const N: usize> Learner<N> for SatLearner {
+    fn learn(&self, sample: &Sample<N>, budget: Budget) -> LearnResult {
+        if !sample.is_solvable() {
+            return LearnResult::Unsolvable;
+        }
+
+        let vars = sample.vars();
+        let deadline = budget.deadline();
+        for size in 1..=budget.max_size.unwrap_or(usize::MAX) {
+            if let Some(result) = self.learn_size(sample, &vars, size, deadline) {
+                return result;
+            }
+        }
+
+        LearnResult::Exhausted
+    }
+}
+
+#[cfg(test)]
+mod sat_learning {
+    use super::*;
+
+    /// Whether every clause has a true literal under the assignment.
+    fn satisfies(cnf: &Cnf, model: &[bool]) -> bool {
+        cnf.clauses.iter().all(|clause| {
+            clause
+                .iter()
+                .any(|&lit| model[lit.var()] != lit.is_negated())
+        })
+    }
+
+    /// The pigeons fit in the holes: each pigeon is in a hole, and no two pigeons share one.
+    fn pigeonhole(pigeons: usize, holes: usize) -> Cnf {
+        let mut cnf = Cnf::default();
+        let pigeons = (0..pigeons)
+            .map(|_| (0..holes).map(|_| cnf.new_var()).collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        for pigeon in &pigeons {
+            cnf.add(pigeon.clone());
+        }
+        for hole in 0..holes {
+            for (i, pigeon) in pigeons.iter().enumerate() {
+                for other in &pigeons[i + 1..] {
+                    cnf.add(vec![!pigeon[hole], !other[hole]]);
+                }
+            }
+        }
+        cnf
+    }
+
+    #[test]
+    fn pigeonhole_principle() {
+        for holes in 1..=5 {
+            // One pigeon too many does not fit.
+            assert_eq!(
+                pigeonhole(holes + 1, holes).solve(None),
+                Satisfiability::Unsat
+            );
+            let cnf = pigeonhole(holes, holes);
+            let Satisfiability::Sat(model) = cnf.solve(None) else {
+                panic!("as many pigeons as holes fit");
+            };
+            assert!(satisfies(&cnf, &model));
+        }
+    }
+
+    #[test]
+    fn learn() {
+        let sample = Sample::<2> {
+            positive_traces: vec![
+                vec![[false, false], [true, false], [false, true]],
+                vec![[false, true]],
+            ],
+            negative_traces: vec![
+                vec![[true, false], [false, false]],
+                vec![[false, false], [false, true], [true, false]],
+            ],
+            ..Sample::default()
+        };
+        let result = SatLearner.learn(&sample, Budget::default());
+        let formula = result.formula().expect("solvable sample");
+        assert!(result.is_consistent());
+        assert!(sample.is_consistent(formula));
+        // The smallest formula has the same size as the one found by brute force.
+        let brute_force = crate::solve(&sample, false, false).expect("solvable sample");
+        assert_eq!(formula.size(), brute_force.size());
+
+        assert_eq!(
+            SatLearner.learn(&sample, Budget::default().max_size(1)),
+            LearnResult::Exhausted
+        );
+    }
+}
@@ -0,0 +1,229 @@
+use crate::learn::*;
+use crate::learner::*;
+use crate::syntax::*;
+use crate::trace::*;
+use itertools::Itertools;
+use std::fmt;
+use std::sync::Arc;
+use std::time::Instant;
+
+/// A partial formula, whose holes are to be filled by formulas (see [`SketchLearner`]),
+/// e.g., `G(?1 -> F ?2)` when the shape of the formula is known, but not its variables.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Sketch {
+    /// A hole, numbered from 0 by order of appearance in the sketch.
+    Hole(usize),
+    Atom(Idx),
+    Not(Arc<Sketch>),
+    Next(Arc<Sketch>),
+    Globally(Arc<Sketch>),
+    Finally(Arc<Sketch>),
+    And(Arc<Sketch>, Arc<Sketch>),
+    Or(Arc<Sketch>, Arc<Sketch>),
+    Implies(Arc<Sketch>, Arc<Sketch>),
+    Until(Arc<Sketch>, Arc<Sketch>),
+}
+
+impl Sketch {
+    /// The number of distinct holes, i.e., the highest hole number plus 1.
+    pub fn holes(&self) -> usize {
+        match self {
+            Sketch::Hole(hole) => hole + 1,
+            Sketch::Atom(_) => 0,
+            Sketch::Not(branch)
+            | Sketch::Next(branch)
+            | Sketch::Globally(branch)
+            | Sketch::Finally(branch) => branch.holes(),
+            Sketch::And(left_branch, right_branch)
+            | Sketch::Or(left_branch, right_branch)
+            | Sketch::Implies(left_branch, right_branch)
+            | Sketch::Until(left_branch, right_branch) => {
+                left_branch.holes().max(right_branch.holes())
+            }
+        }
+    }
+
+    /// Fills each hole with the formula at its number, panicking if some hole has no formula.
+    pub fn fill(&self, fillings: &[Arc<SyntaxTree>]) -> SyntaxTree {
+        let fill = |branch: &Arc<Sketch>| Arc::new(branch.fill(fillings));
+        match self {
+            Sketch::Hole(hole) => fillings[*hole].as_ref().clone(),
+            Sketch::Atom(n) => SyntaxTree::Atom(*n),
+            Sketch::Not(branch) => SyntaxTree::Not(fill(branch)),
+            Sketch::Next(branch) => SyntaxTree::Next(fill(branch)),
+            Sketch::Globally(branch) => SyntaxTree::Globally(fill(branch)),
+            Sketch::Finally(branch) => SyntaxTree::Finally(fill(branch)),
+            Sketch::And(left_branch, right_branch) => {
+                SyntaxTree::And(fill(left_branch), fill(right_branch))
+            }
+            Sketch::Or(left_branch, right_branch) => {
+                SyntaxTree::Or(fill(left_branch), fill(right_branch))
+            }
+            Sketch::Implies(left_branch, right_branch) => {
+                SyntaxTree::Implies(fill(left_branch), fill(right_branch))
+            }
+            Sketch::Until(left_branch, right_branch) => {
+                SyntaxTree::Until(fill(left_branch), fill(right_branch))
+            }
+        }
+    }
+}
+
+impl fmt::Display for Sketch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Sketch::Hole(hole) => write!(f, "?{hole}"),
+            Sketch::Atom(n) => write!(f, "x{n}"),
+            Sketch::Not(branch) => write!(f, "¬({branch})"),
+            Sketch::Next(branch) => write!(f, "X({branch})"),
+            Sketch::Globally(branch) => write!(f, "G({branch})"),
+            Sketch::Finally(branch) => write!(f, "F({branch})"),
+            Sketch::And(left_branch, right_branch) => {
+                write!(f, "({left_branch}) ∧ ({right_branch})")
+            }
+            Sketch::Or(left_branch, right_branch) => {
+                write!(f, "({left_branch}) ∨ ({right_branch})")
+            }
+            Sketch::Implies(left_branch, right_branch) => {
+                write!(f, "({left_branch}) → ({right_branch})")
+            }
+            Sketch::Until(left_branch, right_branch) => {
+                write!(f, "({left_branch}) U ({right_branch})")
+            }
+        }
+    }
+}
+
+/// A learner which only searches the formulas fitting a [`Sketch`],
+/// filling its holes with formulas up to a given size (by default, only variables),
+/// smallest fillings first.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SketchLearner {
+    pub sketch: Sketch,
+    /// The maximum size of the formulas filling the holes.
+    pub hole_size: usize,
+}
+
+impl SketchLearner {
+    pub fn new(sketch: Sketch) -> Self {
+        SketchLearner {
+            sketch,
+            hole_size: 1,
+        }
+    }
+
+    pub fn hole_size(mut self, hole_size: usize) -> Self {
+        self.hole_size = hole_size;
+        self
+    }
+}
+
+impl<const N: usize> Learner<N> for SketchLearner {
+    fn learn(&self, sample: &Sample<N>, budget: Budget) -> LearnResult {
+        if !sample.is_solvable() {
+            return LearnResult::Unsolvable;
+        }
+
+        let deadline = budget.deadline();
+        let holes = self.sketch.holes();
+        if holes == 0 {
+            let formula = self.sketch.fill(&[]);
+            return if sample.is_consistent(&formula) {
+                LearnResult::Consistent(formula)
+            } else {
+                LearnResult::Exhausted
+            };
+        }
+
+        // The formulas of each size that can fill a hole.
+        let vars = sample.vars();
+        let fillings = (1..=self.hole_size)
+            .map(|size| {
+                gen_formulae::<N>(size, &vars)
+                    .into_iter()
+                    .map(Arc::new)
+                    .collect_vec()
+            })
+            .collect_vec();
+        let mut checked = 0usize;
+        for total_size in holes..=holes * self.hole_size {
+            let hole_sizes = (0..holes)
+                .map(|_| 1..=self.hole_size)
+                .multi_cartesian_product()
+                .filter(|sizes| sizes.iter().sum::<usize>() == total_size);
+            for sizes in hole_sizes {
+                let filled = sizes
+                    .iter()
+                    .map(|size| fillings[size - 1].iter().cloned())
+                    .multi_cartesian_product();
+                for filling in filled {
+                    checked += 1;
+                    if checked.is_multiple_of(1024)
+                        && deadline.is_some_and(|deadline| Instant::now() >= deadline)
+                    {
+                        return LearnResult::Exhausted;
+                    }
+                    let formula = self.sketch.fill(&filling);
+                    if budget.max_size.is_none_or(|max| formula.size() <= max)
+                        && sample.is_consistent(&formula)
+                    {
+                        return LearnResult::Consistent(formula);
+                    }
+                }
+            }
+        }
+
+        LearnResult::Exhausted
+    }
+}
+
+#[cfg(test)]
+mod sketches {
+    use super::*;
+
+    #[test]
+    fn parse() {
+        let sketch = "G(?a -> F ?b) & ?a".parse::<Sketch>().expect("sketch");
+        assert_eq!(sketch.holes(), 2);
+        let filling = [Arc::new(SyntaxTree::Atom(1)), Arc::new(SyntaxTree::Atom(0))];
+        assert_eq!(
+            Ok(sketch.fill(&filling)),
+            "G(x1 -> F x0) & x1".parse::<SyntaxTree>()
+        );
+        assert_eq!(sketch.to_string().parse::<Sketch>(), Ok(sketch));
+
+        // Holes are not formulas.
+        assert!("G ?1".parse::<SyntaxTree>().is_err());
+        assert!("G ?".parse::<Sketch>().is_err());
+    }
+
+    #[test]
+    fn learn_sketch() {
+        // Positive traces are those where every x1 is eventually followed by x0.
+        let sample = Sample::<2> {
+            positive_traces: vec![
+                vec![[false, true], [true, false]],
+                vec![[false, false], [false, false]],
+                vec![[true, true]],
+            ],
+            negative_traces: vec![
+                vec![[false, true], [false, false]],
+                vec![[true, false], [false, true]],
+            ],
+            ..Sample::default()
+        };
+        let sketch = "G(?1 -> F ?2)".parse::<Sketch>().expect("sketch");
+        let result = SketchLearner::new(sketch).learn(&sample, Budget::default());
+        assert_eq!(
+            result.into_formula(),
+            "G(x1 -> F x0)".parse::<SyntaxTree>().ok()
+        );
+
+        let sketch = "G ?1".parse::<Sketch>().expect("sketch");
+        let learner = SketchLearner::new(sketch).hole_size(2);
+        assert_eq!(
+            learner.learn(&sample, Budget::default()),
+            LearnResult::Exhausted
+        );
+    }
+}
@@ -0,0 +1,138 @@
+use crate::syntax::*;
+use crate::trace::*;
+use serde::{Deserialize, Serialize};
+use serde_with::*;
+
+/// A trace together with the confidence that it is a positive example,
+/// where `1.0` means certainly positive and `0.0` certainly negative.
+#[serde_as]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SoftTrace<const N: usize> {
+    #[serde_as(as = "Vec<[_; N]>")]
+    pub trace: Trace<N>,
+    pub confidence: f64,
+}
+
+impl<const N: usize> SoftTrace<N> {
+    /// Creates a soft-labelled trace, panicking if the confidence is not in `[0, 1]`.
+    pub fn new(trace: Trace<N>, confidence: f64) -> Self {
+        assert!(
+            (0.0..=1.0).contains(&confidence),
+            "confidence must be in [0, 1]"
+        );
+        SoftTrace { trace, confidence }
+    }
+}
+
+/// A sample of traces with soft (probabilistic) labels, e.g., produced by a noisy classifier.
+#[serde_as]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SoftSample<const N: usize> {
+    #[serde_as(as = "[_; N]")]
+    #[serde(default = "Sample::var_names")]
+    pub var_names: [String; N],
+    pub traces: Vec<SoftTrace<N>>,
+}
+
+impl<const N: usize> Default for SoftSample<N> {
+    fn default() -> Self {
+        SoftSample {
+            var_names: Sample::var_names(),
+            traces: Vec::new(),
+        }
+    }
+}
+
+impl<const N: usize> SoftSample<N> {
+    /// Adds a trace with the given confidence of being positive, panicking if it is not in `[0, 1]`.
+    pub fn add_trace(&mut self, trace: Trace<N>, confidence: f64) {
+        self.traces.push(SoftTrace::new(trace, confidence));
+    }
+
+    /// The expected accuracy of the formula, when each trace is positive with probability given by its confidence:
+    /// a trace contributes its confidence if it satisfies the formula, and the complement of it otherwise.
+    /// Returns `1.0` on an empty sample.
+    pub fn expected_accuracy(&self, formula: &SyntaxTree) -> f64 {
+        if self.traces.is_empty() {
+            return 1.0;
+        }
+        let expected_correct: f64 = self
+            .traces
+            .iter()
+            .map(|soft| {
+                if formula.eval(&soft.trace) {
+                    soft.confidence
+                } else {
+                    1.0 - soft.confidence
+                }
+            })
+            .sum();
+        expected_correct / self.traces.len() as f64
+    }
+
+    /// Turns soft labels into hard ones: traces with confidence at least `threshold` become positive,
+    /// those with confidence at most `1 - threshold` become negative, and the uncertain ones in between are discarded.
+    /// If identical traces end up with opposite labels, only the first one is kept.
+    pub fn harden(&self, threshold: f64) -> Sample<N> {
+        let mut sample = Sample {
+            var_names: self.var_names.clone(),
+            ..Sample::default()
+        };
+        for soft in &self.traces {
+            if soft.confidence >= threshold {
+                let _ = sample.add_positive_trace(soft.trace.clone());
+            } else if soft.confidence <= 1.0 - threshold {
+                let _ = sample.add_negative_trace(soft.trace.clone());
+            }
+        }
+        sample
+    }
+}
+
+impl<const N: usize> From<Sample<N>> for SoftSample<N> {
+    /// Positive traces get confidence `1.0`, and negative traces get confidence `0.0`.
+    fn from(sample: Sample<N>) -> Self {
+        let traces = sample
+            .positive_traces
+            .into_iter()
+            .map(|trace| SoftTrace::new(trace, 1.0))
+            .chain(
+                sample
+                    .negative_traces
+                    .into_iter()
+                    .map(|trace| SoftTrace::new(trace, 0.0)),
+            )
+            .collect();
+        SoftSample {
+            var_names: sample.var_names,
+            traces,
+        }
+    }
+}
+
+#[cfg(test)]
+mod soft_labels {
+    use super::*;
+
+    #[test]
+    fn expected_accuracy() {
+        let mut sample = SoftSample::<1>::default();
+        sample.add_trace(vec![[true]], 0.9);
+        sample.add_trace(vec![[false]], 0.2);
+
+        let formula = SyntaxTree::Atom(0);
+        assert!((sample.expected_accuracy(&formula) - (0.9 + 0.8) / 2.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn harden() {
+        let mut sample = SoftSample::<1>::default();
+        sample.add_trace(vec![[true]], 0.9);
+        sample.add_trace(vec![[false]], 0.5);
+        sample.add_trace(vec![[false], [true]], 0.05);
+
+        let hard = sample.harden(0.8);
+        assert_eq!(hard.positive_traces, vec![vec![[true]]]);
+        assert_eq!(hard.negative_traces, vec![vec![[false], [true]]]);
+    }
+}
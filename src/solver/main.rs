@@ -1,47 +1,177 @@
 use learn_ltl::*;
 
-use std::fs::File;
-use std::io::prelude::*;
-use std::io::BufReader;
+use std::io;
 use std::path::Path;
+use std::time::Duration;
 
-use clap::Parser;
+use clap::{ArgGroup, Parser};
+
+/// The options choosing a search other than the default one, which options of the default search only conflict with.
+const ALTERNATIVE_SEARCHES: [&str; 11] = [
+    "sat",
+    "noise",
+    "sketch",
+    "prune",
+    "distinct",
+    "costs",
+    "mine",
+    "anneal",
+    "walk",
+    "positive_only",
+    "with",
+];
 
 /// Search for a formula consistent with the given sample.
-/// Supported file types: ron, json.
+/// Supported file types: ron, json, trace, binary.
 #[derive(Parser, Debug)]
 #[clap(name = "solver")]
+// At most one alternative search, except --costs which also weighs the operators of --anneal.
+#[command(group(ArgGroup::new("search").multiple(false).args([
+    "sat",
+    "noise",
+    "sketch",
+    "prune",
+    "distinct",
+    "mine",
+    "anneal",
+    "walk",
+    "positive_only",
+    "with",
+])))]
 struct Solver {
     /// The sample for which to learn a solving formula
     sample: String,
     /// Use parallel search via multithreading
     #[arg(short, long, default_value_t = false)]
     multithread: bool,
+    /// Print the solution in the given notation: unicode, ascii, prefix, latex, spot, spin
+    #[arg(short, long)]
+    notation: Option<Notation>,
+    /// Hold out the given fraction of the traces to test the solution on
+    #[arg(long)]
+    holdout: Option<f64>,
+    /// How to deal with traces of different lengths, overriding the sample: natural, pad, truncate, strict
+    #[arg(long)]
+    lengths: Option<LengthPolicy>,
+    /// Search via a SAT encoding instead of enumerating formulas (requires the `sat` feature)
+    #[arg(long, default_value_t = false)]
+    sat: bool,
+    /// Give up after searching formulas of the given size
+    #[arg(long)]
+    max_size: Option<usize>,
+    /// Give up after the given number of seconds
+    #[arg(long)]
+    timeout: Option<f64>,
+    /// Give up after checking the given number of formulas (not with --sat)
+    #[arg(long)]
+    max_formulas: Option<usize>,
+    /// Find the smallest formula misclassifying at most the given fraction of the traces, for noisy samples
+    #[arg(long)]
+    noise: Option<f64>,
+    /// Only search formulas of the given shape, whose holes (e.g., `?1`) are filled with variables
+    #[arg(long)]
+    sketch: Option<String>,
+    /// Skip formulas behaving like smaller ones on the sample, at the cost of memory (only limited by --max-size)
+    #[arg(long, default_value_t = false)]
+    prune: bool,
+    /// Skip formulas equivalent to smaller ones on all finite traces, compared through automata (requires the `equivalence` feature; only limited by --max-size)
+    #[arg(long, default_value_t = false)]
+    distinct: bool,
+    /// Search pure-past formulas evaluated at the end of the traces
+    #[arg(long, default_value_t = false)]
+    past: bool,
+    /// Return the least solution of the smallest size, so that it does not depend on the search order
+    #[arg(long, default_value_t = false)]
+    canonical: bool,
+    /// Search formulas of least total cost, given as operator costs, e.g., until=3,not=0 (--max-size bounds the cost)
+    #[arg(long, conflicts_with_all = ["sat", "noise", "sketch", "prune", "distinct", "mine", "walk", "positive_only", "with"])]
+    costs: Option<OperatorCosts>,
+    /// Mine a conjunction of formulas up to the given size, each satisfied by all positive traces
+    #[arg(long)]
+    mine: Option<usize>,
+    /// Search by simulated annealing for the given number of steps, returning the fittest formula found
+    #[arg(long)]
+    anneal: Option<usize>,
+    /// Search by stochastic local search, repeatedly fixing a misclassified trace, restarting after the given number of flips
+    #[arg(long)]
+    walk: Option<usize>,
+    /// The cooling schedule of --anneal: linear, logarithmic, or exponential with an optional factor, e.g., exponential:0.95
+    #[arg(long)]
+    cooling: Option<Cooling>,
+    /// Only accept solutions entailing the given formula on the traces of the sample, e.g., `G safe` (can be repeated; default search only)
+    #[arg(long, conflicts_with_all = ALTERNATIVE_SEARCHES, conflicts_with_all = ["chance", "canonical"])]
+    entails: Vec<String>,
+    /// Only accept solutions entailed by the given formula on the traces of the sample (can be repeated; default search only)
+    #[arg(long, conflicts_with_all = ALTERNATIVE_SEARCHES, conflicts_with_all = ["chance", "canonical"])]
+    entailed_by: Vec<String>,
+    /// Return the consistent formula least likely to hold on random traces, for samples without negative traces
+    #[arg(long, default_value_t = false)]
+    positive_only: bool,
+    /// Record the progress of the search in the given file, to resume it after the sizes already searched (default search only)
+    #[arg(long, conflicts_with_all = ALTERNATIVE_SEARCHES, conflicts_with_all = ["canonical", "chance", "entails", "entailed_by"])]
+    cache: Option<String>,
+    /// Print statistics about each size searched, including the formulas pruned as equivalent to others (default search only)
+    #[arg(long, default_value_t = false, conflicts_with_all = ALTERNATIVE_SEARCHES, conflicts_with_all = ["canonical", "entails", "entailed_by", "cache", "chance", "order"])]
+    stats: bool,
+    /// Accept solutions satisfying at least the first fraction of positive traces and at most the second of negative ones, e.g., 0.9,0.1 (default search only)
+    #[arg(long, conflicts_with_all = ALTERNATIVE_SEARCHES, conflicts_with_all = ["canonical"])]
+    chance: Option<ChanceConstraint>,
+    /// The order in which to search formulas: size, depth, restarts[:seed], or schedule:<sizes>, e.g., schedule:6,4 (default search only)
+    #[arg(long, conflicts_with_all = ALTERNATIVE_SEARCHES, conflicts_with_all = ["entails", "entailed_by", "chance", "canonical", "cache"])]
+    order: Option<SearchOrder>,
+    /// Also require consistency with the given sample, with the same variables, e.g., from another deployment (can be repeated; default search only)
+    #[arg(long, conflicts_with_all = ["canonical", "holdout"])]
+    with: Vec<String>,
+    /// Search formulas over all the variables, rather than only those needed to tell positive and negative traces apart
+    #[arg(long, default_value_t = false)]
+    all_vars: bool,
 }
 
-fn main() -> std::io::Result<()> {
+fn main() -> io::Result<()> {
     let solver = Solver::parse();
 
     let path = Path::new(&solver.sample);
-    let file = File::open(path)?;
-    let mut buf_reader = BufReader::new(file);
-    let mut contents = Vec::new();
-    buf_reader.read_to_end(&mut contents)?;
-
-    let load_and_solve = match path.extension() {
-        Some(ext) if ext == "ron" => ron_load_and_solve,
-        Some(ext) if ext == "json" => json_load_and_solve,
-        Some(ext) => {
-            println!("File format unknown or not supported: {:#?}", ext);
-            return Ok(());
-        }
-        None => {
-            println!("File format missing");
-            return Ok(());
+    if SampleFormat::from_path(path).is_none() {
+        match path.extension() {
+            Some(ext) => println!("File format unknown or not supported: {:#?}", ext),
+            None => println!("File format missing"),
         }
-    };
+        return Ok(());
+    }
 
-    if let Some(solution) = load_and_solve(contents, solver.multithread) {
+    let solve = Solve {
+        multithread: solver.multithread,
+        notation: solver.notation,
+        holdout: solver.holdout,
+        lengths: solver.lengths,
+        sat: solver.sat,
+        budget: SearchBudget {
+            max_size: solver.max_size,
+            max_duration: solver.timeout.map(Duration::from_secs_f64),
+            max_formulas: solver.max_formulas,
+        },
+        noise: solver.noise,
+        sketch: solver.sketch,
+        prune: solver.prune,
+        distinct: solver.distinct,
+        past: solver.past,
+        canonical: solver.canonical,
+        costs: solver.costs,
+        mine: solver.mine,
+        anneal: solver.anneal,
+        walk: solver.walk,
+        cooling: solver.cooling,
+        entails: solver.entails,
+        entailed_by: solver.entailed_by,
+        positive_only: solver.positive_only,
+        cache: solver.cache,
+        stats: solver.stats,
+        chance: solver.chance,
+        order: solver.order,
+        with: solver.with,
+        all_vars: solver.all_vars,
+    };
+    if let Some(solution) = visit_sample(path, solve)?? {
         println!("Solution: {}", solution);
     } else {
         println!("No solution found");
@@ -50,413 +180,330 @@ fn main() -> std::io::Result<()> {
     Ok(())
 }
 
-fn ron_load_and_solve(contents: Vec<u8>, multithread: bool) -> Option<String> {
-    // Ugly hack to get around limitations of deserialization for types with const generics.
-    // See https://github.com/serde-rs/serde/issues/1937
-    (1..).into_iter().find_map(|n| {
-        match n {
-            0 => ron::de::from_bytes::<Sample<0>>(&contents).map(|sample| {
-                solve(&sample, multithread, true)
-                    .map(|formula| formula.print_w_named_vars(&sample.var_names))
-                    .unwrap_or("No solution".to_string())
-            }),
-            1 => ron::de::from_bytes::<Sample<1>>(&contents).map(|sample| {
-                solve(&sample, multithread, true)
-                    .map(|formula| formula.print_w_named_vars(&sample.var_names))
-                    .unwrap_or("No solution".to_string())
-            }),
-            2 => ron::de::from_bytes::<Sample<2>>(&contents).map(|sample| {
-                solve(&sample, multithread, true)
-                    .map(|formula| formula.print_w_named_vars(&sample.var_names))
-                    .unwrap_or("No solution".to_string())
-            }),
-            3 => ron::de::from_bytes::<Sample<3>>(&contents).map(|sample| {
-                solve(&sample, multithread, true)
-                    .map(|formula| formula.print_w_named_vars(&sample.var_names))
-                    .unwrap_or("No solution".to_string())
-            }),
-            4 => ron::de::from_bytes::<Sample<4>>(&contents).map(|sample| {
-                solve(&sample, multithread, true)
-                    .map(|formula| formula.print_w_named_vars(&sample.var_names))
-                    .unwrap_or("No solution".to_string())
-            }),
-            5 => ron::de::from_bytes::<Sample<5>>(&contents).map(|sample| {
-                solve(&sample, multithread, true)
-                    .map(|formula| formula.print_w_named_vars(&sample.var_names))
-                    .unwrap_or("No solution".to_string())
-            }),
-            6 => ron::de::from_bytes::<Sample<6>>(&contents).map(|sample| {
-                solve(&sample, multithread, true)
-                    .map(|formula| formula.print_w_named_vars(&sample.var_names))
-                    .unwrap_or("No solution".to_string())
-            }),
-            7 => ron::de::from_bytes::<Sample<7>>(&contents).map(|sample| {
-                solve(&sample, multithread, true)
-                    .map(|formula| formula.print_w_named_vars(&sample.var_names))
-                    .unwrap_or("No solution".to_string())
-            }),
-            8 => ron::de::from_bytes::<Sample<8>>(&contents).map(|sample| {
-                solve(&sample, multithread, true)
-                    .map(|formula| formula.print_w_named_vars(&sample.var_names))
-                    .unwrap_or("No solution".to_string())
-            }),
-            9 => ron::de::from_bytes::<Sample<9>>(&contents).map(|sample| {
-                solve(&sample, multithread, true)
-                    .map(|formula| formula.print_w_named_vars(&sample.var_names))
-                    .unwrap_or("No solution".to_string())
-            }),
-            10 => ron::de::from_bytes::<Sample<10>>(&contents).map(|sample| {
-                solve(&sample, multithread, true)
-                    .map(|formula| formula.print_w_named_vars(&sample.var_names))
-                    .unwrap_or("No solution".to_string())
-            }),
-            11 => ron::de::from_bytes::<Sample<11>>(&contents).map(|sample| {
-                solve(&sample, multithread, true)
-                    .map(|formula| formula.print_w_named_vars(&sample.var_names))
-                    .unwrap_or("No solution".to_string())
-            }),
-            12 => ron::de::from_bytes::<Sample<12>>(&contents).map(|sample| {
-                solve(&sample, multithread, true)
-                    .map(|formula| formula.print_w_named_vars(&sample.var_names))
-                    .unwrap_or("No solution".to_string())
-            }),
-            13 => ron::de::from_bytes::<Sample<13>>(&contents).map(|sample| {
-                solve(&sample, multithread, true)
-                    .map(|formula| formula.print_w_named_vars(&sample.var_names))
-                    .unwrap_or("No solution".to_string())
-            }),
-            14 => ron::de::from_bytes::<Sample<14>>(&contents).map(|sample| {
-                solve(&sample, multithread, true)
-                    .map(|formula| formula.print_w_named_vars(&sample.var_names))
-                    .unwrap_or("No solution".to_string())
-            }),
-            15 => ron::de::from_bytes::<Sample<15>>(&contents).map(|sample| {
-                solve(&sample, multithread, true)
-                    .map(|formula| formula.print_w_named_vars(&sample.var_names))
-                    .unwrap_or("No solution".to_string())
-            }),
-            16 => ron::de::from_bytes::<Sample<16>>(&contents).map(|sample| {
-                solve(&sample, multithread, true)
-                    .map(|formula| formula.print_w_named_vars(&sample.var_names))
-                    .unwrap_or("No solution".to_string())
-            }),
-            17 => ron::de::from_bytes::<Sample<17>>(&contents).map(|sample| {
-                solve(&sample, multithread, true)
-                    .map(|formula| formula.print_w_named_vars(&sample.var_names))
-                    .unwrap_or("No solution".to_string())
-            }),
-            18 => ron::de::from_bytes::<Sample<18>>(&contents).map(|sample| {
-                solve(&sample, multithread, true)
-                    .map(|formula| formula.print_w_named_vars(&sample.var_names))
-                    .unwrap_or("No solution".to_string())
-            }),
-            19 => ron::de::from_bytes::<Sample<19>>(&contents).map(|sample| {
-                solve(&sample, multithread, true)
-                    .map(|formula| formula.print_w_named_vars(&sample.var_names))
-                    .unwrap_or("No solution".to_string())
-            }),
-            20 => ron::de::from_bytes::<Sample<20>>(&contents).map(|sample| {
-                solve(&sample, multithread, true)
-                    .map(|formula| formula.print_w_named_vars(&sample.var_names))
-                    .unwrap_or("No solution".to_string())
-            }),
-            21 => ron::de::from_bytes::<Sample<21>>(&contents).map(|sample| {
-                solve(&sample, multithread, true)
-                    .map(|formula| formula.print_w_named_vars(&sample.var_names))
-                    .unwrap_or("No solution".to_string())
-            }),
-            22 => ron::de::from_bytes::<Sample<22>>(&contents).map(|sample| {
-                solve(&sample, multithread, true)
-                    .map(|formula| formula.print_w_named_vars(&sample.var_names))
-                    .unwrap_or("No solution".to_string())
-            }),
-            23 => ron::de::from_bytes::<Sample<23>>(&contents).map(|sample| {
-                solve(&sample, multithread, true)
-                    .map(|formula| formula.print_w_named_vars(&sample.var_names))
-                    .unwrap_or("No solution".to_string())
-            }),
-            24 => ron::de::from_bytes::<Sample<24>>(&contents).map(|sample| {
-                solve(&sample, multithread, true)
-                    .map(|formula| formula.print_w_named_vars(&sample.var_names))
-                    .unwrap_or("No solution".to_string())
-            }),
-            25 => ron::de::from_bytes::<Sample<25>>(&contents).map(|sample| {
-                solve(&sample, multithread, true)
-                    .map(|formula| formula.print_w_named_vars(&sample.var_names))
-                    .unwrap_or("No solution".to_string())
-            }),
-            26 => ron::de::from_bytes::<Sample<26>>(&contents).map(|sample| {
-                solve(&sample, multithread, true)
-                    .map(|formula| formula.print_w_named_vars(&sample.var_names))
-                    .unwrap_or("No solution".to_string())
-            }),
-            27 => ron::de::from_bytes::<Sample<27>>(&contents).map(|sample| {
-                solve(&sample, multithread, true)
-                    .map(|formula| formula.print_w_named_vars(&sample.var_names))
-                    .unwrap_or("No solution".to_string())
-            }),
-            28 => ron::de::from_bytes::<Sample<28>>(&contents).map(|sample| {
-                solve(&sample, multithread, true)
-                    .map(|formula| formula.print_w_named_vars(&sample.var_names))
-                    .unwrap_or("No solution".to_string())
-            }),
-            29 => ron::de::from_bytes::<Sample<29>>(&contents).map(|sample| {
-                solve(&sample, multithread, true)
-                    .map(|formula| formula.print_w_named_vars(&sample.var_names))
-                    .unwrap_or("No solution".to_string())
-            }),
-            30 => ron::de::from_bytes::<Sample<30>>(&contents).map(|sample| {
-                solve(&sample, multithread, true)
-                    .map(|formula| formula.print_w_named_vars(&sample.var_names))
-                    .unwrap_or("No solution".to_string())
-            }),
-            31 => ron::de::from_bytes::<Sample<31>>(&contents).map(|sample| {
-                solve(&sample, multithread, true)
-                    .map(|formula| formula.print_w_named_vars(&sample.var_names))
-                    .unwrap_or("No solution".to_string())
-            }),
-            32 => ron::de::from_bytes::<Sample<32>>(&contents).map(|sample| {
-                solve(&sample, multithread, true)
-                    .map(|formula| formula.print_w_named_vars(&sample.var_names))
-                    .unwrap_or("No solution".to_string())
-            }),
-            33 => ron::de::from_bytes::<Sample<33>>(&contents).map(|sample| {
-                solve(&sample, multithread, true)
-                    .map(|formula| formula.print_w_named_vars(&sample.var_names))
-                    .unwrap_or("No solution".to_string())
-            }),
-            34 => ron::de::from_bytes::<Sample<34>>(&contents).map(|sample| {
-                solve(&sample, multithread, true)
-                    .map(|formula| formula.print_w_named_vars(&sample.var_names))
-                    .unwrap_or("No solution".to_string())
-            }),
-            35 => ron::de::from_bytes::<Sample<35>>(&contents).map(|sample| {
-                solve(&sample, multithread, true)
-                    .map(|formula| formula.print_w_named_vars(&sample.var_names))
-                    .unwrap_or("No solution".to_string())
-            }),
-            36 => ron::de::from_bytes::<Sample<36>>(&contents).map(|sample| {
-                solve(&sample, multithread, true)
-                    .map(|formula| formula.print_w_named_vars(&sample.var_names))
-                    .unwrap_or("No solution".to_string())
-            }),
-            37 => ron::de::from_bytes::<Sample<37>>(&contents).map(|sample| {
-                solve(&sample, multithread, true)
-                    .map(|formula| formula.print_w_named_vars(&sample.var_names))
-                    .unwrap_or("No solution".to_string())
-            }),
-            38 => ron::de::from_bytes::<Sample<38>>(&contents).map(|sample| {
-                solve(&sample, multithread, true)
-                    .map(|formula| formula.print_w_named_vars(&sample.var_names))
-                    .unwrap_or("No solution".to_string())
-            }),
-            _ => panic!("out-of-bound parameter"),
+/// Searches a solution for a sample with any number of variables.
+struct Solve {
+    multithread: bool,
+    notation: Option<Notation>,
+    holdout: Option<f64>,
+    lengths: Option<LengthPolicy>,
+    sat: bool,
+    budget: SearchBudget,
+    noise: Option<f64>,
+    sketch: Option<String>,
+    prune: bool,
+    distinct: bool,
+    past: bool,
+    canonical: bool,
+    costs: Option<OperatorCosts>,
+    mine: Option<usize>,
+    anneal: Option<usize>,
+    walk: Option<usize>,
+    cooling: Option<Cooling>,
+    entails: Vec<String>,
+    entailed_by: Vec<String>,
+    positive_only: bool,
+    cache: Option<String>,
+    stats: bool,
+    chance: Option<ChanceConstraint>,
+    order: Option<SearchOrder>,
+    with: Vec<String>,
+    all_vars: bool,
+}
+
+impl SampleVisitor for Solve {
+    /// The solution, if any, or an error if the sample is inconsistent.
+    type Output = io::Result<Option<String>>;
+
+    fn visit<const N: usize>(self, mut sample: Sample<N>) -> Self::Output {
+        if let Some(lengths) = self.lengths {
+            sample.length_policy = lengths;
+        }
+        let sample = sample.apply_length_policy();
+        // Past formulas on the sample are future formulas on the reversed sample.
+        let sample = if self.past { sample.reversed() } else { sample };
+        if !self.with.is_empty() {
+            return self.solve_common(sample);
+        }
+        if self.noise.is_none() && self.chance.is_none() {
+            check_consistency(&sample)?;
+        }
+        Ok(self.solve(sample))
+    }
+}
+
+/// Fails if no formula can be consistent with the sample, as searching would then be pointless.
+fn check_consistency<const N: usize>(sample: &Sample<N>) -> io::Result<()> {
+    if let Err(contradictions) = sample.check_consistency() {
+        for contradiction in contradictions {
+            println!("Inconsistent sample: {contradiction}");
         }
-        .ok()
-    })
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "no formula is consistent with the sample",
+        ));
+    }
+    Ok(())
 }
 
-fn json_load_and_solve(contents: Vec<u8>, multithread: bool) -> Option<String> {
-    // Ugly hack to get around limitations of deserialization for types with const generics.
-    (1..).into_iter().find_map(|n| {
-        match n {
-            0 => serde_json::from_slice::<Sample<0>>(&contents).map(|sample| {
-                solve(&sample, multithread, true)
-                    .map(|formula| formula.print_w_named_vars(&sample.var_names))
-                    .unwrap_or("No solution".to_string())
-            }),
-            1 => serde_json::from_slice::<Sample<1>>(&contents).map(|sample| {
-                solve(&sample, multithread, true)
-                    .map(|formula| formula.print_w_named_vars(&sample.var_names))
-                    .unwrap_or("No solution".to_string())
-            }),
-            2 => serde_json::from_slice::<Sample<2>>(&contents).map(|sample| {
-                solve(&sample, multithread, true)
-                    .map(|formula| formula.print_w_named_vars(&sample.var_names))
-                    .unwrap_or("No solution".to_string())
-            }),
-            3 => serde_json::from_slice::<Sample<3>>(&contents).map(|sample| {
-                solve(&sample, multithread, true)
-                    .map(|formula| formula.print_w_named_vars(&sample.var_names))
-                    .unwrap_or("No solution".to_string())
-            }),
-            4 => serde_json::from_slice::<Sample<4>>(&contents).map(|sample| {
-                solve(&sample, multithread, true)
-                    .map(|formula| formula.print_w_named_vars(&sample.var_names))
-                    .unwrap_or("No solution".to_string())
-            }),
-            5 => serde_json::from_slice::<Sample<5>>(&contents).map(|sample| {
-                solve(&sample, multithread, true)
-                    .map(|formula| formula.print_w_named_vars(&sample.var_names))
-                    .unwrap_or("No solution".to_string())
-            }),
-            6 => serde_json::from_slice::<Sample<6>>(&contents).map(|sample| {
-                solve(&sample, multithread, true)
-                    .map(|formula| formula.print_w_named_vars(&sample.var_names))
-                    .unwrap_or("No solution".to_string())
-            }),
-            7 => serde_json::from_slice::<Sample<7>>(&contents).map(|sample| {
-                solve(&sample, multithread, true)
-                    .map(|formula| formula.print_w_named_vars(&sample.var_names))
-                    .unwrap_or("No solution".to_string())
-            }),
-            8 => serde_json::from_slice::<Sample<8>>(&contents).map(|sample| {
-                solve(&sample, multithread, true)
-                    .map(|formula| formula.print_w_named_vars(&sample.var_names))
-                    .unwrap_or("No solution".to_string())
-            }),
-            9 => serde_json::from_slice::<Sample<9>>(&contents).map(|sample| {
-                solve(&sample, multithread, true)
-                    .map(|formula| formula.print_w_named_vars(&sample.var_names))
-                    .unwrap_or("No solution".to_string())
-            }),
-            10 => serde_json::from_slice::<Sample<10>>(&contents).map(|sample| {
-                solve(&sample, multithread, true)
-                    .map(|formula| formula.print_w_named_vars(&sample.var_names))
-                    .unwrap_or("No solution".to_string())
-            }),
-            11 => serde_json::from_slice::<Sample<11>>(&contents).map(|sample| {
-                solve(&sample, multithread, true)
-                    .map(|formula| formula.print_w_named_vars(&sample.var_names))
-                    .unwrap_or("No solution".to_string())
-            }),
-            12 => serde_json::from_slice::<Sample<12>>(&contents).map(|sample| {
-                solve(&sample, multithread, true)
-                    .map(|formula| formula.print_w_named_vars(&sample.var_names))
-                    .unwrap_or("No solution".to_string())
-            }),
-            13 => serde_json::from_slice::<Sample<13>>(&contents).map(|sample| {
-                solve(&sample, multithread, true)
-                    .map(|formula| formula.print_w_named_vars(&sample.var_names))
-                    .unwrap_or("No solution".to_string())
-            }),
-            14 => serde_json::from_slice::<Sample<14>>(&contents).map(|sample| {
-                solve(&sample, multithread, true)
-                    .map(|formula| formula.print_w_named_vars(&sample.var_names))
-                    .unwrap_or("No solution".to_string())
-            }),
-            15 => serde_json::from_slice::<Sample<15>>(&contents).map(|sample| {
-                solve(&sample, multithread, true)
-                    .map(|formula| formula.print_w_named_vars(&sample.var_names))
-                    .unwrap_or("No solution".to_string())
-            }),
-            16 => serde_json::from_slice::<Sample<16>>(&contents).map(|sample| {
-                solve(&sample, multithread, true)
-                    .map(|formula| formula.print_w_named_vars(&sample.var_names))
-                    .unwrap_or("No solution".to_string())
-            }),
-            17 => serde_json::from_slice::<Sample<17>>(&contents).map(|sample| {
-                solve(&sample, multithread, true)
-                    .map(|formula| formula.print_w_named_vars(&sample.var_names))
-                    .unwrap_or("No solution".to_string())
-            }),
-            18 => serde_json::from_slice::<Sample<18>>(&contents).map(|sample| {
-                solve(&sample, multithread, true)
-                    .map(|formula| formula.print_w_named_vars(&sample.var_names))
-                    .unwrap_or("No solution".to_string())
-            }),
-            19 => serde_json::from_slice::<Sample<19>>(&contents).map(|sample| {
-                solve(&sample, multithread, true)
-                    .map(|formula| formula.print_w_named_vars(&sample.var_names))
-                    .unwrap_or("No solution".to_string())
-            }),
-            20 => serde_json::from_slice::<Sample<20>>(&contents).map(|sample| {
-                solve(&sample, multithread, true)
-                    .map(|formula| formula.print_w_named_vars(&sample.var_names))
-                    .unwrap_or("No solution".to_string())
-            }),
-            21 => serde_json::from_slice::<Sample<21>>(&contents).map(|sample| {
-                solve(&sample, multithread, true)
-                    .map(|formula| formula.print_w_named_vars(&sample.var_names))
-                    .unwrap_or("No solution".to_string())
-            }),
-            22 => serde_json::from_slice::<Sample<22>>(&contents).map(|sample| {
-                solve(&sample, multithread, true)
-                    .map(|formula| formula.print_w_named_vars(&sample.var_names))
-                    .unwrap_or("No solution".to_string())
-            }),
-            23 => serde_json::from_slice::<Sample<23>>(&contents).map(|sample| {
-                solve(&sample, multithread, true)
-                    .map(|formula| formula.print_w_named_vars(&sample.var_names))
-                    .unwrap_or("No solution".to_string())
-            }),
-            24 => serde_json::from_slice::<Sample<24>>(&contents).map(|sample| {
-                solve(&sample, multithread, true)
-                    .map(|formula| formula.print_w_named_vars(&sample.var_names))
-                    .unwrap_or("No solution".to_string())
-            }),
-            25 => serde_json::from_slice::<Sample<25>>(&contents).map(|sample| {
-                solve(&sample, multithread, true)
-                    .map(|formula| formula.print_w_named_vars(&sample.var_names))
-                    .unwrap_or("No solution".to_string())
-            }),
-            26 => serde_json::from_slice::<Sample<26>>(&contents).map(|sample| {
-                solve(&sample, multithread, true)
-                    .map(|formula| formula.print_w_named_vars(&sample.var_names))
-                    .unwrap_or("No solution".to_string())
-            }),
-            27 => serde_json::from_slice::<Sample<27>>(&contents).map(|sample| {
-                solve(&sample, multithread, true)
-                    .map(|formula| formula.print_w_named_vars(&sample.var_names))
-                    .unwrap_or("No solution".to_string())
-            }),
-            28 => serde_json::from_slice::<Sample<28>>(&contents).map(|sample| {
-                solve(&sample, multithread, true)
-                    .map(|formula| formula.print_w_named_vars(&sample.var_names))
-                    .unwrap_or("No solution".to_string())
-            }),
-            29 => serde_json::from_slice::<Sample<29>>(&contents).map(|sample| {
-                solve(&sample, multithread, true)
-                    .map(|formula| formula.print_w_named_vars(&sample.var_names))
-                    .unwrap_or("No solution".to_string())
-            }),
-            30 => serde_json::from_slice::<Sample<30>>(&contents).map(|sample| {
-                solve(&sample, multithread, true)
-                    .map(|formula| formula.print_w_named_vars(&sample.var_names))
-                    .unwrap_or("No solution".to_string())
-            }),
-            31 => serde_json::from_slice::<Sample<31>>(&contents).map(|sample| {
-                solve(&sample, multithread, true)
-                    .map(|formula| formula.print_w_named_vars(&sample.var_names))
-                    .unwrap_or("No solution".to_string())
-            }),
-            32 => serde_json::from_slice::<Sample<32>>(&contents).map(|sample| {
-                solve(&sample, multithread, true)
-                    .map(|formula| formula.print_w_named_vars(&sample.var_names))
-                    .unwrap_or("No solution".to_string())
-            }),
-            33 => serde_json::from_slice::<Sample<33>>(&contents).map(|sample| {
-                solve(&sample, multithread, true)
-                    .map(|formula| formula.print_w_named_vars(&sample.var_names))
-                    .unwrap_or("No solution".to_string())
-            }),
-            34 => serde_json::from_slice::<Sample<34>>(&contents).map(|sample| {
-                solve(&sample, multithread, true)
-                    .map(|formula| formula.print_w_named_vars(&sample.var_names))
-                    .unwrap_or("No solution".to_string())
-            }),
-            35 => serde_json::from_slice::<Sample<35>>(&contents).map(|sample| {
-                solve(&sample, multithread, true)
-                    .map(|formula| formula.print_w_named_vars(&sample.var_names))
-                    .unwrap_or("No solution".to_string())
-            }),
-            36 => serde_json::from_slice::<Sample<36>>(&contents).map(|sample| {
-                solve(&sample, multithread, true)
-                    .map(|formula| formula.print_w_named_vars(&sample.var_names))
-                    .unwrap_or("No solution".to_string())
-            }),
-            37 => serde_json::from_slice::<Sample<37>>(&contents).map(|sample| {
-                solve(&sample, multithread, true)
-                    .map(|formula| formula.print_w_named_vars(&sample.var_names))
-                    .unwrap_or("No solution".to_string())
-            }),
-            38 => serde_json::from_slice::<Sample<38>>(&contents).map(|sample| {
-                solve(&sample, multithread, true)
-                    .map(|formula| formula.print_w_named_vars(&sample.var_names))
-                    .unwrap_or("No solution".to_string())
-            }),
-            _ => panic!("out-of-bound parameter"),
+impl Solve {
+    /// Searches a formula consistent with the sample, once it is known to be worth searching.
+    fn solve<const N: usize>(self, sample: Sample<N>) -> Option<String> {
+        let (sample, test) = match self.holdout {
+            Some(holdout) => {
+                let (train, test) = sample.split(1.0 - holdout, &mut rand::thread_rng());
+                (train, Some(test))
+            }
+            None => (sample, None),
+        };
+
+        let mut constraints = Constraints::default();
+        for (formulas, entails) in [(&self.entails, true), (&self.entailed_by, false)] {
+            for formula in formulas {
+                match SyntaxTree::parse_w_named_vars(formula, &sample.var_names) {
+                    Ok(formula) if entails => constraints = constraints.entails(formula),
+                    Ok(formula) => constraints = constraints.entailed_by(formula),
+                    Err(err) => {
+                        println!("Invalid constraint: {err}");
+                        return None;
+                    }
+                }
+            }
+        }
+
+        let sample = if self.all_vars {
+            sample
+        } else {
+            let restricted = sample.restrict_to_relevant();
+            let relevant = restricted.vars();
+            let ignored = sample
+                .vars()
+                .into_iter()
+                .filter(|var| !relevant.contains(var))
+                .map(|var| sample.var_names[var as usize].as_str())
+                .collect::<Vec<_>>();
+            if !ignored.is_empty() {
+                println!("Ignoring irrelevant variables: {}", ignored.join(", "));
+            }
+            restricted
+        };
+
+        let budget = Budget {
+            max_size: self.budget.max_size,
+            time: self.budget.max_duration,
+        };
+        let formula = if let Some(max_error) = self.noise {
+            let formula = NoiseTolerantLearner::new(max_error)
+                .learn(&sample, budget)
+                .into_formula()?;
+            println!("Training accuracy: {:.3}", sample.accuracy(&formula));
+            formula
+        } else if let Some(sketch) = self.sketch {
+            let sketch = match Sketch::parse_w_named_vars(&sketch, &sample.var_names) {
+                Ok(sketch) => sketch,
+                Err(err) => {
+                    println!("Invalid sketch: {err}");
+                    return None;
+                }
+            };
+            SketchLearner::new(sketch)
+                .learn(&sample, budget)
+                .into_formula()?
+        } else if let Some(conjunct_size) = self.mine {
+            ConjunctiveLearner::default()
+                .conjunct_size(conjunct_size)
+                .learn(&sample, budget)
+                .into_formula()?
+                .minimize(&sample)
+        } else if let Some(steps) = self.anneal {
+            let formula = AnnealingLearner::new(steps)
+                .cooling(self.cooling.unwrap_or_default())
+                .costs(self.costs.unwrap_or_default())
+                .learn(&sample, budget)
+                .into_formula()?
+                .minimize(&sample);
+            println!("Training accuracy: {:.3}", sample.accuracy(&formula));
+            formula
+        } else if let Some(flips) = self.walk {
+            let formula = LocalSearchLearner::new(flips)
+                .learn(&sample, budget)
+                .into_formula()?
+                .minimize(&sample);
+            println!("Training accuracy: {:.3}", sample.accuracy(&formula));
+            formula
+        } else if self.positive_only {
+            let mut learner = PositiveOnlyLearner::default();
+            if let Some(max_size) = self.budget.max_size {
+                learner = learner.max_size(max_size);
+            }
+            learner.learn(&sample, budget).into_formula()?
+        } else if let Some(costs) = self.costs {
+            solve_weighted(&sample, &costs, self.budget.max_size)?
+        } else if self.prune {
+            solve_pruned(&sample, self.budget.max_size)?
+        } else if self.distinct {
+            solve_distinct_formulas(&sample, self.budget.max_size)?
+        } else if self.sat {
+            solve_sat(&sample, budget)?
+        } else {
+            let (solution, stats) = if !constraints.is_empty() {
+                solve_constrained(&sample, &constraints, self.multithread, true, self.budget)
+            } else if let Some(chance) = &self.chance {
+                solve_chance_constrained(&sample, chance, self.multithread, true, self.budget)
+            } else if self.canonical {
+                solve_canonical(&sample, self.multithread, true, self.budget)
+            } else if let Some(cache) = self.cache {
+                let cache = Path::new(&cache);
+                match solve_cached(&sample, self.multithread, true, self.budget, cache) {
+                    Ok(result) => result,
+                    Err(err) => {
+                        println!("Search cache error: {err}");
+                        return None;
+                    }
+                }
+            } else if let Some(order) = &self.order {
+                solve_ordered(&sample, order, self.multithread, true, self.budget)
+            } else if self.stats {
+                solve_with_stats(&sample, self.multithread, true, self.budget)
+            } else {
+                solve_with_budget(&sample, self.multithread, true, self.budget)
+            };
+            println!("Search {stats}");
+            if self.stats {
+                for size in &stats.sizes {
+                    println!("Searched {size}");
+                }
+            }
+            solution?
+        };
+        if let Err(err) = sample.check_depth(&formula) {
+            println!("Solution rejected: {err}");
+            return None;
         }
-        .ok()
-    })
+        if let Some(test) = test {
+            let confusion = test.confusion(&formula);
+            println!(
+                "Test accuracy: {:.3} ({} of {} held-out traces misclassified)",
+                confusion.accuracy(),
+                confusion.misclassified(),
+                confusion.total()
+            );
+            for counterexample in test.counterexamples(&formula) {
+                println!("Misclassified: {counterexample}");
+            }
+        }
+        if self.past {
+            return Some(PastFormula(formula).print_w_named_vars(&sample.var_names));
+        }
+        Some(match self.notation {
+            Some(notation) => formula
+                .format(notation)
+                .names(&sample.var_names)
+                .to_string(),
+            None => formula.print_w_named_vars(&sample.var_names),
+        })
+    }
+
+    /// Searches a formula consistent with the sample and with those given by `--with` at once.
+    fn solve_common<const N: usize>(self, sample: Sample<N>) -> io::Result<Option<String>> {
+        let mut samples = vec![sample];
+        for path in &self.with {
+            let mut other = match Sample::<N>::load(Path::new(path)) {
+                Ok(other) => other,
+                Err(err) => {
+                    println!("Cannot load {path}: {err}");
+                    return Ok(None);
+                }
+            };
+            if let Some(lengths) = self.lengths {
+                other.length_policy = lengths;
+            }
+            let other = other.apply_length_policy();
+            samples.push(if self.past { other.reversed() } else { other });
+        }
+
+        // The samples together, to check their consistency and find their relevant variables.
+        let mut merged = samples[0].clone();
+        for other in &samples[1..] {
+            merged
+                .merge(other)
+                .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err.to_string()))?;
+        }
+        check_consistency(&merged)?;
+        if !self.all_vars {
+            let var_names = merged.restrict_to_relevant().var_names;
+            let ignored = merged
+                .var_names
+                .iter()
+                .zip(&var_names)
+                .filter(|(name, restricted)| name != restricted)
+                .map(|(name, _)| name.as_str())
+                .collect::<Vec<_>>();
+            if !ignored.is_empty() {
+                println!("Ignoring irrelevant variables: {}", ignored.join(", "));
+            }
+            for sample in &mut samples {
+                sample.var_names = var_names.clone();
+            }
+        }
+
+        let (solution, stats) = match learn_common(&samples, self.multithread, true, self.budget) {
+            Ok(result) => result,
+            Err(err) => {
+                println!("Cannot learn from these samples: {err}");
+                return Ok(None);
+            }
+        };
+        println!("Search {stats}");
+        let Some(formula) = solution else {
+            return Ok(None);
+        };
+        for sample in &samples {
+            if let Err(err) = sample.check_depth(&formula) {
+                println!("Solution rejected: {err}");
+                return Ok(None);
+            }
+        }
+        let names = std::iter::once("the sample").chain(self.with.iter().map(String::as_str));
+        for (name, sample) in names.zip(&samples) {
+            println!("Accuracy on {name}: {:.3}", sample.accuracy(&formula));
+        }
+        let var_names = &samples[0].var_names;
+        if self.past {
+            return Ok(Some(PastFormula(formula).print_w_named_vars(var_names)));
+        }
+        Ok(Some(match self.notation {
+            Some(notation) => formula.format(notation).names(var_names).to_string(),
+            None => formula.print_w_named_vars(var_names),
+        }))
+    }
+}
+
+/// Searches a formula through a SAT encoding (see `SatLearner`).
+#[cfg(feature = "sat")]
+fn solve_sat<const N: usize>(sample: &Sample<N>, budget: Budget) -> Option<SyntaxTree> {
+    SatLearner.learn(sample, budget).into_formula()
+}
+
+#[cfg(not(feature = "sat"))]
+fn solve_sat<const N: usize>(_sample: &Sample<N>, _budget: Budget) -> Option<SyntaxTree> {
+    println!("--sat requires building the solver with the `sat` feature");
+    None
+}
+
+/// Searches formulas pairwise non-equivalent on finite traces (see `solve_distinct`).
+#[cfg(feature = "equivalence")]
+fn solve_distinct_formulas<const N: usize>(
+    sample: &Sample<N>,
+    max_size: Option<usize>,
+) -> Option<SyntaxTree> {
+    solve_distinct(sample, max_size)
+}
+
+#[cfg(not(feature = "equivalence"))]
+fn solve_distinct_formulas<const N: usize>(
+    _sample: &Sample<N>,
+    _max_size: Option<usize>,
+) -> Option<SyntaxTree> {
+    println!("--distinct requires building the solver with the `equivalence` feature");
+    None
 }
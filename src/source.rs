@@ -0,0 +1,236 @@
+use crate::stream::*;
+use crate::syntax::*;
+use crate::trace::*;
+use std::borrow::Cow;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashSet;
+use std::hash::{Hash, Hasher};
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// A source of positive and negative traces, independent of how they are stored.
+/// Learning algorithms (e.g., [`solve`](crate::solve)) only access samples through this trait,
+/// so they can run on in-memory [`Sample`]s as well as on samples streamed from disk ([`TraceFile`]).
+pub trait SampleSource<const N: usize> {
+    /// Iterator over the traces of the source.
+    /// Traces are borrowed when kept in memory, and owned when read on demand.
+    type Traces<'a>: Iterator<Item = Cow<'a, Trace<N>>>
+    where
+        Self: 'a;
+
+    /// Iterates over the positive traces.
+    fn iter_positive(&self) -> Self::Traces<'_>;
+
+    /// Iterates over the negative traces.
+    fn iter_negative(&self) -> Self::Traces<'_>;
+
+    /// The number of positive traces.
+    fn positive_count(&self) -> usize;
+
+    /// The number of negative traces.
+    fn negative_count(&self) -> usize;
+
+    /// The positive lasso traces, which only in-memory samples have.
+    fn positive_lassos(&self) -> Cow<'_, [Lasso<N>]> {
+        Cow::Borrowed(&[])
+    }
+
+    /// The negative lasso traces, which only in-memory samples have.
+    fn negative_lassos(&self) -> Cow<'_, [Lasso<N>]> {
+        Cow::Borrowed(&[])
+    }
+
+    /// The propositional variables that formulae are allowed to use.
+    fn vars(&self) -> Vec<Idx> {
+        (0..N as Idx).collect()
+    }
+
+    /// Whether no positive trace is identical to a negative trace over the allowed variables.
+    fn is_solvable(&self) -> bool {
+        let vars = SampleSource::vars(self);
+        self.iter_positive().all(|pos_trace| {
+            self.iter_negative().all(|neg_trace| {
+                pos_trace.len() != neg_trace.len()
+                    || pos_trace
+                        .iter()
+                        .zip(neg_trace.iter())
+                        .any(|(pos, neg)| vars.iter().any(|n| pos[*n as usize] != neg[*n as usize]))
+            })
+        })
+    }
+
+    /// Whether the formula is satisfied by all positive traces and by no negative trace.
+    fn is_consistent(&self, formula: &SyntaxTree) -> bool {
+        self.iter_positive().all(|trace| formula.eval(&trace))
+            && self.iter_negative().all(|trace| !formula.eval(&trace))
+    }
+}
+
+type BorrowedTraces<'a, const N: usize> =
+    std::iter::Map<std::slice::Iter<'a, Trace<N>>, fn(&'a Trace<N>) -> Cow<'a, Trace<N>>>;
+
+impl<const N: usize> SampleSource<N> for Sample<N> {
+    type Traces<'a> = BorrowedTraces<'a, N>;
+
+    fn iter_positive(&self) -> Self::Traces<'_> {
+        self.positive_traces.iter().map(Cow::Borrowed)
+    }
+
+    fn iter_negative(&self) -> Self::Traces<'_> {
+        self.negative_traces.iter().map(Cow::Borrowed)
+    }
+
+    fn positive_count(&self) -> usize {
+        self.positive_traces.len()
+    }
+
+    fn negative_count(&self) -> usize {
+        self.negative_traces.len()
+    }
+
+    fn positive_lassos(&self) -> Cow<'_, [Lasso<N>]> {
+        Cow::Borrowed(&self.positive_lassos)
+    }
+
+    fn negative_lassos(&self) -> Cow<'_, [Lasso<N>]> {
+        Cow::Borrowed(&self.negative_lassos)
+    }
+
+    fn vars(&self) -> Vec<Idx> {
+        Sample::vars(self)
+    }
+
+    // In-memory samples can also contain lasso traces.
+    fn is_solvable(&self) -> bool {
+        Sample::is_solvable(self)
+    }
+
+    fn is_consistent(&self, formula: &SyntaxTree) -> bool {
+        Sample::is_consistent(self, formula)
+    }
+}
+
+/// A sample streamed from a JSON Lines file (see [`SampleReader`]) every time its traces are iterated over,
+/// so that it never has to be loaded in memory as a whole.
+/// The file is validated when opened, and is assumed not to change afterwards.
+///
+/// # Panics
+///
+/// Iterating over the traces (see [`SampleSource`]) panics if the file cannot be read anymore,
+/// or no longer holds valid traces, since it changed after it was opened.
+#[derive(Debug, Clone)]
+pub struct TraceFile<const N: usize> {
+    path: PathBuf,
+    positive_count: usize,
+    negative_count: usize,
+}
+
+impl<const N: usize> TraceFile<N> {
+    /// Opens a JSON Lines sample file, reading it once to validate and count its traces.
+    pub fn open(path: &Path) -> io::Result<Self> {
+        let mut positive_count = 0;
+        let mut negative_count = 0;
+        for labelled in SampleReader::<N, _>::open(path)? {
+            match labelled?.label {
+                Label::Positive => positive_count += 1,
+                Label::Negative => negative_count += 1,
+            }
+        }
+        Ok(TraceFile {
+            path: path.to_path_buf(),
+            positive_count,
+            negative_count,
+        })
+    }
+
+    fn iter_label(&self, label: Label) -> impl Iterator<Item = Cow<'_, Trace<N>>> {
+        SampleReader::<N, _>::open(&self.path)
+            .expect("sample file cannot be read anymore")
+            .map(|labelled| labelled.expect("sample file changed since it was opened"))
+            .filter(move |labelled| labelled.label == label)
+            .map(|labelled| Cow::Owned(labelled.trace))
+    }
+}
+
+impl<const N: usize> SampleSource<N> for TraceFile<N> {
+    type Traces<'a> = Box<dyn Iterator<Item = Cow<'a, Trace<N>>> + 'a>;
+
+    fn iter_positive(&self) -> Self::Traces<'_> {
+        Box::new(self.iter_label(Label::Positive))
+    }
+
+    fn iter_negative(&self) -> Self::Traces<'_> {
+        Box::new(self.iter_label(Label::Negative))
+    }
+
+    fn positive_count(&self) -> usize {
+        self.positive_count
+    }
+
+    fn negative_count(&self) -> usize {
+        self.negative_count
+    }
+
+    // Only hashes of the negative traces are kept in memory, rather than comparing every pair of traces,
+    // which would read the file once per positive trace.
+    fn is_solvable(&self) -> bool {
+        let hash = |trace: &Trace<N>| {
+            let mut hasher = DefaultHasher::new();
+            trace.hash(&mut hasher);
+            hasher.finish()
+        };
+        let negatives = self
+            .iter_negative()
+            .map(|trace| hash(&trace))
+            .collect::<HashSet<_>>();
+        self.iter_positive().all(|pos_trace| {
+            // Hashes may collide, so a match is only a contradiction if the traces are the same.
+            !negatives.contains(&hash(&pos_trace))
+                || self.iter_negative().all(|neg_trace| neg_trace != pos_trace)
+        })
+    }
+}
+
+#[cfg(test)]
+mod sources {
+    use super::*;
+    use std::fs;
+    use std::sync::Arc;
+
+    #[test]
+    fn trace_file() {
+        let sample = Sample::<2> {
+            positive_traces: vec![vec![[true, false], [false, true]]],
+            negative_traces: vec![vec![[true, false], [true, false]]],
+            ..Sample::default()
+        };
+        let path =
+            std::env::temp_dir().join(format!("learn_ltl_source_{}.jsonl", std::process::id()));
+        sample
+            .write_lines(fs::File::create(&path).expect("create file"))
+            .expect("write sample");
+
+        let source = TraceFile::<2>::open(&path).expect("open sample");
+        assert_eq!(source.positive_count(), 1);
+        assert_eq!(source.negative_count(), 1);
+        assert!(SampleSource::is_solvable(&source));
+
+        // F x1
+        let formula = SyntaxTree::Finally(Arc::new(SyntaxTree::Atom(1)));
+        assert!(SampleSource::is_consistent(&source, &formula));
+        assert!(SampleSource::is_consistent(&sample, &formula));
+
+        // The same trace is both positive and negative.
+        let unsolvable = Sample::<2> {
+            negative_traces: vec![vec![[false, false]], sample.positive_traces[0].clone()],
+            ..sample.clone()
+        };
+        unsolvable
+            .write_lines(fs::File::create(&path).expect("create file"))
+            .expect("write sample");
+        let unsolvable = TraceFile::<2>::open(&path).expect("open sample");
+        assert!(!SampleSource::is_solvable(&unsolvable));
+
+        fs::remove_file(&path).expect("remove file");
+    }
+}
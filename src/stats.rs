@@ -0,0 +1,153 @@
+use crate::trace::*;
+use std::fmt;
+
+/// Summary statistics of a [`Sample`], to diagnose learning issues
+/// (e.g., imbalanced classes, or variables that never change value).
+#[derive(Debug, Clone, PartialEq)]
+pub struct SampleStats {
+    pub var_names: Vec<String>,
+    pub positive_traces: usize,
+    pub negative_traces: usize,
+    pub positive_lassos: usize,
+    pub negative_lassos: usize,
+    /// Length of the shortest (finite) trace.
+    pub min_length: usize,
+    /// Mean length of the (finite) traces.
+    pub mean_length: f64,
+    /// Length of the longest (finite) trace.
+    pub max_length: usize,
+    /// For each variable, the fraction of states (over all traces and lassos) in which it is true.
+    pub atom_frequencies: Vec<f64>,
+}
+
+impl SampleStats {
+    /// The fraction of examples that are positive (`0.0` on an empty sample).
+    pub fn positive_ratio(&self) -> f64 {
+        let positives = self.positive_traces + self.positive_lassos;
+        let total = positives + self.negative_traces + self.negative_lassos;
+        if total == 0 {
+            0.0
+        } else {
+            positives as f64 / total as f64
+        }
+    }
+
+    /// The variables whose value never changes across the sample, which cannot help separating the classes.
+    pub fn constant_vars(&self) -> Vec<usize> {
+        self.atom_frequencies
+            .iter()
+            .enumerate()
+            .filter(|(_, freq)| **freq == 0.0 || **freq == 1.0)
+            .map(|(var, _)| var)
+            .collect()
+    }
+}
+
+impl fmt::Display for SampleStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "Traces: {} positive, {} negative",
+            self.positive_traces, self.negative_traces
+        )?;
+        if self.positive_lassos + self.negative_lassos > 0 {
+            writeln!(
+                f,
+                "Lassos: {} positive, {} negative",
+                self.positive_lassos, self.negative_lassos
+            )?;
+        }
+        writeln!(f, "Positive ratio: {:.3}", self.positive_ratio())?;
+        writeln!(
+            f,
+            "Trace length: min {}, mean {:.1}, max {}",
+            self.min_length, self.mean_length, self.max_length
+        )?;
+        writeln!(f, "Variable frequencies:")?;
+        for (name, freq) in self.var_names.iter().zip(self.atom_frequencies.iter()) {
+            let note = if *freq == 0.0 {
+                " (never true)"
+            } else if *freq == 1.0 {
+                " (always true)"
+            } else {
+                ""
+            };
+            writeln!(f, "  {name}: {freq:.3}{note}")?;
+        }
+        Ok(())
+    }
+}
+
+impl<const N: usize> Sample<N> {
+    /// Computes summary statistics of the sample.
+    pub fn stats(&self) -> SampleStats {
+        let traces = self
+            .positive_traces
+            .iter()
+            .chain(self.negative_traces.iter());
+        let lengths = traces.clone().map(|trace| trace.len()).collect::<Vec<_>>();
+        let states = traces.flatten().chain(
+            self.positive_lassos
+                .iter()
+                .chain(self.negative_lassos.iter())
+                .flat_map(|lasso| lasso.prefix.iter().chain(lasso.cycle.iter())),
+        );
+
+        let mut true_counts = [0usize; N];
+        let mut state_count = 0;
+        for state in states {
+            state_count += 1;
+            for (count, val) in true_counts.iter_mut().zip(state.iter()) {
+                *count += *val as usize;
+            }
+        }
+
+        SampleStats {
+            var_names: self.var_names.to_vec(),
+            positive_traces: self.positive_traces.len(),
+            negative_traces: self.negative_traces.len(),
+            positive_lassos: self.positive_lassos.len(),
+            negative_lassos: self.negative_lassos.len(),
+            min_length: lengths.iter().copied().min().unwrap_or(0),
+            mean_length: if lengths.is_empty() {
+                0.0
+            } else {
+                lengths.iter().sum::<usize>() as f64 / lengths.len() as f64
+            },
+            max_length: lengths.iter().copied().max().unwrap_or(0),
+            atom_frequencies: true_counts
+                .iter()
+                .map(|count| {
+                    if state_count == 0 {
+                        0.0
+                    } else {
+                        *count as f64 / state_count as f64
+                    }
+                })
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod statistics {
+    use super::*;
+
+    #[test]
+    fn stats() {
+        let sample = Sample::<2> {
+            positive_traces: vec![vec![[true, false], [true, true]]],
+            negative_traces: vec![vec![[true, false]; 4], vec![[true, false]; 3]],
+            ..Sample::default()
+        };
+
+        let stats = sample.stats();
+        assert_eq!(stats.min_length, 2);
+        assert_eq!(stats.max_length, 4);
+        assert_eq!(stats.mean_length, 3.0);
+        assert_eq!(stats.atom_frequencies, vec![1.0, 1.0 / 9.0]);
+        assert_eq!(stats.constant_vars(), vec![0]);
+        assert!((stats.positive_ratio() - 1.0 / 3.0).abs() < 1e-9);
+        assert!(stats.to_string().contains("x0: 1.000 (always true)"));
+    }
+}
@@ -0,0 +1,34 @@
+use learn_ltl::*;
+
+use std::path::Path;
+
+use clap::Parser;
+
+/// Print statistics of the given sample, such as trace counts and lengths, and variable frequencies.
+/// Supported file types: ron, json, trace, binary.
+#[derive(Parser, Debug)]
+#[clap(name = "stats")]
+struct Stats {
+    /// The sample to analyze
+    sample: String,
+}
+
+/// Computes the statistics of a sample with any number of variables.
+struct ComputeStats;
+
+impl SampleVisitor for ComputeStats {
+    type Output = SampleStats;
+
+    fn visit<const N: usize>(self, sample: Sample<N>) -> Self::Output {
+        sample.stats()
+    }
+}
+
+fn main() -> std::io::Result<()> {
+    let stats = Stats::parse();
+
+    let stats = visit_sample(Path::new(&stats.sample), ComputeStats)?;
+    print!("{stats}");
+
+    Ok(())
+}
@@ -0,0 +1,179 @@
+use crate::syntax::*;
+use crate::trace::*;
+use serde::{Deserialize, Serialize};
+use serde_with::*;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Write};
+use std::marker::PhantomData;
+use std::path::Path;
+
+/// A single labelled trace, as stored on a line of a JSON Lines sample file.
+#[serde_as]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LabelledTrace<const N: usize> {
+    pub label: Label,
+    #[serde_as(as = "Vec<[_; N]>")]
+    pub trace: Trace<N>,
+}
+
+/// Streams the traces of a sample from a JSON Lines file (`jsonl` extension),
+/// where each line holds a single labelled trace, e.g.,
+/// `{"label":"Positive","trace":[[true,false],[false,true]]}`.
+/// Traces are read one at a time, so that huge samples can be processed without loading them in memory.
+/// Empty lines are skipped.
+pub struct SampleReader<const N: usize, R: BufRead> {
+    reader: R,
+    line: String,
+    line_number: usize,
+    _trace: PhantomData<Trace<N>>,
+}
+
+impl<const N: usize> SampleReader<N, BufReader<File>> {
+    /// Opens a JSON Lines sample file.
+    pub fn open(path: &Path) -> io::Result<Self> {
+        Ok(SampleReader::new(BufReader::new(File::open(path)?)))
+    }
+}
+
+impl<const N: usize, R: BufRead> SampleReader<N, R> {
+    pub fn new(reader: R) -> Self {
+        SampleReader {
+            reader,
+            line: String::new(),
+            line_number: 0,
+            _trace: PhantomData,
+        }
+    }
+
+    /// Counts, for each formula, how many positive and how many negative traces satisfy it,
+    /// reading the sample a single time.
+    pub fn evaluate(self, formulas: &[SyntaxTree]) -> io::Result<Vec<(usize, usize)>> {
+        let mut counts = vec![(0, 0); formulas.len()];
+        for labelled in self {
+            let LabelledTrace { label, trace } = labelled?;
+            for (formula, (positive, negative)) in formulas.iter().zip(counts.iter_mut()) {
+                if formula.eval(&trace) {
+                    match label {
+                        Label::Positive => *positive += 1,
+                        Label::Negative => *negative += 1,
+                    }
+                }
+            }
+        }
+        Ok(counts)
+    }
+
+    /// Reads all the remaining traces into an in-memory sample.
+    pub fn into_sample(self) -> io::Result<Sample<N>> {
+        let mut sample = Sample::default();
+        for labelled in self {
+            let LabelledTrace { label, trace } = labelled?;
+            match label {
+                Label::Positive => sample.positive_traces.push(trace),
+                Label::Negative => sample.negative_traces.push(trace),
+            }
+        }
+        Ok(sample)
+    }
+}
+
+impl<const N: usize, R: BufRead> Iterator for SampleReader<N, R> {
+    type Item = io::Result<LabelledTrace<N>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            self.line.clear();
+            match self.reader.read_line(&mut self.line) {
+                Ok(0) => return None,
+                Ok(_) => self.line_number += 1,
+                Err(err) => return Some(Err(err)),
+            }
+            if !self.line.trim().is_empty() {
+                break;
+            }
+        }
+
+        Some(serde_json::from_str(&self.line).map_err(|err| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("line {}: {err}", self.line_number),
+            )
+        }))
+    }
+}
+
+impl<const N: usize> Sample<N> {
+    /// Writes the (finite) traces of the sample in the JSON Lines format read by [`SampleReader`].
+    /// Variable names, lasso traces and metadata are not part of the format, and are not written.
+    pub fn write_lines<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        let labelled = self
+            .positive_traces
+            .iter()
+            .map(|trace| (Label::Positive, trace))
+            .chain(
+                self.negative_traces
+                    .iter()
+                    .map(|trace| (Label::Negative, trace)),
+            );
+        for (label, trace) in labelled {
+            let line = serde_json::to_string(&LabelledTrace {
+                label,
+                trace: trace.clone(),
+            })?;
+            writeln!(writer, "{line}")?;
+        }
+        writer.flush()
+    }
+}
+
+#[cfg(test)]
+mod streaming {
+    use super::*;
+    use std::sync::Arc;
+
+    fn sample() -> Sample<2> {
+        Sample {
+            positive_traces: vec![vec![[true, false], [false, true]]],
+            negative_traces: vec![vec![[false, false]], vec![[true, true]]],
+            ..Sample::default()
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut contents = Vec::new();
+        sample().write_lines(&mut contents).expect("write sample");
+
+        let read = SampleReader::<2, _>::new(contents.as_slice())
+            .into_sample()
+            .expect("read sample");
+        assert_eq!(read.positive_traces, sample().positive_traces);
+        assert_eq!(read.negative_traces, sample().negative_traces);
+    }
+
+    #[test]
+    fn evaluate() {
+        let mut contents = Vec::new();
+        sample().write_lines(&mut contents).expect("write sample");
+
+        let formulas = [
+            SyntaxTree::Atom(0),
+            SyntaxTree::Finally(Arc::new(SyntaxTree::Atom(1))),
+        ];
+        let counts = SampleReader::<2, _>::new(contents.as_slice())
+            .evaluate(&formulas)
+            .expect("evaluate");
+        assert_eq!(counts, vec![(1, 1), (1, 1)]);
+    }
+
+    #[test]
+    fn malformed_line() {
+        let contents =
+            b"{\"label\":\"Positive\",\"trace\":[[true,false]]}\n\n{\"label\":\"Positive\"}\n";
+        let mut reader = SampleReader::<2, _>::new(&contents[..]);
+        assert!(reader.next().expect("first trace").is_ok());
+        let err = reader.next().expect("second trace").unwrap_err();
+        assert!(err.to_string().starts_with("line 3"));
+        assert!(reader.next().is_none());
+    }
+}
@@ -1,5 +1,6 @@
-use serde::Deserialize;
-use std::{fmt, sync::Arc};
+use crate::trace::{AtomValuation, Lasso};
+use serde::{Deserialize, Serialize};
+use std::{fmt, ops, sync::Arc};
 
 /// The type representing time instants.
 pub type Time = u8;
@@ -10,7 +11,7 @@ pub type Idx = u8;
 /// A formula represented via its syntax tree.
 /// This is a recursive data structure, so it requires the use of smart pointers.
 /// We use `Arc` to make it compatible with parallel computations.
-#[derive(Debug, Clone, PartialOrd, Ord, PartialEq, Eq, Deserialize)]
+#[derive(Debug, Clone, PartialOrd, Ord, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum SyntaxTree {
     Atom(Idx),
     Not(Arc<SyntaxTree>),
@@ -23,6 +24,93 @@ pub enum SyntaxTree {
     Until(Arc<SyntaxTree>, Arc<SyntaxTree>),
 }
 
+/// The verdict of evaluating a formula on a finite prefix of a trace.
+/// A verdict is conclusive if it holds for every possible continuation of the prefix
+/// (including the prefix itself, as finite traces can end at any time),
+/// and inconclusive otherwise.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Verdict {
+    True,
+    False,
+    Unknown,
+}
+
+impl Verdict {
+    /// Kleene (strong) conjunction.
+    pub fn and(self, other: Verdict) -> Verdict {
+        match (self, other) {
+            (Verdict::False, _) | (_, Verdict::False) => Verdict::False,
+            (Verdict::True, Verdict::True) => Verdict::True,
+            _ => Verdict::Unknown,
+        }
+    }
+
+    /// Kleene (strong) disjunction.
+    pub fn or(self, other: Verdict) -> Verdict {
+        match (self, other) {
+            (Verdict::True, _) | (_, Verdict::True) => Verdict::True,
+            (Verdict::False, Verdict::False) => Verdict::False,
+            _ => Verdict::Unknown,
+        }
+    }
+
+    pub fn is_conclusive(self) -> bool {
+        self != Verdict::Unknown
+    }
+}
+
+impl ops::Not for Verdict {
+    type Output = Verdict;
+
+    fn not(self) -> Self::Output {
+        match self {
+            Verdict::True => Verdict::False,
+            Verdict::False => Verdict::True,
+            Verdict::Unknown => Verdict::Unknown,
+        }
+    }
+}
+
+impl From<bool> for Verdict {
+    fn from(value: bool) -> Self {
+        if value {
+            Verdict::True
+        } else {
+            Verdict::False
+        }
+    }
+}
+
+/// Negation of a formula, simplifying it locally.
+impl ops::Not for SyntaxTree {
+    type Output = SyntaxTree;
+
+    fn not(self) -> Self::Output {
+        match self {
+            // ¬¬φ ≡ φ
+            SyntaxTree::Not(child) => unwrap_arc(child),
+            _ => SyntaxTree::Not(Arc::new(self)),
+        }
+    }
+}
+
+/// Takes the formula out of the `Arc` without cloning it, if it is not shared.
+fn unwrap_arc(formula: Arc<SyntaxTree>) -> SyntaxTree {
+    Arc::try_unwrap(formula).unwrap_or_else(|formula| formula.as_ref().clone())
+}
+
+/// Sorts the operands of a commutative operator.
+fn canonical_order(
+    left: Arc<SyntaxTree>,
+    right: Arc<SyntaxTree>,
+) -> (Arc<SyntaxTree>, Arc<SyntaxTree>) {
+    if left <= right {
+        (left, right)
+    } else {
+        (right, left)
+    }
+}
+
 impl fmt::Display for SyntaxTree {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
@@ -47,42 +135,148 @@ impl fmt::Display for SyntaxTree {
     }
 }
 
+/// A formula displayed with custom names for its propositional variables,
+/// such as the `var_names` of a [`Sample`](crate::Sample).
+/// Variables without a name are displayed with their default name.
+#[derive(Debug, Clone, Copy)]
+pub struct NamedSyntaxTree<'a> {
+    formula: &'a SyntaxTree,
+    names: &'a [String],
+}
+
+impl<'a> fmt::Display for NamedSyntaxTree<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let names = self.names;
+        match self.formula {
+            SyntaxTree::Atom(var) => match names.get(*var as usize) {
+                Some(name) => write!(f, "{}", name),
+                None => write!(f, "x{}", var),
+            },
+            SyntaxTree::Not(branch) => write!(f, "¬({})", branch.named(names)),
+            SyntaxTree::Next(branch) => write!(f, "X({})", branch.named(names)),
+            SyntaxTree::Globally(branch) => write!(f, "G({})", branch.named(names)),
+            SyntaxTree::Finally(branch) => write!(f, "F({})", branch.named(names)),
+            SyntaxTree::And(left_branch, right_branch) => write!(
+                f,
+                "({})∧({})",
+                left_branch.named(names),
+                right_branch.named(names)
+            ),
+            SyntaxTree::Or(left_branch, right_branch) => write!(
+                f,
+                "({})∨({})",
+                left_branch.named(names),
+                right_branch.named(names)
+            ),
+            SyntaxTree::Implies(left_branch, right_branch) => write!(
+                f,
+                "({})→({})",
+                left_branch.named(names),
+                right_branch.named(names)
+            ),
+            SyntaxTree::Until(left_branch, right_branch) => write!(
+                f,
+                "({})U({})",
+                left_branch.named(names),
+                right_branch.named(names)
+            ),
+        }
+    }
+}
+
 impl SyntaxTree {
+    /// Displays the formula using the given names for its propositional variables.
+    pub fn named<'a>(&'a self, names: &'a [String]) -> NamedSyntaxTree<'a> {
+        NamedSyntaxTree {
+            formula: self,
+            names,
+        }
+    }
+
     pub fn print_w_named_vars(&self, vars: &[String]) -> String {
-        match self {
-            SyntaxTree::Atom(var) => vars[*var as usize].clone(),
-            SyntaxTree::Not(branch) => format!("¬({})", branch.print_w_named_vars(vars)),
-            SyntaxTree::Next(branch) => format!("X({})", branch.print_w_named_vars(vars)),
-            SyntaxTree::Globally(branch) => format!("G({})", branch.print_w_named_vars(vars)),
-            SyntaxTree::Finally(branch) => format!("F({})", branch.print_w_named_vars(vars)),
-            SyntaxTree::And(left_branch, right_branch) => {
-                format!(
-                    "({})∧({})",
-                    left_branch.print_w_named_vars(vars),
-                    right_branch.print_w_named_vars(vars)
-                )
+        self.named(vars).to_string()
+    }
+
+    /// Builds the conjunction of two formulae, simplifying it locally.
+    /// Operands are sorted in canonical order, so `f.and(g)` and `g.and(f)` build the same formula.
+    pub fn and(self, other: impl Into<Arc<SyntaxTree>>) -> SyntaxTree {
+        let (left, right) = canonical_order(Arc::new(self), other.into());
+        match (left.as_ref(), right.as_ref()) {
+            // φ ∧ φ ≡ φ
+            _ if left == right => unwrap_arc(left),
+            // φ ∧ (φ ∨ ψ) ≡ φ
+            (child, SyntaxTree::Or(c_0, c_1)) if c_0.as_ref() == child || c_1.as_ref() == child => {
+                unwrap_arc(left)
             }
-            SyntaxTree::Or(left_branch, right_branch) => {
-                format!(
-                    "({})∨({})",
-                    left_branch.print_w_named_vars(vars),
-                    right_branch.print_w_named_vars(vars)
-                )
+            (SyntaxTree::Or(c_0, c_1), child) if c_0.as_ref() == child || c_1.as_ref() == child => {
+                unwrap_arc(right)
             }
-            SyntaxTree::Implies(left_branch, right_branch) => {
-                format!(
-                    "({})→({})",
-                    left_branch.print_w_named_vars(vars),
-                    right_branch.print_w_named_vars(vars)
-                )
+            _ => SyntaxTree::And(left, right),
+        }
+    }
+
+    /// Builds the disjunction of two formulae, simplifying it locally.
+    /// Operands are sorted in canonical order, so `f.or(g)` and `g.or(f)` build the same formula.
+    pub fn or(self, other: impl Into<Arc<SyntaxTree>>) -> SyntaxTree {
+        let (left, right) = canonical_order(Arc::new(self), other.into());
+        match (left.as_ref(), right.as_ref()) {
+            // φ ∨ φ ≡ φ
+            _ if left == right => unwrap_arc(left),
+            // φ ∨ (φ ∧ ψ) ≡ φ
+            (child, SyntaxTree::And(c_0, c_1))
+                if c_0.as_ref() == child || c_1.as_ref() == child =>
+            {
+                unwrap_arc(left)
             }
-            SyntaxTree::Until(left_branch, right_branch) => {
-                format!(
-                    "({})U({})",
-                    left_branch.print_w_named_vars(vars),
-                    right_branch.print_w_named_vars(vars)
-                )
+            (SyntaxTree::And(c_0, c_1), child)
+                if c_0.as_ref() == child || c_1.as_ref() == child =>
+            {
+                unwrap_arc(right)
             }
+            _ => SyntaxTree::Or(left, right),
+        }
+    }
+
+    /// Builds the implication between two formulae, simplifying it locally.
+    pub fn implies(self, other: impl Into<Arc<SyntaxTree>>) -> SyntaxTree {
+        match self {
+            // ¬φ -> ψ ≡ φ ∨ ψ
+            SyntaxTree::Not(child) => unwrap_arc(child).or(other),
+            _ => SyntaxTree::Implies(Arc::new(self), other.into()),
+        }
+    }
+
+    /// Builds the until of two formulae, simplifying it locally.
+    pub fn until(self, other: impl Into<Arc<SyntaxTree>>) -> SyntaxTree {
+        let other = other.into();
+        // φ U φ ≡ φ
+        if &self == other.as_ref() {
+            self
+        } else {
+            SyntaxTree::Until(Arc::new(self), other)
+        }
+    }
+
+    /// Builds the next of a formula.
+    pub fn next(self) -> SyntaxTree {
+        SyntaxTree::Next(Arc::new(self))
+    }
+
+    /// Builds the globally of a formula, simplifying it locally.
+    pub fn globally(self) -> SyntaxTree {
+        match self {
+            // G G φ ≡ G φ
+            SyntaxTree::Globally(_) => self,
+            _ => SyntaxTree::Globally(Arc::new(self)),
+        }
+    }
+
+    /// Builds the finally of a formula, simplifying it locally.
+    pub fn finally(self) -> SyntaxTree {
+        match self {
+            // F F φ ≡ F φ
+            SyntaxTree::Finally(_) => self,
+            _ => SyntaxTree::Finally(Arc::new(self)),
         }
     }
 
@@ -104,17 +298,17 @@ impl SyntaxTree {
         }
     }
 
-    /// Evaluate a formula on a trace.
-    pub fn eval<const N: usize>(&self, trace: &[[bool; N]]) -> bool {
+    /// Evaluate a formula on a trace, whose states can be of any type implementing [`AtomValuation`].
+    pub fn eval<S: AtomValuation>(&self, trace: &[S]) -> bool {
         self.eval_at_time(trace, 0)
     }
 
     /// Evaluate a formula on a trace.
-    pub fn eval_at_time<const N: usize>(&self, trace: &[[bool; N]], time: usize) -> bool {
+    pub fn eval_at_time<S: AtomValuation>(&self, trace: &[S], time: usize) -> bool {
         assert!(time < trace.len());
 
         match self {
-            SyntaxTree::Atom(var) => trace[time][*var as usize],
+            SyntaxTree::Atom(var) => trace[time].holds(*var),
             SyntaxTree::Not(branch) => !branch.eval_at_time(trace, time),
             SyntaxTree::Next(branch) => {
                 time + 1 < trace.len() && branch.eval_at_time(trace, time + 1)
@@ -148,6 +342,127 @@ impl SyntaxTree {
             }
         }
     }
+
+    /// Evaluate a formula on a lasso trace, using infinite-word semantics.
+    pub fn eval_lasso<const N: usize>(&self, lasso: &Lasso<N>) -> bool {
+        self.eval_lasso_positions(lasso)[0]
+    }
+
+    /// Computes the truth value of the formula at every position of the lasso.
+    /// Since the lasso has finitely many distinct positions, temporal operators are computed as fixpoints over them.
+    fn eval_lasso_positions<const N: usize>(&self, lasso: &Lasso<N>) -> Vec<bool> {
+        let len = lasso.len();
+        // Positions reachable from `pos` are those from `pos` onward, plus the whole cycle.
+        let reachable = |pos: usize| pos.min(lasso.prefix.len())..len;
+
+        match self {
+            SyntaxTree::Atom(var) => (0..len).map(|pos| lasso.state(pos).holds(*var)).collect(),
+            SyntaxTree::Not(branch) => branch
+                .eval_lasso_positions(lasso)
+                .into_iter()
+                .map(|val| !val)
+                .collect(),
+            SyntaxTree::Next(branch) => {
+                let vals = branch.eval_lasso_positions(lasso);
+                (0..len).map(|pos| vals[lasso.successor(pos)]).collect()
+            }
+            SyntaxTree::Globally(branch) => {
+                let vals = branch.eval_lasso_positions(lasso);
+                (0..len)
+                    .map(|pos| reachable(pos).all(|t| vals[t]))
+                    .collect()
+            }
+            SyntaxTree::Finally(branch) => {
+                let vals = branch.eval_lasso_positions(lasso);
+                (0..len)
+                    .map(|pos| reachable(pos).any(|t| vals[t]))
+                    .collect()
+            }
+            SyntaxTree::And(left_branch, right_branch)
+            | SyntaxTree::Or(left_branch, right_branch)
+            | SyntaxTree::Implies(left_branch, right_branch) => {
+                let left_vals = left_branch.eval_lasso_positions(lasso);
+                let right_vals = right_branch.eval_lasso_positions(lasso);
+                left_vals
+                    .into_iter()
+                    .zip(right_vals)
+                    .map(|(left, right)| match self {
+                        SyntaxTree::And(_, _) => left && right,
+                        SyntaxTree::Or(_, _) => left || right,
+                        _ => !left || right,
+                    })
+                    .collect()
+            }
+            // φ U ψ is the least fixpoint of ψ ∨ (φ ∧ X(φ U ψ)):
+            // two backwards sweeps are enough to propagate values around the cycle.
+            SyntaxTree::Until(left_branch, right_branch) => {
+                let left_vals = left_branch.eval_lasso_positions(lasso);
+                let right_vals = right_branch.eval_lasso_positions(lasso);
+                let mut vals = vec![false; len];
+                for _ in 0..2 {
+                    for pos in (0..len).rev() {
+                        vals[pos] =
+                            right_vals[pos] || (left_vals[pos] && vals[lasso.successor(pos)]);
+                    }
+                }
+                vals
+            }
+        }
+    }
+
+    /// Evaluate a formula on a prefix of a trace, using three-valued (RV-LTL style) semantics.
+    /// The verdict is `True` (resp. `False`) only if the formula is satisfied (resp. violated)
+    /// by the prefix and by every possible continuation of it, and `Unknown` otherwise.
+    /// Operators are evaluated with Kleene logic, so the verdict is sound but not always the most precise one
+    /// (e.g., `x0 ∨ ¬x0` is `Unknown` on an empty prefix).
+    pub fn eval3<S: AtomValuation>(&self, trace: &[S]) -> Verdict {
+        self.eval3_at_time(trace, 0)
+    }
+
+    /// Evaluate a formula on a prefix of a trace at the given time, using three-valued semantics.
+    pub fn eval3_at_time<S: AtomValuation>(&self, trace: &[S], time: usize) -> Verdict {
+        // Nothing is known about time instants past the end of the prefix.
+        if time >= trace.len() {
+            return Verdict::Unknown;
+        }
+
+        match self {
+            SyntaxTree::Atom(var) => Verdict::from(trace[time].holds(*var)),
+            SyntaxTree::Not(branch) => !branch.eval3_at_time(trace, time),
+            SyntaxTree::Next(branch) => branch.eval3_at_time(trace, time + 1),
+            // Temporal operators are unfolded backwards from the end of the prefix,
+            // where the continuation of the trace is unknown.
+            SyntaxTree::Globally(branch) => (time..trace.len())
+                .rev()
+                .fold(Verdict::Unknown, |verdict, t| {
+                    branch.eval3_at_time(trace, t).and(verdict)
+                }),
+            SyntaxTree::Finally(branch) => (time..trace.len())
+                .rev()
+                .fold(Verdict::Unknown, |verdict, t| {
+                    branch.eval3_at_time(trace, t).or(verdict)
+                }),
+            SyntaxTree::And(left_branch, right_branch) => left_branch
+                .eval3_at_time(trace, time)
+                .and(right_branch.eval3_at_time(trace, time)),
+            SyntaxTree::Or(left_branch, right_branch) => left_branch
+                .eval3_at_time(trace, time)
+                .or(right_branch.eval3_at_time(trace, time)),
+            SyntaxTree::Implies(left_branch, right_branch) => (!left_branch
+                .eval3_at_time(trace, time))
+            .or(right_branch.eval3_at_time(trace, time)),
+            // φ U ψ ≡ ψ ∨ (φ ∧ X(φ U ψ))
+            SyntaxTree::Until(left_branch, right_branch) => {
+                (time..trace.len())
+                    .rev()
+                    .fold(Verdict::Unknown, |verdict, t| {
+                        right_branch
+                            .eval3_at_time(trace, t)
+                            .or(left_branch.eval3_at_time(trace, t).and(verdict))
+                    })
+            }
+        }
+    }
 }
 
 #[cfg(test)]
@@ -260,4 +575,169 @@ mod eval {
         // let trace: [[bool; 2]; 0] = [];
         // assert!(!formula.eval(&trace));
     }
+
+    #[test]
+    fn lasso() {
+        // x0 · (¬x0 · x0)^ω
+        let lasso = Lasso::new(vec![[true]], vec![[false], [true]]);
+
+        let formula = SyntaxTree::Globally(Arc::new(SyntaxTree::Finally(Arc::new(ATOM_0))));
+        assert!(formula.eval_lasso(&lasso));
+
+        let formula = SyntaxTree::Finally(Arc::new(SyntaxTree::Globally(Arc::new(ATOM_0))));
+        assert!(!formula.eval_lasso(&lasso));
+
+        // X X X ¬x0 holds, as the cycle loops back
+        let formula = SyntaxTree::Next(Arc::new(SyntaxTree::Next(Arc::new(SyntaxTree::Next(
+            Arc::new(SyntaxTree::Not(Arc::new(ATOM_0))),
+        )))));
+        assert!(formula.eval_lasso(&lasso));
+    }
+
+    #[test]
+    fn lasso_until() {
+        let formula = SyntaxTree::Until(Arc::new(ATOM_0), Arc::new(ATOM_1));
+
+        let lasso = Lasso::new(vec![[true, false]], vec![[true, false], [false, true]]);
+        assert!(formula.eval_lasso(&lasso));
+
+        // From the end of the cycle, the right-hand-side argument only becomes true after looping back.
+        let globally = SyntaxTree::Globally(Arc::new(formula.clone()));
+        let lasso = Lasso::new(vec![], vec![[false, true], [true, false]]);
+        assert!(globally.eval_lasso(&lasso));
+
+        // Until is not satisfied if its right-hand-side argument never becomes true.
+        let lasso = Lasso::new(vec![], vec![[true, false]]);
+        assert!(!formula.eval_lasso(&lasso));
+    }
+}
+
+#[cfg(test)]
+mod eval3 {
+    use super::*;
+
+    const ATOM_0: SyntaxTree = SyntaxTree::Atom(0);
+
+    const ATOM_1: SyntaxTree = SyntaxTree::Atom(1);
+
+    #[test]
+    fn empty_prefix() {
+        let trace: [[bool; 1]; 0] = [];
+        assert_eq!(ATOM_0.eval3(&trace), Verdict::Unknown);
+    }
+
+    #[test]
+    fn next() {
+        let formula = SyntaxTree::Next(Arc::new(ATOM_0));
+
+        let trace = [[false]];
+        assert_eq!(formula.eval3(&trace), Verdict::Unknown);
+
+        let trace = [[false], [true]];
+        assert_eq!(formula.eval3(&trace), Verdict::True);
+    }
+
+    #[test]
+    fn globally() {
+        let formula = SyntaxTree::Globally(Arc::new(ATOM_0));
+
+        let trace = [[true], [true]];
+        assert_eq!(formula.eval3(&trace), Verdict::Unknown);
+
+        let trace = [[true], [false]];
+        assert_eq!(formula.eval3(&trace), Verdict::False);
+    }
+
+    #[test]
+    fn finally() {
+        let formula = SyntaxTree::Finally(Arc::new(ATOM_0));
+
+        let trace = [[false], [false]];
+        assert_eq!(formula.eval3(&trace), Verdict::Unknown);
+
+        let trace = [[false], [true]];
+        assert_eq!(formula.eval3(&trace), Verdict::True);
+    }
+
+    #[test]
+    fn until() {
+        let formula = SyntaxTree::Until(Arc::new(ATOM_0), Arc::new(ATOM_1));
+
+        let trace = [[true, false], [true, false]];
+        assert_eq!(formula.eval3(&trace), Verdict::Unknown);
+
+        let trace = [[true, false], [false, true]];
+        assert_eq!(formula.eval3(&trace), Verdict::True);
+
+        let trace = [[true, false], [false, false]];
+        assert_eq!(formula.eval3(&trace), Verdict::False);
+    }
+}
+
+#[cfg(test)]
+mod combinators {
+    use super::*;
+
+    const ATOM_0: SyntaxTree = SyntaxTree::Atom(0);
+
+    const ATOM_1: SyntaxTree = SyntaxTree::Atom(1);
+
+    #[test]
+    fn canonical_order() {
+        assert_eq!(ATOM_0.and(ATOM_1), ATOM_1.and(ATOM_0));
+        assert_eq!(ATOM_0.or(ATOM_1), ATOM_1.or(ATOM_0));
+    }
+
+    #[test]
+    fn simplification() {
+        assert_eq!(!!ATOM_0, ATOM_0);
+        assert_eq!(ATOM_0.and(ATOM_0), ATOM_0);
+        assert_eq!(ATOM_0.or(ATOM_0.and(ATOM_1)), ATOM_0);
+        assert_eq!(ATOM_0.globally().globally(), ATOM_0.globally());
+        assert_eq!((!ATOM_0).implies(ATOM_1), ATOM_0.or(ATOM_1));
+    }
+}
+
+#[cfg(test)]
+mod named {
+    use super::*;
+
+    #[test]
+    fn display() {
+        let names = ["request".to_string(), "grant".to_string()];
+        let formula = SyntaxTree::Atom(0)
+            .implies(SyntaxTree::Atom(1).finally())
+            .globally();
+
+        assert_eq!(formula.named(&names).to_string(), "G((request)→(F(grant)))");
+        // Variables without a name fall back to the default one.
+        assert_eq!(
+            formula.named(&names[..1]).to_string(),
+            "G((request)→(F(x1)))"
+        );
+    }
+}
+
+#[cfg(test)]
+mod serialization {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let formula = SyntaxTree::Atom(0)
+            .implies(SyntaxTree::Atom(1).finally())
+            .globally();
+
+        let ron = ron::ser::to_string(&formula).expect("serialize to ron");
+        assert_eq!(
+            ron::de::from_str::<SyntaxTree>(&ron).expect("deserialize from ron"),
+            formula
+        );
+
+        let json = serde_json::to_string(&formula).expect("serialize to json");
+        assert_eq!(
+            serde_json::from_str::<SyntaxTree>(&json).expect("deserialize from json"),
+            formula
+        );
+    }
 }
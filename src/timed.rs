@@ -0,0 +1,229 @@
+use crate::eventlog::*;
+use crate::syntax::*;
+use crate::trace::*;
+use serde::{Deserialize, Serialize};
+use serde_with::*;
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt;
+use std::ops::Range;
+
+/// A trace with the time at which each state was observed, for irregularly sampled data,
+/// where the number of steps between two states says little about the time elapsed between them.
+#[serde_as]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TimedTrace<const N: usize> {
+    /// The (non-decreasing) timestamps of the states.
+    times: Vec<f64>,
+    #[serde_as(as = "Vec<[_; N]>")]
+    states: Trace<N>,
+}
+
+/// An error preventing a timed trace from being built.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimedTraceError {
+    /// There are not as many timestamps as states.
+    LengthMismatch { times: usize, states: usize },
+    /// The timestamp at the given position is not finite, or earlier than the previous one.
+    Unordered { position: usize },
+}
+
+impl fmt::Display for TimedTraceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TimedTraceError::LengthMismatch { times, states } => {
+                write!(f, "{times} timestamps for {states} states")
+            }
+            TimedTraceError::Unordered { position } => {
+                write!(f, "timestamp at position {position} is out of order")
+            }
+        }
+    }
+}
+
+impl Error for TimedTraceError {}
+
+impl<const N: usize> TimedTrace<N> {
+    /// Pairs each state with its timestamp, which must be finite and non-decreasing.
+    pub fn new(times: Vec<f64>, states: Trace<N>) -> Result<Self, TimedTraceError> {
+        if times.len() != states.len() {
+            return Err(TimedTraceError::LengthMismatch {
+                times: times.len(),
+                states: states.len(),
+            });
+        }
+        if let Some(position) = (0..times.len())
+            .find(|&pos| !times[pos].is_finite() || (pos > 0 && times[pos] < times[pos - 1]))
+        {
+            return Err(TimedTraceError::Unordered { position });
+        }
+        Ok(TimedTrace { times, states })
+    }
+
+    /// Appends a state observed at the given time, panicking if it is earlier than the last one.
+    pub fn push(&mut self, time: f64, state: [bool; N]) {
+        assert!(
+            time.is_finite() && self.times.last().is_none_or(|last| *last <= time),
+            "timestamps must be finite and non-decreasing"
+        );
+        self.times.push(time);
+        self.states.push(state);
+    }
+
+    pub fn times(&self) -> &[f64] {
+        &self.times
+    }
+
+    /// The states of the trace, forgetting their timestamps.
+    pub fn states(&self) -> &[[bool; N]] {
+        &self.states
+    }
+
+    pub fn len(&self) -> usize {
+        self.states.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.states.is_empty()
+    }
+
+    /// The positions from the given one onward observed between `from` and `to` time units (included) after it.
+    pub fn within(&self, position: usize, from: f64, to: f64) -> Range<usize> {
+        let origin = self.times[position];
+        let times = &self.times[position..];
+        let start = times.partition_point(|time| time - origin < from);
+        let end = times.partition_point(|time| time - origin <= to);
+        position + start..position + end.max(start)
+    }
+
+    /// Samples the trace at regular intervals of the given period, starting from the first timestamp,
+    /// holding each state until the next one is observed,
+    /// so that index-based semantics reflect the time elapsed between states.
+    pub fn resample(&self, period: f64) -> Trace<N> {
+        assert!(period > 0.0, "sampling period must be positive");
+        let (Some(first), Some(last)) = (self.times.first(), self.times.last()) else {
+            return Trace::new();
+        };
+
+        let steps = ((last - first) / period).floor() as usize + 1;
+        (0..steps)
+            .map(|step| {
+                let time = first + step as f64 * period;
+                // The last state observed at or before the sampling time.
+                let observed = self.times.partition_point(|t| *t <= time);
+                self.states[observed.max(1) - 1]
+            })
+            .collect()
+    }
+}
+
+// Formulae have no bounded operators (yet), so their real-time semantics is provided
+// by evaluating the bounded versions of Finally and Globally on top of a formula.
+impl SyntaxTree {
+    /// Evaluate a formula on a timed trace.
+    /// Untimed operators only depend on the order of the states, so timestamps are not relevant,
+    /// and the result is the same as [`SyntaxTree::eval`] on its states.
+    pub fn eval_timed<const N: usize>(&self, trace: &TimedTrace<N>) -> bool {
+        self.eval(trace.states())
+    }
+
+    /// Evaluate `F[from, to] self` at the given position of a timed trace,
+    /// i.e., whether the formula holds at some position observed between `from` and `to` time units after it.
+    pub fn eval_finally_within<const N: usize>(
+        &self,
+        trace: &TimedTrace<N>,
+        position: usize,
+        from: f64,
+        to: f64,
+    ) -> bool {
+        trace
+            .within(position, from, to)
+            .any(|t| self.eval_at_time(trace.states(), t))
+    }
+
+    /// Evaluate `G[from, to] self` at the given position of a timed trace,
+    /// i.e., whether the formula holds at all the positions observed between `from` and `to` time units after it.
+    pub fn eval_globally_within<const N: usize>(
+        &self,
+        trace: &TimedTrace<N>,
+        position: usize,
+        from: f64,
+        to: f64,
+    ) -> bool {
+        trace
+            .within(position, from, to)
+            .all(|t| self.eval_at_time(trace.states(), t))
+    }
+}
+
+impl EventLog {
+    /// Turns each case of the log into a timed trace, with a state per distinct timestamp
+    /// in which the n-th variable is true if the n-th of the given events occurs at that time.
+    /// Unlike [`EventLog::discretize`], no window width has to be chosen, and timing is preserved exactly.
+    pub fn timed_traces<const N: usize>(
+        &self,
+        events: &[String; N],
+    ) -> BTreeMap<String, TimedTrace<N>> {
+        let mut cases = BTreeMap::<String, Vec<&Event>>::new();
+        for event in &self.events {
+            cases.entry(event.case.clone()).or_default().push(event);
+        }
+
+        cases
+            .into_iter()
+            .map(|(case, mut case_events)| {
+                case_events.sort_by(|a, b| a.time.total_cmp(&b.time));
+                let mut trace = TimedTrace::default();
+                for event in case_events {
+                    if trace.times.last() != Some(&event.time) {
+                        trace.push(event.time, [false; N]);
+                    }
+                    if let Some(var) = events.iter().position(|name| *name == event.name) {
+                        trace.states.last_mut().expect("state")[var] = true;
+                    }
+                }
+                (case, trace)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod timed_traces {
+    use super::*;
+
+    fn trace() -> TimedTrace<1> {
+        // Irregularly sampled: the last state comes much later than the others.
+        TimedTrace::new(
+            vec![0.0, 0.5, 1.0, 10.0],
+            vec![[false], [false], [false], [true]],
+        )
+        .expect("timed trace")
+    }
+
+    #[test]
+    fn bounded_operators() {
+        let trace = trace();
+        let atom = SyntaxTree::Atom(0);
+        assert_eq!(trace.within(0, 0.5, 1.0), 1..3);
+        assert_eq!(trace.within(1, 20.0, 30.0), 4..4);
+        // Three steps away, but ten time units.
+        assert!(!atom.eval_finally_within(&trace, 0, 0.0, 3.0));
+        assert!(atom.eval_finally_within(&trace, 0, 5.0, 10.0));
+        assert!((!atom.clone()).eval_globally_within(&trace, 0, 0.0, 9.0));
+        assert!(atom.eval_timed(&trace) == atom.eval(trace.states()));
+    }
+
+    #[test]
+    fn resample() {
+        let trace = trace().resample(2.0);
+        assert_eq!(trace.len(), 6);
+        assert_eq!(trace.last(), Some(&[true]));
+        assert!(trace[..5].iter().all(|state| *state == [false]));
+
+        assert_eq!(
+            TimedTrace::<1>::new(vec![1.0, 0.0], vec![[true], [false]]),
+            Err(TimedTraceError::Unordered { position: 1 })
+        );
+    }
+}
@@ -1,13 +1,132 @@
+use crate::lengths::*;
+use crate::metadata::*;
+use crate::migrate::*;
 use crate::syntax::*;
 use itertools::Itertools;
+use rand::Rng;
 use serde::{Deserialize, Serialize};
 use serde_with::*;
 
 pub type Trace<const N: usize> = Vec<[bool; N]>;
 
+/// A state of a trace, assigning a truth value to each propositional variable.
+/// Formulae can be evaluated on traces over any state type implementing this trait
+/// (see [`SyntaxTree::eval`]), e.g., bitsets or user-defined enum-valued states.
+pub trait AtomValuation {
+    /// Whether the given propositional variable is true in the state.
+    fn holds(&self, atom: Idx) -> bool;
+}
+
+impl<const N: usize> AtomValuation for [bool; N] {
+    fn holds(&self, atom: Idx) -> bool {
+        self[atom as usize]
+    }
+}
+
+impl AtomValuation for [bool] {
+    fn holds(&self, atom: Idx) -> bool {
+        self[atom as usize]
+    }
+}
+
+impl AtomValuation for Vec<bool> {
+    fn holds(&self, atom: Idx) -> bool {
+        self[atom as usize]
+    }
+}
+
+impl<S: AtomValuation + ?Sized> AtomValuation for &S {
+    fn holds(&self, atom: Idx) -> bool {
+        (*self).holds(atom)
+    }
+}
+
+// Unsigned integers are bitsets, where the n-th bit is the value of the n-th variable.
+macro_rules! impl_bitset_valuation {
+    ($($t:ty)*) => {
+        $(impl AtomValuation for $t {
+            fn holds(&self, atom: Idx) -> bool {
+                assert!((atom as u32) < <$t>::BITS, "variable out of range of the bitset");
+                self & (1 << atom) != 0
+            }
+        })*
+    };
+}
+
+impl_bitset_valuation!(u8 u16 u32 u64 u128);
+
+/// Generates a trace of the given length whose states are drawn uniformly at random.
+pub fn random_trace<const N: usize, R: Rng + ?Sized>(length: usize, rng: &mut R) -> Trace<N> {
+    (0..length)
+        .map(|_| {
+            let mut state = [false; N];
+            rng.fill(&mut state[..]);
+            state
+        })
+        .collect()
+}
+
+/// An ultimately-periodic (lasso) trace `prefix · cycle^ω`,
+/// representing the infinite word where the `cycle` repeats forever after the `prefix`.
+#[serde_as]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Lasso<const N: usize> {
+    #[serde_as(as = "Vec<[_; N]>")]
+    pub prefix: Trace<N>,
+    /// The repeating part of the trace, which must not be empty.
+    #[serde_as(as = "Vec<[_; N]>")]
+    pub cycle: Trace<N>,
+}
+
+impl<const N: usize> Lasso<N> {
+    /// Creates a new lasso, panicking if the cycle is empty.
+    pub fn new(prefix: Trace<N>, cycle: Trace<N>) -> Self {
+        assert!(!cycle.is_empty(), "the cycle of a lasso must not be empty");
+        Lasso { prefix, cycle }
+    }
+
+    /// The number of distinct positions of the lasso, i.e., the length of prefix and cycle together.
+    pub fn len(&self) -> usize {
+        self.prefix.len() + self.cycle.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cycle.is_empty() && self.prefix.is_empty()
+    }
+
+    /// The state at the given position, which must be less than [`Lasso::len`].
+    pub fn state(&self, pos: usize) -> &[bool; N] {
+        if pos < self.prefix.len() {
+            &self.prefix[pos]
+        } else {
+            &self.cycle[pos - self.prefix.len()]
+        }
+    }
+
+    /// The position following the given one, looping back to the start of the cycle at its end.
+    pub fn successor(&self, pos: usize) -> usize {
+        if pos + 1 < self.len() {
+            pos + 1
+        } else {
+            self.prefix.len()
+        }
+    }
+}
+
+/// Whether a trace is a positive or a negative example.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Label {
+    Positive,
+    Negative,
+}
+
 #[serde_as]
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Sample<const N: usize> {
+    /// The format version of the file the sample was loaded from (`1` for files predating versioning).
+    /// Samples are always serialized with the current [`SAMPLE_FORMAT_VERSION`].
+    #[serde(default = "legacy_version", serialize_with = "serialize_version")]
+    pub version: u32,
     #[serde_as(as = "[_; N]")]
     #[serde(default = "Sample::var_names")]
     pub var_names: [String; N],
@@ -15,20 +134,41 @@ pub struct Sample<const N: usize> {
     pub positive_traces: Vec<Trace<N>>,
     #[serde_as(as = "Vec<Vec<[_; N]>>")]
     pub negative_traces: Vec<Trace<N>>,
+    /// Positive examples given as infinite (lasso) traces, evaluated with infinite-word semantics.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub positive_lassos: Vec<Lasso<N>>,
+    /// Negative examples given as infinite (lasso) traces, evaluated with infinite-word semantics.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub negative_lassos: Vec<Lasso<N>>,
+    /// Metadata of positive traces (e.g., source file, run ID, timestamp), by trace index.
+    #[serde(default, skip_serializing_if = "MetadataMap::is_empty")]
+    pub positive_metadata: MetadataMap,
+    /// Metadata of negative traces, by trace index.
+    #[serde(default, skip_serializing_if = "MetadataMap::is_empty")]
+    pub negative_metadata: MetadataMap,
+    /// How traces of different lengths are dealt with (see [`Sample::apply_length_policy`]).
+    #[serde(default, skip_serializing_if = "LengthPolicy::is_natural")]
+    pub length_policy: LengthPolicy,
 }
 
 impl<const N: usize> Default for Sample<N> {
     fn default() -> Self {
         Sample {
+            version: SAMPLE_FORMAT_VERSION,
             var_names: Sample::var_names(),
             positive_traces: Vec::default(),
             negative_traces: Vec::default(),
+            positive_lassos: Vec::default(),
+            negative_lassos: Vec::default(),
+            positive_metadata: MetadataMap::default(),
+            negative_metadata: MetadataMap::default(),
+            length_policy: LengthPolicy::default(),
         }
     }
 }
 
 impl<const N: usize> Sample<N> {
-    fn var_names() -> [String; N] {
+    pub(crate) fn var_names() -> [String; N] {
         (0..N)
             .map(|n| format!("x{n}"))
             .collect::<Vec<_>>()
@@ -50,19 +190,10 @@ impl<const N: usize> Sample<N> {
             .collect_vec()
     }
 
+    /// Whether no positive trace (or lasso) is identical to a negative one over the allowed variables
+    /// (see [`Sample::check_consistency`]).
     pub fn is_solvable(&self) -> bool {
-        let vars = self.vars();
-
-        self.positive_traces.iter().all(|pos_trace| {
-            self.negative_traces.iter().all(|neg_trace| {
-                pos_trace.len() != neg_trace.len()
-                    || pos_trace.iter().enumerate().any(|(time, &pos_tuple)| {
-                        let neg_tuple = neg_trace[time];
-                        vars.iter()
-                            .any(|n| pos_tuple[*n as usize] != neg_tuple[*n as usize])
-                    })
-            })
-        })
+        self.contradictions().next().is_none()
     }
 
     pub fn is_consistent(&self, formula: &SyntaxTree) -> bool {
@@ -77,19 +208,30 @@ impl<const N: usize> Sample<N> {
                     .map(|trace| !formula.eval(trace.as_slice())),
             )
             .all(|val| val)
+            && self
+                .positive_lassos
+                .iter()
+                .all(|lasso| formula.eval_lasso(lasso))
+            && self
+                .negative_lassos
+                .iter()
+                .all(|lasso| !formula.eval_lasso(lasso))
     }
 
+    /// The length of the longest trace, that of a lasso being the length of its prefix and cycle together.
     pub fn time_lenght(&self) -> Time {
         let positive_lenght = self
             .positive_traces
             .iter()
             .map(|trace| trace.len())
+            .chain(self.positive_lassos.iter().map(Lasso::len))
             .max()
             .unwrap_or(0);
         let negative_lenght = self
             .negative_traces
             .iter()
             .map(|trace| trace.len())
+            .chain(self.negative_lassos.iter().map(Lasso::len))
             .max()
             .unwrap_or(0);
         positive_lenght.max(negative_lenght) as Time
@@ -128,6 +270,23 @@ impl<const N: usize> Sample<N> {
     }
 }
 
+#[cfg(test)]
+impl Sample<2> {
+    /// The sample shared by the tests of the searches and learners, whose positive traces are those where x0 always holds,
+    /// so that `G x0` is its smallest consistent formula.
+    pub(crate) fn always_x0() -> Self {
+        Sample {
+            positive_traces: vec![vec![[true, false], [true, true]], vec![[true, true]]],
+            negative_traces: vec![
+                vec![[true, true], [false, true]],
+                vec![[false, false]],
+                vec![[false, true], [true, false]],
+            ],
+            ..Sample::default()
+        }
+    }
+}
+
 #[cfg(test)]
 mod consistency {
     use std::sync::Arc;
@@ -141,17 +300,46 @@ mod consistency {
     #[test]
     fn and() {
         let sample = Sample {
-            var_names: Sample::var_names(),
             positive_traces: vec![vec![[true, true]]],
             negative_traces: vec![
                 vec![[false, true]],
                 vec![[true, false]],
                 vec![[false, false]],
             ],
+            ..Sample::default()
         };
 
         let formula = SyntaxTree::And(Arc::new(ATOM_0), Arc::new(ATOM_1));
 
         assert!(sample.is_consistent(&formula));
     }
+
+    #[test]
+    fn lassos() {
+        let sample = Sample {
+            positive_lassos: vec![Lasso::new(vec![[false, false]], vec![[true, false]])],
+            negative_lassos: vec![Lasso::new(vec![], vec![[true, false], [false, false]])],
+            ..Sample::default()
+        };
+
+        // F G x0
+        let formula = SyntaxTree::Finally(Arc::new(SyntaxTree::Globally(Arc::new(ATOM_0))));
+
+        assert!(sample.is_solvable());
+        assert!(sample.is_consistent(&formula));
+    }
+
+    #[test]
+    fn bitset_states() {
+        // x0 U x1
+        let formula = SyntaxTree::Until(Arc::new(ATOM_0), Arc::new(ATOM_1));
+
+        let bool_trace = vec![[true, false], [true, false], [false, true]];
+        let bitset_trace: Vec<u8> = vec![0b01, 0b01, 0b10];
+        assert!(formula.eval(&bool_trace));
+        assert!(formula.eval(&bitset_trace));
+
+        let vec_trace = vec![vec![true, false], vec![false, false]];
+        assert!(!formula.eval(&vec_trace));
+    }
 }
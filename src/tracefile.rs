@@ -0,0 +1,338 @@
+use crate::load::*;
+use crate::trace::*;
+use std::io::{self, Write};
+use std::str::FromStr;
+
+/// The separator between the blocks of a benchmark trace file.
+const SEPARATOR: &str = "---";
+
+/// The flavour of `.trace` file to write, depending on the learner it is meant for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TraceFileDialect {
+    /// The format of Flie, which supports lassos and all the operators.
+    Flie,
+    /// The format of Scarlet, which only supports finite traces and no binary temporal operators,
+    /// and lists the variable names after the operators.
+    Scarlet,
+}
+
+impl TraceFileDialect {
+    /// The operators the learner is allowed to use, as listed in the file.
+    fn operators(self) -> &'static str {
+        match self {
+            TraceFileDialect::Flie => "!,X,G,F,&,|,->,U",
+            TraceFileDialect::Scarlet => "!,X,G,F,&,|",
+        }
+    }
+}
+
+impl FromStr for TraceFileDialect {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "flie" => Ok(TraceFileDialect::Flie),
+            "scarlet" => Ok(TraceFileDialect::Scarlet),
+            _ => Err(format!("unknown trace file dialect: {s}")),
+        }
+    }
+}
+
+/// A trace read from a line of a benchmark trace file.
+struct TraceLine {
+    line: usize,
+    states: Vec<Vec<bool>>,
+    /// The position where the loop starts, for lassos.
+    loop_start: Option<usize>,
+}
+
+fn syntax_error(line: usize, column: usize, message: String) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        SyntaxError {
+            line,
+            column,
+            message,
+        },
+    )
+}
+
+/// Parses a trace such as `1,0;0,1;1,1::1`, where `::1` marks the position where the loop starts.
+fn parse_trace(line: usize, contents: &str) -> io::Result<TraceLine> {
+    let (trace, loop_start) = match contents.split_once("::") {
+        Some((trace, start)) => {
+            let start = start.trim().parse::<usize>().map_err(|err| {
+                syntax_error(line, trace.len() + 3, format!("invalid loop start: {err}"))
+            })?;
+            (trace, Some(start))
+        }
+        None => (contents, None),
+    };
+
+    let mut column = 1;
+    let mut states = Vec::new();
+    for state in trace.split(';') {
+        let values = state
+            .split(',')
+            .map(|value| match value.trim() {
+                "1" => Ok(true),
+                "0" => Ok(false),
+                value => Err(syntax_error(
+                    line,
+                    column,
+                    format!("expected 0 or 1, found `{value}`"),
+                )),
+            })
+            .collect::<io::Result<Vec<_>>>()?;
+        states.push(values);
+        column += state.len() + 1;
+    }
+
+    if let Some(start) = loop_start {
+        if start >= states.len() {
+            return Err(syntax_error(
+                line,
+                trace.len() + 3,
+                format!(
+                    "loop start {start} is past the end of a trace of length {}",
+                    states.len()
+                ),
+            ));
+        }
+    }
+    Ok(TraceLine {
+        line,
+        states,
+        loop_start,
+    })
+}
+
+/// The traces of a benchmark trace file.
+struct TraceFile<'a> {
+    positives: Vec<TraceLine>,
+    negatives: Vec<TraceLine>,
+    /// The line following the operators, holding the variable names in the Scarlet dialect
+    /// (but the formula size in the Flie one).
+    names: Option<&'a str>,
+}
+
+/// Splits a benchmark trace file into its positive and negative traces.
+/// Whatever follows the second separator (operators, formula size, intended formula) is ignored,
+/// but for the line that may hold the variable names.
+fn parse_trace_file(contents: &[u8]) -> io::Result<TraceFile<'_>> {
+    let contents = std::str::from_utf8(contents)
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+    let mut blocks = [Vec::new(), Vec::new()];
+    let mut block = 0;
+    let mut names = None;
+    for (number, line) in contents.lines().enumerate() {
+        let line = line.trim();
+        if line == SEPARATOR {
+            block += 1;
+        } else if line.is_empty() {
+            continue;
+        } else if block < blocks.len() {
+            blocks[block].push(parse_trace(number + 1, line)?);
+        } else if block == 3 {
+            names = Some(line);
+            break;
+        }
+    }
+    let [positives, negatives] = blocks;
+    Ok(TraceFile {
+        positives,
+        negatives,
+        names,
+    })
+}
+
+/// Writes a trace, as a line of a trace file.
+fn write_trace<const N: usize, W: Write>(writer: &mut W, trace: &[[bool; N]]) -> io::Result<()> {
+    let states = trace
+        .iter()
+        .map(|state| {
+            state
+                .iter()
+                .map(|val| if *val { "1" } else { "0" })
+                .collect::<Vec<_>>()
+                .join(",")
+        })
+        .collect::<Vec<_>>();
+    write!(writer, "{}", states.join(";"))
+}
+
+/// Reads the number of variables of a benchmark trace file, from its first state.
+pub(crate) fn trace_file_vars(contents: &[u8]) -> io::Result<usize> {
+    let file = parse_trace_file(contents)?;
+    Ok(file
+        .positives
+        .iter()
+        .chain(file.negatives.iter())
+        .find_map(|trace| trace.states.first())
+        .map_or(0, Vec::len))
+}
+
+impl<const N: usize> Sample<N> {
+    /// Reads a sample in the `.trace` format of the benchmarks of other LTL learners (such as Flie and Scarlet),
+    /// where each line holds a trace, as states separated by `;` of values `0`/`1` separated by `,`,
+    /// and `---` separates the positive traces from the negative ones, e.g.,
+    ///
+    /// ```text
+    /// 1,0;0,1;1,1
+    /// ---
+    /// 0,0;1,1::1
+    /// ```
+    ///
+    /// Traces ending with `::k` are lassos, looping back to position `k`.
+    /// Variable names are read from the line following the operators, as written by Scarlet,
+    /// if it lists as many names as variables (and is not the formula size written by Flie);
+    /// otherwise the default ones are used.
+    pub fn read_trace_file(contents: &[u8]) -> io::Result<Self> {
+        let file = parse_trace_file(contents)?;
+        let mut sample = Sample::default();
+        if let Some(names) = file.names {
+            let names = names.split(',').map(|name| name.trim().to_string());
+            let names = names.collect::<Vec<_>>();
+            if names.iter().all(|name| name.parse::<usize>().is_err()) {
+                if let Ok(names) = <[String; N]>::try_from(names) {
+                    sample.var_names = names;
+                }
+            }
+        }
+        for (label, traces) in [
+            (Label::Positive, file.positives),
+            (Label::Negative, file.negatives),
+        ] {
+            for TraceLine {
+                line,
+                states,
+                loop_start,
+            } in traces
+            {
+                let mut trace = Vec::with_capacity(states.len());
+                for values in states {
+                    let state = <[bool; N]>::try_from(values).map_err(|values| {
+                        syntax_error(
+                            line,
+                            1,
+                            format!("expected {N} variables, found {}", values.len()),
+                        )
+                    })?;
+                    trace.push(state);
+                }
+                match (label, loop_start) {
+                    (Label::Positive, None) => sample.positive_traces.push(trace),
+                    (Label::Negative, None) => sample.negative_traces.push(trace),
+                    (label, Some(start)) => {
+                        let cycle = trace.split_off(start);
+                        let lasso = Lasso::new(trace, cycle);
+                        match label {
+                            Label::Positive => sample.positive_lassos.push(lasso),
+                            Label::Negative => sample.negative_lassos.push(lasso),
+                        }
+                    }
+                }
+            }
+        }
+        Ok(sample)
+    }
+
+    /// Writes the sample as a `.trace` file (see [`Sample::read_trace_file`]) in the given dialect,
+    /// to compare with other LTL learners on the same sample.
+    /// Scarlet does not support lassos, so they are only written in the Flie dialect,
+    /// while metadata is never written.
+    pub fn write_trace_file<W: Write>(
+        &self,
+        mut writer: W,
+        dialect: TraceFileDialect,
+    ) -> io::Result<()> {
+        for (traces, lassos) in [
+            (&self.positive_traces, &self.positive_lassos),
+            (&self.negative_traces, &self.negative_lassos),
+        ] {
+            for trace in traces {
+                write_trace(&mut writer, trace)?;
+                writeln!(writer)?;
+            }
+            if dialect == TraceFileDialect::Flie {
+                for lasso in lassos {
+                    write_trace(
+                        &mut writer,
+                        &[lasso.prefix.as_slice(), &lasso.cycle].concat(),
+                    )?;
+                    writeln!(writer, "::{}", lasso.prefix.len())?;
+                }
+            }
+            writeln!(writer, "{SEPARATOR}")?;
+        }
+        writeln!(writer, "{}", dialect.operators())?;
+        if dialect == TraceFileDialect::Scarlet {
+            writeln!(writer, "{SEPARATOR}")?;
+            writeln!(writer, "{}", self.var_names.join(","))?;
+        }
+        writer.flush()
+    }
+}
+
+#[cfg(test)]
+mod trace_files {
+    use super::*;
+
+    const CONTENTS: &str = "1,0;0,1;1,1\n1,1\n---\n0,0;1,1::1\n\n---\nG,F,!\n---\n3\n";
+
+    #[test]
+    fn read() {
+        let sample = Sample::<2>::read_trace_file(CONTENTS.as_bytes()).expect("read sample");
+        assert_eq!(
+            sample.positive_traces,
+            vec![
+                vec![[true, false], [false, true], [true, true]],
+                vec![[true, true]]
+            ]
+        );
+        assert!(sample.negative_traces.is_empty());
+        assert_eq!(
+            sample.negative_lassos,
+            vec![Lasso::new(vec![[false, false]], vec![[true, true]])]
+        );
+        assert_eq!(trace_file_vars(CONTENTS.as_bytes()).expect("count vars"), 2);
+    }
+
+    #[test]
+    fn errors() {
+        let err = Sample::<2>::read_trace_file(b"1,0;0,2\n---\n").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "line 1, column 5: expected 0 or 1, found `2`"
+        );
+        let err = Sample::<2>::read_trace_file(b"1,0;0,1::2\n---\n").unwrap_err();
+        assert!(err
+            .to_string()
+            .starts_with("line 1, column 10: loop start 2"));
+        assert!(Sample::<3>::read_trace_file(CONTENTS.as_bytes()).is_err());
+    }
+
+    #[test]
+    fn write() {
+        let mut sample = Sample::<2>::read_trace_file(CONTENTS.as_bytes()).expect("read sample");
+        sample.var_names = ["p".to_string(), "q".to_string()];
+
+        let mut flie = Vec::new();
+        sample
+            .write_trace_file(&mut flie, TraceFileDialect::Flie)
+            .expect("write sample");
+        assert_eq!(
+            String::from_utf8(flie).expect("utf8"),
+            "1,0;0,1;1,1\n1,1\n---\n0,0;1,1::1\n---\n!,X,G,F,&,|,->,U\n"
+        );
+
+        let mut scarlet = Vec::new();
+        sample
+            .write_trace_file(&mut scarlet, TraceFileDialect::Scarlet)
+            .expect("write sample");
+        let read = Sample::<2>::read_trace_file(&scarlet).expect("read sample");
+        assert_eq!(read.var_names, sample.var_names);
+        assert_eq!(read.positive_traces, sample.positive_traces);
+        assert!(read.negative_lassos.is_empty());
+    }
+}
@@ -0,0 +1,94 @@
+use crate::metadata::*;
+use crate::syntax::*;
+use crate::trace::*;
+
+/// The windows of the given length of a trace, starting every `stride` positions, with their starting position.
+/// Windows overlap if the stride is shorter than the length, and the last positions of the trace
+/// are left out if they do not make up a whole window.
+pub fn windows<const N: usize>(
+    trace: &[[bool; N]],
+    length: usize,
+    stride: usize,
+) -> impl Iterator<Item = (usize, &[[bool; N]])> {
+    assert!(length > 0, "window length must be positive");
+    assert!(stride > 0, "window stride must be positive");
+    (0..trace.len().saturating_sub(length - 1))
+        .step_by(stride)
+        .map(move |start| (start, &trace[start..start + length]))
+}
+
+impl<const N: usize> Sample<N> {
+    /// Slices a long trace into windows (see [`windows`]), labelled by the given function of their start and states,
+    /// which discards those labelled `None`.
+    /// The start of each window within the long trace is kept in the metadata of its trace, under `start`.
+    /// Windows can repeat, which [`Sample::dedup`] removes.
+    pub fn from_windows<F>(trace: &[[bool; N]], length: usize, stride: usize, mut label: F) -> Self
+    where
+        F: FnMut(usize, &[[bool; N]]) -> Option<Label>,
+    {
+        let mut sample = Sample::default();
+        for (start, window) in windows(trace, length, stride) {
+            let Some(label) = label(start, window) else {
+                continue;
+            };
+            let traces = match label {
+                Label::Positive => &mut sample.positive_traces,
+                Label::Negative => &mut sample.negative_traces,
+            };
+            traces.push(window.to_vec());
+            let index = traces.len() - 1;
+            sample.set_metadata(
+                label,
+                index,
+                Metadata::from([("start".to_string(), start.to_string())]),
+            );
+        }
+        sample
+    }
+
+    /// Slices a long trace into windows (see [`windows`]),
+    /// which are positive if they satisfy the reference formula and negative otherwise.
+    pub fn from_windows_with_formula(
+        trace: &[[bool; N]],
+        length: usize,
+        stride: usize,
+        formula: &SyntaxTree,
+    ) -> Self {
+        Sample::from_windows(trace, length, stride, |_, window| {
+            Some(if formula.eval(window) {
+                Label::Positive
+            } else {
+                Label::Negative
+            })
+        })
+    }
+}
+
+#[cfg(test)]
+mod sliding_windows {
+    use super::*;
+
+    #[test]
+    fn windows() {
+        let trace = (0..7).map(|time| [time % 3 == 0]).collect::<Vec<_>>();
+        let starts = super::windows(&trace, 3, 2)
+            .map(|(start, window)| {
+                assert_eq!(window, &trace[start..start + 3]);
+                start
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(starts, vec![0, 2, 4]);
+        assert_eq!(super::windows(&trace, 8, 1).count(), 0);
+
+        let sample = Sample::from_windows_with_formula(&trace, 2, 1, &SyntaxTree::Atom(0));
+        assert_eq!(sample.positive_traces(), 2);
+        assert_eq!(sample.negative_traces(), 4);
+        assert_eq!(
+            sample
+                .metadata(Label::Positive, 1)
+                .and_then(|data| data.get("start"))
+                .map(String::as_str),
+            Some("3")
+        );
+    }
+}